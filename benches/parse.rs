@@ -0,0 +1,77 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+/// Builds a fixture of unquoted, single-quoted and double-quoted variables, with comments in between.
+fn fixture(variables: usize) -> String {
+    let mut data = String::new();
+
+    for index in 0..variables {
+        match index % 4 {
+            0 => data.push_str(&format!("# Variable {}\nUNQUOTED_{}=value-{}\n", index, index, index)),
+            1 => data.push_str(&format!("SINGLE_QUOTED_{}='a value with spaces {}'\n", index, index)),
+            2 => data.push_str(&format!("DOUBLE_QUOTED_{}=\"a value with \\\"escapes\\\"\\n{}\"\n", index, index)),
+            _ => data.push_str(&format!("export EXPORTED_{}=value # inline comment\n", index)),
+        }
+    }
+
+    data
+}
+
+/// Builds a fixture of variables with long, multiline quoted values, such as certificates.
+fn long_quoted_fixture(variables: usize) -> String {
+    let line = "MIIDdzCCAl+gAwIBAgIEAgAAuTANBgkqhkiG9w0BAQUFADBaMQswCQYDVQQGEwJJRTESMBAGA1UEChMJQmFsdGltb3JlMRMw";
+    let value = vec![line; 40].join("\n");
+    let mut data = String::new();
+
+    for index in 0..variables {
+        match index % 2 {
+            0 => data.push_str(&format!("CERTIFICATE_{}='{}'\n", index, value)),
+            _ => data.push_str(&format!("CERTIFICATE_{}=\"{}\"\n", index, value)),
+        }
+    }
+
+    data
+}
+
+fn parse(criterion: &mut Criterion) {
+    let fixtures = [
+        ("small", fixture(20)),
+        ("large", fixture(5_000)),
+        ("long quoted", long_quoted_fixture(200)),
+    ];
+
+    let mut group = criterion.benchmark_group("parse");
+
+    for (name, data) in fixtures.iter() {
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_function(*name, |bencher| bencher.iter(|| darkweb_dotenv::parse(data).unwrap()));
+    }
+
+    // Non-default options bypass the fast path of `parse()`, so parse with one the way loading a file does.
+    let data = fixture(5_000).replace('=', " = ");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("large, whitespace around equals", |bencher| bencher.iter(|| {
+        darkweb_dotenv::Dotenv::new().whitespace_around_equals(true).overload_from_str(&data).unwrap()
+    }));
+
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
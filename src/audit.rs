@@ -0,0 +1,197 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::time::SystemTime;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{json, metadata, OverridePolicy};
+
+/// Decision taken for a variable while populating the environment
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditAction {
+    /// The variable was not defined and is set.
+    Set,
+    /// The variable was already defined and is overwritten.
+    Override,
+    /// The variable is left untouched.
+    Skip,
+    /// The variable is removed by an `unset` statement.
+    Unset,
+}
+
+/// Entry of the audit log kept by `Dotenv::audit()`, accounting for how a variable was established
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditEntry {
+    timestamp: SystemTime,
+    action: AuditAction,
+    key: String,
+    value_hash: Option<String>,
+    source: Option<String>,
+    policy: &'static str,
+}
+
+impl AuditAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Set => "set",
+            Self::Override => "override",
+            Self::Skip => "skip",
+            Self::Unset => "unset",
+        }
+    }
+}
+
+impl AuditEntry {
+    pub(crate) fn new(action: AuditAction, key: &str, value_hash: Option<String>, source: Option<&str>, policy: &'static str) -> Self {
+        Self {
+            timestamp: SystemTime::now(),
+            action,
+            key: key.to_string(),
+            value_hash,
+            source: source.map(str::to_string),
+            policy,
+        }
+    }
+
+    /// Time of the decision.
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    /// Decision taken for the variable.
+    pub fn action(&self) -> AuditAction {
+        self.action
+    }
+
+    /// Name of the variable.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// HMAC-SHA256 fingerprint of the loaded value, like `hmac-sha256:9615a95d…`, if there is one. It tells whether two
+    /// entries of the same `Dotenv` instance have the same value without revealing it. The key is random per instance,
+    /// so fingerprints cannot be compared across instances, nor brute-forced without the key.
+    pub fn value_hash(&self) -> Option<&str> {
+        self.value_hash.as_deref()
+    }
+
+    /// File or source the value comes from, if known.
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// Policy that led to the decision, like `OverridePolicy::Never` or `ProtectedVariablePolicy::Skip`.
+    pub fn policy(&self) -> &str {
+        self.policy
+    }
+
+    ///
+    /// Renders the entry as a single-line JSON object, for appending to a JSON Lines audit file.
+    ///
+    pub fn to_json(&self) -> String {
+        let optional = |value: Option<&str>| value.map_or_else(|| "null".to_string(), json::string);
+
+        format!(
+            "{{\"timestamp\":{},\"action\":{},\"key\":{},\"value_hash\":{},\"source\":{},\"policy\":{}}}",
+            json::string(metadata::timestamp(self.timestamp)),
+            json::string(self.action.as_str()),
+            json::string(&self.key),
+            optional(self.value_hash.as_deref()),
+            optional(self.source.as_deref()),
+            json::string(self.policy),
+        )
+    }
+}
+
+impl fmt::Display for AuditEntry {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{} {} {} ({})", metadata::timestamp(self.timestamp), self.action.as_str(), self.key, self.policy)?;
+
+        if let Some(source) = &self.source {
+            write!(formatter, " from {}", source)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Name of an override policy, as recorded in the audit log.
+pub(crate) fn policy_name(override_policy: &OverridePolicy) -> &'static str {
+    match override_policy {
+        OverridePolicy::Never => "OverridePolicy::Never",
+        OverridePolicy::Always => "OverridePolicy::Always",
+        OverridePolicy::OnlyIfEmpty => "OverridePolicy::OnlyIfEmpty",
+        OverridePolicy::List(_) => "OverridePolicy::List",
+    }
+}
+
+/// Random key for the fingerprints of the values of one audit log.
+pub(crate) fn fingerprint_key() -> [u8; 32] {
+    let mut key = [0; 32];
+
+    // The randomly seeded hashers of the standard library are the fallback if the system has no random source.
+    if getrandom::getrandom(&mut key).is_err() {
+        for chunk in key.chunks_mut(8) {
+            chunk.copy_from_slice(&RandomState::new().build_hasher().finish().to_le_bytes());
+        }
+    }
+
+    key
+}
+
+/// Keyed fingerprint of a value, as recorded in the audit log.
+pub(crate) fn fingerprint(key: &[u8; 32], value: &str) -> String {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(value.as_bytes());
+
+    let digest = mac.finalize().into_bytes();
+
+    format!("hmac-sha256:{}", digest.iter().map(|byte| format!("{:02x}", byte)).collect::<String>())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use crate::audit::{self, AuditAction, AuditEntry};
+
+    const FINGERPRINT: &str = "hmac-sha256:9615a95d4a336118c435b9cd54c5e8644ab956b573aa2926274a1280b6674713";
+
+    #[test]
+    fn to_json() {
+        let mut entry = AuditEntry::new(AuditAction::Set, "APP_SECRET", Some(audit::fingerprint(&[0; 32], "a")), Some(".env"), "OverridePolicy::Never");
+        entry.timestamp = UNIX_EPOCH;
+
+        assert_eq!(entry.value_hash(), Some(FINGERPRINT));
+        assert_eq!(entry.to_json(), format!("{{\"timestamp\":\"1970-01-01T00:00:00Z\",\"action\":\"set\",\"key\":\"APP_SECRET\",\"value_hash\":\"{}\",\"source\":\".env\",\"policy\":\"OverridePolicy::Never\"}}", FINGERPRINT));
+        assert_eq!(entry.to_string(), "1970-01-01T00:00:00Z set APP_SECRET (OverridePolicy::Never) from .env");
+    }
+
+    #[test]
+    fn fingerprint_keys() {
+        let key = audit::fingerprint_key();
+
+        assert_ne!(key, audit::fingerprint_key());
+        assert_eq!(audit::fingerprint(&key, "a"), audit::fingerprint(&key, "a"));
+        assert_ne!(audit::fingerprint(&key, "a"), FINGERPRINT);
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::process::Command;
+
+use crate::{json, resolver, source, EnvValue, Exception};
+
+///
+/// Fetches the secrets of an Azure Key Vault whose name starts with the prefix, as pairs of environment variable names
+/// and values.
+///
+pub(crate) fn fetch_key_vault(vault: &str, prefix: &str) -> Result<Vec<(String, String)>, Exception> {
+    let unavailable = |reason: String| Exception::unavailable_source(vault.to_string(), reason);
+
+    let output = resolver::run(Command::new("az").args(["keyvault", "secret", "list", "--vault-name", vault, "--query", "[].name", "--output", "json"]))
+        .map_err(unavailable)?;
+
+    names(&output).map_err(unavailable)?.into_iter()
+        .filter(|name| name.starts_with(prefix))
+        .map(|name| {
+            let value = resolver::run(Command::new("az").args(["keyvault", "secret", "show", "--vault-name", vault, "--name", &name, "--query", "value", "--output", "tsv"]))
+                .map_err(unavailable)?;
+
+            Ok((source::key(prefix, &name), value))
+        })
+        .collect()
+}
+
+///
+/// Fetches the key-values of an Azure App Configuration store whose key starts with the prefix, with the label or,
+/// without one, with no label, as pairs of environment variable names and values.
+///
+pub(crate) fn fetch_app_config(store: &str, prefix: &str, label: Option<&str>) -> Result<Vec<(String, String)>, Exception> {
+    let unavailable = |reason: String| Exception::unavailable_source(store.to_string(), reason);
+
+    let output = resolver::run(Command::new("az").args([
+        "appconfig", "kv", "list", "--name", store, "--key", &format!("{}*", prefix), "--label", label.unwrap_or("\\0"), "--all",
+        "--query", "[].{key: key, value: value}", "--output", "json",
+    ])).map_err(unavailable)?;
+
+    key_values(prefix, &output).map_err(unavailable)
+}
+
+fn names(output: &str) -> Result<Vec<String>, String> {
+    match json::parse(output)? {
+        EnvValue::Array(names) => names.into_iter()
+            .map(|name| match name {
+                EnvValue::String(name) => Ok(name),
+                _ => Err("expected a list of secret names".to_string()),
+            })
+            .collect(),
+        _ => Err("expected a list of secret names".to_string()),
+    }
+}
+
+fn key_values(prefix: &str, output: &str) -> Result<Vec<(String, String)>, String> {
+    let key_values = match json::parse(output)? {
+        EnvValue::Array(key_values) => key_values,
+        _ => return Err("expected a list of key-values".to_string()),
+    };
+
+    key_values.into_iter()
+        .filter_map(|key_value| match key_value {
+            EnvValue::Object(mut key_value) => match (key_value.remove("key"), key_value.remove("value")) {
+                (Some(EnvValue::String(key)), Some(EnvValue::String(value))) => Some(Ok((source::key(prefix, &key), value))),
+                (Some(EnvValue::String(_)), Some(EnvValue::Null)) => None,
+                _ => Some(Err("expected key-values with a key and value".to_string())),
+            },
+            _ => Some(Err("expected a list of key-values".to_string())),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::azure;
+
+    #[test]
+    fn names() {
+        assert_eq!(azure::names(r#"["myapp-db-password", "other"]"#).unwrap(), vec!["myapp-db-password", "other"]);
+        assert!(azure::names("{}").is_err());
+    }
+
+    #[test]
+    fn key_values() {
+        let output = r#"[
+            {"key": "myapp:Logging:Level", "value": "debug"},
+            {"key": "myapp:FeatureFlag", "value": null}
+        ]"#;
+
+        assert_eq!(azure::key_values("myapp:", output).unwrap(), vec![("LOGGING_LEVEL".to_string(), "debug".to_string())]);
+        assert!(azure::key_values("myapp:", r#"[{"value": "debug"}]"#).is_err());
+    }
+}
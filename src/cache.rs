@@ -0,0 +1,167 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::Exception;
+use crate::hash::Fnv1a;
+use crate::parser::{self, ParserOptions, Statement};
+
+/// Number of files whose statements are kept, after which the least recently parsed file is evicted.
+const MAX_FILES: usize = 64;
+
+/// Size of the largest file whose statements are kept.
+const MAX_FILE_SIZE: usize = 1024 * 1024;
+
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+
+/// Number of hits and misses of the process-wide cache of parsed files, as returned by `Dotenv::cache_stats()`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    hits: u64,
+    misses: u64,
+}
+
+impl CacheStats {
+    /// Number of files whose statements were taken from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Number of files that were parsed because they were not cached or their contents had changed.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+}
+
+#[derive(Default)]
+struct Cache {
+    files: HashMap<String, ParsedFile>,
+    generation: u64,
+    stats: CacheStats,
+}
+
+struct ParsedFile {
+    hash: u64,
+    len: usize,
+    options: ParserOptions,
+    statements: Vec<Statement>,
+    generation: u64,
+}
+
+fn cache() -> MutexGuard<'static, Option<Cache>> {
+    CACHE.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+///
+/// Parses the contents of a file, reusing the statements of an earlier parse if the contents and the options are the
+/// same. The cache is keyed on a hash of the contents rather than the modification time, so a file rewritten within
+/// the granularity of the modification time is never served stale.
+///
+pub(crate) fn parse_statements(data: String, path: &str, options: &ParserOptions) -> Result<Vec<Statement>, Exception> {
+    if data.len() > MAX_FILE_SIZE {
+        return parser::parse_statements(data, options);
+    }
+
+    let mut hasher = Fnv1a::new();
+    hasher.write(data.as_bytes());
+    let hash = hasher.finish();
+    let len = data.len();
+
+    {
+        let mut cache = cache();
+        let cache = cache.get_or_insert_with(Cache::default);
+        cache.generation += 1;
+        let generation = cache.generation;
+
+        if let Some(parsed) = cache.files.get_mut(path).filter(|parsed| parsed.hash == hash && parsed.len == len && parsed.options == *options) {
+            parsed.generation = generation;
+            let statements = parsed.statements.clone();
+            cache.stats.hits += 1;
+
+            return Ok(statements);
+        }
+    }
+
+    let statements = parser::parse_statements(data, options)?;
+    let mut cache = cache();
+    let cache = cache.get_or_insert_with(Cache::default);
+    cache.stats.misses += 1;
+    cache.generation += 1;
+
+    if cache.files.len() >= MAX_FILES && !cache.files.contains_key(path) {
+        let oldest = cache.files.iter().min_by_key(|(_, parsed)| parsed.generation).map(|(path, _)| path.clone());
+
+        if let Some(oldest) = oldest {
+            cache.files.remove(&oldest);
+        }
+    }
+
+    let parsed = ParsedFile { hash, len, options: options.clone(), statements: statements.clone(), generation: cache.generation };
+    cache.files.insert(path.to_string(), parsed);
+
+    Ok(statements)
+}
+
+/// Hits and misses of the cache since it was last cleared.
+pub(crate) fn stats() -> CacheStats {
+    cache().as_ref().map(|cache| cache.stats).unwrap_or_default()
+}
+
+/// Empties the cache and resets its stats.
+pub(crate) fn clear() {
+    *cache() = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::Dotenv;
+    use crate::temp_dir::TempDir;
+
+    #[test]
+    fn cached_loads() {
+        let directory = TempDir::new("cache");
+        let path = directory.join(".env");
+        let path = path.to_string_lossy().to_string();
+        fs::write(&path, "DOTENV_TEST_CACHE=first").unwrap();
+
+        let mut dotenv = Dotenv::new().cache(true);
+        dotenv.overload(&path).unwrap();
+        let hits = Dotenv::cache_stats().hits();
+
+        dotenv.overload(&path).unwrap();
+        assert!(Dotenv::cache_stats().hits() > hits);
+        assert_eq!(dotenv.values()["DOTENV_TEST_CACHE"], "first");
+
+        fs::write(&path, "DOTENV_TEST_CACHE=changed").unwrap();
+        dotenv.overload(&path).unwrap();
+        assert_eq!(dotenv.values()["DOTENV_TEST_CACHE"], "changed");
+
+        // Same size, and likely the same modification time.
+        fs::write(&path, "DOTENV_TEST_CACHE=chanGED").unwrap();
+        dotenv.overload(&path).unwrap();
+        assert_eq!(dotenv.values()["DOTENV_TEST_CACHE"], "chanGED");
+
+        let mut dotenv = Dotenv::new();
+        fs::write(&path, "DOTENV_TEST_CACHE=uncached").unwrap();
+        dotenv.overload(&path).unwrap();
+        assert_eq!(dotenv.values()["DOTENV_TEST_CACHE"], "uncached");
+    }
+}
@@ -0,0 +1,215 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Passing environment variables to child processes without loading them into the environment of the current process.
+//!
+//! `CommandExt` extends `std::process::Command`, so task runners can run every command with its own environment file
+//! without the variables leaking into the runner or into the other commands. With the `tokio` feature, it extends
+//! `tokio::process::Command` as well, and with the `duct` feature, `ExpressionExt` extends `duct` expressions.
+
+use std::process::Command;
+
+use crate::{Dotenv, EnvMap, Exception};
+
+/// Extension of `std::process::Command` setting the environment variables of the child process from an environment file
+pub trait CommandExt {
+    ///
+    /// Sets the variables of an environment file in the environment of the child process, overriding the variables
+    /// it would inherit. The environment of the current process is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::process::Command;
+    /// use darkweb_dotenv::command::CommandExt;
+    ///
+    /// let status = Command::new("sh")
+    ///     .arg("-c")
+    ///     .arg("echo $APP_NAME")
+    ///     .envs_from_dotenv(".env")
+    ///     .unwrap()
+    ///     .status()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    fn envs_from_dotenv<Path>(&mut self, path: Path) -> Result<&mut Self, Exception>
+        where
+            Path: AsRef<str>;
+
+    ///
+    /// Sets the variables of a map in the environment of the child process, such as one resolved by a configured
+    /// `Dotenv` instance with `Dotenv::env_map()`.
+    ///
+    fn envs_from_map(&mut self, map: &EnvMap) -> &mut Self;
+}
+
+impl CommandExt for Command {
+    fn envs_from_dotenv<Path>(&mut self, path: Path) -> Result<&mut Self, Exception>
+        where
+            Path: AsRef<str> {
+
+        let map = Dotenv::new().env_map(path)?;
+
+        Ok(self.envs_from_map(&map))
+    }
+
+    fn envs_from_map(&mut self, map: &EnvMap) -> &mut Self {
+        self.envs(map.as_map())
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl CommandExt for tokio::process::Command {
+    fn envs_from_dotenv<Path>(&mut self, path: Path) -> Result<&mut Self, Exception>
+        where
+            Path: AsRef<str> {
+
+        let map = Dotenv::new().env_map(path)?;
+
+        Ok(self.envs_from_map(&map))
+    }
+
+    fn envs_from_map(&mut self, map: &EnvMap) -> &mut Self {
+        self.envs(map.as_map())
+    }
+}
+
+/// Extension of `duct` expressions setting the environment variables of the child processes from an environment file
+///
+/// Requires the `duct` feature.
+#[cfg(feature = "duct")]
+pub trait ExpressionExt {
+    ///
+    /// Returns the expression with the variables of an environment file set in the environment of its child
+    /// processes. The environment of the current process is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::command::ExpressionExt;
+    ///
+    /// let output = duct::cmd!("sh", "-c", "echo $APP_NAME")
+    ///     .envs_from_dotenv(".env")
+    ///     .unwrap()
+    ///     .read()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    fn envs_from_dotenv<Path>(&self, path: Path) -> Result<duct::Expression, Exception>
+        where
+            Path: AsRef<str>;
+
+    ///
+    /// Returns the expression with the variables of a map set in the environment of its child processes, such as one
+    /// resolved by `Dotenv::layered_env_map()`.
+    ///
+    fn envs_from_map(&self, map: &EnvMap) -> duct::Expression;
+}
+
+#[cfg(feature = "duct")]
+impl ExpressionExt for duct::Expression {
+    fn envs_from_dotenv<Path>(&self, path: Path) -> Result<duct::Expression, Exception>
+        where
+            Path: AsRef<str> {
+
+        let map = Dotenv::new().env_map(path)?;
+
+        Ok(self.envs_from_map(&map))
+    }
+
+    fn envs_from_map(&self, map: &EnvMap) -> duct::Expression {
+        map.as_map().iter().fold(self.clone(), |expression, (key, value)| expression.env(key, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::process::Command;
+
+    use crate::{Dotenv, ExceptionKind};
+    use crate::command::CommandExt;
+    use crate::temp_dir::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn envs_from_dotenv() {
+        let directory = TempDir::new("command");
+        let path = directory.join(".env");
+        fs::write(&path, "DOTENV_TEST_COMMAND=\"hello child\"").unwrap();
+
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg("printf %s \"$DOTENV_TEST_COMMAND\"")
+            .envs_from_dotenv(path.to_string_lossy())
+            .unwrap()
+            .output()
+            .unwrap();
+
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "hello child");
+        assert!(env::var("DOTENV_TEST_COMMAND").is_err());
+    }
+
+    #[test]
+    fn envs_from_missing_dotenv() {
+        let exception = Command::new("sh").envs_from_dotenv("dotenv-test-command-missing.env").unwrap_err();
+
+        assert_eq!(exception.kind(), ExceptionKind::Path);
+    }
+
+    #[test]
+    fn envs_from_layered_env_map() {
+        let directory = TempDir::new("command-layered");
+        let path = directory.write(".env", "DOTENV_TEST_COMMAND_LAYERED=default\nDOTENV_TEST_COMMAND_LAYERED_NAME=app");
+        directory.write(".env.test", "DOTENV_TEST_COMMAND_LAYERED=test");
+
+        let map = Dotenv::new().layered_env_map(&path, "DOTENV_TEST_COMMAND_LAYERED_ENV", "test").unwrap();
+        assert_eq!(map.get("DOTENV_TEST_COMMAND_LAYERED"), Some("test"));
+        assert_eq!(map.get("DOTENV_TEST_COMMAND_LAYERED_NAME"), Some("app"));
+        assert!(env::var("DOTENV_TEST_COMMAND_LAYERED").is_err());
+
+        let mut command = Command::new("sh");
+        command.envs_from_map(&map);
+        assert!(command.get_envs().any(|(key, value)| key == "DOTENV_TEST_COMMAND_LAYERED" && value == Some("test".as_ref())));
+
+        #[cfg(feature = "tokio")]
+        {
+            let mut command = tokio::process::Command::new("sh");
+            command.envs_from_map(&map);
+            assert!(command.as_std().get_envs().any(|(key, value)| key == "DOTENV_TEST_COMMAND_LAYERED" && value == Some("test".as_ref())));
+        }
+
+        #[cfg(all(unix, feature = "duct"))]
+        {
+            use crate::command::ExpressionExt;
+
+            let output = duct::cmd!("sh", "-c", "printf %s \"$DOTENV_TEST_COMMAND_LAYERED\"").envs_from_map(&map).read().unwrap();
+            assert_eq!(output, "test");
+        }
+    }
+}
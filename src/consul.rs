@@ -0,0 +1,155 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::time::Duration;
+
+use crate::{base64, json, source, EnvValue, Exception};
+
+///
+/// Fetches the keys under a prefix of the Consul KV store as pairs of environment variable names and values, along
+/// with the index of the result. With an index, the request blocks until the result changes or the wait time expires.
+///
+pub(crate) fn fetch(url: &str, prefix: &str, index: Option<u64>, wait: Duration, bearer_token: Option<&str>) -> Result<(Vec<(String, String)>, u64), Exception> {
+    let mut request_url = format!("{}/v1/kv/{}?recurse=true", url.trim_end_matches('/'), prefix.trim_start_matches('/'));
+    let mut timeout = wait;
+
+    if let Some(index) = index {
+        request_url.push_str(&format!("&index={}&wait={}s", index, wait.as_secs().max(1)));
+        // Consul adds up to wait / 16 of jitter to blocking queries
+        timeout = wait + wait / 16 + Duration::from_secs(5);
+    }
+
+    let unavailable = |reason: String| Exception::unavailable_source(request_url.clone(), reason);
+
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .http_status_as_error(false)
+        .build()
+        .into();
+
+    let mut request = agent.get(&request_url);
+
+    if let Some(token) = bearer_token {
+        request = request.header("Authorization", &format!("Bearer {}", token));
+    }
+
+    let mut response = request.call().map_err(|error| unavailable(error.to_string()))?;
+
+    // Without the index, a blocking query would return immediately, and watching would spin
+    let next_index = response.headers().get("X-Consul-Index")
+        .and_then(|index| index.to_str().ok()?.parse::<u64>().ok())
+        .ok_or_else(|| unavailable("missing or invalid X-Consul-Index header".to_string()))?;
+
+    let body = match response.status().as_u16() {
+        200 => response.body_mut().read_to_string().map_err(|error| unavailable(error.to_string()))?,
+        404 => return Ok((Vec::new(), next_index)),
+        status => return Err(unavailable(format!("http status: {}", status))),
+    };
+
+    Ok((entries(prefix, &body).map_err(unavailable)?, next_index))
+}
+
+fn entries(prefix: &str, body: &str) -> Result<Vec<(String, String)>, String> {
+    let entries = match json::parse(body)? {
+        EnvValue::Array(entries) => entries,
+        _ => return Err("expected a list of entries".to_string()),
+    };
+
+    entries.into_iter()
+        .filter_map(|entry| match entry {
+            EnvValue::Object(mut entry) => match (entry.remove("Key"), entry.remove("Value")) {
+                (Some(EnvValue::String(key)), _) if key.ends_with('/') => None,
+                (Some(EnvValue::String(key)), Some(EnvValue::Null)) => Some(Ok((source::key(prefix, &key), String::new()))),
+                (Some(EnvValue::String(key)), Some(EnvValue::String(value))) => Some(
+                    base64::decode(&value)
+                        .and_then(|value| String::from_utf8(value).ok())
+                        .map(|value| (source::key(prefix, &key), value))
+                        .ok_or_else(|| format!("the value of \"{}\" is not valid UTF-8", key)),
+                ),
+                _ => Some(Err("expected entries with a key and value".to_string())),
+            },
+            _ => Some(Err("expected a list of entries".to_string())),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::consul;
+
+    #[test]
+    fn entries() {
+        let body = r#"[
+            {"Key": "myapp/prod/", "Value": null, "ModifyIndex": 10},
+            {"Key": "myapp/prod/db/password", "Value": "c2VjcmV0", "ModifyIndex": 12},
+            {"Key": "myapp/prod/debug", "Value": null, "ModifyIndex": 14}
+        ]"#;
+
+        assert_eq!(consul::entries("myapp/prod/", body).unwrap(), vec![
+            ("DB_PASSWORD".to_string(), "secret".to_string()),
+            ("DEBUG".to_string(), String::new()),
+        ]);
+        assert!(consul::entries("myapp/prod/", r#"[{"Key": "myapp/prod/db", "Value": "not base64!"}]"#).is_err());
+    }
+
+    #[test]
+    fn fetch() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 4096];
+            let length = stream.read(&mut request).unwrap();
+            let body = r#"[{"Key": "myapp/db/user", "Value": "cm9vdA=="}]"#;
+
+            write!(stream, "HTTP/1.1 200 OK\r\nX-Consul-Index: 42\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body).unwrap();
+
+            String::from_utf8_lossy(&request[..length]).to_string()
+        });
+
+        let (entries, index) = consul::fetch(&url, "myapp/", Some(7), Duration::from_secs(5), Some("secret")).unwrap();
+        assert_eq!(entries, vec![("DB_USER".to_string(), "root".to_string())]);
+        assert_eq!(index, 42);
+
+        let request = handle.join().unwrap();
+        assert!(request.starts_with("GET /v1/kv/myapp/?recurse=true&index=7&wait=5s "));
+        assert!(request.to_lowercase().contains("authorization: bearer secret"));
+    }
+
+    #[test]
+    fn fetch_without_index() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.read(&mut [0; 4096]).unwrap();
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n[]").unwrap();
+        });
+
+        let exception = consul::fetch(&url, "myapp/", None, Duration::from_secs(5), None).unwrap_err();
+        assert!(exception.to_string().contains("X-Consul-Index"));
+
+        handle.join().unwrap();
+    }
+}
@@ -0,0 +1,286 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Transformation of `prefix:value` values through user-registered decorators.
+//!
+//! `Base64Decorator` decodes base64 values, `FileDecorator` reads the value from a file, like the secrets Docker
+//! mounts in `/run/secrets`, and `ResolveDecorator` resolves relative paths against the directory of the environment
+//! file. With the `generate` feature, `GenerateDecorator` generates random secrets. Decorators are opt-in: values are
+//! only transformed for registered prefixes.
+
+use std::{env, fs};
+#[cfg(feature = "generate")]
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+use crate::base64;
+
+/// Decorator of `prefix:value` values
+pub trait ValueDecorator {
+    ///
+    /// Transforms the part of a value after `prefix:`.
+    ///
+    fn decorate(&self, value: &str) -> Result<String, String>;
+
+    ///
+    /// Transforms the part of the value of a variable after `prefix:`, knowing the name of the variable and the path of
+    /// the environment file that defines it, if any. Defaults to `decorate()`.
+    ///
+    fn decorate_entry(&self, _key: &str, value: &str, _path: Option<&str>) -> Result<String, String> {
+        self.decorate(value)
+    }
+}
+
+impl<F> ValueDecorator for F
+    where
+        F: Fn(&str) -> Result<String, String> {
+
+    fn decorate(&self, value: &str) -> Result<String, String> {
+        self(value)
+    }
+}
+
+/// Decorator that decodes standard base64 values, as in `base64:SGVsbG8=`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Base64Decorator;
+
+impl ValueDecorator for Base64Decorator {
+    fn decorate(&self, value: &str) -> Result<String, String> {
+        let bytes = base64::decode(value.trim()).ok_or_else(|| "the value is not valid base64".to_string())?;
+
+        String::from_utf8(bytes).map_err(|_| "the decoded value is not valid UTF-8".to_string())
+    }
+}
+
+/// Decorator that reads the value from a file, as in `file:/run/secrets/db_pass`, without trailing line breaks
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FileDecorator;
+
+impl ValueDecorator for FileDecorator {
+    fn decorate(&self, value: &str) -> Result<String, String> {
+        let content = fs::read_to_string(value).map_err(|error| format!("unable to read \"{}\": {}", value, error))?;
+
+        Ok(content.trim_end_matches(['\r', '\n']).to_string())
+    }
+}
+
+/// Decorator that resolves a relative path against the directory of the environment file that defines it, as in
+/// `resolve:./certs/dev.pem`, instead of the working directory. Values loaded from a string are resolved against the
+/// working directory.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResolveDecorator;
+
+impl ValueDecorator for ResolveDecorator {
+    fn decorate(&self, value: &str) -> Result<String, String> {
+        self.decorate_entry("", value, None)
+    }
+
+    fn decorate_entry(&self, _key: &str, value: &str, path: Option<&str>) -> Result<String, String> {
+        let directory = path.and_then(|path| Path::new(path).parent()).unwrap_or_else(|| Path::new(""));
+
+        let directory = match directory.is_absolute() {
+            true => directory.to_path_buf(),
+            false => env::current_dir()
+                .map_err(|error| format!("unable to determine the working directory: {}", error))?
+                .join(directory),
+        };
+
+        let resolved: PathBuf = directory.join(value).components()
+            .filter(|component| *component != Component::CurDir)
+            .collect();
+
+        Ok(resolved.to_string_lossy().to_string())
+    }
+}
+
+/// Decorator that generates a random value at load time, as in `generate:hex:32`, `generate:base64:32` and
+/// `generate:uuid`, where the number is the number of random bytes
+///
+/// With persistence, generated values are appended to the `.local` file of the environment file that defines them,
+/// such as `.env.local` for `.env`, and reused on later loads.
+#[cfg(feature = "generate")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GenerateDecorator {
+    persist: bool,
+}
+
+#[cfg(feature = "generate")]
+impl GenerateDecorator {
+    ///
+    /// Creates a decorator that generates a new value on every load.
+    ///
+    pub fn new() -> Self {
+        Self { persist: false }
+    }
+
+    ///
+    /// Persists the generated values to the `.local` file of the environment file that defines them, so later loads
+    /// reuse them. Values of `.local` files and values loaded from a string are not persisted.
+    ///
+    /// # Examples
+    ///
+    /// ```dotenv
+    /// # .env
+    /// APP_SECRET=generate:hex:32
+    /// ```
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    /// use darkweb_dotenv::decorator::GenerateDecorator;
+    ///
+    /// let mut dotenv = Dotenv::new().decorator("generate", GenerateDecorator::new().persist(true));
+    /// dotenv.load_env(".env", "APP_ENV", "dev").unwrap();
+    /// ```
+    ///
+    pub fn persist(mut self, persist: bool) -> Self {
+        self.persist = persist;
+        self
+    }
+
+    fn generate(format: &str) -> Result<String, String> {
+        let (kind, length) = match format.split_once(':') {
+            Some((kind, length)) => {
+                let length = length.parse::<usize>().ok()
+                    .filter(|length| (1..=1024).contains(length))
+                    .ok_or_else(|| format!("invalid length \"{}\", expected a number of bytes from 1 to 1024", length))?;
+
+                (kind, length)
+            },
+            None => (format, 16),
+        };
+
+        let mut bytes = vec![0; length];
+        getrandom::getrandom(&mut bytes).map_err(|error| format!("unable to generate random bytes: {}", error))?;
+
+        match kind {
+            "hex" => Ok(hex(&bytes)),
+            "base64" => Ok(base64::encode(&bytes)),
+            "uuid" if length == 16 => {
+                bytes[6] = bytes[6] & 0x0f | 0x40;
+                bytes[8] = bytes[8] & 0x3f | 0x80;
+
+                let hex = hex(&bytes);
+
+                Ok(format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..]))
+            },
+            "uuid" => Err("a uuid has no length".to_string()),
+            _ => Err(format!("unknown format \"{}\", expected hex, base64 or uuid", kind)),
+        }
+    }
+}
+
+#[cfg(feature = "generate")]
+impl ValueDecorator for GenerateDecorator {
+    fn decorate(&self, value: &str) -> Result<String, String> {
+        Self::generate(value)
+    }
+
+    fn decorate_entry(&self, key: &str, value: &str, path: Option<&str>) -> Result<String, String> {
+        let local_path = match path {
+            Some(path) if self.persist && !path.ends_with(".local") => format!("{}.local", path),
+            _ => return Self::generate(value),
+        };
+
+        let local_data = fs::read_to_string(&local_path).unwrap_or_default();
+        let local_values = crate::parse(&local_data).map_err(|exception| format!("unable to parse \"{}\": {}", local_path, exception))?;
+
+        if let Some(persisted_value) = local_values.get(key) {
+            return Ok(persisted_value.clone());
+        }
+
+        let generated_value = Self::generate(value)?;
+        let separator = if local_data.is_empty() || local_data.ends_with('\n') { "" } else { "\n" };
+
+        crate::dotenv::private_file_options().create(true).append(true).open(&local_path)
+            .and_then(|mut file| writeln!(file, "{}{}={}", separator, key, generated_value))
+            .map_err(|error| format!("unable to persist the value to \"{}\": {}", local_path, error))?;
+
+        Ok(generated_value)
+    }
+}
+
+#[cfg(feature = "generate")]
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use crate::decorator::{Base64Decorator, FileDecorator, ResolveDecorator, ValueDecorator};
+    use crate::temp_dir::TempDir;
+
+    #[test]
+    fn base64_decorator() {
+        assert_eq!(Base64Decorator.decorate("SGVsbG8=").unwrap(), "Hello");
+        assert!(Base64Decorator.decorate("not base64!").is_err());
+    }
+
+    #[test]
+    fn file_decorator() {
+        let directory = TempDir::new("file-decorator");
+        let path = directory.join(".env");
+        fs::write(&path, "secret\n").unwrap();
+
+        assert_eq!(FileDecorator.decorate(&path.to_string_lossy()).unwrap(), "secret");
+
+        fs::remove_file(&path).unwrap();
+        assert!(FileDecorator.decorate(&path.to_string_lossy()).is_err());
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn generate_decorator() {
+        use crate::decorator::GenerateDecorator;
+
+        assert_eq!(GenerateDecorator::new().decorate("hex:32").unwrap().len(), 64);
+        assert_ne!(GenerateDecorator::new().decorate("hex:32").unwrap(), GenerateDecorator::new().decorate("hex:32").unwrap());
+        assert_eq!(crate::base64::decode(&GenerateDecorator::new().decorate("base64:24").unwrap()).unwrap().len(), 24);
+
+        let uuid = GenerateDecorator::new().decorate("uuid").unwrap();
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(&uuid[14..15], "4");
+
+        assert!(GenerateDecorator::new().decorate("hex").is_ok());
+        assert!(GenerateDecorator::new().decorate("hex:0").is_err());
+        assert!(GenerateDecorator::new().decorate("uuid:8").is_err());
+        assert!(GenerateDecorator::new().decorate("octal:8").is_err());
+
+        let directory = TempDir::new("generate-decorator");
+        fs::write(directory.join(".env.local"), "DEBUG=1").unwrap();
+        let path = directory.join(".env").to_string_lossy().to_string();
+
+        let decorator = GenerateDecorator::new().persist(true);
+        let secret = decorator.decorate_entry("APP_SECRET", "hex:32", Some(&path)).unwrap();
+        assert_eq!(decorator.decorate_entry("APP_SECRET", "hex:32", Some(&path)).unwrap(), secret);
+        assert_eq!(fs::read_to_string(directory.join(".env.local")).unwrap(), format!("DEBUG=1\nAPP_SECRET={}\n", secret));
+    }
+
+    #[test]
+    fn resolve_decorator() {
+        let directory = env::temp_dir().join("config");
+        let path = directory.join(".env").to_string_lossy().to_string();
+
+        assert_eq!(ResolveDecorator.decorate_entry("TLS_CERT", "./certs/dev.pem", Some(&path)).unwrap(), directory.join("certs").join("dev.pem").to_string_lossy());
+        assert_eq!(ResolveDecorator.decorate_entry("TLS_CERT", "../data", Some(&path)).unwrap(), directory.join("..").join("data").to_string_lossy());
+        assert_eq!(ResolveDecorator.decorate("certs").unwrap(), env::current_dir().unwrap().join("certs").to_string_lossy());
+
+        let absolute = env::temp_dir().join("dev.pem").to_string_lossy().to_string();
+        assert_eq!(ResolveDecorator.decorate_entry("TLS_CERT", &absolute, Some(&path)).unwrap(), absolute);
+    }
+}
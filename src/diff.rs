@@ -0,0 +1,162 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::json;
+
+/// Differences between two sets of environment variables
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnvDiff {
+    added: BTreeMap<String, String>,
+    removed: BTreeMap<String, String>,
+    changed: BTreeMap<String, (String, String)>,
+}
+
+impl EnvDiff {
+    const MASK: &'static str = "****";
+
+    pub(crate) fn new(old: &HashMap<String, String>, new: &HashMap<String, String>) -> Self {
+        let mut diff = Self::default();
+
+        for (name, old_value) in old.iter() {
+            match new.get(name) {
+                Some(new_value) if new_value != old_value => {
+                    diff.changed.insert(name.clone(), (old_value.clone(), new_value.clone()));
+                },
+                Some(_) => {},
+                None => {
+                    diff.removed.insert(name.clone(), old_value.clone());
+                },
+            }
+        }
+
+        for (name, new_value) in new.iter() {
+            if !old.contains_key(name) {
+                diff.added.insert(name.clone(), new_value.clone());
+            }
+        }
+
+        diff
+    }
+
+    /// Variables only present in the second file.
+    pub fn added(&self) -> &BTreeMap<String, String> {
+        &self.added
+    }
+
+    /// Variables only present in the first file.
+    pub fn removed(&self) -> &BTreeMap<String, String> {
+        &self.removed
+    }
+
+    /// Variables present in both files with different values, as `(old, new)` pairs.
+    pub fn changed(&self) -> &BTreeMap<String, (String, String)> {
+        &self.changed
+    }
+
+    /// Whether both files define exactly the same variables.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    ///
+    /// Returns a copy of the diff with all values masked, so it can be shown without leaking secrets.
+    ///
+    pub fn masked(&self) -> Self {
+        let mask = |values: &BTreeMap<String, String>| {
+            values.keys().map(|name| (name.clone(), Self::MASK.to_string())).collect()
+        };
+
+        Self {
+            added: mask(&self.added),
+            removed: mask(&self.removed),
+            changed: self.changed.keys()
+                .map(|name| (name.clone(), (Self::MASK.to_string(), Self::MASK.to_string())))
+                .collect(),
+        }
+    }
+
+    ///
+    /// Renders the diff as a JSON object with `added`, `removed` and `changed` members.
+    ///
+    pub fn to_json(&self) -> String {
+        let values = |values: &BTreeMap<String, String>| {
+            values.iter()
+                .map(|(name, value)| format!("{}:{}", json::string(name), json::string(value)))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        let changed = self.changed.iter()
+            .map(|(name, (old, new))| format!("{}:{{\"old\":{},\"new\":{}}}", json::string(name), json::string(old), json::string(new)))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!("{{\"added\":{{{}}},\"removed\":{{{}}},\"changed\":{{{}}}}}", values(&self.added), values(&self.removed), changed)
+    }
+}
+
+impl fmt::Display for EnvDiff {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for (name, value) in self.added.iter() {
+            writeln!(formatter, "+ {}={}", name, value)?;
+        }
+
+        for (name, value) in self.removed.iter() {
+            writeln!(formatter, "- {}={}", name, value)?;
+        }
+
+        for (name, (old, new)) in self.changed.iter() {
+            writeln!(formatter, "~ {}: {} -> {}", name, old, new)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::EnvDiff;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn new_reports_added_removed_and_changed() {
+        let diff = EnvDiff::new(&values(&[("FOO", "1"), ("BAR", "2")]), &values(&[("FOO", "3"), ("BAZ", "4")]));
+        assert_eq!(diff.added().get("BAZ").unwrap(), "4");
+        assert_eq!(diff.removed().get("BAR").unwrap(), "2");
+        assert_eq!(diff.changed().get("FOO").unwrap(), &("1".to_string(), "3".to_string()));
+    }
+
+    #[test]
+    fn masked_hides_values() {
+        let diff = EnvDiff::new(&values(&[("FOO", "secret")]), &values(&[("FOO", "other")])).masked();
+        assert_eq!(diff.to_string(), "~ FOO: **** -> ****\n");
+    }
+
+    #[test]
+    fn to_json() {
+        let diff = EnvDiff::new(&values(&[("FOO", "1")]), &values(&[("FOO", "2"), ("BAR", "\"")]));
+        assert_eq!(diff.to_json(), "{\"added\":{\"BAR\":\"\\\"\"},\"removed\":{},\"changed\":{\"FOO\":{\"old\":\"1\",\"new\":\"2\"}}}");
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+///
+/// Renders a variable as a line of a `docker run --env-file` file, which takes everything after the first `=` as the
+/// literal value, without processing quotes or escapes, and cannot continue a value on the next line.
+///
+pub(crate) fn render_entry(name: &str, value: &str) -> Result<String, &'static str> {
+    if name.is_empty() || name.starts_with('#') || name.contains(|character: char| character.is_whitespace() || character == '=') {
+        return Err("its name is not accepted by Docker");
+    }
+
+    if value.contains(['\n', '\r']) {
+        return Err("Docker env files cannot contain multi-line values");
+    }
+
+    if value.contains('\0') {
+        return Err("Docker env files cannot contain NUL characters");
+    }
+
+    Ok(format!("{}={}", name, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::docker;
+
+    #[test]
+    fn render_entry() {
+        assert_eq!(docker::render_entry("GREETING", "\"hello world\" # not a comment").unwrap(), "GREETING=\"hello world\" # not a comment");
+        assert_eq!(docker::render_entry("EMPTY", "").unwrap(), "EMPTY=");
+        assert!(docker::render_entry("CERT", "line 1\nline 2").is_err());
+        assert!(docker::render_entry("CERT", "line 1\r").is_err());
+        assert!(docker::render_entry("#KEY", "value").is_err());
+        assert!(docker::render_entry("MY KEY", "value").is_err());
+    }
+}
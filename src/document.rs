@@ -0,0 +1,630 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::HashMap;
+
+use crate::{format, secrets, Entry, EntrySpans, Exception, ExceptionKind, KeySpec, QuoteStyle};
+use crate::format::Line;
+use crate::parser::{self, ParserOptions, Statement};
+
+///
+/// Environment file parsed together with the comments documenting its variables, as returned by
+/// `Dotenv::parse_document()`.
+///
+/// The document consists of the entries declaring the variables, and the trivia in between, like comments and blank
+/// lines. The map of variables as loaded is derived from the entries with `to_map()`.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DotenvDocument {
+    source: String,
+    entries: Vec<Entry>,
+    specs: HashMap<String, KeySpec>,
+    trivia: Vec<Trivia>,
+    statements: Vec<Statement>,
+    options: ParserOptions,
+    quote_style: QuoteStyle,
+}
+
+/// Line of an environment file that does not declare a variable, like a comment, a blank line or a section header
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trivia {
+    line_number: usize,
+    text: String,
+}
+
+impl DotenvDocument {
+    pub(crate) fn parse(source: String, options: ParserOptions) -> Result<Self, Exception> {
+        let statements = parser::parse_statements(&source, &options)?;
+
+        Ok(Self { options, ..Self::new(statements, source) })
+    }
+
+    pub(crate) fn new(statements: Vec<Statement>, source: String) -> Self {
+        let mut entries: HashMap<String, Entry> = HashMap::new();
+        let mut last_assignment_line_number = None;
+        let mut specs = HashMap::new();
+        let mut comments: Vec<String> = Vec::new();
+        let mut last_line_number = 0;
+
+        for statement in statements.iter().cloned() {
+            match statement {
+                Statement::Comment(comment, line_number) => {
+                    if last_assignment_line_number == Some(line_number) {
+                        continue;
+                    }
+
+                    if line_number != last_line_number + 1 {
+                        comments.clear();
+                    }
+
+                    comments.push(comment);
+                    last_line_number = line_number;
+                },
+                Statement::Assignment(name, value, line_number, spans) => {
+                    if line_number != last_line_number + 1 {
+                        comments.clear();
+                    }
+
+                    let mut spec = KeySpec::default();
+                    comments.retain(|comment| spec.parse_line(comment).is_none());
+
+                    let comment = match comments.is_empty() {
+                        true => None,
+                        false => Some(comments.join("\n")),
+                    };
+
+                    specs.insert(name.clone(), spec);
+                    entries.insert(name.clone(), Entry::new(name, value, line_number, spans, &source).with_comment(comment));
+                    last_assignment_line_number = Some(line_number);
+                    comments.clear();
+                    last_line_number = line_number;
+                },
+                Statement::Unset(name, line_number) => {
+                    entries.remove(&name);
+                    specs.remove(&name);
+                    comments.clear();
+                    last_line_number = line_number;
+                },
+                Statement::Include(_, line_number) | Statement::Section(_, line_number) => {
+                    comments.clear();
+                    last_line_number = line_number;
+                },
+            }
+        }
+
+        let mut entries: Vec<Entry> = entries.into_values().collect();
+        entries.sort_by_key(Entry::line_number);
+
+        let trivia = Trivia::of(&statements, &source);
+
+        Self {
+            source,
+            entries,
+            specs,
+            trivia,
+            statements,
+            options: ParserOptions { comments: true, ..ParserOptions::default() },
+            quote_style: QuoteStyle::Preserve,
+        }
+    }
+
+    ///
+    /// Sets the quoting of the values written by `set()`, which defaults to `QuoteStyle::Preserve`: a changed value keeps
+    /// the quotes of the value it replaces, and other values are only quoted if needed. Values that are not changed are
+    /// always kept as written, so diffs stay small.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::{DotenvDocument, QuoteStyle};
+    ///
+    /// let mut document = DotenvDocument::default();
+    /// document.set_quote_style(QuoteStyle::AlwaysDouble);
+    /// document.set("GREETING", "hello").unwrap();
+    ///
+    /// assert_eq!(document.source(), "GREETING=\"hello\"\n");
+    /// ```
+    ///
+    pub fn set_quote_style(&mut self, quote_style: QuoteStyle) {
+        self.quote_style = quote_style;
+    }
+
+    /// Names of the variables, in the order of the file.
+    pub fn keys(&self) -> Vec<&str> {
+        self.entries.iter().map(Entry::key).collect()
+    }
+
+    ///
+    /// Entries declaring the variables, in the order of the file. Only the last declaration of a variable, which is the
+    /// one that is loaded by default, is included.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::{DotenvDocument, Quoting};
+    ///
+    /// let mut document = DotenvDocument::default();
+    /// document.set("GREETING", "hello world").unwrap();
+    ///
+    /// let entry = &document.entries()[0];
+    ///
+    /// assert_eq!((entry.key(), entry.value(), entry.quoting()), ("GREETING", "hello world", Quoting::Single));
+    /// ```
+    ///
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    ///
+    /// Lines that do not declare a variable, in the order of the file, like comments, blank lines, section headers and
+    /// directives. Comments documenting a variable are included as well.
+    ///
+    pub fn trivia(&self) -> &[Trivia] {
+        &self.trivia
+    }
+
+    ///
+    /// Returns the variables as loaded by default, by name.
+    ///
+    pub fn to_map(&self) -> HashMap<String, String> {
+        self.entries.iter()
+            .map(|entry| (entry.key().to_string(), entry.value().to_string()))
+            .collect()
+    }
+
+    /// Returns the value of a variable.
+    pub fn get<Key>(&self, key: Key) -> Option<&str>
+        where
+            Key: AsRef<str> {
+
+        self.entry(key.as_ref()).map(Entry::value)
+    }
+
+    ///
+    /// Returns the comment lines directly above a variable, without the leading `#` and the annotation lines. Multiple
+    /// lines are joined with a line break.
+    ///
+    pub fn comment<Key>(&self, key: Key) -> Option<&str>
+        where
+            Key: AsRef<str> {
+
+        self.entry(key.as_ref()).and_then(Entry::comment)
+    }
+
+    /// Returns the specification declared by the annotations above a variable.
+    pub fn spec<Key>(&self, key: Key) -> Option<&KeySpec>
+        where
+            Key: AsRef<str> {
+
+        self.specs.get(key.as_ref())
+    }
+
+    ///
+    /// Masks a value of a variable for showing, if the variable is annotated with `@secret` or classified as a secret.
+    ///
+    pub fn redact<Key>(&self, key: Key, value: &str) -> String
+        where
+            Key: AsRef<str> {
+
+        let key = key.as_ref();
+
+        match self.spec(key).is_some_and(KeySpec::secret) || secrets::classify(key, value).is_some() {
+            true => secrets::redact(value),
+            false => value.to_string(),
+        }
+    }
+
+    /// Line number of the definition of a variable.
+    pub fn line_number<Key>(&self, key: Key) -> Option<usize>
+        where
+            Key: AsRef<str> {
+
+        self.entry(key.as_ref()).map(Entry::line_number)
+    }
+
+    /// Byte ranges of the definition of a variable in the contents of the file.
+    pub fn spans<Key>(&self, key: Key) -> Option<&EntrySpans>
+        where
+            Key: AsRef<str> {
+
+        self.entry(key.as_ref()).map(Entry::spans)
+    }
+
+    ///
+    /// Returns the lines of the definition of a variable as written, with only the value masked if the variable is a
+    /// secret, as with `redact()`. Comments and quoting are kept.
+    ///
+    pub fn redacted_line<Key>(&self, key: Key) -> Option<String>
+        where
+            Key: AsRef<str> {
+
+        let key = key.as_ref();
+        let entry = self.entry(key)?;
+        let redacted_value = self.redact(key, entry.value());
+
+        let mut line = self.source[entry.spans().line()].to_string();
+
+        if redacted_value != entry.value() {
+            let line_start = entry.spans().line().start;
+            let value = entry.spans().value();
+
+            line.replace_range(value.start - line_start..value.end - line_start, &redacted_value);
+        }
+
+        Some(line)
+    }
+
+    /// Contents of the file.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    ///
+    /// Sorts the variables by name within each group of lines separated by blank lines, along with the comments directly
+    /// above them. Other lines are kept as written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut document = Dotenv::new().parse_document(".env").unwrap();
+    /// document.sort_keys().unwrap();
+    ///
+    /// std::fs::write(".env", document.source()).unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format` if the rewritten document does not parse. The document is left unchanged.
+    ///
+    pub fn sort_keys(&mut self) -> Result<(), Exception> {
+        self.rewrite(format::sort)
+    }
+
+    ///
+    /// Groups the variables by the part of their name before the first underscore, such as `DB` for `DB_HOST`, with a
+    /// `# DB` comment above each group and a blank line between groups. Groups are ordered by their first variable, and
+    /// the variables in a group keep their order and the comments directly above them. Other comments are moved above
+    /// the groups. Sections and includes are kept in place, and the variables are only grouped between them.
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format` if the rewritten document does not parse. The document is left unchanged.
+    ///
+    pub fn group_by_prefix(&mut self) -> Result<(), Exception> {
+        self.rewrite(format::group)
+    }
+
+    ///
+    /// Removes all but the last declaration of each variable, which is the one that is loaded by default, along with
+    /// the comments directly above the removed declarations. If the last declaration is not commented, it takes over
+    /// the comments of the removed ones.
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format` if the rewritten document does not parse. The document is left unchanged.
+    ///
+    pub fn dedupe(&mut self) -> Result<(), Exception> {
+        self.rewrite(format::dedupe)
+    }
+
+    ///
+    /// Sets the value of a variable, replacing the value of its last declaration, which is the one that is loaded by
+    /// default, or appending a declaration to the end of the file if it is not declared. The value is quoted as needed
+    /// to load as is.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::DotenvDocument;
+    ///
+    /// let mut document = DotenvDocument::default();
+    /// document.set("GREETING", "it's me").unwrap();
+    ///
+    /// assert_eq!(document.source(), "GREETING='it'\"'\"'s me'\n");
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format` if the name is not a valid variable name. The document is left unchanged.
+    ///
+    pub fn set<Key, Value>(&mut self, key: Key, value: Value) -> Result<(), Exception>
+        where
+            Key: AsRef<str>,
+            Value: AsRef<str> {
+
+        let key = key.as_ref();
+        let value = format::quote_as(value.as_ref(), self.quote_style, self.entry(key).map(Entry::quoting));
+        let mut source = self.source.clone();
+
+        match self.entry(key) {
+            Some(entry) => source.replace_range(entry.spans().value(), &value),
+            None => {
+                if !source.is_empty() && !source.ends_with('\n') {
+                    source.push('\n');
+                }
+
+                source.push_str(&format!("{}={}\n", key, value));
+            },
+        }
+
+        let document = Self::parse(source, self.options.clone())?;
+
+        match document.get(key) {
+            Some(_) => {
+                *self = Self { quote_style: self.quote_style, ..document };
+                Ok(())
+            },
+            None => Err(Exception::new(ExceptionKind::Format, "format.invalid_name", vec![("key", key.to_string())]).with_key(key.to_string())),
+        }
+    }
+
+    ///
+    /// Removes all declarations of a variable, along with the comments directly above them.
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format` if the rewritten document does not parse. The document is left unchanged.
+    ///
+    pub fn remove<Key>(&mut self, key: Key) -> Result<(), Exception>
+        where
+            Key: AsRef<str> {
+
+        self.rewrite(|lines| format::remove(lines, key.as_ref()))
+    }
+
+    fn rewrite<Transform>(&mut self, transform: Transform) -> Result<(), Exception>
+        where
+            Transform: FnOnce(Vec<Line>) -> Vec<Line> {
+
+        let lines = transform(format::lines(&self.statements, &self.source, None));
+        let document = Self::parse(format::render(&lines, false), self.options.clone())?;
+
+        *self = Self { quote_style: self.quote_style, ..document };
+
+        Ok(())
+    }
+
+    fn entry(&self, key: &str) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.key() == key)
+    }
+}
+
+impl Default for DotenvDocument {
+    fn default() -> Self {
+        Self {
+            source: String::new(),
+            entries: Vec::new(),
+            specs: HashMap::new(),
+            trivia: Vec::new(),
+            statements: Vec::new(),
+            options: ParserOptions::default(),
+            quote_style: QuoteStyle::Preserve,
+        }
+    }
+}
+
+impl Trivia {
+    // Lines of the source outside the declarations of the statements, which may span multiple lines.
+    fn of(statements: &[Statement], source: &str) -> Vec<Self> {
+        let mut declarations: Vec<_> = statements.iter()
+            .filter_map(|statement| match statement {
+                Statement::Assignment(_, _, _, spans) => Some(spans.line()),
+                _ => None,
+            })
+            .collect();
+        declarations.sort_by_key(|declaration| declaration.start);
+
+        let mut trivia = Vec::new();
+        let mut line_start = 0;
+        let mut line_number = 1;
+
+        while line_start < source.len() {
+            let rest = &source[line_start..];
+            let line_end = rest.find(['\n', '\r']).map_or(source.len(), |index| line_start + index);
+            let line_break = match &source[line_end..] {
+                rest if rest.starts_with("\r\n") => 2,
+                "" => 0,
+                _ => 1,
+            };
+
+            // Declarations do not overlap, so only the last one starting at or before the line can contain it.
+            let preceding = declarations.partition_point(|declaration| declaration.start <= line_start);
+            let declared = preceding > 0 && {
+                let declaration = &declarations[preceding - 1];
+                line_start < declaration.end.max(declaration.start + 1)
+            };
+
+            if !declared {
+                trivia.push(Self { line_number, text: source[line_start..line_end].to_string() });
+            }
+
+            line_start = line_end + line_break;
+            line_number += 1;
+        }
+
+        trivia
+    }
+
+    /// Line number of the line.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Contents of the line as written, without the line break.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DotenvDocument, QuoteStyle, Quoting};
+    use crate::format::Line;
+    use crate::parser::{parse_statements, ParserOptions};
+
+    #[test]
+    fn comment() {
+        let data = "# Application\n\n# The database host\n# (without port)\nDB_HOST=localhost # inline\nDB_PORT=5432\n# Unused\n\nDB_NAME=app";
+        let document = DotenvDocument::new(parse_statements(data, &ParserOptions { comments: true, ..ParserOptions::default() }).unwrap(), data.to_string());
+
+        assert_eq!(document.keys(), ["DB_HOST", "DB_PORT", "DB_NAME"]);
+        assert_eq!(document.comment("DB_HOST"), Some("The database host\n(without port)"));
+        assert_eq!(document.comment("DB_PORT"), None);
+        assert_eq!(document.comment("DB_NAME"), None);
+        assert_eq!(document.get("DB_PORT"), Some("5432"));
+        assert_eq!(document.line_number("DB_NAME"), Some(9));
+    }
+
+    #[test]
+    fn spec() {
+        let data = "# The database password\n# @type: string @required @secret\nDB_PASSWORD=hunter2\nDB_NAME=app";
+        let document = DotenvDocument::new(parse_statements(data, &ParserOptions { comments: true, ..ParserOptions::default() }).unwrap(), data.to_string());
+
+        assert_eq!(document.comment("DB_PASSWORD"), Some("The database password"));
+        assert!(document.spec("DB_PASSWORD").unwrap().required());
+        assert!(document.spec("DB_NAME").unwrap().is_empty());
+        assert_eq!(document.redact("DB_PASSWORD", "hunter2"), "****");
+        assert_eq!(document.redact("DB_NAME", "app"), "app");
+    }
+
+    #[test]
+    fn entries() {
+        let data = "# Application\n\n# The database host\nDB_HOST=\"localhost\"\nDB_PORT=5432\nDB_HOST='db'\n[prod]\nCERT=<<EOF\nabc\nEOF\n";
+        let document = DotenvDocument::parse(data.to_string(), ParserOptions { comments: true, sections: true, heredocs: true, ..ParserOptions::default() }).unwrap();
+
+        let entries: Vec<_> = document.entries().iter().map(|entry| (entry.key(), entry.value(), entry.quoting(), entry.line_number())).collect();
+        assert_eq!(entries, [
+            ("DB_PORT", "5432", Quoting::Unquoted, 5),
+            ("DB_HOST", "db", Quoting::Single, 6),
+            ("CERT", "abc", Quoting::Heredoc, 8),
+        ]);
+        assert_eq!(document.entries()[1].comment(), None);
+
+        let trivia: Vec<_> = document.trivia().iter().map(|trivia| (trivia.line_number(), trivia.text())).collect();
+        assert_eq!(trivia, [(1, "# Application"), (2, ""), (3, "# The database host"), (7, "[prod]")]);
+
+        assert_eq!(document.to_map()["DB_HOST"], "db");
+        assert_eq!(document.to_map().len(), 3);
+    }
+
+    #[test]
+    fn spans() {
+        let data = "DB_HOST=localhost\r\n\r\n  export DB_PASSWORD=\"correct horse\"  # admin\r\nDB_NAME=";
+        let document = DotenvDocument::new(parse_statements(data, &ParserOptions { comments: true, ..ParserOptions::default() }).unwrap(), data.to_string());
+
+        let spans = document.spans("DB_PASSWORD").unwrap();
+        assert_eq!(&data[spans.key()], "DB_PASSWORD");
+        assert_eq!(&data[spans.value()], "\"correct horse\"");
+        assert_eq!(&data[spans.line()], "  export DB_PASSWORD=\"correct horse\"  # admin");
+        assert_eq!(document.spans("DB_NAME").unwrap().value(), 76..76);
+
+        assert_eq!(document.redacted_line("DB_PASSWORD").unwrap(), "  export DB_PASSWORD=co****  # admin");
+        assert_eq!(document.redacted_line("DB_HOST").unwrap(), "DB_HOST=localhost");
+    }
+
+    fn document(data: &str) -> DotenvDocument {
+        DotenvDocument::parse(data.to_string(), ParserOptions { comments: true, sections: true, ..ParserOptions::default() }).unwrap()
+    }
+
+    #[test]
+    fn sort_keys() {
+        let mut document = document("# Cache\nREDIS_URL=redis://\n# The database host\nDB_HOST=localhost  # primary\n\nB=b\nA=a\n");
+        document.sort_keys().unwrap();
+
+        assert_eq!(document.source(), "# The database host\nDB_HOST=localhost  # primary\n# Cache\nREDIS_URL=redis://\n\nA=a\nB=b\n");
+        assert_eq!(document.comment("DB_HOST"), Some("The database host"));
+    }
+
+    #[test]
+    fn group_by_prefix() {
+        let mut document = document("# Settings\n\nDB_HOST=localhost\nAPP_NAME=example\n# The database port\nDB_PORT=5432\nDEBUG=1\n[prod]\nDB_HOST=db\n");
+        document.group_by_prefix().unwrap();
+
+        let grouped = "# Settings\n\n# DB\nDB_HOST=localhost\n# The database port\nDB_PORT=5432\n\n# APP\nAPP_NAME=example\n\nDEBUG=1\n\n[prod]\n# DB\nDB_HOST=db\n";
+        assert_eq!(document.source(), grouped);
+
+        document.group_by_prefix().unwrap();
+        assert_eq!(document.source(), grouped);
+    }
+
+    #[test]
+    fn dedupe() {
+        let mut document = document("# The database host\nDB_HOST=localhost\nDB_PORT=5432\n# Overridden\nDB_PORT=5433\nDB_HOST=db\n[prod]\nDB_HOST=prod\n");
+        document.dedupe().unwrap();
+
+        assert_eq!(document.source(), "# Overridden\nDB_PORT=5433\n# The database host\nDB_HOST=db\n[prod]\nDB_HOST=prod\n");
+        assert_eq!(document.get("DB_PORT"), Some("5433"));
+    }
+
+    #[test]
+    fn set() {
+        let mut document = document("# The database host\nexport DB_HOST=localhost # primary\nDB_PASS=\"old\"\nDB_PASS=older");
+        document.set("DB_HOST", "db.internal").unwrap();
+        document.set("DB_PASS", "it's a \"secret\" $HOME\n").unwrap();
+        document.set("DB_NAME", "").unwrap();
+
+        assert_eq!(document.source(), "# The database host\nexport DB_HOST=db.internal # primary\nDB_PASS=\"old\"\nDB_PASS='it'\"'\"'s a \"secret\" $HOME\n'\nDB_NAME=\n");
+        assert_eq!(document.get("DB_PASS"), Some("it's a \"secret\" $HOME\n"));
+        assert_eq!(document.comment("DB_HOST"), Some("The database host"));
+
+        assert_eq!(document.set("DB HOST", "db").unwrap_err().kind(), crate::ExceptionKind::Format);
+        assert_eq!(document.get("DB_HOST"), Some("db.internal"));
+    }
+
+    #[test]
+    fn set_preserves_quoting() {
+        let mut document = document("DB_HOST=\"localhost\"\nDB_NAME='app'\nDB_USER=root\n");
+        document.set("DB_HOST", "db").unwrap();
+        document.set("DB_NAME", "it's").unwrap();
+        document.set("DB_USER", "admin").unwrap();
+        document.set("DB_PORT", "5432").unwrap();
+
+        assert_eq!(document.source(), "DB_HOST=\"db\"\nDB_NAME='it'\"'\"'s'\nDB_USER=admin\nDB_PORT=5432\n");
+
+        document.set_quote_style(QuoteStyle::Minimal);
+        document.set("DB_HOST", "db.internal").unwrap();
+        document.set_quote_style(QuoteStyle::AlwaysDouble);
+        document.set("DB_USER", "root").unwrap();
+
+        assert_eq!(document.source(), "DB_HOST=db.internal\nDB_NAME='it'\"'\"'s'\nDB_USER=\"root\"\nDB_PORT=5432\n");
+    }
+
+    #[test]
+    fn remove() {
+        let mut document = document("# Application\n\n# The database host\nDB_HOST=localhost\nDB_PORT=5432\n[prod]\nDB_HOST=db\n");
+        document.remove("DB_HOST").unwrap();
+
+        assert_eq!(document.source(), "# Application\n\nDB_PORT=5432\n[prod]\n");
+        assert_eq!(document.get("DB_HOST"), None);
+    }
+
+    #[test]
+    fn rewrite_format_exception() {
+        let source = "# The database host\nDB_HOST=localhost\n";
+        let mut document = document(source);
+        let exception = document.rewrite(|mut lines| {
+            lines.push(Line::Other("DB_PASS=\"unterminated".to_string()));
+            lines
+        }).unwrap_err();
+
+        assert_eq!(exception.kind(), crate::ExceptionKind::Format);
+        assert_eq!(document.source(), source);
+        assert_eq!(document.get("DB_HOST"), Some("localhost"));
+    }
+}
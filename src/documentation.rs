@@ -0,0 +1,135 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::BTreeMap;
+
+use crate::{json, KeySpec};
+
+/// Format of the documentation generated by `Dotenv::document()`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentationFormat {
+    /// Markdown table, as for a `CONFIGURATION.md` file.
+    Markdown,
+    /// JSON object keyed by variable name.
+    Json,
+}
+
+/// Documentation of a single variable, gathered from the files defining it
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DocumentedKey {
+    pub(crate) description: Option<String>,
+    pub(crate) spec: KeySpec,
+    pub(crate) default: Option<String>,
+    pub(crate) files: Vec<String>,
+}
+
+impl DocumentationFormat {
+    pub(crate) fn render(&self, keys: &BTreeMap<String, DocumentedKey>) -> String {
+        match self {
+            DocumentationFormat::Markdown => render_markdown(keys),
+            DocumentationFormat::Json => render_json(keys),
+        }
+    }
+}
+
+fn render_markdown(keys: &BTreeMap<String, DocumentedKey>) -> String {
+    let mut markdown = String::from("# Configuration\n\n");
+    markdown.push_str("| Variable | Type | Required | Secret | Default | Defined in | Description |\n");
+    markdown.push_str("| --- | --- | --- | --- | --- | --- | --- |\n");
+
+    let yes_no = |flag: bool| if flag { "yes" } else { "no" };
+    let cell = |text: &str| text.replace('|', "\\|").replace('\n', "<br>");
+
+    for (name, key) in keys.iter() {
+        let files: Vec<String> = key.files.iter().map(|file| format!("`{}`", cell(file))).collect();
+
+        markdown.push_str(&format!(
+            "| `{}` | {} | {} | {} | {} | {} | {} |\n",
+            name,
+            key.spec.value_type().unwrap_or("string"),
+            yes_no(key.spec.required()),
+            yes_no(key.spec.secret()),
+            key.default.as_deref().map(|default| format!("`{}`", cell(default))).unwrap_or_default(),
+            files.join(", "),
+            key.description.as_deref().map(cell).unwrap_or_default(),
+        ));
+    }
+
+    markdown
+}
+
+fn render_json(keys: &BTreeMap<String, DocumentedKey>) -> String {
+    let optional = |text: Option<&str>| text.map(json::string).unwrap_or_else(|| "null".to_string());
+
+    let members: Vec<String> = keys.iter()
+        .map(|(name, key)| {
+            let files: Vec<String> = key.files.iter().map(json::string).collect();
+
+            format!(
+                "{}:{{\"description\":{},\"type\":{},\"required\":{},\"secret\":{},\"default\":{},\"files\":[{}]}}",
+                json::string(name),
+                optional(key.description.as_deref()),
+                optional(key.spec.value_type()),
+                key.spec.required(),
+                key.spec.secret(),
+                optional(key.default.as_deref()),
+                files.join(","),
+            )
+        })
+        .collect();
+
+    format!("{{{}}}", members.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{DocumentationFormat, KeySpec};
+    use crate::documentation::DocumentedKey;
+
+    fn keys() -> BTreeMap<String, DocumentedKey> {
+        let mut spec = KeySpec::default();
+        spec.parse_line("@type: int @required");
+
+        let mut keys = BTreeMap::new();
+        keys.insert("APP_PORT".to_string(), DocumentedKey {
+            description: Some("The port | to listen on".to_string()),
+            spec,
+            default: Some("8080".to_string()),
+            files: vec![".env".to_string(), ".env.local".to_string()],
+        });
+        keys.insert("APP_NAME".to_string(), DocumentedKey { files: vec![".env".to_string()], ..DocumentedKey::default() });
+        keys
+    }
+
+    #[test]
+    fn render_markdown() {
+        assert_eq!(DocumentationFormat::Markdown.render(&keys()), "# Configuration\n\n\
+            | Variable | Type | Required | Secret | Default | Defined in | Description |\n\
+            | --- | --- | --- | --- | --- | --- | --- |\n\
+            | `APP_NAME` | string | no | no |  | `.env` |  |\n\
+            | `APP_PORT` | int | yes | no | `8080` | `.env`, `.env.local` | The port \\| to listen on |\n");
+    }
+
+    #[test]
+    fn render_json() {
+        assert_eq!(DocumentationFormat::Json.render(&keys()), "{\
+            \"APP_NAME\":{\"description\":null,\"type\":null,\"required\":false,\"secret\":false,\"default\":null,\"files\":[\".env\"]},\
+            \"APP_PORT\":{\"description\":\"The port | to listen on\",\"type\":\"int\",\"required\":true,\"secret\":false,\"default\":\"8080\",\"files\":[\".env\",\".env.local\"]}}");
+    }
+}
@@ -15,26 +15,108 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use std::{env, fs};
-use std::collections::HashMap;
+use std::{env, fs, io, mem};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::SystemTime;
+#[cfg(feature = "remote")]
+use std::time::Duration;
 
-use regex::Regex;
-
-use crate::Exception;
+use crate::{AuditAction, AuditEntry, CacheStats, DocumentationFormat, DotenvDocument, DuplicateKeyPolicy, EmptyValuePolicy, Entries, EscapeStyle, EnvDiff, EnvMap, ExampleInit, ExampleSync, FilePermissionPolicy, FormatStyle, InterpolationMode, KeySpec, EnvValue, Exception, ExceptionKind, Explanation, LintWarning, LoadReport, MergeResult, OverridePolicy, ProtectedVariablePolicy, ScopedEnv, Shell, TrackedEnv, Violation, ViolationKind};
+use crate::{audit, cache, docker, encrypted_file, encryption, example, format, glob, lint, merge, metadata, parser, processor, resolver, secrets, template, validation};
+use crate::hash::Fnv1a;
+use crate::encryption::Decryptor;
+use crate::decorator::ValueDecorator;
+use crate::merge::MergeSide;
+use crate::middleware::Middleware;
+use crate::documentation::DocumentedKey;
+use crate::parser::{ParserOptions, Statement};
+use crate::report::{DeprecatedKey, DuplicateKey, InsecureFile, ProtectedVariable};
+use crate::resolver::Resolver;
+use crate::store::RemoteEnvStore;
+use crate::secrets::SecretFinding;
+#[cfg(feature = "prompt")]
+use crate::prompt;
+#[cfg(feature = "remote")]
+use crate::remote;
+#[cfg(feature = "aws")]
+use crate::ssm;
+#[cfg(feature = "azure")]
+use crate::azure;
+#[cfg(feature = "consul")]
+use crate::consul;
+#[cfg(feature = "etcd")]
+use crate::{etcd, EtcdWatch};
+#[cfg(feature = "web")]
+use crate::web;
 
 /// Dotenv file loader
 pub struct Dotenv {
-    path: String,
-    data: String,
-    line_number: usize,
-    cursor: usize,
-    end: usize,
-    state: usize,
+    values: HashMap<String, String>,
+    sources: HashMap<String, String>,
+    defaults: HashMap<String, String>,
+    key_specs: HashMap<String, KeySpec>,
+    aliases: HashMap<String, String>,
+    env_required_keys: HashMap<String, Vec<String>>,
+    frozen_values: Option<HashMap<String, Option<String>>>,
+    audit_log: Option<Vec<AuditEntry>>,
+    // Key of the fingerprints of the values in the audit log, random per instance.
+    audit_key: [u8; 32],
+    previous_values: HashMap<String, (Option<String>, bool)>,
+    strict_templates: bool,
+    case_insensitive_keys: bool,
+    expand_percent_variables: bool,
+    expand_tilde: bool,
+    interpolation_mode: InterpolationMode,
+    escape_style: EscapeStyle,
+    includes: bool,
+    duplicate_key_policy: DuplicateKeyPolicy,
+    empty_value_policy: EmptyValuePolicy,
+    whitespace_around_equals: bool,
+    heredocs: bool,
+    unquoted_spaces: bool,
+    extended_escapes: bool,
+    unset_exports: bool,
+    // Variables unset by `export FOO` in the files being loaded, applied when populating them.
+    unset_keys: Vec<String>,
+    inject_metadata: bool,
+    // Files being loaded, which are added to the loaded files when populating them.
+    merged_files: Vec<String>,
+    files: Vec<String>,
+    resolved_env: Option<String>,
+    cache: bool,
+    only: Option<Vec<String>>,
+    except: Vec<String>,
+    protected_variable_policy: ProtectedVariablePolicy,
+    file_permission_policy: FilePermissionPolicy,
+    protected_variables: Vec<String>,
+    max_file_size: Option<u64>,
+    max_variables: Option<usize>,
+    max_value_length: Option<usize>,
+    report: LoadReport,
+    decryptor: Option<Box<dyn Decryptor>>,
+    master_key: Option<Box<dyn Fn() -> Option<String>>>,
+    resolvers: HashMap<String, Box<dyn Resolver>>,
+    decorators: HashMap<String, Box<dyn ValueDecorator>>,
+    middlewares: Vec<Box<dyn Middleware>>,
+    #[cfg(feature = "remote")]
+    remote_timeout: Duration,
+    #[cfg(feature = "remote")]
+    bearer_token: Option<String>,
+    #[cfg(feature = "prompt")]
+    persist_answers: Option<String>,
 }
 
 impl Dotenv {
-    const STATE_VARNAME: usize = 0;
-    const STATE_VALUE: usize = 1;
+    const LOADED_KEYS_VARIABLE: &'static str = "DOTENV_VARS";
+
+    // Total size of the files from which they are parsed in parallel, below which spawning threads costs more than it saves.
+    #[cfg(feature = "parallel")]
+    const PARALLEL_THRESHOLD: usize = 256 * 1024;
+
+    const PROTECTED_VARIABLES: [&'static str; 10] = [
+        "PATH", "HOME", "USER", "SHELL", "IFS", "LD_PRELOAD", "LD_LIBRARY_PATH", "LD_AUDIT", "DYLD_*", "NODE_OPTIONS",
+    ];
 
     ///
     /// Creates a new instance of the Dotenv file loader.
@@ -47,7 +129,7 @@ impl Dotenv {
     /// DB_PASS=pass
     /// ```
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// use darkweb_dotenv::Dotenv;
     ///
     /// let mut dotenv = Dotenv::new();
@@ -58,436 +140,5404 @@ impl Dotenv {
     ///
     pub fn new() -> Self {
         Self {
-            path: "".to_string(),
-            data: "".to_string(),
-            line_number: 0,
-            cursor: 0,
-            end: 0,
-            state: Self::STATE_VARNAME,
+            values: HashMap::new(),
+            sources: HashMap::new(),
+            defaults: HashMap::new(),
+            key_specs: HashMap::new(),
+            aliases: HashMap::new(),
+            env_required_keys: HashMap::new(),
+            frozen_values: None,
+            audit_log: None,
+            audit_key: [0; 32],
+            previous_values: HashMap::new(),
+            strict_templates: false,
+            case_insensitive_keys: cfg!(windows),
+            expand_percent_variables: false,
+            expand_tilde: false,
+            interpolation_mode: InterpolationMode::Disabled,
+            escape_style: EscapeStyle::None,
+            includes: false,
+            duplicate_key_policy: DuplicateKeyPolicy::default(),
+            empty_value_policy: EmptyValuePolicy::default(),
+            whitespace_around_equals: false,
+            heredocs: false,
+            unquoted_spaces: false,
+            extended_escapes: false,
+            unset_exports: false,
+            unset_keys: Vec::new(),
+            inject_metadata: false,
+            merged_files: Vec::new(),
+            files: Vec::new(),
+            resolved_env: None,
+            cache: false,
+            only: None,
+            except: Vec::new(),
+            protected_variable_policy: ProtectedVariablePolicy::default(),
+            file_permission_policy: FilePermissionPolicy::default(),
+            protected_variables: Self::PROTECTED_VARIABLES.iter().map(|name| name.to_string()).collect(),
+            max_file_size: None,
+            max_variables: None,
+            max_value_length: None,
+            report: LoadReport::default(),
+            decryptor: None,
+            master_key: None,
+            resolvers: HashMap::new(),
+            decorators: HashMap::new(),
+            middlewares: Vec::new(),
+            #[cfg(feature = "remote")]
+            remote_timeout: Duration::from_secs(30),
+            #[cfg(feature = "remote")]
+            bearer_token: None,
+            #[cfg(feature = "prompt")]
+            persist_answers: None,
+        }
+    }
+
+    ///
+    /// Keeps an audit log of the decision taken for each variable when populating the environment, returned by
+    /// `audit_log()`. Entries record the key, a fingerprint of the value keyed per instance, the source and the policy
+    /// applied, but never the value itself.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::{AuditAction, Dotenv};
+    ///
+    /// let mut dotenv = Dotenv::new().audit(true);
+    /// dotenv.load_from_str("DOTENV_DOC_AUDIT=secret").unwrap();
+    ///
+    /// let entry = &dotenv.audit_log()[0];
+    ///
+    /// assert_eq!(entry.action(), AuditAction::Set);
+    /// assert_eq!(entry.key(), "DOTENV_DOC_AUDIT");
+    /// assert!(entry.to_json().contains("\"value_hash\":\"hmac-sha256:"));
+    /// assert!(!entry.to_json().contains("secret"));
+    /// ```
+    ///
+    pub fn audit(mut self, enabled: bool) -> Self {
+        self.audit_log = match enabled {
+            true => Some(Vec::new()),
+            false => None,
+        };
+
+        if enabled {
+            self.audit_key = audit::fingerprint_key();
+        }
+
+        self
+    }
+
+    ///
+    /// Makes `render_template()` fail on variables that are not defined, instead of substituting an empty string.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().strict_templates(true);
+    /// ```
+    ///
+    pub fn strict_templates(mut self, strict: bool) -> Self {
+        self.strict_templates = strict;
+        self
+    }
+
+    ///
+    /// Compares variable names case-insensitively when checking whether a variable is already defined in the
+    /// environment, like Windows does. Enabled by default on Windows.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().case_insensitive_keys(true);
+    /// ```
+    ///
+    pub fn case_insensitive_keys(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive_keys = case_insensitive;
+        self
+    }
+
+    ///
+    /// Expands Windows-style `%VAR%` references in values, using the variables defined earlier in the same file and
+    /// the process environment. References to undefined variables are left untouched, like `cmd.exe` does.
+    ///
+    /// # Examples
+    ///
+    /// ```dotenv
+    /// # .env
+    /// CACHE_DIR=%LOCALAPPDATA%\myapp\cache
+    /// ```
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().expand_percent_variables(true);
+    /// ```
+    ///
+    pub fn expand_percent_variables(mut self, expand: bool) -> Self {
+        self.expand_percent_variables = expand;
+        self
+    }
+
+    ///
+    /// Expands a leading `~` in values to the home directory of the current user, and a leading `~user` to the home
+    /// directory of that user on Unix, like shells do for paths. Values whose user or home directory cannot be
+    /// determined are left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```dotenv
+    /// # .env
+    /// DATA_DIR=~/data
+    /// ```
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().expand_tilde(true);
+    /// ```
+    ///
+    pub fn expand_tilde(mut self, expand: bool) -> Self {
+        self.expand_tilde = expand;
+        self
+    }
+
+    ///
+    /// Interpolates `${VAR}` references in values. References to undefined variables are replaced with an empty
+    /// string.
+    ///
+    /// With `InterpolationMode::Topological`, a value may reference variables defined further down, as the values are
+    /// resolved in the order of their dependencies.
+    ///
+    /// # Examples
+    ///
+    /// ```dotenv
+    /// # .env
+    /// DATABASE_URL=postgres://${DB_HOST}/app
+    /// DB_HOST=localhost
+    /// ```
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::{Dotenv, InterpolationMode};
+    ///
+    /// let mut dotenv = Dotenv::new().interpolation(InterpolationMode::Topological);
+    /// ```
+    ///
+    pub fn interpolation(mut self, interpolation_mode: InterpolationMode) -> Self {
+        self.interpolation_mode = interpolation_mode;
+        self
+    }
+
+    ///
+    /// Sets how literal dollar signs are escaped in values, which defaults to `EscapeStyle::None`.
+    ///
+    /// With `EscapeStyle::Symfony`, `\$` and `$$` are literal dollar signs, and single-quoted values are not
+    /// interpolated, while double-quoted and unquoted values are. `EscapeStyle::Shell` also accepts the ANSI-C quoted
+    /// values of bash, like `$'line1\nline2'`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::{Dotenv, EscapeStyle, InterpolationMode};
+    ///
+    /// let mut dotenv = Dotenv::new().interpolation(InterpolationMode::Sequential).escape_style(EscapeStyle::Symfony);
+    /// dotenv.load_from_str("PRICE=\\$5\nPATTERN='^${NAME}$'").unwrap();
+    ///
+    /// assert_eq!(dotenv.get("PRICE").unwrap(), "$5");
+    /// assert_eq!(dotenv.get("PATTERN").unwrap(), "^${NAME}$");
+    /// ```
+    ///
+    pub fn escape_style(mut self, escape_style: EscapeStyle) -> Self {
+        self.escape_style = escape_style;
+        self
+    }
+
+    ///
+    /// Enables `#!include path` and `source path` directives, which load another file at that point of the including
+    /// file. Relative paths are resolved against the directory of the including file.
+    ///
+    /// # Examples
+    ///
+    /// ```dotenv
+    /// # .env
+    /// #!include shared/database.env
+    /// DB_NAME=app
+    /// ```
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().includes(true);
+    /// ```
+    ///
+    pub fn includes(mut self, includes: bool) -> Self {
+        self.includes = includes;
+        self
+    }
+
+    ///
+    /// Sets how variables that are defined more than once in the same file are handled, which defaults to
+    /// `DuplicateKeyPolicy::LastWins`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::{Dotenv, DuplicateKeyPolicy};
+    ///
+    /// let mut dotenv = Dotenv::new().duplicate_key_policy(DuplicateKeyPolicy::Warn);
+    /// dotenv.load_from_str("APP_NAME=first\nAPP_NAME=second").unwrap();
+    ///
+    /// for duplicate in dotenv.report().duplicates() {
+    ///     eprintln!("warning: {}", duplicate);
+    /// }
+    /// ```
+    ///
+    pub fn duplicate_key_policy(mut self, duplicate_key_policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_key_policy = duplicate_key_policy;
+        self
+    }
+
+    ///
+    /// Sets how variables with an empty value, as in `FOO=`, are handled, which defaults to `EmptyValuePolicy::Allow`.
+    ///
+    /// Quoted empty values, as in `FOO=""`, are handled the same way.
+    ///
+    pub fn empty_value_policy(mut self, empty_value_policy: EmptyValuePolicy) -> Self {
+        self.empty_value_policy = empty_value_policy;
+        self
+    }
+
+    ///
+    /// Allows whitespace around `=`, as in `FOO = bar`, which many generated files contain. The whitespace is trimmed.
+    ///
+    pub fn whitespace_around_equals(mut self, whitespace_around_equals: bool) -> Self {
+        self.whitespace_around_equals = whitespace_around_equals;
+        self
+    }
+
+    ///
+    /// Allows spaces in unquoted values, as in `GREETING=Hello World`, which many real files contain. The value runs up to
+    /// the end of the line or an inline comment, and its trailing whitespace is trimmed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().unquoted_spaces(true);
+    /// dotenv.load_from_str("DOTENV_DOC_GREETING=Hello World # comment").unwrap();
+    ///
+    /// assert_eq!(dotenv.values()["DOTENV_DOC_GREETING"], "Hello World");
+    /// ```
+    ///
+    pub fn unquoted_spaces(mut self, unquoted_spaces: bool) -> Self {
+        self.unquoted_spaces = unquoted_spaces;
+        self
+    }
+
+    ///
+    /// Enables heredoc values for large multi-line values, such as certificates and JSON documents. The content between
+    /// the `<<DELIMITER` line and the line consisting of the delimiter is preserved verbatim.
+    ///
+    /// # Examples
+    ///
+    /// ```dotenv
+    /// # .env
+    /// TLS_CERT=<<EOF
+    /// -----BEGIN CERTIFICATE-----
+    /// MIIBszCCAVmgAwIBAgIU...
+    /// -----END CERTIFICATE-----
+    /// EOF
+    /// ```
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().heredocs(true);
+    /// dotenv.load(".env").unwrap();
+    /// ```
+    ///
+    pub fn heredocs(mut self, heredocs: bool) -> Self {
+        self.heredocs = heredocs;
+        self
+    }
+
+    ///
+    /// Enables the escape sequences `\t`, `\f`, `\0`, `\uXXXX` and `\u{1F600}` in double-quoted values, in addition to
+    /// `\"`, `\r`, `\n` and `\\`. An invalid `\u` escape fails the load.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().extended_escapes(true);
+    /// dotenv.load_from_str("DOTENV_DOC_ESCAPES=\"caf\\u00e9\\t\\u{1F600}\"").unwrap();
+    ///
+    /// assert_eq!(dotenv.values()["DOTENV_DOC_ESCAPES"], "café\t😀");
+    /// ```
+    ///
+    pub fn extended_escapes(mut self, extended_escapes: bool) -> Self {
+        self.extended_escapes = extended_escapes;
+        self
+    }
+
+    ///
+    /// Treats `export FOO` without a value as unsetting the variable, instead of failing on it.
+    ///
+    /// The variable is removed from the files loaded so far, so an uncommitted `.env.local` can unset a default of
+    /// `.env`. A variable that is already defined is removed from the environment when loading would overwrite it,
+    /// such as one set by an earlier load.
+    ///
+    /// # Examples
+    ///
+    /// ```dotenv
+    /// # .env.local
+    /// export DEBUG
+    /// ```
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().unset_exports(true);
+    /// dotenv.load_env(".env", "APP_ENV", "dev").unwrap();
+    ///
+    /// assert!(std::env::var("DEBUG").is_err());
+    /// ```
+    ///
+    pub fn unset_exports(mut self, unset_exports: bool) -> Self {
+        self.unset_exports = unset_exports;
+        self
+    }
+
+    ///
+    /// Sets variables describing the load along with the loaded ones, for runtime diagnostics:
+    /// * `DOTENV_LOADED_AT` --> time of the last load, as an RFC 3339 timestamp in UTC
+    /// * `DOTENV_FILES` --> paths of the files loaded, separated like the entries of `PATH`
+    /// * `DOTENV_ENV` --> environment resolved by `load_env()`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().inject_metadata(true);
+    /// dotenv.load_env(".env", "APP_ENV", "dev").unwrap();
+    ///
+    /// println!("loaded {}", std::env::var("DOTENV_FILES").unwrap());
+    /// ```
+    ///
+    pub fn inject_metadata(mut self, inject_metadata: bool) -> Self {
+        self.inject_metadata = inject_metadata;
+        self
+    }
+
+    ///
+    /// Sets whether files are parsed through the process-wide cache, which is disabled by default. Files are always read,
+    /// and only parsed again when their contents change. The cache keeps the statements of up to 64 files of at most a
+    /// megabyte, including decrypted values, until `Dotenv::clear_cache()` is called.
+    ///
+    pub fn cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    ///
+    /// Restricts the environment variables that are populated to the given ones. Other variables in the loaded files
+    /// are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().only(&["DB_HOST", "DB_PORT"]);
+    /// dotenv.load(".env").unwrap();
+    /// ```
+    ///
+    pub fn only<Key>(mut self, keys: &[Key]) -> Self
+        where
+            Key: AsRef<str> {
+
+        self.only = Some(keys.iter().map(|key| key.as_ref().to_string()).collect());
+        self
+    }
+
+    ///
+    /// Prevents the given environment variables from being populated, so a `.env` file can never overwrite them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().except(&["PATH", "HOME", "LD_PRELOAD"]);
+    /// dotenv.load(".env").unwrap();
+    /// ```
+    ///
+    pub fn except<Key>(mut self, keys: &[Key]) -> Self
+        where
+            Key: AsRef<str> {
+
+        self.except = keys.iter().map(|key| key.as_ref().to_string()).collect();
+        self
+    }
+
+    ///
+    /// Sets how attempts to modify protected variables are handled, which defaults to
+    /// `ProtectedVariablePolicy::Allow`. Important for applications that load env files provided by untrusted
+    /// repositories.
+    ///
+    /// By default, `PATH`, `HOME`, `USER`, `SHELL`, `IFS`, `LD_PRELOAD`, `LD_LIBRARY_PATH`, `LD_AUDIT`, `DYLD_*` and
+    /// `NODE_OPTIONS` are protected. Setting a variable to its current value is not considered a modification.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::{Dotenv, ProtectedVariablePolicy};
+    ///
+    /// let mut dotenv = Dotenv::new().protected_variable_policy(ProtectedVariablePolicy::Skip);
+    /// dotenv.load(".env").unwrap();
+    ///
+    /// for protected_variable in dotenv.report().protected_variables() {
+    ///     eprintln!("warning: {}", protected_variable);
+    /// }
+    /// ```
+    ///
+    pub fn protected_variable_policy(mut self, protected_variable_policy: ProtectedVariablePolicy) -> Self {
+        self.protected_variable_policy = protected_variable_policy;
+        self
+    }
+
+    ///
+    /// Sets how loaded files that contain secrets and are accessible by other users than their owner are handled, which
+    /// defaults to `FilePermissionPolicy::Ignore`. Like ssh does for private keys, files with permissions more
+    /// permissive than `0600` are considered insecure. Permissions are only checked on unix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::{Dotenv, FilePermissionPolicy};
+    ///
+    /// let mut dotenv = Dotenv::new().file_permission_policy(FilePermissionPolicy::Warn);
+    /// dotenv.load(".env.local").unwrap();
+    ///
+    /// for insecure_file in dotenv.report().insecure_files() {
+    ///     eprintln!("warning: {}", insecure_file);
+    /// }
+    /// ```
+    ///
+    pub fn file_permission_policy(mut self, file_permission_policy: FilePermissionPolicy) -> Self {
+        self.file_permission_policy = file_permission_policy;
+        self
+    }
+
+    ///
+    /// Replaces the set of protected variables. Names may end with a `*` wildcard, as in `DYLD_*`.
+    ///
+    pub fn protected_variables<Name>(mut self, names: &[Name]) -> Self
+        where
+            Name: AsRef<str> {
+
+        self.protected_variables = names.iter().map(|name| name.as_ref().to_string()).collect();
+        self
+    }
+
+    ///
+    /// Limits the size of each loaded file, in bytes. Protects services that load env files from semi-trusted sources
+    /// against memory blowups, together with `max_variables()` and `max_value_length()`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new()
+    ///     .max_file_size(64 * 1024)
+    ///     .max_variables(500)
+    ///     .max_value_length(8 * 1024);
+    /// dotenv.load(".env").unwrap();
+    /// ```
+    ///
+    pub fn max_file_size(mut self, max_file_size: u64) -> Self {
+        self.max_file_size = Some(max_file_size);
+        self
+    }
+
+    ///
+    /// Limits the number of variables a single load may define.
+    ///
+    pub fn max_variables(mut self, max_variables: usize) -> Self {
+        self.max_variables = Some(max_variables);
+        self
+    }
+
+    ///
+    /// Limits the length of each value, in bytes.
+    ///
+    pub fn max_value_length(mut self, max_value_length: usize) -> Self {
+        self.max_value_length = Some(max_value_length);
+        self
+    }
+
+    ///
+    /// Values returned by `get()` for variables that are neither loaded nor defined in the environment.
+    ///
+    pub fn defaults(mut self, defaults: HashMap<String, String>) -> Self {
+        self.defaults = defaults;
+        self.report_defaults();
+        self
+    }
+
+    ///
+    /// Registers the default value of a variable, which applies only if neither the loaded files nor the environment
+    /// define it. Defaults are returned by `get()` and `get_processed()`, and listed in the load report.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.set_default("APP_PORT", "8080");
+    /// dotenv.load_from_str("APP_NAME=example").unwrap();
+    ///
+    /// assert_eq!(dotenv.get_processed("int:APP_PORT").unwrap().as_int(), Some(8080));
+    /// assert_eq!(dotenv.report().defaults(), ["APP_PORT"]);
+    /// ```
+    ///
+    pub fn set_default<Key, Value>(&mut self, key: Key, value: Value)
+        where
+            Key: Into<String>,
+            Value: Into<String> {
+
+        self.defaults.insert(key.into(), value.into());
+        self.report_defaults();
+    }
+
+    ///
+    /// Declares variables that `load_env()` requires when the resolved environment is the given one, such as a
+    /// `SENTRY_DSN` that is only required in `prod`. Can be called multiple times for the same environment.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new()
+    ///     .required_in_env("prod", &["SENTRY_DSN", "DATABASE_URL"])
+    ///     .required_in_env("dev", &["DATABASE_URL"]);
+    ///
+    /// dotenv.load_env(".env", "APP_ENV", "dev").unwrap();
+    /// ```
+    ///
+    pub fn required_in_env<Env, Key>(mut self, env: Env, keys: &[Key]) -> Self
+        where
+            Env: Into<String>,
+            Key: AsRef<str> {
+
+        let required_keys = self.env_required_keys.entry(env.into()).or_default();
+
+        for key in keys {
+            if !required_keys.iter().any(|required_key| required_key == key.as_ref()) {
+                required_keys.push(key.as_ref().to_string());
+            }
+        }
+
+        self
+    }
+
+    ///
+    /// Registers a deprecated name of a variable, for migrating configuration to new names. Files still declaring the
+    /// old name populate the new name instead, and each declaration is reported as deprecated in the load report. If a
+    /// file declares both names, the new one is loaded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().alias("DOTENV_DOC_DB_URL", "DOTENV_DOC_DATABASE_URL");
+    /// dotenv.load_from_str("DOTENV_DOC_DB_URL=postgres://localhost/app").unwrap();
+    ///
+    /// assert_eq!(dotenv.get("DOTENV_DOC_DATABASE_URL").unwrap(), "postgres://localhost/app");
+    /// assert_eq!(dotenv.report().deprecated_keys()[0].name(), "DOTENV_DOC_DB_URL");
+    /// ```
+    ///
+    pub fn alias<OldKey, NewKey>(mut self, old_key: OldKey, new_key: NewKey) -> Self
+        where
+            OldKey: Into<String>,
+            NewKey: Into<String> {
+
+        self.aliases.insert(old_key.into(), new_key.into());
+        self
+    }
+
+    ///
+    /// Declares the specification of a variable in code, which `validate()` and `violations()` check instead of the
+    /// annotations of the document.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::{Dotenv, DotenvDocument, KeySpec, ViolationKind};
+    ///
+    /// let mut dotenv = Dotenv::new().key_spec("DOTENV_DOC_APP_ENV", KeySpec::parse("@required @enum: dev,prod"));
+    /// dotenv.load_from_str("DOTENV_DOC_APP_ENV=test").unwrap();
+    ///
+    /// let violations = dotenv.violations(&DotenvDocument::default());
+    /// assert_eq!(violations[0].kind(), ViolationKind::Enum);
+    /// ```
+    ///
+    pub fn key_spec<Key>(mut self, key: Key, spec: KeySpec) -> Self
+        where
+            Key: Into<String> {
+
+        self.key_specs.insert(key.into(), spec);
+        self
+    }
+
+    ///
+    /// Decrypts values marked as encrypted (`enc:v1:BASE64...`) at load time with the given decryptor.
+    ///
+    /// The master key is read from the `DOTENV_MASTER_KEY` environment variable, unless a `master_key()` callback is
+    /// configured. Without a decryptor, encrypted values are loaded as is.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    /// use darkweb_dotenv::encryption::Decryptor;
+    ///
+    /// // Reverses the payload, for illustration, where a real decryptor would use the master key.
+    /// struct ReverseDecryptor;
+    ///
+    /// impl Decryptor for ReverseDecryptor {
+    ///     fn decrypt(&self, _master_key: &str, ciphertext: &[u8]) -> Result<String, String> {
+    ///         String::from_utf8(ciphertext.iter().rev().copied().collect()).map_err(|error| error.to_string())
+    ///     }
+    /// }
+    ///
+    /// let mut dotenv = Dotenv::new()
+    ///     .decryptor(ReverseDecryptor)
+    ///     .master_key(|| Some("master key".to_string()));
+    /// dotenv.load_from_str("DB_PASS=enc:v1:c3NhcA==").unwrap();
+    ///
+    /// assert_eq!(dotenv.values()["DB_PASS"], "pass");
+    /// ```
+    ///
+    /// With the `encryption` feature, `encryption::ChaCha20Poly1305Decryptor` can be used.
+    ///
+    pub fn decryptor<D>(mut self, decryptor: D) -> Self
+        where
+            D: Decryptor + 'static {
+
+        self.decryptor = Some(Box::new(decryptor));
+        self
+    }
+
+    ///
+    /// Provides the master key used to decrypt encrypted values, instead of the `DOTENV_MASTER_KEY` environment
+    /// variable.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().master_key(|| std::fs::read_to_string("/run/secrets/master_key").ok());
+    /// ```
+    ///
+    pub fn master_key<MasterKey>(mut self, master_key: MasterKey) -> Self
+        where
+            MasterKey: Fn() -> Option<String> + 'static {
+
+        self.master_key = Some(Box::new(master_key));
+        self
+    }
+
+    ///
+    /// Registers a resolver for values of the form `scheme://path`, which are replaced at load time by the value the
+    /// resolver returns for the path. Values with schemes without a registered resolver (such as `https://`) are
+    /// loaded as is.
+    ///
+    /// # Examples
+    ///
+    /// ```dotenv
+    /// # .env
+    /// DB_PASS=secrets://database/password
+    /// ```
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().resolver("secrets", |path: &str| {
+    ///     std::fs::read_to_string(format!("/run/secrets/{}", path)).map_err(|error| error.to_string())
+    /// });
+    /// ```
+    ///
+    /// With the `vault`, `aws`, `gcp`, `onepassword` and `bitwarden` features, `resolver::VaultResolver`,
+    /// `resolver::AwsSecretsManagerResolver`, `resolver::GcpSecretManagerResolver`, `resolver::OnePasswordResolver` and
+    /// `resolver::BitwardenResolver` can be registered for `vault://`, `aws-sm://`, `gcp-sm://`, `op://` and `bw://`.
+    ///
+    pub fn resolver<Scheme, R>(mut self, scheme: Scheme, resolver: R) -> Self
+        where
+            Scheme: AsRef<str>,
+            R: Resolver + 'static {
+
+        self.resolvers.insert(scheme.as_ref().to_string(), Box::new(resolver));
+        self
+    }
+
+    ///
+    /// Registers a decorator for values of the form `prefix:value`, which are replaced at load time by the value the
+    /// decorator returns. Values with prefixes without a registered decorator are loaded as is.
+    ///
+    /// # Examples
+    ///
+    /// ```dotenv
+    /// # .env
+    /// GREETING=base64:SGVsbG8=
+    /// DB_PASS=file:/run/secrets/db_pass
+    /// TLS_CERT=resolve:./certs/dev.pem
+    /// ```
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    /// use darkweb_dotenv::decorator::{Base64Decorator, FileDecorator, ResolveDecorator};
+    ///
+    /// let mut dotenv = Dotenv::new()
+    ///     .decorator("base64", Base64Decorator)
+    ///     .decorator("file", FileDecorator)
+    ///     .decorator("resolve", ResolveDecorator);
+    /// dotenv.load(".env").unwrap();
+    /// ```
+    ///
+    pub fn decorator<Prefix, D>(mut self, prefix: Prefix, decorator: D) -> Self
+        where
+            Prefix: AsRef<str>,
+            D: ValueDecorator + 'static {
+
+        self.decorators.insert(prefix.as_ref().to_string(), Box::new(decorator));
+        self
+    }
+
+    ///
+    /// Registers a middleware hooking into the stages of loading, which run after the built-in behavior of each stage
+    /// and after the middleware registered before it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::collections::HashMap;
+    /// use darkweb_dotenv::Dotenv;
+    /// use darkweb_dotenv::middleware::Middleware;
+    ///
+    /// struct Audit;
+    ///
+    /// impl Middleware for Audit {
+    ///     fn populate(&self, values: &HashMap<String, String>) {
+    ///         eprintln!("loaded {} variables", values.len());
+    ///     }
+    /// }
+    ///
+    /// let mut dotenv = Dotenv::new().middleware(Audit);
+    /// dotenv.load(".env").unwrap();
+    /// ```
+    ///
+    pub fn middleware<M>(mut self, middleware: M) -> Self
+        where
+            M: Middleware + 'static {
+
+        self.middlewares.push(Box::new(middleware));
+        self
+    }
+
+    ///
+    /// Sets the timeout of `load_url()` requests, which defaults to 30 seconds.
+    ///
+    /// Requires the `remote` feature.
+    ///
+    #[cfg(feature = "remote")]
+    pub fn remote_timeout(mut self, remote_timeout: Duration) -> Self {
+        self.remote_timeout = remote_timeout;
+        self
+    }
+
+    ///
+    /// Sets a token that `load_url()` requests send as `Authorization: Bearer <token>`.
+    ///
+    /// Requires the `remote` feature.
+    ///
+    #[cfg(feature = "remote")]
+    pub fn bearer_token<Token>(mut self, bearer_token: Token) -> Self
+        where
+            Token: AsRef<str> {
+
+        self.bearer_token = Some(bearer_token.as_ref().to_string());
+        self
+    }
+
+    ///
+    /// Sets a file, such as `.env.local`, that `load_interactive()` writes the answers to, so they are only asked once.
+    ///
+    /// Requires the `prompt` feature.
+    ///
+    #[cfg(feature = "prompt")]
+    pub fn persist_answers<Path>(mut self, path: Path) -> Self
+        where
+            Path: AsRef<str> {
+
+        self.persist_answers = Some(path.as_ref().to_string());
+        self
+    }
+
+    ///
+    /// Loads environment variables from file a `.env` file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load(".env").unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn load<Path>(&mut self, path: Path) -> Result<(), Exception>
+        where
+            Path: AsRef<str> {
+
+        self.load_files(&[path.as_ref().to_string()], OverridePolicy::Never)
+    }
+
+    ///
+    /// Loads environment variables from a `.env` file and overwrites exiting environment variables.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.overload(".env").unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn overload<Path>(&mut self, path: Path) -> Result<(), Exception>
+        where
+            Path: AsRef<str> {
+
+        self.load_files(&[path.as_ref().to_string()], OverridePolicy::Always)
+    }
+
+    ///
+    /// Loads only the environment variables with the given prefix from a `.env` file, and does not overwrite existing
+    /// environment variables. Helps services in a monorepo that share a `.env` file to keep to their own variables.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_prefixed(".env", "MYAPP_").unwrap();
+    ///
+    /// let port = dotenv.scoped("MYAPP_").get("PORT");
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn load_prefixed<Path, Prefix>(&mut self, path: Path, prefix: Prefix) -> Result<(), Exception>
+        where
+            Path: AsRef<str>,
+            Prefix: AsRef<str> {
+
+        let path = path.as_ref();
+        let prefix = prefix.as_ref();
+
+        let mut values = HashMap::new();
+        let mut sources = HashMap::new();
+        self.start_load();
+
+        let data = self.read_file(path)?;
+        self.merge(data, path, &mut values, &mut sources)?;
+        self.interpolate_values(&mut values)?;
+
+        values.retain(|key, _| key.starts_with(prefix));
+        self.unset_keys.retain(|key| key.starts_with(prefix));
+
+        self.populate_from(&values, &sources, &OverridePolicy::Never).map(|_| ())
+    }
+
+    ///
+    /// Loads environment variables from a single file with `[section]` headers, such as `[production]` and
+    /// `[development]`. The variables defined before the first header are shared by all sections, and the variables
+    /// of the given section take precedence over them. Existing environment variables are never overwritten.
+    ///
+    /// # Examples
+    ///
+    /// ```dotenv
+    /// # .env
+    /// APP_NAME=example
+    ///
+    /// [production]
+    /// APP_DEBUG=false
+    ///
+    /// [development]
+    /// APP_DEBUG=true
+    /// ```
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_section(".env", "production").unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn load_section<Path, Section>(&mut self, path: Path, section: Section) -> Result<(), Exception>
+        where
+            Path: AsRef<str>,
+            Section: AsRef<str> {
+
+        let path = path.as_ref();
+
+        let mut values = HashMap::new();
+        let mut sources = HashMap::new();
+        self.start_load();
+
+        let data = self.read_file(path)?;
+        self.merge_included(data, path, &mut values, &mut sources, Some(section.as_ref()), &mut Vec::new())?;
+        self.interpolate_values(&mut values)?;
+
+        self.populate_from(&values, &sources, &OverridePolicy::Never).map(|_| ())
+    }
+
+    ///
+    /// Loads environment-specific environment variables from multiple `.env` files in an hierarchy.
+    ///
+    /// The following files are loaded if they exist, the latter taking precedence over the former:
+    /// * `.env` --> committed environment defaults
+    /// * `.env.local` --> uncommitted file with local overrides
+    /// * `.env.{APP_ENV}` --> committed environment-specific defaults
+    /// * `.env.{APP_ENV}.local` --> uncommitted environment-specific local overrides
+    ///
+    /// The variables declared with `required_in_env()` for the resolved environment must end up defined with a non-empty
+    /// value, and all that would not are reported at once, before any variable is set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_env(".env", "APP_ENV", "dev").unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    /// * `ExceptionKind::UndefinedVariable` if variables required in the resolved environment are not defined or empty.
+    ///
+    pub fn load_env<Path, EnvKey, DefaultEnv>(&mut self, path: Path, env_key: EnvKey, default_env: DefaultEnv) -> Result<(), Exception>
+        where
+            Path: AsRef<str>,
+            EnvKey: AsRef<str>,
+            DefaultEnv: AsRef<str> {
+
+        let (path, env_key, default_env) = (path.as_ref(), env_key.as_ref(), default_env.as_ref());
+
+        self.load_env_checked(|dotenv, explanation| dotenv.load_env_stages(path, env_key, default_env, None, explanation))
+    }
+
+    ///
+    /// Loads environment-specific environment variables from multiple `.env` files in an hierarchy, like `load_env()`,
+    /// for the given environment. The environment is neither read from the process environment nor from the loaded
+    /// files, which allows build tooling to simulate other environments.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_env_forced(".env", "prod").unwrap();
+    ///
+    /// assert_eq!(dotenv.resolved_env(), Some("prod"));
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    /// * `ExceptionKind::UndefinedVariable` if variables required in the given environment are not defined or empty.
+    ///
+    pub fn load_env_forced<Path, Env>(&mut self, path: Path, env: Env) -> Result<(), Exception>
+        where
+            Path: AsRef<str>,
+            Env: AsRef<str> {
+
+        let (path, env) = (path.as_ref(), env.as_ref());
+
+        self.load_env_checked(|dotenv, explanation| dotenv.load_env_stages(path, "", env, Some(env), explanation))
+    }
+
+    ///
+    /// Loads environment variables for the `test` environment, regardless of the environment variable selecting it.
+    ///
+    /// The following files are loaded if they exist, the latter taking precedence over the former:
+    /// * `.env` --> committed environment defaults
+    /// * `.env.test` --> committed test defaults
+    /// * `.env.test.local` --> uncommitted test local overrides
+    ///
+    /// Unlike `load_env()`, the `.env.local` file is ignored, so that tests behave the same on every machine.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_test_env(".env").unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    /// * `ExceptionKind::UndefinedVariable` if variables required in the `test` environment are not defined or empty.
+    ///
+    pub fn load_test_env<Path>(&mut self, path: Path) -> Result<(), Exception>
+        where
+            Path: AsRef<str> {
+
+        let path = path.as_ref();
+
+        self.load_env_checked(|dotenv, explanation| dotenv.load_test_env_stages(path, explanation))
+    }
+
+    ///
+    /// Performs the resolution of `load_env()` without setting any environment variable, and returns a trace of the
+    /// files that were found or missing, the value each variable ends up with and the file it comes from, and the
+    /// variables that were skipped because they were already defined.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// println!("{}", dotenv.explain(".env", "APP_ENV", "dev").unwrap());
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    ///
+    pub fn explain<Path, EnvKey, DefaultEnv>(&mut self, path: Path, env_key: EnvKey, default_env: DefaultEnv) -> Result<Explanation, Exception>
+        where
+            Path: AsRef<str>,
+            EnvKey: AsRef<str>,
+            DefaultEnv: AsRef<str> {
+
+        let mut explanation = Explanation::default();
+
+        self.load_env_stages(path.as_ref(), env_key.as_ref(), default_env.as_ref(), None, Some(&mut explanation))?;
+
+        Ok(explanation)
+    }
+
+    ///
+    /// Loads environment variables from all files matching a pattern, like a `conf.d` directory.
+    ///
+    /// The last path component of the pattern may contain `*` and `?` wildcards. Matching files are loaded in
+    /// lexicographical order, the latter taking precedence over the former, so prefixing file names with a number
+    /// (`10-database.env`, `20-cache.env`) defines their precedence.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_glob(".env.d/*.env").unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn load_glob<Pattern>(&mut self, pattern: Pattern) -> Result<(), Exception>
+        where
+            Pattern: AsRef<str> {
+
+        let paths = glob::expand(pattern.as_ref())?;
+
+        self.load_files(&paths, OverridePolicy::Never)
+    }
+
+    ///
+    /// Loads the `.env.profile.{name}` files of the given profiles from the current directory. See `load_profiles()`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.with_profiles(&["base", "gpu", "eu-west"]).unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn with_profiles<Profile>(&mut self, profiles: &[Profile]) -> Result<(), Exception>
+        where
+            Profile: AsRef<str> {
+
+        self.load_profiles(".env", profiles)
+    }
+
+    ///
+    /// Loads the `{path}.profile.{name}` file of each given profile, such as `.env.profile.gpu`, for composing
+    /// environments out of named fragments independently of the `load_env()` hierarchy.
+    ///
+    /// Profiles are loaded in the given order, the latter taking precedence over the former. Every profile file must
+    /// exist.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_profiles("config/.env", &["base", "gpu"]).unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn load_profiles<Path, Profile>(&mut self, path: Path, profiles: &[Profile]) -> Result<(), Exception>
+        where
+            Path: AsRef<str>,
+            Profile: AsRef<str> {
+
+        let paths: Vec<String> = profiles.iter()
+            .map(|profile| format!("{}.profile.{}", path.as_ref(), profile.as_ref()))
+            .collect();
+
+        self.load_files(&paths, OverridePolicy::Never)
+    }
+
+    ///
+    /// Loads environment variables from every regular file in a directory, like a `.env.d` directory.
+    ///
+    /// Files are loaded in lexicographical order, the latter taking precedence over the former. The file each variable
+    /// was loaded from can be looked up with `source_of()`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_dir(".env.d").unwrap();
+    ///
+    /// println!("DB_HOST was loaded from {:?}", dotenv.source_of("DB_HOST"));
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn load_dir<Path>(&mut self, path: Path) -> Result<(), Exception>
+        where
+            Path: AsRef<str> {
+
+        self.load_dir_filtered(path.as_ref(), None)
+    }
+
+    ///
+    /// Loads environment variables from every regular file with the given extension in a directory.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_dir_with_extension(".env.d", "env").unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn load_dir_with_extension<Path, Extension>(&mut self, path: Path, extension: Extension) -> Result<(), Exception>
+        where
+            Path: AsRef<str>,
+            Extension: AsRef<str> {
+
+        self.load_dir_filtered(path.as_ref(), Some(extension.as_ref()))
+    }
+
+    ///
+    /// Returns the path of the file a loaded environment variable was read from.
+    ///
+    pub fn source_of<Key>(&self, key: Key) -> Option<&str>
+        where
+            Key: AsRef<str> {
+
+        self.sources.get(key.as_ref()).map(String::as_str)
+    }
+
+    ///
+    /// Loads environment variables from the contents of a `.env` file.
+    ///
+    /// On targets without a process environment (such as `wasm32-unknown-unknown`) the variables are only kept in
+    /// this instance, see `values()`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_from_str("API_URL=https://example.com").unwrap();
+    ///
+    /// assert_eq!(dotenv.values()["API_URL"], "https://example.com");
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    ///
+    pub fn load_from_str<Data>(&mut self, data: Data) -> Result<(), Exception>
+        where
+            Data: AsRef<str> {
+
+        self.check_file_size(data.as_ref().len() as u64, None)?;
+
+        let entries = self.apply_policies(parser::parse_entries(data, &self.parser_options())?, None)?;
+        let values = self.resolve_entries(entries, None)?;
+
+        self.populate(&values, OverridePolicy::Never)
+    }
+
+    ///
+    /// Loads environment variables from the contents of a `.env` file and overwrites existing environment variables.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.overload_from_str("API_URL=https://example.com").unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    ///
+    pub fn overload_from_str<Data>(&mut self, data: Data) -> Result<(), Exception>
+        where
+            Data: AsRef<str> {
+
+        self.check_file_size(data.as_ref().len() as u64, None)?;
+
+        let entries = self.apply_policies(parser::parse_entries(data, &self.parser_options())?, None)?;
+        let values = self.resolve_entries(entries, None)?;
+
+        self.populate(&values, OverridePolicy::Always)
+    }
+
+    ///
+    /// Loads environment variables from an environment file served over HTTP(S), such as by a configuration service.
+    ///
+    /// The file is parsed like a local `.env` file and, like `load()`, does not overwrite existing environment
+    /// variables. HTTPS certificates are verified against the Mozilla root certificates. Requires the `remote` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().bearer_token("token");
+    /// dotenv.load_url("https://config.internal/app.env").unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Source`
+    ///
+    #[cfg(feature = "remote")]
+    pub fn load_url<Url>(&mut self, url: Url) -> Result<(), Exception>
+        where
+            Url: AsRef<str> {
+
+        let url = url.as_ref();
+        let data = remote::fetch(url, self.remote_timeout, self.bearer_token.as_deref())?;
+
+        let mut values = HashMap::new();
+        let mut sources = HashMap::new();
+        self.start_load();
+
+        self.merge(data, url, &mut values, &mut sources)?;
+        self.interpolate_values(&mut values)?;
+        self.populate_from(&values, &sources, &OverridePolicy::Never).map(|_| ())
+    }
+
+    ///
+    /// Loads environment variables from the parameters under a path of the AWS SSM Parameter Store, using the `aws`
+    /// command line tool and thereby its regular authentication.
+    ///
+    /// Parameter names are converted to environment variable names relative to the path, as in `DB_PASSWORD` for
+    /// `/myapp/prod/db/password`, and secure strings are decrypted. Like `load()`, existing environment variables are
+    /// not overwritten, so a load of `.env` files after this one only fills in the parameters that are missing.
+    /// Requires the `aws` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_ssm("/myapp/prod/").unwrap();
+    /// dotenv.load(".env").unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Source`
+    ///
+    #[cfg(feature = "aws")]
+    pub fn load_ssm<Path>(&mut self, path: Path) -> Result<(), Exception>
+        where
+            Path: AsRef<str> {
+
+        let path = path.as_ref();
+        let parameters = ssm::fetch(path)?;
+
+        self.load_source(&format!("ssm:{}", path), parameters)
+    }
+
+    ///
+    /// Loads environment variables from the secrets of an Azure Key Vault, using the `az` command line tool and thereby
+    /// its regular authentication.
+    ///
+    /// Only secrets whose name starts with the prefix are loaded. Secret names are converted to environment variable
+    /// names relative to the prefix, as in `DB_PASSWORD` for `myapp-db-password` with the prefix `myapp-`. Like
+    /// `load()`, existing environment variables are not overwritten. Requires the `azure` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_azure_key_vault("myapp-vault", "myapp-").unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Source`
+    ///
+    #[cfg(feature = "azure")]
+    pub fn load_azure_key_vault<Vault, Prefix>(&mut self, vault: Vault, prefix: Prefix) -> Result<(), Exception>
+        where
+            Vault: AsRef<str>,
+            Prefix: AsRef<str> {
+
+        let vault = vault.as_ref();
+        let secrets = azure::fetch_key_vault(vault, prefix.as_ref())?;
+
+        self.load_source(&format!("azure-key-vault:{}", vault), secrets)
+    }
+
+    ///
+    /// Loads environment variables from the key-values of an Azure App Configuration store, using the `az` command line
+    /// tool and thereby its regular authentication.
+    ///
+    /// Only keys that start with the prefix are loaded, with the given label or, without one, with no label. Keys are
+    /// converted to environment variable names relative to the prefix, as in `LOGGING_LEVEL` for `myapp:Logging:Level`
+    /// with the prefix `myapp:`. Like `load()`, existing environment variables are not overwritten. Requires the
+    /// `azure` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_azure_app_config("myapp-config", "myapp:", Some("prod")).unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Source`
+    ///
+    #[cfg(feature = "azure")]
+    pub fn load_azure_app_config<Store, Prefix>(&mut self, store: Store, prefix: Prefix, label: Option<&str>) -> Result<(), Exception>
+        where
+            Store: AsRef<str>,
+            Prefix: AsRef<str> {
+
+        let store = store.as_ref();
+        let key_values = azure::fetch_app_config(store, prefix.as_ref(), label)?;
+
+        self.load_source(&format!("azure-app-config:{}", store), key_values)
+    }
+
+    ///
+    /// Loads environment variables from the keys under a prefix of the Consul KV store, through its HTTP API.
+    ///
+    /// Keys are converted to environment variable names relative to the prefix, as in `DB_PASSWORD` for
+    /// `myapp/prod/db/password` with the prefix `myapp/prod/`. Like `load()`, existing environment variables are not
+    /// overwritten. A token set with `bearer_token()` is sent as ACL token. Requires the `consul` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_consul("http://127.0.0.1:8500", "myapp/prod/").unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Source`
+    ///
+    #[cfg(feature = "consul")]
+    pub fn load_consul<Url, Prefix>(&mut self, url: Url, prefix: Prefix) -> Result<(), Exception>
+        where
+            Url: AsRef<str>,
+            Prefix: AsRef<str> {
+
+        let (url, prefix) = (url.as_ref(), prefix.as_ref());
+        let (entries, _) = consul::fetch(url, prefix, None, self.remote_timeout, self.bearer_token.as_deref())?;
+
+        self.load_source(&format!("consul:{}", prefix), entries)
+    }
+
+    ///
+    /// Loads environment variables from the keys under a prefix of the Consul KV store like `load_consul()`, then
+    /// blocks and reloads them whenever they change, until the callback returns `false`.
+    ///
+    /// Changes are awaited with blocking queries of at most the `remote_timeout()`. Reloaded variables overwrite the
+    /// ones loaded before, while variables of removed keys keep their values. Requires the `consul` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.watch_consul("http://127.0.0.1:8500", "myapp/prod/", |_dotenv| {
+    ///     eprintln!("reloaded the configuration");
+    ///     true
+    /// }).unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Source`, also if a response lacks the `X-Consul-Index` header, without which changes cannot be
+    ///   awaited.
+    ///
+    #[cfg(feature = "consul")]
+    pub fn watch_consul<Url, Prefix, F>(&mut self, url: Url, prefix: Prefix, mut on_change: F) -> Result<(), Exception>
+        where
+            Url: AsRef<str>,
+            Prefix: AsRef<str>,
+            F: FnMut(&mut Self) -> bool {
+
+        let (url, prefix) = (url.as_ref(), prefix.as_ref());
+        let source = format!("consul:{}", prefix);
+
+        let (entries, mut index) = consul::fetch(url, prefix, None, self.remote_timeout, self.bearer_token.as_deref())?;
+        self.load_source(&source, entries)?;
+
+        loop {
+            let (entries, next_index) = consul::fetch(url, prefix, Some(index), self.remote_timeout, self.bearer_token.as_deref())?;
+
+            if next_index == index {
+                continue;
+            }
+
+            // Consul resets the index when it goes backwards, such as after a snapshot restore
+            index = if next_index < index { 0 } else { next_index };
+            self.load_source(&source, entries)?;
+
+            if !on_change(self) {
+                return Ok(());
+            }
+        }
+    }
+
+    ///
+    /// Loads environment variables from the keys under a prefix of etcd, through the JSON gateway of its v3 API.
+    ///
+    /// Keys are converted to environment variable names relative to the prefix, as in `DB_PASSWORD` for
+    /// `/myapp/prod/db/password` with the prefix `/myapp/prod/`. Like `load()`, existing environment variables are not
+    /// overwritten. A token set with `bearer_token()`, as returned by `/v3/auth/authenticate`, is sent as is. Requires
+    /// the `etcd` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_etcd("http://127.0.0.1:2379", "/myapp/prod/").unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Source`
+    ///
+    #[cfg(feature = "etcd")]
+    pub fn load_etcd<Url, Prefix>(&mut self, url: Url, prefix: Prefix) -> Result<(), Exception>
+        where
+            Url: AsRef<str>,
+            Prefix: AsRef<str> {
+
+        let prefix = prefix.as_ref();
+        let (pairs, _) = etcd::fetch(url.as_ref(), prefix, self.remote_timeout, self.bearer_token.as_deref())?;
+
+        self.load_source(&format!("etcd:{}", prefix), pairs)
+    }
+
+    ///
+    /// Loads environment variables from the keys under a prefix of etcd like `load_etcd()`, and watches them for
+    /// changes from the loaded revision on.
+    ///
+    /// Every change is loaded into the environment as it is read from the returned stream, overwriting the variables
+    /// loaded before, while variables of deleted keys keep their values. Requires the `etcd` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    ///
+    /// for diff in dotenv.watch_etcd("http://127.0.0.1:2379", "/myapp/prod/").unwrap() {
+    ///     eprintln!("reloaded the configuration: {}", diff.unwrap().masked());
+    /// }
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Source`
+    ///
+    #[cfg(feature = "etcd")]
+    pub fn watch_etcd<Url, Prefix>(&mut self, url: Url, prefix: Prefix) -> Result<EtcdWatch<'_>, Exception>
+        where
+            Url: AsRef<str>,
+            Prefix: AsRef<str> {
+
+        let (timeout, bearer_token) = (self.remote_timeout, self.bearer_token.clone());
+
+        EtcdWatch::new(self, url.as_ref(), prefix.as_ref(), timeout, bearer_token.as_deref())
+    }
+
+    #[cfg(any(feature = "aws", feature = "azure", feature = "consul", feature = "etcd"))]
+    pub(crate) fn load_source(&mut self, source: &str, pairs: Vec<(String, String)>) -> Result<(), Exception> {
+        let mut values = HashMap::new();
+        let mut sources = HashMap::new();
+        self.start_load();
+
+        for (key, value) in pairs {
+            sources.insert(key.clone(), source.to_string());
+            values.insert(key, value);
+        }
+
+        self.populate_from(&values, &sources, &OverridePolicy::Never).map(|_| ())
+    }
+
+    ///
+    /// Loads environment variables from a JavaScript global object, such as the one bundlers generate for
+    /// `import.meta.env`.
+    ///
+    /// Only properties with string values are loaded. Requires the `web` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_from_js_global("__ENV__").unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Source`
+    ///
+    #[cfg(feature = "web")]
+    pub fn load_from_js_global<Name>(&mut self, name: Name) -> Result<(), Exception>
+        where
+            Name: AsRef<str> {
+
+        let values = web::read_global(name.as_ref())?;
+
+        self.populate(&values, OverridePolicy::Never)
+    }
+
+    ///
+    /// Sets environment variables, handling the ones that are already defined according to the override policy.
+    ///
+    /// The filters, protections and limits of this instance apply as they do when loading files.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use darkweb_dotenv::{Dotenv, OverridePolicy};
+    ///
+    /// let mut values = HashMap::new();
+    /// values.insert("APP_NAME".to_string(), "example".to_string());
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.populate(&values, OverridePolicy::OnlyIfEmpty).unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::ProtectedVariable`
+    ///
+    pub fn populate(&mut self, values: &HashMap<String, String>, override_policy: OverridePolicy) -> Result<(), Exception> {
+        self.start_load();
+        self.populate_from(values, &HashMap::new(), &override_policy).map(|_| ())
+    }
+
+    ///
+    /// Returns the names of the environment variables set by this crate in this process, as recorded in the
+    /// `DOTENV_VARS` environment variable, which is inherited by child processes.
+    ///
+    /// Variables recorded this way are overwritten by later loads, while variables that were defined before loading
+    /// are respected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_from_str("APP_NAME=example").unwrap();
+    ///
+    /// assert!(dotenv.loaded_keys().contains(&"APP_NAME".to_string()));
+    /// ```
+    ///
+    pub fn loaded_keys(&self) -> Vec<String> {
+        match env::var(Self::LOADED_KEYS_VARIABLE) {
+            Ok(keys) => keys.split(',').filter(|key| !key.is_empty()).map(str::to_string).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    ///
+    /// Removes the environment variables set by this instance, and restores the values they overwrote. Lets embedded
+    /// tools and test runners clean up after themselves.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_from_str("APP_NAME=example").unwrap();
+    ///
+    /// dotenv.unload();
+    /// assert!(std::env::var("APP_NAME").is_err());
+    /// ```
+    ///
+    pub fn unload(&mut self) {
+        let keys: Vec<String> = self.values.keys().cloned().collect();
+
+        for key in keys.iter() {
+            match self.previous_values.get(key).and_then(|(previous_value, _)| previous_value.clone()) {
+                #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+                Some(previous_value) => env::set_var(key, previous_value),
+                #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+                None => env::remove_var(key),
+                #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+                _ => {},
+            }
+        }
+
+        let loaded_keys: Vec<String> = self.loaded_keys().into_iter()
+            .filter(|key| !keys.contains(key) || self.previous_values.get(key.as_str()).is_some_and(|(_, previously_loaded)| *previously_loaded))
+            .collect();
+
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        match loaded_keys.is_empty() {
+            true => env::remove_var(Self::LOADED_KEYS_VARIABLE),
+            false => env::set_var(Self::LOADED_KEYS_VARIABLE, loaded_keys.join(",")),
+        }
+
+        self.values.clear();
+        self.sources.clear();
+        self.previous_values.clear();
+        self.report_defaults();
+    }
+
+    ///
+    /// Whether a name is accepted as a variable name by the parser, so tools generating environment files can validate
+    /// keys up front.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let dotenv = Dotenv::new();
+    /// assert!(dotenv.is_valid_name("APP_NAME"));
+    /// assert!(!dotenv.is_valid_name("app-name"));
+    /// ```
+    ///
+    pub fn is_valid_name<Name>(&self, name: Name) -> bool
+        where
+            Name: AsRef<str> {
+
+        parser::is_valid_name(name.as_ref())
+    }
+
+    ///
+    /// Derives a valid variable name from an arbitrary string, by replacing invalid characters with underscores and
+    /// dropping any leading characters that are not letters. Returns `None` if no letter remains.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let dotenv = Dotenv::new();
+    /// assert_eq!(dotenv.sanitize_name("app-name.v2"), Some("app_name_v2".to_string()));
+    /// assert_eq!(dotenv.sanitize_name("__1"), None);
+    /// ```
+    ///
+    pub fn sanitize_name<Name>(&self, name: Name) -> Option<String>
+        where
+            Name: AsRef<str> {
+
+        parser::sanitize_name(name.as_ref())
+    }
+
+    ///
+    /// Returns the environment variables loaded by this instance.
+    ///
+    pub fn values(&self) -> &HashMap<String, String> {
+        &self.values
+    }
+
+    ///
+    /// Returns the value of a variable, looking at the variables loaded by this instance, then the environment, and
+    /// then the defaults.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut defaults = HashMap::new();
+    /// defaults.insert("APP_PORT".to_string(), "8080".to_string());
+    ///
+    /// let mut dotenv = Dotenv::new().defaults(defaults);
+    /// dotenv.load_from_str("APP_NAME=example").unwrap();
+    ///
+    /// assert_eq!(dotenv.get("APP_NAME"), Some("example".to_string()));
+    /// assert_eq!(dotenv.get("APP_PORT"), Some("8080".to_string()));
+    /// ```
+    ///
+    pub fn get<Key>(&self, key: Key) -> Option<String>
+        where
+            Key: AsRef<str> {
+
+        let key = key.as_ref();
+
+        self.values.get(key).cloned()
+            .or_else(|| self.existing_value(key))
+            .or_else(|| self.defaults.get(key).cloned())
+    }
+
+    ///
+    /// Returns the value of an environment variable after applying Symfony-style env processors, which are prefixed
+    /// to the name of the variable and applied from right to left.
+    ///
+    /// The variables loaded by this instance take precedence over the environment. The supported processors are
+    /// `string:`, `bool:`, `not:`, `int:`, `float:`, `json:`, `csv:`, `trim:`, `base64:` and `default:FALLBACK:`,
+    /// which uses the fallback if the variable is not defined or empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_from_str("APP_DEBUG=true").unwrap();
+    ///
+    /// let debug = dotenv.get_processed("bool:APP_DEBUG").unwrap().as_bool().unwrap();
+    /// let port = dotenv.get_processed("int:default:8080:APP_PORT").unwrap().as_int().unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Processing`
+    /// * `ExceptionKind::UndefinedVariable`
+    ///
+    pub fn get_processed<Expression>(&self, expression: Expression) -> Result<EnvValue, Exception>
+        where
+            Expression: AsRef<str> {
+
+        processor::process(expression.as_ref(), &|name: &str| self.get(name))
+    }
+
+    ///
+    /// Returns the value of an environment variable, converted to the type declared by its `@type` annotation in the
+    /// document. Variables without a type are returned as strings.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// let document = dotenv.parse_document(".env").unwrap();
+    /// dotenv.load(".env").unwrap();
+    ///
+    /// let port = dotenv.get_typed(&document, "APP_PORT").unwrap().as_int();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Processing`
+    /// * `ExceptionKind::UndefinedVariable`
+    ///
+    pub fn get_typed<Key>(&self, document: &DotenvDocument, key: Key) -> Result<EnvValue, Exception>
+        where
+            Key: AsRef<str> {
+
+        let key = key.as_ref();
+
+        match document.spec(key).and_then(KeySpec::value_type) {
+            Some(value_type) => self.get_processed(format!("{}:{}", value_type, key)),
+            None => self.get_processed(key),
+        }
+    }
+
+    ///
+    /// Validates the environment variables against the annotations of a document, and the specifications declared
+    /// with `key_spec()`, failing on the first violation. Use `violations()` to report all of them.
+    ///
+    /// # Examples
+    ///
+    /// ```dotenv
+    /// # .env.example
+    /// # @type: int @required
+    /// APP_PORT=
+    /// ```
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// let document = dotenv.parse_document(".env.example").unwrap();
+    /// dotenv.load(".env").unwrap();
+    /// dotenv.validate(&document).unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Processing` if a value does not match its type.
+    /// * `ExceptionKind::UndefinedVariable` if a required variable is not defined or empty.
+    /// * `ExceptionKind::Validation` if a value does not satisfy its pattern, length, enum or format constraints.
+    ///
+    pub fn validate(&self, document: &DotenvDocument) -> Result<(), Exception> {
+        for (key, spec) in self.specs(document) {
+            let value = self.get(&key).filter(|value| !value.is_empty());
+
+            let value = match value {
+                Some(value) => value,
+                None if spec.required() => return Err(Exception::required_variable(key)),
+                None => continue,
+            };
+
+            if let Some(value_type) = spec.value_type() {
+                self.get_processed(format!("{}:{}", value_type, key))?;
+            }
+
+            if let Some(violation) = validation::check(&key, &spec, &value).first() {
+                return Err(Exception::validation(key, violation.message()));
+            }
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Checks the environment variables against the annotations of a document, and the specifications declared with
+    /// `key_spec()`, and reports all violations, such as for showing them at once on startup.
+    ///
+    /// # Examples
+    ///
+    /// ```dotenv
+    /// # .env.example
+    /// # @required @format: url
+    /// DATABASE_URL=
+    /// # @enum: debug,info,warn,error
+    /// LOG_LEVEL=info
+    /// ```
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// let document = dotenv.parse_document(".env.example").unwrap();
+    /// dotenv.load(".env").unwrap();
+    ///
+    /// for violation in dotenv.violations(&document) {
+    ///     eprintln!("{}", violation);
+    /// }
+    /// ```
+    ///
+    pub fn violations(&self, document: &DotenvDocument) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        for (key, spec) in self.specs(document) {
+            let value = match self.get(&key).filter(|value| !value.is_empty()) {
+                Some(value) => value,
+                None if spec.required() => {
+                    violations.push(Violation::new(ViolationKind::Required, &key, "is required, but not defined".to_string()));
+                    continue;
+                },
+                None => continue,
+            };
+
+            if let Some(value_type) = spec.value_type() {
+                if self.get_processed(format!("{}:{}", value_type, key)).is_err() {
+                    violations.push(Violation::new(ViolationKind::Type, &key, format!("is not a valid {}", value_type)));
+                }
+            }
+
+            violations.extend(validation::check(&key, &spec, &value));
+        }
+
+        violations
+    }
+
+    // Specifications of the variables of a document, in the order of the document, overridden by and followed by the
+    // ones declared in code.
+    fn specs(&self, document: &DotenvDocument) -> Vec<(String, KeySpec)> {
+        let mut specs: Vec<(String, KeySpec)> = document.keys().into_iter()
+            .map(|key| (key.to_string(), self.key_specs.get(key).or(document.spec(key)).cloned().unwrap_or_default()))
+            .collect();
+
+        let mut code_keys: Vec<&String> = self.key_specs.keys().filter(|key| document.spec(key).is_none()).collect();
+        code_keys.sort();
+
+        specs.extend(code_keys.into_iter().map(|key| (key.clone(), self.key_specs[key].clone())));
+        specs
+    }
+
+    ///
+    /// Loads environment variables from a `.env` file, as by `load()`, and prompts on stdin for the variables annotated
+    /// with `@required` in a schema, such as `.env.example`, that are still not defined or empty. Helps developers set
+    /// up a project on first run.
+    ///
+    /// The prompts are written to stderr, along with the comments documenting the variables. The answers for variables
+    /// annotated with `@secret` are not echoed if stdin is a terminal. A `.env` file that does not exist is skipped.
+    /// With `persist_answers()`, the answers are also written to a file.
+    ///
+    /// Requires the `prompt` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new().persist_answers(".env.local");
+    /// let schema = dotenv.parse_document(".env.example").unwrap();
+    /// dotenv.load_interactive(".env", &schema).unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    /// * `ExceptionKind::Source` if stdin cannot be read.
+    /// * `ExceptionKind::UndefinedVariable` if stdin ends before a required variable is answered.
+    /// * `ExceptionKind::Write`
+    ///
+    #[cfg(feature = "prompt")]
+    pub fn load_interactive<Path>(&mut self, path: Path, schema: &DotenvDocument) -> Result<(), Exception>
+        where
+            Path: AsRef<str> {
+
+        use std::io::IsTerminal;
+
+        let path = path.as_ref();
+
+        match fs::metadata(path) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {},
+            _ => self.load(path)?,
+        }
+
+        let questions: Vec<prompt::Question> = schema.keys().into_iter()
+            .filter(|key| schema.spec(key).is_some_and(KeySpec::required) && self.is_allowed(key))
+            .filter(|key| self.get(key).filter(|value| !value.is_empty()).is_none())
+            .map(|key| prompt::Question {
+                key: key.to_string(),
+                comment: schema.comment(key).map(str::to_string),
+                secret: schema.spec(key).is_some_and(KeySpec::secret),
+            })
+            .collect();
+
+        if questions.is_empty() {
+            return Ok(());
+        }
+
+        let stdin = io::stdin();
+        let hide_secrets = stdin.is_terminal();
+        let answers = prompt::ask(&questions, &mut stdin.lock(), &mut io::stderr(), hide_secrets)?;
+
+        let mut sources = HashMap::new();
+
+        if let Some(persist_path) = self.persist_answers.clone() {
+            let mut document = self.store_document(&persist_path)?;
+
+            for question in questions.iter() {
+                document.set(&question.key, &answers[&question.key]).map_err(|exception| exception.with_path(persist_path.clone()))?;
+                sources.insert(question.key.clone(), persist_path.clone());
+            }
+
+            if write_private(&persist_path, document.source()).is_err() {
+                return Err(Exception::unwritable(persist_path));
+            }
+        }
+
+        self.start_load();
+        self.populate_from(&answers, &sources, &OverridePolicy::OnlyIfEmpty).map(|_| ())
+    }
+
+    ///
+    /// Returns a view of the environment variables with the given prefix, whose `get("PORT")` returns the value of
+    /// `{prefix}PORT`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_from_str("MYAPP_PORT=8080").unwrap();
+    ///
+    /// assert_eq!(dotenv.scoped("MYAPP_").get("PORT").unwrap(), "8080");
+    /// ```
+    ///
+    pub fn scoped<'a>(&'a self, prefix: &'a str) -> ScopedEnv<'a> {
+        ScopedEnv::new(self, prefix)
+    }
+
+    ///
+    /// Records the current values of the variables loaded by this instance, after which later loads refuse to modify
+    /// them and `verify()` detects modifications made elsewhere in the process, like stray `std::env::set_var()` calls.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::{Dotenv, ExceptionKind};
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_from_str("DOTENV_DOC_FROZEN=a").unwrap();
+    /// dotenv.freeze();
+    ///
+    /// assert!(dotenv.verify().is_ok());
+    ///
+    /// std::env::set_var("DOTENV_DOC_FROZEN", "b");
+    ///
+    /// assert_eq!(dotenv.verify().unwrap_err().kind(), ExceptionKind::Frozen);
+    /// ```
+    ///
+    pub fn freeze(&mut self) {
+        let frozen_values = self.values.keys()
+            .map(|key| (key.clone(), env::var(key).ok()))
+            .collect();
+
+        self.frozen_values = Some(frozen_values);
+    }
+
+    ///
+    /// Whether `freeze()` was called.
+    ///
+    pub fn is_frozen(&self) -> bool {
+        self.frozen_values.is_some()
+    }
+
+    ///
+    /// Checks that the variables recorded by `freeze()` still have the values they had when frozen. Succeeds if the
+    /// instance is not frozen.
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Frozen` if frozen variables were modified or removed, all of which are listed.
+    ///
+    pub fn verify(&self) -> Result<(), Exception> {
+        let frozen_values = match &self.frozen_values {
+            Some(frozen_values) => frozen_values,
+            None => return Ok(()),
+        };
+
+        let mut modified_keys: Vec<&str> = frozen_values.iter()
+            .filter(|(key, value)| env::var(key).ok() != **value)
+            .map(|(key, _)| key.as_str())
+            .collect();
+        modified_keys.sort_unstable();
+
+        match modified_keys.is_empty() {
+            true => Ok(()),
+            false => Err(Exception::frozen_modified(&modified_keys)),
+        }
+    }
+
+    ///
+    /// Returns a view of the environment variables that records which ones the application reads, to find the loaded
+    /// variables that are never used. Reads must go through the view instead of `std::env` to be recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_from_str("DOTENV_DOC_TRACKED_USED=a\nDOTENV_DOC_TRACKED_STALE=b").unwrap();
+    ///
+    /// let tracked = dotenv.tracked();
+    /// tracked.get("DOTENV_DOC_TRACKED_USED");
+    ///
+    /// assert_eq!(tracked.unused_keys(), vec!["DOTENV_DOC_TRACKED_STALE"]);
+    /// ```
+    ///
+    pub fn tracked(&self) -> TrackedEnv<'_> {
+        TrackedEnv::new(self)
+    }
+
+    ///
+    /// Returns the environment resolved by the last `load_env()`, `load_env_forced()` or `load_test_env()`, if any.
+    ///
+    pub fn resolved_env(&self) -> Option<&str> {
+        self.resolved_env.as_deref()
+    }
+
+    ///
+    /// Returns the audit log of the loads of this instance, oldest first, if enabled with `audit()`.
+    ///
+    pub fn audit_log(&self) -> &[AuditEntry] {
+        self.audit_log.as_deref().unwrap_or_default()
+    }
+
+    ///
+    /// Returns the report of the notable things encountered by the loads of this instance, such as duplicate variables.
+    ///
+    pub fn report(&self) -> &LoadReport {
+        &self.report
+    }
+
+    ///
+    /// Returns the number of hits and misses of the process-wide cache of parsed files since it was last cleared.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// Dotenv::new().cache(true).load(".env").unwrap();
+    /// Dotenv::new().cache(true).load(".env").unwrap();
+    ///
+    /// assert!(Dotenv::cache_stats().hits() > 0);
+    /// ```
+    ///
+    pub fn cache_stats() -> CacheStats {
+        cache::stats()
+    }
+
+    ///
+    /// Empties the process-wide cache of parsed files, so they are parsed again by the next loads.
+    ///
+    pub fn clear_cache() {
+        cache::clear();
+    }
+
+    ///
+    /// Returns a stable fingerprint of the environment variables loaded by this instance, as 16 hexadecimal characters.
+    ///
+    /// The fingerprint only depends on the names and values, not on the order in which they were loaded, so it can be
+    /// logged as a configuration version and compared between instances to detect drift.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_from_str("APP_NAME=example").unwrap();
+    ///
+    /// println!("Configuration version {}", dotenv.fingerprint());
+    /// ```
+    ///
+    pub fn fingerprint(&self) -> String {
+        self.fingerprint_excluding::<&str>(&[])
+    }
+
+    ///
+    /// Returns a stable fingerprint like `fingerprint()`, leaving out volatile variables such as build numbers or
+    /// hostnames.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load_from_str("APP_NAME=example\nBUILD_NUMBER=42").unwrap();
+    ///
+    /// let fingerprint = dotenv.fingerprint_excluding(&["BUILD_NUMBER"]);
+    /// ```
+    ///
+    pub fn fingerprint_excluding<Key>(&self, excluded_keys: &[Key]) -> String
+        where
+            Key: AsRef<str> {
+
+        let mut keys: Vec<&String> = self.values.keys()
+            .filter(|key| !excluded_keys.iter().any(|excluded_key| excluded_key.as_ref() == key.as_str()))
+            .collect();
+        keys.sort();
+
+        let mut hasher = Fnv1a::new();
+
+        for key in keys {
+            hasher.write(key.as_bytes());
+            hasher.write(&[0]);
+            hasher.write(self.values[key].as_bytes());
+            hasher.write(&[0]);
+        }
+
+        format!("{:016x}", hasher.finish())
+    }
+
+    ///
+    /// Compares two `.env` files and reports the variables that were added, removed or changed in the second one.
+    ///
+    /// Nothing is loaded into the environment.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// let diff = dotenv.diff(".env.example", ".env").unwrap();
+    ///
+    /// print!("{}", diff.masked());
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn diff<PathA, PathB>(&mut self, path_a: PathA, path_b: PathB) -> Result<EnvDiff, Exception>
+        where
+            PathA: AsRef<str>,
+            PathB: AsRef<str> {
+
+        let data = self.read_file(&path_a)?;
+        let values_a = self.parse(data, path_a)?;
+
+        let data = self.read_file(&path_b)?;
+        let values_b = self.parse(data, path_b)?;
+
+        Ok(EnvDiff::new(&values_a, &values_b))
+    }
+
+    ///
+    /// Updates an environment file with the variables of a store, and reports the variables that were added, removed
+    /// or changed in the file.
+    ///
+    /// Changed values are replaced in place, new variables are appended and removed variables are deleted along with
+    /// the comments directly above them, so the rest of the file is kept as written. The file is created if it does
+    /// not exist, and only written if it changes. Nothing is loaded into the environment.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    /// use darkweb_dotenv::store::RemoteEnvStore;
+    ///
+    /// fn pull(store: &dyn RemoteEnvStore) {
+    ///     let diff = Dotenv::new().pull(store, ".env.production").unwrap();
+    ///
+    ///     print!("{}", diff.masked());
+    /// }
+    /// ```
+    ///
+    /// With the `remote` feature, `store::HttpEnvStore` pulls the variables from a JSON object behind a URL.
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    /// * `ExceptionKind::Source`
+    /// * `ExceptionKind::Write`
+    ///
+    pub fn pull<S, Path>(&mut self, store: &S, path: Path) -> Result<EnvDiff, Exception>
+        where
+            S: RemoteEnvStore + ?Sized,
+            Path: AsRef<str> {
+
+        let path = path.as_ref();
+        let remote_values = store.pull()?;
+
+        let mut document = self.store_document(path)?;
+        let diff = EnvDiff::new(&document.to_map(), remote_values.as_map());
+
+        if diff.is_empty() {
+            return Ok(diff);
+        }
+
+        for key in diff.removed().keys() {
+            document.remove(key)?;
+        }
+
+        for (key, value) in diff.added().iter().chain(diff.changed().iter().map(|(key, (_, value))| (key, value))) {
+            document.set(key, value)?;
+        }
+
+        match write_private(path, document.source()) {
+            Ok(_) => Ok(diff),
+            Err(_) => Err(Exception::unwritable(path.to_string())),
+        }
+    }
+
+    ///
+    /// Replaces the variables of a store with the ones of an environment file, and reports the variables that were
+    /// added, removed or changed in the store.
+    ///
+    /// The values are pushed as written, without resolving references or decorators. The store is only updated if it
+    /// changes. Nothing is loaded into the environment.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    /// use darkweb_dotenv::store::RemoteEnvStore;
+    ///
+    /// fn push(store: &dyn RemoteEnvStore) {
+    ///     let diff = Dotenv::new().push(store, ".env.production").unwrap();
+    ///
+    ///     print!("{}", diff.masked());
+    /// }
+    /// ```
+    ///
+    /// With the `remote` feature, `store::HttpEnvStore` pushes the variables to a JSON object behind a URL.
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    /// * `ExceptionKind::Source`
+    /// * `ExceptionKind::Write`
+    ///
+    pub fn push<S, Path>(&mut self, store: &S, path: Path) -> Result<EnvDiff, Exception>
+        where
+            S: RemoteEnvStore + ?Sized,
+            Path: AsRef<str> {
+
+        let local_values = self.parse_document(path)?.to_map();
+        let remote_values = store.pull()?;
+
+        let diff = EnvDiff::new(remote_values.as_map(), &local_values);
+
+        if !diff.is_empty() {
+            store.push(&EnvMap::from(local_values))?;
+        }
+
+        Ok(diff)
+    }
+
+    ///
+    /// Sets a variable in an environment file and in the environment, such as for saving a token obtained by a CLI
+    /// tool. The value of the last declaration of the variable is replaced, or a declaration is appended if the file
+    /// does not declare it, as by `DotenvDocument::set()`. The value is quoted as needed, and the rest of the file is
+    /// kept as written. The file is created if it does not exist.
+    ///
+    /// The variable is set in the environment even if it is already defined. The filters and protections of this
+    /// instance apply as they do when loading files, and the file is not written if the variable is filtered out or
+    /// protected. The environment is only modified once the file is written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.persist_set(".env.local", "API_KEY", "s3cr3t").unwrap();
+    ///
+    /// assert_eq!(std::env::var("API_KEY").unwrap(), "s3cr3t");
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    /// * `ExceptionKind::ProtectedVariable`
+    /// * `ExceptionKind::Write`
+    ///
+    pub fn persist_set<Path, Key, Value>(&mut self, path: Path, key: Key, value: Value) -> Result<(), Exception>
+        where
+            Path: AsRef<str>,
+            Key: AsRef<str>,
+            Value: AsRef<str> {
+
+        let (path, key, value) = (path.as_ref(), key.as_ref(), value.as_ref());
+
+        let mut document = self.store_document(path)?;
+        document.set(key, value).map_err(|exception| exception.with_path(path.to_string()))?;
+
+        let values = HashMap::from([(key.to_string(), value.to_string())]);
+        let sources = HashMap::from([(key.to_string(), path.to_string())]);
+
+        self.start_load();
+        self.check_populate(&values, &sources, &OverridePolicy::Always)?;
+
+        let skipped = !self.is_allowed(key)
+            || (self.protected_variable_policy == ProtectedVariablePolicy::Skip && self.is_protected_modification(key, value));
+
+        if !skipped && write_private(path, document.source()).is_err() {
+            return Err(Exception::unwritable(path.to_string()));
+        }
+
+        self.populate_from(&values, &sources, &OverridePolicy::Always)?;
+
+        Ok(())
+    }
+
+    fn store_document(&mut self, path: &str) -> Result<DotenvDocument, Exception> {
+        match fs::metadata(path) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                DotenvDocument::parse(String::new(), ParserOptions { comments: true, interpolation: false, ..self.parser_options() })
+            },
+            _ => self.parse_document(path),
+        }
+    }
+
+    ///
+    /// Resolves the variables of an environment file into a map, without loading them into the environment, such as
+    /// for passing them to a child process.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::process::Command;
+    /// use darkweb_dotenv::Dotenv;
+    /// use darkweb_dotenv::command::CommandExt;
+    ///
+    /// let map = Dotenv::new().includes(true).env_map(".env").unwrap();
+    ///
+    /// Command::new("make").envs_from_map(&map).status().unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn env_map<Path>(&mut self, path: Path) -> Result<EnvMap, Exception>
+        where
+            Path: AsRef<str> {
+
+        let path = path.as_ref();
+        let mut values = HashMap::new();
+        let mut sources = HashMap::new();
+        self.start_load();
+
+        let data = self.read_file(path)?;
+        self.merge(data, path, &mut values, &mut sources)?;
+        self.interpolate_values(&mut values)?;
+
+        Ok(EnvMap::from(values))
+    }
+
+    ///
+    /// Resolves the hierarchy of `load_env()` into a map, without loading it into the environment. The map contains
+    /// the variables `load_env()` would set, so variables already defined in the environment are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::process::Command;
+    /// use darkweb_dotenv::Dotenv;
+    /// use darkweb_dotenv::command::CommandExt;
+    ///
+    /// let map = Dotenv::new().layered_env_map(".env", "APP_ENV", "test").unwrap();
+    ///
+    /// Command::new("cargo").arg("test").envs_from_map(&map).status().unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    ///
+    pub fn layered_env_map<Path, EnvKey, DefaultEnv>(&mut self, path: Path, env_key: EnvKey, default_env: DefaultEnv) -> Result<EnvMap, Exception>
+        where
+            Path: AsRef<str>,
+            EnvKey: AsRef<str>,
+            DefaultEnv: AsRef<str> {
+
+        let explanation = self.explain(path, env_key, default_env)?;
+
+        Ok(explanation.variables().iter()
+            .filter(|(_, variable)| !variable.skipped())
+            .map(|(name, variable)| (name.clone(), variable.value().to_string()))
+            .collect::<BTreeMap<_, _>>()
+            .into())
+    }
+
+    ///
+    /// Merges two `.env` files changed from a common ancestor, variable by variable, such as for a git merge driver.
+    ///
+    /// The merged file is based on our file. A variable changed on one side takes the value of that side, variables
+    /// added by their side are appended, and a variable changed differently on both sides is a conflict, surrounded by
+    /// conflict markers and listed in the result. Nothing is loaded into the environment.
+    ///
+    /// # Examples
+    ///
+    /// Registered as a merge driver with `git config merge.dotenv.driver "myapp merge-env %O %A %B"` and
+    /// `.env* merge=dotenv` in `.gitattributes`:
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let arguments: Vec<String> = std::env::args().collect();
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// let result = dotenv.merge_files(&arguments[2], &arguments[3], &arguments[4]).unwrap();
+    ///
+    /// std::fs::write(&arguments[3], result.merged()).unwrap();
+    /// std::process::exit(if result.is_clean() { 0 } else { 1 });
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn merge_files<BasePath, OurPath, TheirPath>(&mut self, base_path: BasePath, our_path: OurPath, their_path: TheirPath) -> Result<MergeResult, Exception>
+        where
+            BasePath: AsRef<str>,
+            OurPath: AsRef<str>,
+            TheirPath: AsRef<str> {
+
+        let base = self.merge_side(base_path.as_ref())?;
+        let ours = self.merge_side(our_path.as_ref())?;
+        let theirs = self.merge_side(their_path.as_ref())?;
+
+        Ok(merge::merge(&base, &ours, &theirs))
+    }
+
+    ///
+    /// Checks a `.env` file for common mistakes and non-portable constructs.
+    ///
+    /// Warnings are reported for duplicate keys, keys missing from the `.env.example` file next to it, unquoted values
+    /// containing shell special characters, trailing whitespace, lowercase keys, likely secrets in committed files
+    /// (any file not ending in `.local`) and likely secrets in files accessible by other users than their owner on unix.
+    /// Nothing is loaded into the environment.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    ///
+    /// for warning in dotenv.lint(".env").unwrap() {
+    ///     println!("{}", warning);
+    /// }
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn lint<Path>(&mut self, path: Path) -> Result<Vec<LintWarning>, Exception>
+        where
+            Path: AsRef<str> {
+
+        let path = path.as_ref().to_string();
+        let data = self.read_file(&path)?;
+        let entries = self.parse_entries(&data, &path)?;
+
+        let example_path = lint::example_path(&path);
+        let example_values = match example_path {
+            Some(example_path) => match self.read_file(&example_path) {
+                Ok(example_data) => Some(self.parse(example_data, example_path)?),
+                Err(_) => None,
+            },
+            None => None,
+        };
+
+        Ok(lint::lint(&path, &data, &entries, example_values.as_ref()))
+    }
+
+    ///
+    /// Renders the environment variables loaded by this instance as a shell script.
+    ///
+    /// Every variable is rendered on its own line, sorted by name, with its value quoted and escaped for the given
+    /// shell, so the output can be evaluated directly (e.g. `eval "$(myapp env)"`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::{Dotenv, Shell};
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load(".env").unwrap();
+    ///
+    /// print!("{}", dotenv.to_shell_exports(Shell::Bash));
+    /// ```
+    ///
+    pub fn to_shell_exports(&self, shell: Shell) -> String {
+        let mut names = self.values.keys().collect::<Vec<_>>();
+        names.sort();
+
+        names.into_iter()
+            .map(|name| format!("{}\n", shell.render_export(name, &self.values[name])))
+            .collect()
+    }
+
+    ///
+    /// Renders the environment variables loaded by this instance as a file for `docker run --env-file`.
+    ///
+    /// Docker takes everything after the first `=` as the value, without processing quotes or escapes, so values are
+    /// rendered as is, sorted by name. Values that Docker cannot represent, such as multi-line values, are rejected
+    /// rather than silently changed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load(".env").unwrap();
+    ///
+    /// std::fs::write("docker.env", dotenv.to_docker_env_file().unwrap()).unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Unrepresentable`
+    ///
+    pub fn to_docker_env_file(&self) -> Result<String, Exception> {
+        let mut names = self.values.keys().collect::<Vec<_>>();
+        names.sort();
+
+        names.into_iter()
+            .map(|name| match docker::render_entry(name, &self.values[name]) {
+                Ok(line) => Ok(format!("{}\n", line)),
+                Err(reason) => Err(Exception::unrepresentable(name.clone(), "a Docker env file", reason)),
+            })
+            .collect()
+    }
+
+    ///
+    /// Flags the likely secrets among the environment variables loaded by this instance.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load(".env").unwrap();
+    ///
+    /// for finding in dotenv.detect_secrets() {
+    ///     println!("{} looks like a secret", finding.name());
+    /// }
+    /// ```
+    ///
+    pub fn detect_secrets(&self) -> Vec<SecretFinding> {
+        secrets::detect(&self.values)
+    }
+
+    ///
+    /// Renders a template file by substituting `${VAR}` placeholders, like `envsubst`.
+    ///
+    /// Placeholders are resolved from the environment variables loaded by this instance first, and from the process
+    /// environment second. Undefined variables are replaced by an empty string, unless `strict_templates()` is
+    /// enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.load(".env").unwrap();
+    /// dotenv.render_template("nginx.conf.dist", "nginx.conf").unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Path`
+    /// * `ExceptionKind::UndefinedVariable`
+    /// * `ExceptionKind::Write`
+    ///
+    pub fn render_template<TemplatePath, OutputPath>(&mut self, template_path: TemplatePath, output_path: OutputPath) -> Result<(), Exception>
+        where
+            TemplatePath: AsRef<str>,
+            OutputPath: AsRef<str> {
+
+        let template_path = template_path.as_ref();
+        let output_path = output_path.as_ref();
+
+        let template = self.read_file(template_path)?;
+
+        let output = template::render(&template, template_path, self.strict_templates, |name| {
+            match self.values.get(name) {
+                Some(value) => Some(value.clone()),
+                None => env::var(name).ok(),
+            }
+        })?;
+
+        match fs::write(output_path, output) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Exception::unwritable(output_path.to_string())),
+        }
+    }
+
+    ///
+    /// Synchronizes an example file, such as `.env.example`, with an environment file.
+    ///
+    /// Variables missing from the example file are appended to it with a blank value, leaving its existing contents
+    /// and comments untouched. The example file is created if it does not exist. Variables documented in the example
+    /// file but missing from the environment file are reported, not removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// let sync = dotenv.sync_example(".env", ".env.example").unwrap();
+    ///
+    /// for name in sync.missing() {
+    ///     println!("{} is documented, but not defined in .env", name);
+    /// }
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    /// * `ExceptionKind::Write`
+    ///
+    pub fn sync_example<EnvPath, ExamplePath>(&mut self, env_path: EnvPath, example_path: ExamplePath) -> Result<ExampleSync, Exception>
+        where
+            EnvPath: AsRef<str>,
+            ExamplePath: AsRef<str> {
+
+        let env_path = env_path.as_ref();
+        let example_path = example_path.as_ref();
+
+        let data = self.read_file(env_path)?;
+        let env_keys: Vec<String> = self.parse_entries(data, env_path)?.into_iter().map(|(name, _, _)| name).collect();
+
+        let mut example = match std::path::Path::new(example_path).exists() {
+            true => self.read_file(example_path)?,
+            false => String::new(),
+        };
+        let example_keys: Vec<String> = self.parse_entries(&example, example_path)?.into_iter().map(|(name, _, _)| name).collect();
+
+        let mut added = Vec::new();
+        let mut missing = Vec::new();
+
+        for name in env_keys.iter() {
+            if !example_keys.contains(name) && !added.contains(name) {
+                added.push(name.clone());
+            }
+        }
+
+        for name in example_keys.iter() {
+            if !env_keys.contains(name) && !missing.contains(name) {
+                missing.push(name.clone());
+            }
+        }
+
+        if !added.is_empty() {
+            if !example.is_empty() && !example.ends_with('\n') {
+                example.push('\n');
+            }
+
+            for name in added.iter() {
+                example.push_str(&format!("{}=\n", name));
+            }
+
+            if fs::write(example_path, example).is_err() {
+                return Err(Exception::unwritable(example_path.to_string()));
+            }
+        }
+
+        Ok(ExampleSync::new(added, missing))
+    }
+
+    ///
+    /// Creates an environment file from an example file, such as `.env.example`, for `myapp init` style commands.
+    ///
+    /// The example file is copied with its comments, annotations and layout, and each variable is given the value of
+    /// its `@default` annotation, or else the value in the example file unless it is a placeholder such as `changeme`.
+    /// Variables without a value are left empty and reported as missing, for the user to fill in. An existing
+    /// environment file is never overwritten.
+    ///
+    /// # Examples
+    ///
+    /// ```dotenv
+    /// # .env.example
+    /// # The listen port
+    /// # @type: int @default: 8080
+    /// APP_PORT=
+    /// APP_SECRET=changeme
+    /// ```
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// let init = dotenv.init_from_example(".env.example", ".env").unwrap();
+    ///
+    /// for name in init.missing() {
+    ///     println!("Fill in {} in .env", name);
+    /// }
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    /// * `ExceptionKind::Write` if the environment file already exists or cannot be written.
+    ///
+    pub fn init_from_example<ExamplePath, EnvPath>(&mut self, example_path: ExamplePath, env_path: EnvPath) -> Result<ExampleInit, Exception>
+        where
+            ExamplePath: AsRef<str>,
+            EnvPath: AsRef<str> {
+
+        use std::io::Write;
+
+        let env_path = env_path.as_ref();
+
+        let mut document = self.parse_document(example_path)?;
+        let mut filled = Vec::new();
+        let mut missing = Vec::new();
+
+        for key in document.keys().into_iter().map(str::to_string).collect::<Vec<_>>() {
+            let value = document.spec(&key).and_then(KeySpec::default_value)
+                .or_else(|| document.get(&key).filter(|value| !example::is_placeholder(value)))
+                .unwrap_or_default()
+                .to_string();
+
+            match value.is_empty() {
+                true => missing.push(key.clone()),
+                false => filled.push(key.clone()),
+            }
+
+            document.set(&key, value)?;
+        }
+
+        let written = fs::OpenOptions::new().write(true).create_new(true).open(env_path)
+            .and_then(|mut file| file.write_all(document.source().as_bytes()));
+
+        match written {
+            Ok(_) => Ok(ExampleInit::new(filled, missing)),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => Err(Exception::already_exists(env_path.to_string())),
+            Err(_) => Err(Exception::unwritable(env_path.to_string())),
+        }
+    }
+
+    ///
+    /// Returns an iterator over the variables declared in an environment file, in the order of the file, without
+    /// loading them. Values are neither interpolated nor decorated, and includes are not followed.
+    ///
+    /// Declarations are parsed one at a time as the iterator advances, so the caller can stop early without parsing the
+    /// rest of the file. Only the extended syntax enabled by `whitespace_around_equals()`, `unquoted_spaces()`,
+    /// `heredocs()`, `extended_escapes()` and `includes()` requires parsing the file up front.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    ///
+    /// for entry in dotenv.iter(".env").take(3) {
+    ///     let entry = entry.unwrap();
+    ///     println!("{}:{}", entry.line_number(), entry.key());
+    /// }
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn iter<Path>(&mut self, path: Path) -> Entries
+        where
+            Path: AsRef<str> {
+
+        let path = path.as_ref().to_string();
+
+        match self.read_file(&path) {
+            Ok(data) => Entries::new(data, path, &ParserOptions { interpolation: false, ..self.parser_options() }),
+            Err(exception) => Entries::failed(exception, path),
+        }
+    }
+
+    ///
+    /// Parses an environment file together with the comments documenting its variables, without loading it.
+    ///
+    /// # Examples
+    ///
+    /// ```dotenv
+    /// # .env
+    /// # The database host
+    /// DB_HOST=localhost
+    /// ```
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// let document = dotenv.parse_document(".env").unwrap();
+    ///
+    /// assert_eq!(document.comment("DB_HOST"), Some("The database host"));
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn parse_document<Path>(&mut self, path: Path) -> Result<DotenvDocument, Exception>
+        where
+            Path: AsRef<str> {
+
+        let path = path.as_ref();
+
+        let data = self.read_file(path)?;
+
+        DotenvDocument::parse(data, ParserOptions { comments: true, interpolation: false, ..self.parser_options() })
+            .map_err(|exception| exception.with_path(path.to_string()))
+    }
+
+    ///
+    /// Generates documentation of the variables defined by an environment file and its siblings, such as
+    /// `.env.local`, `.env.production` and `.env.example`, for instance to maintain a `CONFIGURATION.md` file.
+    ///
+    /// Each variable is documented with its comment and annotations, its default registered with `set_default()` or
+    /// else declared by its `@default` annotation, and the files defining it. Siblings that cannot be parsed are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::{DocumentationFormat, Dotenv};
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// let markdown = dotenv.document(".env", DocumentationFormat::Markdown).unwrap();
+    ///
+    /// std::fs::write("CONFIGURATION.md", markdown).unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn document<Path>(&mut self, path: Path, format: DocumentationFormat) -> Result<String, Exception>
+        where
+            Path: AsRef<str> {
+
+        let path = path.as_ref();
+
+        let mut documents = vec![(path.to_string(), self.parse_document(path)?)];
+
+        for sibling_path in glob::expand(&format!("{}.*", path))? {
+            if let Ok(document) = self.parse_document(&sibling_path) {
+                documents.push((sibling_path, document));
+            }
+        }
+
+        let mut keys: BTreeMap<String, DocumentedKey> = BTreeMap::new();
+
+        for (path, document) in documents.iter() {
+            for name in document.keys() {
+                let key = keys.entry(name.to_string()).or_default();
+
+                if key.description.is_none() {
+                    key.description = document.comment(name).map(str::to_string);
+                }
+
+                if key.spec.is_empty() {
+                    key.spec = document.spec(name).cloned().unwrap_or_default();
+                }
+
+                key.files.push(path.clone());
+            }
+        }
+
+        for (name, default) in self.defaults.iter() {
+            keys.entry(name.clone()).or_default().default = Some(default.clone());
+        }
+
+        for key in keys.values_mut().filter(|key| key.default.is_none()) {
+            key.default = key.spec.default_value().map(str::to_string);
+        }
+
+        Ok(format.render(&keys))
+    }
+
+    ///
+    /// Generates an example file, such as `.env.example`, from an environment file, for sharing its structure without
+    /// its secrets.
+    ///
+    /// Values are replaced by placeholders: empty values stay empty, integers become `<int>`, booleans become `<bool>`
+    /// and any other value becomes `changeme`. Comments and the order of the variables are preserved. An existing
+    /// example file is overwritten.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::Dotenv;
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.generate_example(".env", ".env.example").unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    /// * `ExceptionKind::Write`
+    ///
+    pub fn generate_example<EnvPath, ExamplePath>(&mut self, env_path: EnvPath, example_path: ExamplePath) -> Result<(), Exception>
+        where
+            EnvPath: AsRef<str>,
+            ExamplePath: AsRef<str> {
+
+        let env_path = env_path.as_ref();
+        let example_path = example_path.as_ref();
+
+        let data = self.read_file(env_path)?;
+        let statements = parser::parse_statements(&data, &ParserOptions { comments: true, interpolation: false, ..self.parser_options() })
+            .map_err(|exception| exception.with_path(env_path.to_string()))?;
+
+        match fs::write(example_path, example::generate(&statements, &data)) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Exception::unwritable(example_path.to_string())),
+        }
+    }
+
+    ///
+    /// Formats the contents of an environment file in a canonical style, normalizing the spacing, quoting, blank lines
+    /// and comments, and optionally sorting the variables. The variables keep their values.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::{Dotenv, FormatStyle};
+    ///
+    /// let dotenv = Dotenv::new();
+    /// let formatted = dotenv.format_str("  APP_NAME='example'  #name\n\n\nAPP_DEBUG=1", &FormatStyle::new()).unwrap();
+    ///
+    /// assert_eq!(formatted, "APP_NAME=example # name\n\nAPP_DEBUG=1\n");
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    ///
+    pub fn format_str<Data>(&self, data: Data, style: &FormatStyle) -> Result<String, Exception>
+        where
+            Data: AsRef<str> {
+
+        let data = data.as_ref();
+        let statements = parser::parse_statements(data, &ParserOptions { interpolation: false, ..self.parser_options() })?;
+
+        Ok(format::format(&statements, data, style))
+    }
+
+    ///
+    /// Formats an environment file in place, as by `format_str()`, and returns whether it changed. The file is only
+    /// written if it changed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::{Dotenv, FormatStyle, KeyOrder};
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.format(".env", &FormatStyle::new().key_order(KeyOrder::Sorted)).unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    /// * `ExceptionKind::Write`
+    ///
+    pub fn format<Path>(&mut self, path: Path, style: &FormatStyle) -> Result<bool, Exception>
+        where
+            Path: AsRef<str> {
+
+        let path = path.as_ref();
+
+        let data = self.read_file(path)?;
+        let formatted = self.format_str(&data, style).map_err(|exception| exception.with_path(path.to_string()))?;
+
+        if formatted == data {
+            return Ok(false);
+        }
+
+        match fs::write(path, formatted) {
+            Ok(_) => Ok(true),
+            Err(_) => Err(Exception::unwritable(path.to_string())),
+        }
+    }
+
+    ///
+    /// Checks whether an environment file is formatted, as by `format_str()`, without writing it, such as in CI.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::{Dotenv, FormatStyle};
+    ///
+    /// let mut dotenv = Dotenv::new();
+    ///
+    /// if !dotenv.check_format(".env.example", &FormatStyle::new()).unwrap() {
+    ///     eprintln!(".env.example is not formatted");
+    ///     std::process::exit(1);
+    /// }
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    /// * `ExceptionKind::Path`
+    ///
+    pub fn check_format<Path>(&mut self, path: Path, style: &FormatStyle) -> Result<bool, Exception>
+        where
+            Path: AsRef<str> {
+
+        let path = path.as_ref();
+
+        let data = self.read_file(path)?;
+        let formatted = self.format_str(&data, style).map_err(|exception| exception.with_path(path.to_string()))?;
+
+        Ok(formatted == data)
+    }
+
+    fn merge_side(&mut self, path: &str) -> Result<MergeSide, Exception> {
+        let data = self.read_file(path)?;
+        let statements = parser::parse_statements(&data, &ParserOptions { interpolation: false, ..self.parser_options() })
+            .map_err(|exception| exception.with_path(path.to_string()))?;
+
+        Ok(MergeSide::new(&statements, &data))
+    }
+
+    fn load_dir_filtered(&mut self, path: &str, extension: Option<&str>) -> Result<(), Exception> {
+        let directory = std::path::Path::new(path);
+
+        let mut paths = glob::list_files(directory)?
+            .into_iter()
+            .filter(|file_name| match extension {
+                Some(extension) => std::path::Path::new(file_name).extension().is_some_and(|file_extension| file_extension == extension),
+                None => true,
+            })
+            .map(|file_name| directory.join(file_name).to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+
+        paths.sort();
+
+        self.load_files(&paths, OverridePolicy::Never)
+    }
+
+    // Performs the stages of an environment-specific load, returning the resolved environment. If variables are required
+    // in environments, the stages are resolved with an explanation first, so that a load in which any of them is missing
+    // fails before populating anything.
+    fn load_env_checked<Stages>(&mut self, mut stages: Stages) -> Result<(), Exception>
+        where
+            Stages: FnMut(&mut Self, Option<&mut Explanation>) -> Result<String, Exception> {
+
+        if !self.env_required_keys.is_empty() {
+            let mut explanation = Explanation::default();
+            let env = stages(self, Some(&mut explanation))?;
+
+            self.check_env_required_keys(&env, &explanation)?;
+        }
+
+        stages(self, None).map(|_| ())
+    }
+
+    fn load_env_stages(&mut self, path: &str, env_key: &str, default_env: &str, forced_env: Option<&str>, mut explanation: Option<&mut Explanation>) -> Result<String, Exception> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("load_env", path, env_key, forced_env, dry_run = explanation.is_some()).entered();
+
+        let paths = [path.to_string(), format!("{}.local", path)];
+        let loaded_keys = self.load_stage(&paths, &OverridePolicy::Never, explanation.as_deref_mut())?;
+
+        let env = match (forced_env, explanation.as_deref().and_then(|explanation| explanation.get(env_key))) {
+            (Some(forced_env), _) => forced_env.to_string(),
+            (None, Some(value)) => value.to_string(),
+            (None, None) => match env::var_os(env_key) {
+                Some(value) => value.to_string_lossy().to_string(),
+                None => default_env.to_string(),
+            },
+        };
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(env = env.as_str(), "resolved environment");
+
+        if explanation.is_none() {
+            self.resolved_env = Some(env.clone());
+        }
+
+        if &env == "local" {
+            return Ok(env);
+        }
+
+        let paths = [format!("{}.{}", path, env), format!("{}.{}.local", path, env)];
+        self.load_stage(&paths, &OverridePolicy::List(loaded_keys), explanation)?;
+
+        Ok(env)
+    }
+
+    fn load_test_env_stages(&mut self, path: &str, mut explanation: Option<&mut Explanation>) -> Result<String, Exception> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("load_test_env", path, dry_run = explanation.is_some()).entered();
+
+        let loaded_keys = self.load_stage(&[path.to_string()], &OverridePolicy::Never, explanation.as_deref_mut())?;
+
+        if explanation.is_none() {
+            self.resolved_env = Some("test".to_string());
+        }
+
+        let paths = [format!("{}.test", path), format!("{}.test.local", path)];
+        self.load_stage(&paths, &OverridePolicy::List(loaded_keys), explanation)?;
+
+        Ok("test".to_string())
+    }
+
+    fn check_env_required_keys(&self, env: &str, explanation: &Explanation) -> Result<(), Exception> {
+        let missing_keys: Vec<&str> = self.env_required_keys.get(env).into_iter().flatten()
+            .filter(|key| {
+                explanation.get(key).map(str::to_string).or_else(|| self.get(key))
+                    .filter(|value| !value.is_empty())
+                    .is_none()
+            })
+            .map(String::as_str)
+            .collect();
+
+        match missing_keys.is_empty() {
+            true => Ok(()),
+            false => Err(Exception::required_in_env(env.to_string(), &missing_keys)),
+        }
+    }
+
+    fn load_stage(&mut self, paths: &[String], override_policy: &OverridePolicy, mut explanation: Option<&mut Explanation>) -> Result<Vec<String>, Exception> {
+        let mut values = HashMap::new();
+        let mut sources = HashMap::new();
+        self.start_load();
+
+        let mut files = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            let data = match self.read_file(path) {
+                Err(exception) if matches!(exception.kind(), ExceptionKind::LimitExceeded | ExceptionKind::Directory | ExceptionKind::SpecialFile | ExceptionKind::Decryption) => {
+                    return Err(exception);
+                },
+                data => data,
+            };
+
+            if let Some(explanation) = explanation.as_deref_mut() {
+                explanation.file(path, data.is_ok());
+            }
+
+            if let Ok(data) = data {
+                files.push((path.clone(), data));
+            }
+        }
+
+        self.merge_all(files, &mut values, &mut sources)?;
+        self.interpolate_values(&mut values)?;
+
+        match explanation {
+            Some(explanation) => Ok(self.explain_populate(&values, &sources, override_policy, explanation)),
+            None => self.populate_from(&values, &sources, override_policy),
+        }
+    }
+
+    fn load_files(&mut self, paths: &[String], override_policy: OverridePolicy) -> Result<(), Exception> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("load", ?paths, ?override_policy).entered();
+
+        let mut values = HashMap::new();
+        let mut sources = HashMap::new();
+        self.start_load();
+
+        let mut files = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            files.push((path.clone(), self.read_file(path)?));
+        }
+
+        self.merge_all(files, &mut values, &mut sources)?;
+        self.interpolate_values(&mut values)?;
+
+        self.populate_from(&values, &sources, &override_policy).map(|_| ())
+    }
+
+    fn merge(&mut self, data: String, path: &str, values: &mut HashMap<String, String>, sources: &mut HashMap<String, String>) -> Result<(), Exception> {
+        self.merge_included(data, path, values, sources, None, &mut Vec::new())
+    }
+
+    // Merges the files in the given order. With the `parallel` feature, files that are large enough together are parsed
+    // on scoped threads first, and their statements are merged in the same order, so the result does not depend on which
+    // thread finishes first.
+    fn merge_all(&mut self, files: Vec<(String, String)>, values: &mut HashMap<String, String>, sources: &mut HashMap<String, String>) -> Result<(), Exception> {
+        #[cfg(feature = "parallel")]
+        if files.len() > 1 && files.iter().map(|(_, data)| data.len()).sum::<usize>() >= Self::PARALLEL_THRESHOLD {
+            for (path, data) in files.iter() {
+                self.check_file_size(data.len() as u64, Some(path))?;
+            }
+
+            let options = &self.parser_options();
+            let cache = self.cache;
+            let threads = std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+            let chunk_size = files.len().div_ceil(threads);
+
+            let sizes: Vec<usize> = files.iter().map(|(_, data)| data.len()).collect();
+            let (paths, data): (Vec<String>, Vec<String>) = files.into_iter().unzip();
+            let mut data = data.into_iter();
+
+            let parsed: Vec<Result<Vec<Statement>, Exception>> = std::thread::scope(|scope| {
+                let handles: Vec<_> = paths.chunks(chunk_size)
+                    .map(|chunk| {
+                        let chunk_data: Vec<String> = data.by_ref().take(chunk.len()).collect();
+
+                        scope.spawn(move || {
+                            chunk.iter().zip(chunk_data)
+                                .map(|(path, data)| Self::parse_file(data, path, options, cache))
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .collect();
+
+                handles.into_iter()
+                    .flat_map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+                    .collect()
+            });
+
+            for ((path, size), statements) in paths.iter().zip(sizes).zip(parsed) {
+                let mut include_stack = Vec::new();
+                self.enter_file(path, size, &mut include_stack)?;
+                self.merge_statements(statements?, path, values, sources, None, &mut include_stack)?;
+            }
+
+            return Ok(());
+        }
+
+        for (path, data) in files {
+            self.merge(data, &path, values, sources)?;
+        }
+
+        Ok(())
+    }
+
+    fn merge_included(&mut self, data: String, path: &str, values: &mut HashMap<String, String>, sources: &mut HashMap<String, String>, section: Option<&str>, include_stack: &mut Vec<(PathBuf, String)>) -> Result<(), Exception> {
+        self.enter_file(path, data.len(), include_stack)?;
+
+        let options = ParserOptions { sections: section.is_some(), ..self.parser_options() };
+        let statements = Self::parse_file(data, path, &options, self.cache)?;
+
+        self.merge_statements(statements, path, values, sources, section, include_stack)
+    }
+
+    fn enter_file(&mut self, path: &str, size: usize, include_stack: &mut Vec<(PathBuf, String)>) -> Result<(), Exception> {
+        let canonical_path = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+
+        if let Some(position) = include_stack.iter().position(|(included_path, _)| *included_path == canonical_path) {
+            let mut chain: Vec<String> = include_stack[position..].iter().map(|(_, path)| path.clone()).collect();
+            chain.push(path.to_string());
+
+            return Err(Exception::circular_include(path.to_string(), &chain));
+        }
+
+        include_stack.push((canonical_path, path.to_string()));
+
+        if !self.merged_files.iter().any(|merged_file| merged_file == path) {
+            self.merged_files.push(path.to_string());
+        }
+
+        self.check_file_size(size as u64, Some(path))
+    }
+
+    fn parse_file(data: String, path: &str, options: &ParserOptions, cache: bool) -> Result<Vec<Statement>, Exception> {
+        let statements = match cache {
+            true => cache::parse_statements(data, path, options),
+            false => parser::parse_statements(data, options),
+        };
+
+        statements.map_err(|exception| exception.with_path(path.to_string()))
+    }
+
+    fn merge_statements(&mut self, statements: Vec<Statement>, path: &str, values: &mut HashMap<String, String>, sources: &mut HashMap<String, String>, section: Option<&str>, include_stack: &mut Vec<(PathBuf, String)>) -> Result<(), Exception> {
+        let statements = match section {
+            Some(section) => {
+                let (shared_statements, section_statements) = Self::split_section(statements, section);
+                let mut statements = self.apply_policies(shared_statements, Some(path))?;
+                statements.extend(self.apply_policies(section_statements, Some(path))?);
+                statements
+            },
+            None => self.apply_policies(statements, Some(path))?,
+        };
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(path, statements = statements.len(), "parsed environment file");
+
+        self.check_file_permissions(path, &statements)?;
+
+        for statement in statements {
+            match statement {
+                Statement::Assignment(name, value, ..) => {
+                    let value = self.resolve_value(&name, value, values, Some(path))
+                        .map_err(|exception| exception.with_path(path.to_string()))?;
+                    sources.insert(name.clone(), path.to_string());
+                    values.insert(name, value);
+                    self.check_variable_count(values.len(), Some(path))?;
+                },
+                Statement::Unset(name, _) => {
+                    sources.remove(&name);
+                    values.remove(&name);
+                    self.unset_keys.push(name);
+                },
+                Statement::Include(include_path, _) => {
+                    let include_path = match std::path::Path::new(path).parent() {
+                        Some(directory) => directory.join(include_path).to_string_lossy().to_string(),
+                        None => include_path,
+                    };
+
+                    let include_data = self.read_file(&include_path)?;
+
+                    self.merge_included(include_data, &include_path, values, sources, section, include_stack)?;
+                },
+                Statement::Section(_, _) | Statement::Comment(_, _) => {},
+            }
+        }
+
+        include_stack.pop();
+
+        Ok(())
+    }
+
+    fn read_file<Path>(&mut self, path: Path) -> Result<String, Exception>
+        where
+            Path: AsRef<str> {
+
+        let path = path.as_ref();
+
+        let metadata = match fs::metadata(path) {
+            Ok(metadata) if metadata.is_dir() => return Err(Exception::directory(path.to_string())),
+            Ok(metadata) if !metadata.is_file() => return Err(Exception::special_file(path.to_string(), special_file_type(&metadata))),
+            Ok(metadata) => Some(metadata),
+            Err(_) if fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_symlink()) => {
+                return Err(Exception::broken_symlink(path.to_string()));
+            },
+            Err(_) => None,
+        };
+
+        if let Some(metadata) = &metadata {
+            self.check_file_size(metadata.len(), Some(path))?;
+        }
+
+        let bytes = match fs::read(path) {
+            Ok(bytes) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(path, bytes = bytes.len(), "read environment file");
+                bytes
+            },
+            Err(_error) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(path, error = %_error, "unable to read environment file");
+                return Err(Exception::unreadable(path.to_string()));
+            },
+        };
+
+        let bytes = self.decrypt_file(path, bytes)?;
+
+        let bytes = self.middlewares.iter()
+            .try_fold(bytes, |bytes, middleware| middleware.read(path, bytes))
+            .map_err(|reason| Exception::middleware("read", reason).with_path(path.to_string()))?;
+
+        let data = String::from_utf8(bytes).map_err(|_| Exception::unreadable(path.to_string()))?;
+
+        self.middlewares.iter()
+            .try_fold(data, |data, middleware| middleware.decode(path, data))
+            .map_err(|reason| Exception::middleware("decode", reason).with_path(path.to_string()))
+    }
+
+    fn decrypt_file(&self, path: &str, bytes: Vec<u8>) -> Result<Vec<u8>, Exception> {
+        if encrypted_file::is_git_crypt_encrypted(&bytes) {
+            return Err(Exception::encrypted_file(path.to_string(), "it is encrypted by git-crypt, run `git-crypt unlock` first"));
+        }
+
+        if !encrypted_file::is_sops_encrypted(&bytes) {
+            return Ok(bytes);
+        }
+
+        #[cfg(feature = "sops")]
+        return encrypted_file::decrypt_sops(path).map_err(|reason| Exception::encrypted_file(path.to_string(), &reason));
+
+        #[cfg(not(feature = "sops"))]
+        Err(Exception::encrypted_file(path.to_string(), "it is encrypted by SOPS, enable the `sops` feature or decrypt it with `sops --decrypt` first"))
+    }
+
+    fn parse<Data, Path>(&self, data: Data, path: Path) -> Result<HashMap<String, String>, Exception>
+        where
+            Data: AsRef<str>,
+            Path: AsRef<str> {
+
+        let path = path.as_ref();
+
+        self.resolve_entries(self.parse_entries(data, path)?, Some(path))
+            .map_err(|exception| exception.with_path(path.to_string()))
+    }
+
+    pub(crate) fn parse_entries<Data, Path>(&self, data: Data, path: Path) -> Result<Vec<(String, String, usize)>, Exception>
+        where
+            Data: AsRef<str>,
+            Path: AsRef<str> {
+
+        parser::parse_entries(data, &self.parser_options()).map_err(|exception| exception.with_path(path.as_ref().to_string()))
+    }
+
+    fn parser_options(&self) -> ParserOptions {
+        ParserOptions {
+            includes: self.includes,
+            whitespace_around_equals: self.whitespace_around_equals,
+            heredocs: self.heredocs,
+            unquoted_spaces: self.unquoted_spaces,
+            extended_escapes: self.extended_escapes,
+            sections: false,
+            comments: false,
+            unset_exports: self.unset_exports,
+            literal_dollars: self.escape_style != EscapeStyle::None,
+            double_dollars: self.escape_style == EscapeStyle::Symfony,
+            ansi_c_quotes: self.escape_style == EscapeStyle::Shell,
+            interpolation: self.interpolation_mode != InterpolationMode::Disabled,
+        }
+    }
+
+    // Splits the statements into the ones before the first section header, and the ones of the given section. Policies
+    // apply to both separately, so a section may redefine a shared variable.
+    fn split_section(statements: Vec<Statement>, section: &str) -> (Vec<Statement>, Vec<Statement>) {
+        let mut shared_statements = Vec::new();
+        let mut section_statements = Vec::new();
+        let mut current_section = None;
+
+        for statement in statements {
+            match (&statement, current_section.as_deref()) {
+                (Statement::Section(name, _), _) => current_section = Some(name.clone()),
+                (_, None) => shared_statements.push(statement),
+                (_, Some(current_section)) if current_section == section => section_statements.push(statement),
+                _ => {},
+            }
+        }
+
+        (shared_statements, section_statements)
+    }
+
+    fn apply_policies<Entry>(&mut self, entries: Vec<Entry>, path: Option<&str>) -> Result<Vec<Entry>, Exception>
+        where
+            Entry: AsAssignment {
+
+        let entries = self.rename_aliases(entries, path);
+
+        let mut line_numbers: HashMap<String, usize> = HashMap::new();
+        let mut deduplicated = Vec::with_capacity(entries.len());
+
+        for entry in entries {
+            let (name, value, line_number) = match entry.assignment() {
+                Some(assignment) => assignment,
+                None => {
+                    deduplicated.push(entry);
+                    continue;
+                },
+            };
+
+            if let Some(max_value_length) = self.max_value_length.filter(|max_value_length| value.len() > *max_value_length) {
+                return Err(Exception::limit_exceeded(
+                    "limit_exceeded.value_length",
+                    vec![("key", name.to_string()), ("length", value.len().to_string()), ("max", max_value_length.to_string())],
+                    path.map(str::to_string),
+                ).with_key(name.to_string()));
+            }
+
+            if value.is_empty() {
+                match self.empty_value_policy {
+                    EmptyValuePolicy::Allow => {},
+                    EmptyValuePolicy::Skip => continue,
+                    EmptyValuePolicy::Error => {
+                        return Err(Exception::empty_value(name.to_string(), path.map(str::to_string), line_number));
+                    },
+                }
+            }
+
+            let first_line_number = match line_numbers.get(name) {
+                Some(first_line_number) => *first_line_number,
+                None => {
+                    line_numbers.insert(name.to_string(), line_number);
+                    deduplicated.push(entry);
+                    continue;
+                },
+            };
+
+            match self.duplicate_key_policy {
+                DuplicateKeyPolicy::LastWins => deduplicated.push(entry),
+                DuplicateKeyPolicy::FirstWins => {},
+                DuplicateKeyPolicy::Error => {
+                    return Err(Exception::duplicate_key(name.to_string(), path.map(str::to_string), line_number));
+                },
+                DuplicateKeyPolicy::Warn => {
+                    self.report.duplicate(DuplicateKey::new(name.to_string(), path.map(str::to_string), first_line_number, line_number));
+                    deduplicated.push(entry);
+                },
+            }
+        }
+
+        Ok(deduplicated)
+    }
+
+    // Renames the variables declared under a deprecated name registered with `alias()`, and reports them. Declarations
+    // under a deprecated name are dropped if the file also declares the new name.
+    fn rename_aliases<Entry>(&mut self, entries: Vec<Entry>, path: Option<&str>) -> Vec<Entry>
+        where
+            Entry: AsAssignment {
+
+        if self.aliases.is_empty() {
+            return entries;
+        }
+
+        let declared_names: Vec<String> = entries.iter()
+            .filter_map(|entry| entry.assignment().map(|(name, ..)| name.to_string()))
+            .collect();
+
+        let mut renamed = Vec::with_capacity(entries.len());
+
+        for mut entry in entries {
+            let alias = entry.assignment()
+                .and_then(|(name, _, line_number)| self.aliases.get(name).map(|new_name| (name.to_string(), new_name.clone(), line_number)));
+
+            if let Some((name, new_name, line_number)) = alias {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(key = name.as_str(), replacement = new_name.as_str(), "deprecated variable");
+
+                self.report.deprecated_key(DeprecatedKey::new(name, new_name.clone(), path.map(str::to_string), line_number));
+
+                if declared_names.contains(&new_name) {
+                    continue;
+                }
+
+                entry.rename(new_name);
+            }
+
+            renamed.push(entry);
+        }
+
+        renamed
+    }
+
+    fn resolve_entries(&self, entries: Vec<(String, String, usize)>, path: Option<&str>) -> Result<HashMap<String, String>, Exception> {
+        let mut values = HashMap::new();
+
+        for (name, value, _) in entries {
+            let value = self.resolve_value(&name, value, &values, path)?;
+            values.insert(name, value);
+            self.check_variable_count(values.len(), None)?;
+        }
+
+        self.interpolate_values(&mut values)?;
+
+        Ok(values)
+    }
+
+    fn check_file_permissions(&mut self, path: &str, statements: &[Statement]) -> Result<(), Exception> {
+        if self.file_permission_policy == FilePermissionPolicy::Ignore {
+            return Ok(());
+        }
+
+        let mode = match lint::insecure_mode(path) {
+            Some(mode) => mode,
+            None => return Ok(()),
+        };
+
+        let secret_keys: Vec<String> = statements.iter()
+            .filter_map(|statement| statement.assignment())
+            .filter(|(name, value, _)| secrets::classify(name, value).is_some())
+            .map(|(name, ..)| name.to_string())
+            .collect();
+
+        if secret_keys.is_empty() {
+            return Ok(());
+        }
+
+        match self.file_permission_policy {
+            FilePermissionPolicy::Error => Err(Exception::insecure_permissions(path.to_string(), mode)),
+            _ => {
+                self.report.insecure_file(InsecureFile::new(path.to_string(), mode, secret_keys));
+                Ok(())
+            },
+        }
+    }
+
+    fn check_file_size(&self, size: u64, path: Option<&str>) -> Result<(), Exception> {
+        match self.max_file_size {
+            Some(max_file_size) if size > max_file_size => Err(Exception::limit_exceeded(
+                "limit_exceeded.file_size",
+                vec![("size", size.to_string()), ("max", max_file_size.to_string())],
+                path.map(str::to_string),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_variable_count(&self, count: usize, path: Option<&str>) -> Result<(), Exception> {
+        match self.max_variables {
+            Some(max_variables) if count > max_variables => Err(Exception::limit_exceeded(
+                "limit_exceeded.variables",
+                vec![("max", max_variables.to_string())],
+                path.map(str::to_string),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn resolve_value(&self, name: &str, value: String, values: &HashMap<String, String>, path: Option<&str>) -> Result<String, Exception> {
+        let value = self.decrypt_value(name, value)?;
+        let value = self.resolve_reference(name, value)?;
+        let value = self.decorate_value(name, value, path)?;
+        let value = self.middlewares.iter()
+            .try_fold(value, |value, middleware| middleware.transform(name, value, path))
+            .map_err(|reason| Exception::middleware("transform", reason).with_key(name.to_string()))?;
+
+        let value = match self.interpolation_mode {
+            InterpolationMode::Sequential => template::interpolate(&value, self.escape_style != EscapeStyle::None, |reference| {
+                values.get(reference).cloned().or_else(|| env::var(reference).ok())
+            }),
+            _ => value,
+        };
+
+        let value = match self.expand_percent_variables {
+            true => template::expand_percent_variables(&value, |reference| {
+                values.get(reference).cloned().or_else(|| env::var(reference).ok())
+            }),
+            false => value,
+        };
+
+        if !self.expand_tilde {
+            return Ok(value);
+        }
+
+        Ok(template::expand_tilde(&value, template::home_directory))
+    }
+
+    fn interpolate_values(&self, values: &mut HashMap<String, String>) -> Result<(), Exception> {
+        if self.interpolation_mode != InterpolationMode::Topological {
+            return Ok(());
+        }
+
+        let mut keys: Vec<String> = values.keys().cloned().collect();
+        keys.sort();
+
+        let mut resolved = HashMap::new();
+
+        for key in keys {
+            Self::interpolate_value(&key, values, self.escape_style != EscapeStyle::None, &mut resolved, &mut Vec::new())?;
+        }
+
+        *values = resolved;
+
+        Ok(())
+    }
+
+    fn interpolate_value(key: &str, values: &HashMap<String, String>, escaped: bool, resolved: &mut HashMap<String, String>, stack: &mut Vec<String>) -> Result<(), Exception> {
+        if resolved.contains_key(key) {
+            return Ok(());
+        }
+
+        if let Some(position) = stack.iter().position(|name| name == key) {
+            let mut chain = stack[position..].to_vec();
+            chain.push(key.to_string());
+
+            return Err(Exception::circular_reference(key.to_string(), &chain));
+        }
+
+        stack.push(key.to_string());
+
+        let value = &values[key];
+
+        for reference in template::references(value, escaped) {
+            if values.contains_key(&reference) {
+                Self::interpolate_value(&reference, values, escaped, resolved, stack)?;
+            }
+        }
+
+        stack.pop();
+
+        let value = template::interpolate(value, escaped, |reference| {
+            resolved.get(reference).cloned().or_else(|| env::var(reference).ok())
+        });
+        resolved.insert(key.to_string(), value);
+
+        Ok(())
+    }
+
+    fn resolve_reference(&self, name: &str, value: String) -> Result<String, Exception> {
+        let (resolver, path) = match resolver::parse_reference(&value)
+            .and_then(|(scheme, path)| Some((self.resolvers.get(scheme)?, path))) {
+            Some(reference) => reference,
+            None => return Ok(value),
+        };
+
+        resolver.resolve(path)
+            .map_err(|reason| Exception::resolution(name.to_string(), value.clone(), reason))
+    }
+
+    fn decorate_value(&self, name: &str, value: String, path: Option<&str>) -> Result<String, Exception> {
+        let (decorator, decorated) = match value.split_once(':')
+            .and_then(|(prefix, decorated)| Some((self.decorators.get(prefix)?, decorated))) {
+            Some(decoration) => decoration,
+            None => return Ok(value),
+        };
+
+        decorator.decorate_entry(name, decorated, path)
+            .map_err(|reason| Exception::decoration(name.to_string(), reason))
+    }
+
+    fn decrypt_value(&self, name: &str, value: String) -> Result<String, Exception> {
+        let decryptor = match &self.decryptor {
+            Some(decryptor) if value.starts_with(encryption::PREFIX) => decryptor,
+            _ => return Ok(value),
+        };
+
+        let master_key = match &self.master_key {
+            Some(master_key) => master_key(),
+            None => env::var(encryption::MASTER_KEY_VARIABLE).ok(),
+        };
+
+        let master_key = master_key.ok_or_else(|| {
+            Exception::decryption(name.to_string(), "no master key is available".to_string())
+        })?;
+
+        encryption::decrypt(decryptor.as_ref(), &master_key, &value)
+            .map_err(|reason| Exception::decryption(name.to_string(), reason))
+    }
+
+    #[cfg(test)]
+    fn is_defined(&self, key: &str) -> bool {
+        self.existing_value(key).is_some()
+    }
+
+    fn existing_value(&self, key: &str) -> Option<String> {
+        if self.case_insensitive_keys {
+            return env::vars_os()
+                .find(|(name, _)| name.to_string_lossy().eq_ignore_ascii_case(key))
+                .map(|(_, value)| value.to_string_lossy().to_string());
+        }
+
+        env::var_os(key).map(|value| value.to_string_lossy().to_string())
+    }
+
+    fn is_same_key(&self, name: &str, key: &str) -> bool {
+        if self.case_insensitive_keys { name.eq_ignore_ascii_case(key) } else { name == key }
+    }
+
+    fn is_allowed(&self, key: &str) -> bool {
+        let matches = |name: &String| self.is_same_key(name, key);
+
+        self.only.as_ref().is_none_or(|only| only.iter().any(matches)) && !self.except.iter().any(matches)
+    }
+
+    // Keys of `loaded_keys()`, in the form `is_loaded()` looks them up in, so a load does not split and scan the list of
+    // loaded keys for every variable.
+    fn loaded_key_set(&self) -> HashSet<String> {
+        self.loaded_keys().into_iter().map(|key| self.lookup_key(key)).collect()
+    }
+
+    fn is_loaded(&self, loaded_keys: &HashSet<String>, key: &str) -> bool {
+        loaded_keys.contains(&self.lookup_key(key.to_string()))
+    }
+
+    fn lookup_key(&self, key: String) -> String {
+        match self.case_insensitive_keys {
+            true => key.to_ascii_uppercase(),
+            false => key,
+        }
+    }
+
+    fn is_overridable(&self, key: &str, existing_value: Option<&str>, override_policy: &OverridePolicy, loaded_keys: &HashSet<String>) -> bool {
+        let existing_value = match existing_value {
+            Some(existing_value) => existing_value,
+            None => return true,
+        };
+
+        if self.is_loaded(loaded_keys, key) {
+            return true;
+        }
+
+        match override_policy {
+            OverridePolicy::Never => false,
+            OverridePolicy::Always => true,
+            OverridePolicy::OnlyIfEmpty => existing_value.is_empty(),
+            OverridePolicy::List(keys) => keys.iter().any(|name| self.is_same_key(name, key)),
+        }
+    }
+
+    fn is_protected_modification(&self, key: &str, value: &str) -> bool {
+        if self.protected_variable_policy == ProtectedVariablePolicy::Allow {
+            return false;
+        }
+
+        let protected = self.protected_variables.iter().any(|pattern| {
+            if self.case_insensitive_keys {
+                glob::matches(&pattern.to_ascii_uppercase(), &key.to_ascii_uppercase())
+            } else {
+                glob::matches(pattern, key)
+            }
+        });
+
+        protected && env::var(key).ok().as_deref() != Some(value)
+    }
+
+    // Checks that populating the values would not fail, before anything is modified.
+    fn check_populate(&self, values: &HashMap<String, String>, sources: &HashMap<String, String>, override_policy: &OverridePolicy) -> Result<(), Exception> {
+        if let Some(frozen_values) = &self.frozen_values {
+            let frozen_key = values.iter()
+                .find(|(key, value)| frozen_values.get(*key).is_some_and(|frozen_value| frozen_value.as_ref() != Some(*value)))
+                .map(|(key, _)| key)
+                .or_else(|| self.unset_keys.iter().find(|key| frozen_values.get(*key).is_some_and(Option::is_some)));
+
+            if let Some(key) = frozen_key {
+                return Err(Exception::frozen(key.clone(), sources.get(key).cloned()));
+            }
+        }
+
+        if self.protected_variable_policy == ProtectedVariablePolicy::Error {
+            let loaded_keys = self.loaded_key_set();
+
+            let protected_key = values.iter()
+                .find(|(key, value)| {
+                    self.is_allowed(key)
+                        && self.is_overridable(key, self.existing_value(key).as_deref(), override_policy, &loaded_keys)
+                        && self.is_protected_modification(key, value)
+                })
+                .map(|(key, _)| key)
+                .or_else(|| self.unset_keys.iter()
+                    .find(|key| self.is_unsettable(key, values, override_policy, &loaded_keys) && self.is_protected_modification(key, "")));
+
+            if let Some(key) = protected_key {
+                return Err(Exception::protected_variable(key.clone(), sources.get(key).cloned()));
+            }
+        }
+
+        for middleware in self.middlewares.iter() {
+            middleware.validate(values).map_err(|reason| Exception::middleware("validate", reason))?;
+        }
+
+        Ok(())
+    }
+
+    fn populate_from(&mut self, values: &HashMap<String, String>, sources: &HashMap<String, String>, override_policy: &OverridePolicy) -> Result<Vec<String>, Exception> {
+        let mut populated_keys = Vec::new();
+
+        for file in mem::take(&mut self.merged_files) {
+            if !self.files.contains(&file) {
+                self.files.push(file);
+            }
+        }
+
+        let values_with_metadata;
+        let values = match self.inject_metadata {
+            true => {
+                values_with_metadata = self.with_metadata(values);
+                &values_with_metadata
+            },
+            false => values,
+        };
+
+        self.check_populate(values, sources, override_policy)?;
+
+        let loaded_keys = self.loaded_key_set();
+
+        for key in mem::take(&mut self.unset_keys) {
+            if !self.is_unsettable(&key, values, override_policy, &loaded_keys) {
+                continue;
+            }
+
+            if self.is_protected_modification(&key, "") {
+                let skipped = self.protected_variable_policy == ProtectedVariablePolicy::Skip;
+                self.report.protected_variable(ProtectedVariable::new(key.clone(), None, skipped));
+
+                if skipped {
+                    self.audit_entry(AuditAction::Skip, &key, None, None, "ProtectedVariablePolicy::Skip");
+                    continue;
+                }
+            }
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(key = key.as_str(), "unset variable");
+
+            self.audit_entry(AuditAction::Unset, &key, None, None, audit::policy_name(override_policy));
+            self.unset_variable(&key);
+        }
+
+        // Unsetting variables removes them from the loaded keys.
+        let loaded_keys = self.loaded_key_set();
+
+        for (key, value) in values.iter() {
+            let source = sources.get(key).map(String::as_str);
+
+            if !self.is_allowed(key) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(key = key.as_str(), "skipped variable, not allowed");
+
+                self.audit_entry(AuditAction::Skip, key, Some(value), source, "Dotenv::only/except");
+                continue;
+            }
+
+            let existing_value = self.existing_value(key);
+
+            if !self.is_overridable(key, existing_value.as_deref(), override_policy, &loaded_keys) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(key = key.as_str(), "skipped variable, already defined");
+
+                self.audit_entry(AuditAction::Skip, key, Some(value), source, audit::policy_name(override_policy));
+                continue;
+            }
+
+            if self.is_protected_modification(key, value) {
+                let skipped = self.protected_variable_policy == ProtectedVariablePolicy::Skip;
+                self.report.protected_variable(ProtectedVariable::new(key.clone(), sources.get(key).cloned(), skipped));
+
+                #[cfg(feature = "tracing")]
+                tracing::warn!(key = key.as_str(), skipped, "modification of a protected variable");
+
+                if skipped {
+                    self.audit_entry(AuditAction::Skip, key, Some(value), source, "ProtectedVariablePolicy::Skip");
+                    continue;
+                }
+            }
+
+            let action = match existing_value {
+                Some(_) => AuditAction::Override,
+                None => AuditAction::Set,
+            };
+            self.audit_entry(action, key, Some(value), source, audit::policy_name(override_policy));
+
+            #[cfg(feature = "tracing")]
+            {
+                let redacted = secrets::redact(value);
+                let source = sources.get(key).map(String::as_str);
+
+                if existing_value.is_some() {
+                    tracing::debug!(key = key.as_str(), value = redacted.as_str(), source, "overrode variable");
+                } else {
+                    tracing::trace!(key = key.as_str(), value = redacted.as_str(), source, "set variable");
+                }
+            }
+
+            if !self.previous_values.contains_key(key) {
+                let previously_loaded = self.is_loaded(&loaded_keys, key);
+                self.previous_values.insert(key.clone(), (existing_value.clone(), previously_loaded));
+            }
+
+            #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+            env::set_var(key, value);
+            self.values.insert(key.clone(), value.clone());
+
+            match sources.get(key) {
+                Some(source) => self.sources.insert(key.clone(), source.clone()),
+                None => self.sources.remove(key),
+            };
+
+            populated_keys.push(key.clone());
+        }
+
+        self.record_loaded_keys(&populated_keys);
+        self.report_defaults();
+
+        if !self.middlewares.is_empty() {
+            let populated_values = populated_keys.iter()
+                .map(|key| (key.clone(), values[key].clone()))
+                .collect::<HashMap<_, _>>();
+
+            for middleware in self.middlewares.iter() {
+                middleware.populate(&populated_values);
+            }
+        }
+
+        Ok(populated_keys)
+    }
+
+    fn audit_entry(&mut self, action: AuditAction, key: &str, value: Option<&str>, source: Option<&str>, policy: &'static str) {
+        let audit_key = &self.audit_key;
+
+        if let Some(audit_log) = &mut self.audit_log {
+            let value_hash = value.map(|value| audit::fingerprint(audit_key, value));
+            audit_log.push(AuditEntry::new(action, key, value_hash, source, policy));
+        }
+    }
+
+    // Forgets the state of an earlier load that failed before populating.
+    fn start_load(&mut self) {
+        self.unset_keys.clear();
+        self.merged_files.clear();
+    }
+
+    fn with_metadata(&self, values: &HashMap<String, String>) -> HashMap<String, String> {
+        let mut values = values.clone();
+        values.insert(metadata::LOADED_AT_VARIABLE.to_string(), metadata::timestamp(SystemTime::now()));
+
+        if !self.files.is_empty() {
+            values.insert(metadata::FILES_VARIABLE.to_string(), metadata::join_paths(&self.files));
+        }
+
+        if let Some(resolved_env) = &self.resolved_env {
+            values.insert(metadata::ENV_VARIABLE.to_string(), resolved_env.clone());
+        }
+
+        values
+    }
+
+    // Whether a variable unset by `export FOO` is removed from the environment, which is when it is not assigned again
+    // and loading would overwrite it.
+    fn is_unsettable(&self, key: &str, values: &HashMap<String, String>, override_policy: &OverridePolicy, loaded_keys: &HashSet<String>) -> bool {
+        if values.contains_key(key) || !self.is_allowed(key) {
+            return false;
+        }
+
+        match self.existing_value(key) {
+            Some(existing_value) => self.is_overridable(key, Some(&existing_value), override_policy, loaded_keys),
+            None => false,
+        }
+    }
+
+    fn unset_variable(&mut self, key: &str) {
+        let loaded_keys: Vec<String> = self.loaded_keys().into_iter().filter(|name| name != key).collect();
+
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        {
+            env::remove_var(key);
+
+            match loaded_keys.is_empty() {
+                true => env::remove_var(Self::LOADED_KEYS_VARIABLE),
+                false => env::set_var(Self::LOADED_KEYS_VARIABLE, loaded_keys.join(",")),
+            }
+        }
+
+        self.values.remove(key);
+        self.sources.remove(key);
+    }
+
+    fn report_defaults(&mut self) {
+        let mut defaults: Vec<String> = self.defaults.keys()
+            .filter(|key| !self.values.contains_key(*key) && self.existing_value(key).is_none())
+            .cloned()
+            .collect();
+        defaults.sort();
+
+        self.report.set_defaults(defaults);
+    }
+
+    fn record_loaded_keys(&self, keys: &[String]) {
+        if keys.is_empty() {
+            return;
+        }
+
+        let mut loaded_keys = self.loaded_keys();
+        loaded_keys.extend(keys.iter().cloned());
+        loaded_keys.sort();
+        loaded_keys.dedup();
+
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        env::set_var(Self::LOADED_KEYS_VARIABLE, loaded_keys.join(","));
+    }
+
+    fn explain_populate(&self, values: &HashMap<String, String>, sources: &HashMap<String, String>, override_policy: &OverridePolicy, explanation: &mut Explanation) -> Vec<String> {
+        let mut populated_keys = Vec::new();
+        let loaded_keys = self.loaded_key_set();
+
+        for (key, value) in values.iter() {
+            if !self.is_allowed(key) {
+                continue;
+            }
+
+            let existing_value = explanation.get(key).map(str::to_string).or_else(|| self.existing_value(key));
+
+            if !self.is_overridable(key, existing_value.as_deref(), override_policy, &loaded_keys) {
+                explanation.variable(key, &existing_value.unwrap_or_default(), None, true);
+                continue;
+            }
+
+            explanation.variable(key, value, sources.get(key), false);
+            populated_keys.push(key.clone());
+        }
+
+        populated_keys
+    }
+}
+
+/// Options for opening a file that receives secrets, which is created readable and writable by its owner only on unix
+pub(crate) fn private_file_options() -> fs::OpenOptions {
+    let mut options = fs::OpenOptions::new();
+
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    options
+}
+
+/// Writes a file that receives secrets, as by `fs::write()`, but creates it with the options of `private_file_options()`
+fn write_private(path: &str, contents: &str) -> io::Result<()> {
+    use std::io::Write;
+
+    private_file_options().write(true).create(true).truncate(true).open(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+}
+
+/// Kind of a file that is neither a regular file nor a directory, for `Dotenv::read_file()`
+fn special_file_type(metadata: &fs::Metadata) -> &'static str {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+
+        let file_type = metadata.file_type();
+
+        if file_type.is_fifo() {
+            return "fifo";
+        }
+
+        if file_type.is_socket() {
+            return "socket";
+        }
+
+        if file_type.is_block_device() || file_type.is_char_device() {
+            return "device";
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = metadata;
+
+    "special file"
+}
+
+/// Parsed entry that may assign a variable, for `Dotenv::apply_policies()`
+trait AsAssignment {
+    fn assignment(&self) -> Option<(&str, &str, usize)>;
+
+    fn rename(&mut self, name: String);
+}
+
+impl AsAssignment for (String, String, usize) {
+    fn assignment(&self) -> Option<(&str, &str, usize)> {
+        Some((&self.0, &self.1, self.2))
+    }
+
+    fn rename(&mut self, name: String) {
+        self.0 = name;
+    }
+}
+
+impl AsAssignment for Statement {
+    fn assignment(&self) -> Option<(&str, &str, usize)> {
+        match self {
+            Statement::Assignment(name, value, line_number, _) => Some((name, value, *line_number)),
+            Statement::Include(_, _) | Statement::Section(_, _) | Statement::Comment(_, _) | Statement::Unset(_, _) => None,
+        }
+    }
+
+    fn rename(&mut self, name: String) {
+        if let Statement::Assignment(assigned_name, ..) = self {
+            *assigned_name = name;
+        }
+    }
+}
+
+impl Default for Dotenv {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+    use std::collections::HashMap;
+
+    use crate::{AuditAction, DocumentationFormat, Dotenv, DuplicateKeyPolicy, EmptyValuePolicy, EscapeStyle, ExceptionKind, FilePermissionPolicy, FormatStyle, InterpolationMode, KeyOrder, KeySpec, LintKind, OverridePolicy, ProtectedVariablePolicy, ViolationKind};
+    use crate::decorator::{Base64Decorator, ResolveDecorator};
+    use crate::encryption::Decryptor;
+    use crate::temp_dir::TempDir;
+
+    #[test]
+    fn load_from_str() {
+        let mut dotenv = Dotenv::new();
+        dotenv.load_from_str("DOTENV_TEST_LOAD_FROM_STR=bar").unwrap();
+        assert_eq!(dotenv.values().get("DOTENV_TEST_LOAD_FROM_STR").unwrap(), "bar");
+        assert_eq!(env::var("DOTENV_TEST_LOAD_FROM_STR").unwrap(), "bar");
+    }
+
+    #[test]
+    fn load_glob() {
+        let directory = TempDir::new("load-glob");
+        fs::write(directory.join("10-first.env"), "DOTENV_TEST_GLOB_A=first\nDOTENV_TEST_GLOB_B=first").unwrap();
+        fs::write(directory.join("20-second.env"), "DOTENV_TEST_GLOB_B=second").unwrap();
+        fs::write(directory.join("30-ignored.txt"), "DOTENV_TEST_GLOB_C=ignored").unwrap();
+
+        let mut dotenv = Dotenv::new();
+        dotenv.load_glob(directory.join("*.env").to_string_lossy()).unwrap();
+
+        assert_eq!(dotenv.values().get("DOTENV_TEST_GLOB_A").unwrap(), "first");
+        assert_eq!(dotenv.values().get("DOTENV_TEST_GLOB_B").unwrap(), "second");
+        assert!(dotenv.values().get("DOTENV_TEST_GLOB_C").is_none());
+    }
+
+    #[test]
+    fn load_dir_merges_many_files_in_order() {
+        let directory = TempDir::new("load-dir-many");
+
+        // Each file is padded with a comment, so that together they are large enough to be parsed in parallel.
+        let padding = "#".repeat(16 * 1024);
+
+        for index in 0..24 {
+            fs::write(directory.join(format!("{:02}.env", index)), format!("{1}\nDOTENV_TEST_DIR_MANY_LAST={0}\nDOTENV_TEST_DIR_MANY_{0}={0}", index, padding)).unwrap();
+        }
+
+        let mut dotenv = Dotenv::new();
+        dotenv.load_dir(directory.to_string_lossy()).unwrap();
+        assert_eq!(dotenv.values().get("DOTENV_TEST_DIR_MANY_LAST").unwrap(), "23");
+        assert_eq!(dotenv.values().get("DOTENV_TEST_DIR_MANY_7").unwrap(), "7");
+        assert_eq!(dotenv.source_of("DOTENV_TEST_DIR_MANY_LAST"), Some(directory.join("23.env").to_string_lossy().as_ref()));
+
+        fs::write(directory.join("05.env"), "INVALID").unwrap();
+        fs::write(directory.join("17.env"), "INVALID").unwrap();
+        let exception = Dotenv::new().load_dir(directory.to_string_lossy()).unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Format);
+        assert_eq!(exception.path(), Some(directory.join("05.env").to_string_lossy().as_ref()));
+    }
+
+    #[test]
+    fn load_dir_with_extension() {
+        let directory = TempDir::new("load-dir");
+        fs::write(directory.join("b.env"), "DOTENV_TEST_DIR_A=b").unwrap();
+        fs::write(directory.join("a.env"), "DOTENV_TEST_DIR_A=a\nDOTENV_TEST_DIR_B=a").unwrap();
+        fs::write(directory.join("c.txt"), "DOTENV_TEST_DIR_C=c").unwrap();
+
+        let mut dotenv = Dotenv::new();
+        dotenv.load_dir_with_extension(directory.to_string_lossy(), "env").unwrap();
+
+        assert_eq!(dotenv.values().get("DOTENV_TEST_DIR_A").unwrap(), "b");
+        assert!(dotenv.source_of("DOTENV_TEST_DIR_A").unwrap().ends_with("b.env"));
+        assert!(dotenv.source_of("DOTENV_TEST_DIR_B").unwrap().ends_with("a.env"));
+        assert!(dotenv.values().get("DOTENV_TEST_DIR_C").is_none());
+    }
+
+    #[test]
+    fn load_includes() {
+        let directory = TempDir::new("includes");
+        fs::create_dir_all(directory.join("shared")).unwrap();
+        fs::write(directory.join(".env"), "DOTENV_TEST_INCLUDE_A=main\n#!include shared/base.env\nDOTENV_TEST_INCLUDE_C=main").unwrap();
+        fs::write(directory.join("shared/base.env"), "DOTENV_TEST_INCLUDE_A=base\nDOTENV_TEST_INCLUDE_B=base\nDOTENV_TEST_INCLUDE_C=base").unwrap();
+
+        let mut dotenv = Dotenv::new().includes(true);
+        dotenv.load(directory.join(".env").to_string_lossy()).unwrap();
+
+        assert_eq!(dotenv.values().get("DOTENV_TEST_INCLUDE_A").unwrap(), "base");
+        assert_eq!(dotenv.values().get("DOTENV_TEST_INCLUDE_B").unwrap(), "base");
+        assert_eq!(dotenv.values().get("DOTENV_TEST_INCLUDE_C").unwrap(), "main");
+    }
+
+    #[test]
+    fn load_section() {
+        let directory = TempDir::new("section");
+        let path = directory.join(".env");
+        fs::write(&path, "DOTENV_TEST_SECTION_NAME=shared\nDOTENV_TEST_SECTION_DEBUG=unset\n\n\
+            [production]\nDOTENV_TEST_SECTION_DEBUG=false\n\n\
+            [development]\nDOTENV_TEST_SECTION_DEBUG=true\nDOTENV_TEST_SECTION_DEVELOPMENT=true\n").unwrap();
+
+        let mut dotenv = Dotenv::new().duplicate_key_policy(DuplicateKeyPolicy::Error);
+        dotenv.load_section(path.to_string_lossy(), "production").unwrap();
+
+        assert_eq!(dotenv.values()["DOTENV_TEST_SECTION_NAME"], "shared");
+        assert_eq!(dotenv.values()["DOTENV_TEST_SECTION_DEBUG"], "false");
+        assert!(!dotenv.values().contains_key("DOTENV_TEST_SECTION_DEVELOPMENT"));
+    }
+
+    #[test]
+    fn load_profiles() {
+        let directory = TempDir::new("profiles");
+        fs::write(directory.join(".env.profile.base"), "DOTENV_TEST_PROFILE_NAME=base\nDOTENV_TEST_PROFILE_REGION=none").unwrap();
+        fs::write(directory.join(".env.profile.eu-west"), "DOTENV_TEST_PROFILE_REGION=eu-west").unwrap();
+
+        let path = directory.join(".env").to_string_lossy().to_string();
+
+        let mut dotenv = Dotenv::new();
+        dotenv.load_profiles(&path, &["base", "eu-west"]).unwrap();
+        let exception = dotenv.load_profiles(&path, &["missing"]).unwrap_err();
+
+        assert_eq!(dotenv.values()["DOTENV_TEST_PROFILE_NAME"], "base");
+        assert_eq!(dotenv.values()["DOTENV_TEST_PROFILE_REGION"], "eu-west");
+        assert_eq!(exception.kind(), ExceptionKind::Path);
+    }
+
+    #[test]
+    fn sync_example() {
+        let directory = TempDir::new("sync-example");
+        fs::write(directory.join(".env"), "APP_NAME=example\nAPP_SECRET=secret\nDB_HOST=localhost").unwrap();
+        fs::write(directory.join(".env.example"), "# Application\nAPP_NAME=\nAPP_DEBUG=false").unwrap();
+
+        let env_path = directory.join(".env").to_string_lossy().to_string();
+        let example_path = directory.join(".env.example").to_string_lossy().to_string();
+
+        let mut dotenv = Dotenv::new();
+        let sync = dotenv.sync_example(&env_path, &example_path).unwrap();
+        let example = fs::read_to_string(&example_path).unwrap();
+        let resync = dotenv.sync_example(&env_path, &example_path).unwrap();
+
+        assert_eq!(sync.added(), ["APP_SECRET", "DB_HOST"]);
+        assert_eq!(sync.missing(), ["APP_DEBUG"]);
+        assert_eq!(example, "# Application\nAPP_NAME=\nAPP_DEBUG=false\nAPP_SECRET=\nDB_HOST=\n");
+        assert!(resync.added().is_empty());
+    }
+
+    #[test]
+    fn init_from_example() {
+        let directory = TempDir::new("init-from-example");
+        fs::write(directory.join(".env.example"), "# The listen port\n# @type: int @default: 8080\nAPP_PORT=<int>\nAPP_NAME=example # name\nAPP_SECRET=changeme\n").unwrap();
+
+        let env_path = directory.join(".env").to_string_lossy().to_string();
+        let example_path = directory.join(".env.example").to_string_lossy().to_string();
+
+        let mut dotenv = Dotenv::new();
+        let init = dotenv.init_from_example(&example_path, &env_path).unwrap();
+        let env = fs::read_to_string(&env_path).unwrap();
+        let exception = dotenv.init_from_example(&example_path, &env_path).unwrap_err();
+
+        assert_eq!(init.filled(), ["APP_PORT", "APP_NAME"]);
+        assert_eq!(init.missing(), ["APP_SECRET"]);
+        assert_eq!(env, "# The listen port\n# @type: int @default: 8080\nAPP_PORT=8080\nAPP_NAME=example # name\nAPP_SECRET=\n");
+        assert_eq!(exception.kind(), ExceptionKind::Write);
+    }
+
+    #[test]
+    fn validate_document() {
+        let directory = TempDir::new("validate");
+        let path = directory.join(".env");
+        fs::write(&path, "# @type: int @required\nDOTENV_TEST_VALIDATE_PORT=\n# @type: bool\nDOTENV_TEST_VALIDATE_DEBUG=\n").unwrap();
+
+        let mut dotenv = Dotenv::new();
+        let document = dotenv.parse_document(path.to_string_lossy()).unwrap();
+
+        assert_eq!(dotenv.validate(&document).unwrap_err().kind(), ExceptionKind::UndefinedVariable);
+
+        dotenv.overload_from_str("DOTENV_TEST_VALIDATE_PORT=http").unwrap();
+        assert_eq!(dotenv.validate(&document).unwrap_err().kind(), ExceptionKind::Processing);
+
+        dotenv.overload_from_str("DOTENV_TEST_VALIDATE_PORT=8080").unwrap();
+        dotenv.validate(&document).unwrap();
+        assert_eq!(dotenv.get_typed(&document, "DOTENV_TEST_VALIDATE_PORT").unwrap().as_int(), Some(8080));
+    }
+
+    #[test]
+    fn violations() {
+        let data = "# @required @format: url\nDOTENV_TEST_VIOLATIONS_URL=\n# @type: int @format: port\nDOTENV_TEST_VIOLATIONS_PORT=\n# @enum: dev,prod\nDOTENV_TEST_VIOLATIONS_ENV=\n";
+        let document = crate::DotenvDocument::parse(data.to_string(), crate::parser::ParserOptions { comments: true, ..Default::default() }).unwrap();
+
+        let mut dotenv = Dotenv::new().key_spec("DOTENV_TEST_VIOLATIONS_NAME", KeySpec::parse("@max-length: 4"));
+        dotenv.overload_from_str("DOTENV_TEST_VIOLATIONS_PORT=http\nDOTENV_TEST_VIOLATIONS_ENV=test\nDOTENV_TEST_VIOLATIONS_NAME=example").unwrap();
+
+        let violations = dotenv.violations(&document);
+        let violations: Vec<(&str, ViolationKind)> = violations.iter()
+            .map(|violation| (violation.key().trim_start_matches("DOTENV_TEST_VIOLATIONS_"), violation.kind()))
+            .collect();
+        assert_eq!(violations, [
+            ("URL", ViolationKind::Required),
+            ("PORT", ViolationKind::Type),
+            ("PORT", ViolationKind::Format),
+            ("ENV", ViolationKind::Enum),
+            ("NAME", ViolationKind::Length),
+        ]);
+
+        dotenv.overload_from_str("DOTENV_TEST_VIOLATIONS_URL=https://example.com\nDOTENV_TEST_VIOLATIONS_PORT=8080\nDOTENV_TEST_VIOLATIONS_ENV=prod").unwrap();
+        let exception = dotenv.validate(&document).unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Validation);
+        assert_eq!(exception.message(), "Environment variable \"DOTENV_TEST_VIOLATIONS_NAME\" is longer than 4 characters");
+
+        dotenv.overload_from_str("DOTENV_TEST_VIOLATIONS_NAME=app").unwrap();
+        assert!(dotenv.violations(&document).is_empty());
+    }
+
+    #[test]
+    fn document() {
+        let directory = TempDir::new("document");
+        fs::write(directory.join(".env"), "APP_NAME=example").unwrap();
+        fs::write(directory.join(".env.example"), "# The port\n# @type: int\nAPP_PORT=\nAPP_NAME=").unwrap();
+        fs::write(directory.join(".env.swp"), "not an environment file").unwrap();
+
+        let path = directory.join(".env").to_string_lossy().to_string();
+
+        let mut dotenv = Dotenv::new();
+        dotenv.set_default("APP_PORT", "8080");
+        let json = dotenv.document(&path, DocumentationFormat::Json).unwrap();
+
+        assert_eq!(json, format!("{{\
+            \"APP_NAME\":{{\"description\":null,\"type\":null,\"required\":false,\"secret\":false,\"default\":null,\"files\":[\"{0}\",\"{0}.example\"]}},\
+            \"APP_PORT\":{{\"description\":\"The port\",\"type\":\"int\",\"required\":false,\"secret\":false,\"default\":\"8080\",\"files\":[\"{0}.example\"]}}}}", path));
+    }
+
+    #[test]
+    fn load_circular_includes() {
+        let directory = TempDir::new("circular-includes");
+        fs::write(directory.join("a.env"), "source b.env").unwrap();
+        fs::write(directory.join("b.env"), "source a.env").unwrap();
+
+        let mut dotenv = Dotenv::new().includes(true);
+        let path = directory.join("a.env").to_string_lossy().to_string();
+        let exception = dotenv.load(&path).unwrap_err();
+
+        let chain = format!("{} -> {} -> {}", path, directory.join("b.env").to_string_lossy(), path);
+        assert_eq!(exception.kind(), ExceptionKind::CircularInclude);
+        assert!(exception.to_string().contains(&chain));
+    }
+
+    #[test]
+    fn load_encrypted_values() {
+        struct ReverseDecryptor;
+
+        impl Decryptor for ReverseDecryptor {
+            fn decrypt(&self, master_key: &str, ciphertext: &[u8]) -> Result<String, String> {
+                assert_eq!(master_key, "key");
+                Ok(String::from_utf8_lossy(ciphertext).chars().rev().collect())
+            }
         }
+
+        let mut dotenv = Dotenv::new()
+            .decryptor(ReverseDecryptor)
+            .master_key(|| Some("key".to_string()));
+
+        dotenv.load_from_str("DOTENV_TEST_ENCRYPTED=enc:v1:c3NhcA==\nDOTENV_TEST_PLAIN=pass").unwrap();
+        assert_eq!(dotenv.values().get("DOTENV_TEST_ENCRYPTED").unwrap(), "pass");
+        assert_eq!(dotenv.values().get("DOTENV_TEST_PLAIN").unwrap(), "pass");
+
+        let exception = Dotenv::new()
+            .decryptor(ReverseDecryptor)
+            .master_key(|| None)
+            .load_from_str("DOTENV_TEST_ENCRYPTED=enc:v1:c3NhcA==")
+            .unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Decryption);
+        assert_eq!(exception.key(), Some("DOTENV_TEST_ENCRYPTED"));
     }
 
-    ///
-    /// Loads environment variables from file a `.env` file.
-    ///
-    /// # Examples
-    ///
-    /// ```rust
-    /// use darkweb_dotenv::Dotenv;
-    ///
-    /// let mut dotenv = Dotenv::new();
-    /// dotenv.load(".env").unwrap();
-    /// ```
-    ///
-    /// # Exceptions
-    ///
-    /// * `Exception::FormatException`
-    /// * `Exception::PathException`
-    ///
-    pub fn load<Path>(&mut self, path: Path) -> Result<(), Exception>
-        where
-            Path: AsRef<str> {
+    #[test]
+    fn load_resolved_references() {
+        let mut dotenv = Dotenv::new().resolver("test", |path: &str| match path {
+            "database/password" => Ok("pass".to_string()),
+            _ => Err("not found".to_string()),
+        });
 
-        let path = path.as_ref().to_string();
-        let data = self.read_file(&path)?;
+        dotenv.load_from_str("DOTENV_TEST_RESOLVED=test://database/password\nDOTENV_TEST_URL=https://example.com").unwrap();
+        assert_eq!(dotenv.values().get("DOTENV_TEST_RESOLVED").unwrap(), "pass");
+        assert_eq!(dotenv.values().get("DOTENV_TEST_URL").unwrap(), "https://example.com");
 
-        let values = self.parse(data, path)?;
+        let exception = dotenv.load_from_str("DOTENV_TEST_RESOLVED=test://missing").unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Resolution);
+    }
 
-        self.populate(&values, false);
+    #[test]
+    fn explain() {
+        let directory = TempDir::new("explain");
+        fs::write(directory.join(".env"), "DOTENV_TEST_EXPLAIN_A=a\nDOTENV_TEST_EXPLAIN_B=b").unwrap();
+        fs::write(directory.join(".env.test"), "DOTENV_TEST_EXPLAIN_B=c").unwrap();
 
-        Ok(())
+        let path = directory.join(".env").to_string_lossy().to_string();
+        let mut dotenv = Dotenv::new();
+        let explanation = dotenv.explain(&path, "DOTENV_TEST_EXPLAIN_ENV", "test").unwrap();
+
+        let found: Vec<bool> = explanation.files().iter().map(|file| file.found()).collect();
+        assert_eq!(found, vec![true, false, true, false]);
+
+        let variable = explanation.variables().get("DOTENV_TEST_EXPLAIN_B").unwrap();
+        assert_eq!(variable.value(), "c");
+        assert_eq!(variable.source(), Some(format!("{}.test", path).as_str()));
+        assert!(!variable.skipped());
+
+        assert!(env::var("DOTENV_TEST_EXPLAIN_A").is_err());
+        assert!(dotenv.values().is_empty());
     }
 
-    ///
-    /// Loads environment variables from a `.env` file and overwrites exiting environment variables.
-    ///
-    /// # Examples
-    ///
-    /// ```rust
-    /// use darkweb_dotenv::Dotenv;
-    ///
-    /// let mut dotenv = Dotenv::new();
-    /// dotenv.overload(".env").unwrap();
-    /// ```
-    ///
-    /// # Exceptions
-    ///
-    /// * `Exception::FormatException`
-    /// * `Exception::PathException`
-    ///
-    pub fn overload<Path>(&mut self, path: Path) -> Result<(), Exception>
-        where
-            Path: AsRef<str> {
+    #[test]
+    fn duplicate_key_policy() {
+        let data = "DOTENV_TEST_DUPLICATE=first\nDOTENV_TEST_DUPLICATE=second";
 
-        let path = path.as_ref().to_string();
-        let data = self.read_file(&path)?;
+        let mut dotenv = Dotenv::new();
+        dotenv.overload_from_str(data).unwrap();
+        assert_eq!(dotenv.values().get("DOTENV_TEST_DUPLICATE").unwrap(), "second");
+        assert!(dotenv.report().is_empty());
 
-        let values = self.parse(data, path)?;
+        let mut dotenv = Dotenv::new().duplicate_key_policy(DuplicateKeyPolicy::FirstWins);
+        dotenv.overload_from_str(data).unwrap();
+        assert_eq!(dotenv.values().get("DOTENV_TEST_DUPLICATE").unwrap(), "first");
 
-        self.populate(&values, true);
+        let mut dotenv = Dotenv::new().duplicate_key_policy(DuplicateKeyPolicy::Warn);
+        dotenv.overload_from_str(data).unwrap();
+        assert_eq!(dotenv.values().get("DOTENV_TEST_DUPLICATE").unwrap(), "second");
+        assert_eq!(dotenv.report().duplicates().len(), 1);
+        assert_eq!(dotenv.report().duplicates()[0].first_line_number(), 1);
+        assert_eq!(dotenv.report().duplicates()[0].line_number(), 2);
 
-        Ok(())
+        let mut dotenv = Dotenv::new().duplicate_key_policy(DuplicateKeyPolicy::Error);
+        let exception = dotenv.overload_from_str(data).unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::DuplicateKey);
+        assert_eq!(exception.line(), Some(2));
     }
 
-    ///
-    /// Loads environment-specific environment variables from multiple `.env` files in an hierarchy.
-    ///
-    /// The following files are loaded if they exist, the latter taking precedence over the former:
-    /// * `.env` --> committed environment defaults
-    /// * `.env.local` --> uncommitted file with local overrides
-    /// * `.env.{APP_ENV}` --> committed environment-specific defaults
-    /// * `.env.{APP_ENV}.local` --> uncommitted environment-specific local overrides
-    ///
-    /// # Examples
-    ///
-    /// ```rust
-    /// use darkweb_dotenv::Dotenv;
-    ///
-    /// let mut dotenv = Dotenv::new();
-    /// dotenv.load_env(".env", "APP_ENV", "dev").unwrap();
-    /// ```
-    ///
-    /// # Exceptions
-    ///
-    /// * `Exception::FormatException`
-    /// * `Exception::PathException`
-    ///
-    pub fn load_env<Path, EnvKey, DefaultEnv>(&mut self, path: Path, env_key: EnvKey, default_env: DefaultEnv) -> Result<(), Exception>
-        where
-            Path: AsRef<str>,
-            EnvKey: AsRef<str>,
-            DefaultEnv: AsRef<str> {
+    #[test]
+    fn empty_value_policy() {
+        let data = "DOTENV_TEST_EMPTY_SKIPPED=\nDOTENV_TEST_EMPTY_SET=set";
 
-        let path = path.as_ref().to_string();
-        let env_key = env_key.as_ref().to_string();
-        let default_env = default_env.as_ref().to_string();
+        let mut dotenv = Dotenv::new().empty_value_policy(EmptyValuePolicy::Skip);
+        dotenv.load_from_str(data).unwrap();
+        assert!(!dotenv.values().contains_key("DOTENV_TEST_EMPTY_SKIPPED"));
+        assert_eq!(dotenv.values().get("DOTENV_TEST_EMPTY_SET").unwrap(), "set");
 
-        let mut values = HashMap::new();
+        let mut dotenv = Dotenv::new().empty_value_policy(EmptyValuePolicy::Error);
+        let exception = dotenv.load_from_str(data).unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::EmptyValue);
+        assert_eq!(exception.key(), Some("DOTENV_TEST_EMPTY_SKIPPED"));
+    }
 
-        if let Ok(data) = self.read_file(&path) {
-            values.extend(self.parse(data, &path)?)
-        }
+    #[test]
+    fn whitespace_around_equals() {
+        let mut dotenv = Dotenv::new().whitespace_around_equals(true);
+        dotenv.load_from_str("DOTENV_TEST_WHITESPACE = value").unwrap();
+        assert_eq!(dotenv.values().get("DOTENV_TEST_WHITESPACE").unwrap(), "value");
+    }
 
-        let local_path = format!("{}.local", path);
+    #[test]
+    fn load_prefixed() {
+        let directory = TempDir::new("load-prefixed");
+        let path = directory.join(".env");
+        fs::write(&path, "DOTENV_TEST_PREFIXED_PORT=8080\nDOTENV_TEST_UNPREFIXED_PORT=8081").unwrap();
 
-        if let Ok(data) = self.read_file(&local_path) {
-            values.extend(self.parse(data, local_path)?)
-        }
+        let mut dotenv = Dotenv::new();
+        dotenv.load_prefixed(path.to_string_lossy(), "DOTENV_TEST_PREFIXED_").unwrap();
+        assert!(env::var("DOTENV_TEST_UNPREFIXED_PORT").is_err());
+
+        let scope = dotenv.scoped("DOTENV_TEST_PREFIXED_");
+        assert_eq!(scope.get("PORT").unwrap(), "8080");
+        assert_eq!(scope.keys(), vec!["PORT"]);
+    }
 
-        self.populate(&values, false);
-        values.clear();
+    #[test]
+    fn only_except() {
+        let data = "DOTENV_TEST_ONLY_A=a\nDOTENV_TEST_ONLY_B=b\nDOTENV_TEST_ONLY_C=c";
 
-        let env = match env::var_os(env_key) {
-            Some(value) => value.to_string_lossy().to_string(),
-            None => default_env,
-        };
+        let mut dotenv = Dotenv::new().only(&["DOTENV_TEST_ONLY_A", "DOTENV_TEST_ONLY_B"]).except(&["DOTENV_TEST_ONLY_B"]);
+        dotenv.load_from_str(data).unwrap();
 
-        if &env == "local" {
-            return Ok(());
-        }
+        let mut keys: Vec<&String> = dotenv.values().keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["DOTENV_TEST_ONLY_A"]);
+        assert!(env::var("DOTENV_TEST_ONLY_C").is_err());
+    }
+
+    #[test]
+    fn protected_variable_policy() {
+        env::set_var("DOTENV_TEST_PROTECTED_PATH", "/usr/bin");
+        let data = "DOTENV_TEST_PROTECTED_PATH=/tmp/evil\nDOTENV_TEST_PROTECTED_OTHER=value";
 
-        let env_path = format!("{}.{}", path, env);
+        let mut dotenv = Dotenv::new()
+            .protected_variables(&["DOTENV_TEST_PROTECTED_P*"])
+            .protected_variable_policy(ProtectedVariablePolicy::Error);
+        let exception = dotenv.overload_from_str(data).unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::ProtectedVariable);
+        assert!(env::var("DOTENV_TEST_PROTECTED_OTHER").is_err());
 
-        if let Ok(data) = self.read_file(&env_path) {
-            values.extend(self.parse(data, env_path)?)
-        }
+        let mut dotenv = Dotenv::new()
+            .protected_variables(&["DOTENV_TEST_PROTECTED_P*"])
+            .protected_variable_policy(ProtectedVariablePolicy::Skip);
+        dotenv.overload_from_str(data).unwrap();
+        assert_eq!(env::var("DOTENV_TEST_PROTECTED_PATH").unwrap(), "/usr/bin");
+        assert_eq!(env::var("DOTENV_TEST_PROTECTED_OTHER").unwrap(), "value");
+        assert_eq!(dotenv.report().protected_variables()[0].name(), "DOTENV_TEST_PROTECTED_PATH");
+        assert!(dotenv.report().protected_variables()[0].skipped());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_permission_policy() {
+        use std::os::unix::fs::PermissionsExt;
 
-        let env_local_path = format!("{}.{}.local", path, env);
+        let directory = TempDir::new("permissions");
+        let path = directory.join(".env");
+        fs::write(&path, "DOTENV_TEST_PERMISSIONS_API_TOKEN=abc\nDOTENV_TEST_PERMISSIONS_NAME=app").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        let path = path.to_string_lossy().to_string();
 
-        if let Ok(data) = self.read_file(&env_local_path) {
-            values.extend(self.parse(data, env_local_path)?)
-        }
+        let mut dotenv = Dotenv::new().file_permission_policy(FilePermissionPolicy::Error);
+        let exception = dotenv.overload(&path).unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::InsecurePermissions);
+        assert!(env::var("DOTENV_TEST_PERMISSIONS_NAME").is_err());
 
-        self.populate(&values, false);
+        let mut dotenv = Dotenv::new().file_permission_policy(FilePermissionPolicy::Warn);
+        dotenv.overload(&path).unwrap();
+        let insecure_file = &dotenv.report().insecure_files()[0];
+        assert_eq!((insecure_file.mode(), insecure_file.secret_keys()), (0o644, &["DOTENV_TEST_PERMISSIONS_API_TOKEN".to_string()][..]));
 
-        Ok(())
+        let warnings = Dotenv::new().lint(&path).unwrap();
+        assert!(warnings.iter().any(|warning| warning.kind() == LintKind::InsecurePermissions));
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        let mut dotenv = Dotenv::new().file_permission_policy(FilePermissionPolicy::Error);
+        dotenv.overload(&path).unwrap();
+        assert!(dotenv.report().is_empty());
     }
 
-    fn read_file<Path>(&mut self, path: Path) -> Result<String, Exception>
-        where
-            Path: AsRef<str> {
+    #[test]
+    fn limits() {
+        let data = "DOTENV_TEST_LIMIT_A=a\nDOTENV_TEST_LIMIT_B=bbbb";
 
-        let path = path.as_ref();
+        let exception = Dotenv::new().max_file_size(10).load_from_str(data).unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::LimitExceeded);
 
-        match fs::read_to_string(path) {
-            Ok(data) => Ok(data),
-            Err(_) => Err(Exception::PathException(path.to_string())),
-        }
+        let exception = Dotenv::new().max_variables(1).load_from_str(data).unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::LimitExceeded);
+
+        let exception = Dotenv::new().max_value_length(3).load_from_str(data).unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::LimitExceeded);
+        assert_eq!(exception.key(), Some("DOTENV_TEST_LIMIT_B"));
+
+        let directory = TempDir::new("limits");
+        let path = directory.join(".env");
+        fs::write(&path, data).unwrap();
+        let exception = Dotenv::new().max_file_size(10).load(path.to_string_lossy()).unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::LimitExceeded);
+        assert_eq!(exception.path(), Some(path.to_string_lossy().as_ref()));
+
+        assert!(env::var("DOTENV_TEST_LIMIT_A").is_err());
     }
 
-    fn parse<Data, Path>(&mut self, data: Data, path: Path) -> Result<HashMap<String, String>, Exception>
-        where
-            Data: AsRef<str>,
-            Path: AsRef<str> {
+    #[test]
+    fn load_does_not_override() {
+        env::set_var("DOTENV_TEST_LOAD_EXISTING", "existing");
 
-        self.path = path.as_ref().to_string();
-        self.data = data.as_ref().replace("\r\n", "\n");
-        self.line_number = 1;
-        self.cursor = 0;
-        self.end = self.data.len();
-        self.state = Self::STATE_VARNAME;
+        let mut dotenv = Dotenv::new();
+        dotenv.load_from_str("DOTENV_TEST_LOAD_EXISTING=loaded\nDOTENV_TEST_LOAD_NEW=loaded").unwrap();
+        assert_eq!(env::var("DOTENV_TEST_LOAD_EXISTING").unwrap(), "existing");
+        assert_eq!(env::var("DOTENV_TEST_LOAD_NEW").unwrap(), "loaded");
+        assert!(!dotenv.values().contains_key("DOTENV_TEST_LOAD_EXISTING"));
+    }
 
-        let mut values = HashMap::new();
+    #[test]
+    fn overload_overrides() {
+        env::set_var("DOTENV_TEST_OVERLOAD_EXISTING", "existing");
 
-        let mut name = "".to_string();
+        let mut dotenv = Dotenv::new();
+        dotenv.overload_from_str("DOTENV_TEST_OVERLOAD_EXISTING=overloaded").unwrap();
+        assert_eq!(env::var("DOTENV_TEST_OVERLOAD_EXISTING").unwrap(), "overloaded");
+        assert_eq!(dotenv.values().get("DOTENV_TEST_OVERLOAD_EXISTING").unwrap(), "overloaded");
+    }
 
-        self.skip_empty_lines();
+    #[test]
+    fn reload_overrides_loaded_keys() {
+        env::set_var("DOTENV_TEST_RELOAD_EXISTING", "existing");
 
-        while self.cursor < self.end {
-            match self.state {
-                Self::STATE_VARNAME => {
-                    name = self.lex_varname()?;
-                    self.state = Self::STATE_VALUE;
-                },
-                Self::STATE_VALUE => {
-                    let value = self.lex_value()?;
-                    values.insert(name.clone(), value);
-                    self.state = Self::STATE_VARNAME;
-                },
-                _ => unreachable!("invalid state"),
-            }
-        }
+        Dotenv::new().load_from_str("DOTENV_TEST_RELOAD_LOADED=first\nDOTENV_TEST_RELOAD_EXISTING=first").unwrap();
 
-        if self.state == Self::STATE_VALUE {
-            values.insert(name.clone(), "".to_string());
-        }
+        let mut dotenv = Dotenv::new();
+        dotenv.load_from_str("DOTENV_TEST_RELOAD_LOADED=second\nDOTENV_TEST_RELOAD_EXISTING=second").unwrap();
+        assert_eq!(env::var("DOTENV_TEST_RELOAD_LOADED").unwrap(), "second");
+        assert_eq!(env::var("DOTENV_TEST_RELOAD_EXISTING").unwrap(), "existing");
 
-        Ok(values)
+        let loaded_keys = dotenv.loaded_keys();
+        assert!(loaded_keys.contains(&"DOTENV_TEST_RELOAD_LOADED".to_string()));
+        assert!(!loaded_keys.contains(&"DOTENV_TEST_RELOAD_EXISTING".to_string()));
     }
 
-    fn lex_varname(&mut self) -> Result<String, Exception> {
-        let regex = Regex::new(r"^(export[ \t]++)?((?i:[A-Z][A-Z0-9_]*+))").unwrap();
-        let regex_value = self.data.clone().chars().skip(self.cursor).collect::<String>();
-        let regex_captures = regex.captures(&regex_value);
+    #[test]
+    fn alias() {
+        let directory = TempDir::new("alias");
+        let path = directory.join(".env");
+        fs::write(&path, "DOTENV_TEST_ALIAS_OLD=old\nDOTENV_TEST_ALIAS_BOTH_OLD=old\nDOTENV_TEST_ALIAS_BOTH_NEW=new\n").unwrap();
 
-        if regex_captures.is_none() {
-            return Err(self.create_format_exception("Invalid character in variable name".to_string()));
-        }
+        let mut dotenv = Dotenv::new()
+            .alias("DOTENV_TEST_ALIAS_OLD", "DOTENV_TEST_ALIAS_NEW")
+            .alias("DOTENV_TEST_ALIAS_BOTH_OLD", "DOTENV_TEST_ALIAS_BOTH_NEW");
+        dotenv.load(path.to_string_lossy()).unwrap();
 
-        let captures = regex_captures.unwrap();
+        assert_eq!(env::var("DOTENV_TEST_ALIAS_NEW").unwrap(), "old");
+        assert!(env::var("DOTENV_TEST_ALIAS_OLD").is_err());
+        assert_eq!(env::var("DOTENV_TEST_ALIAS_BOTH_NEW").unwrap(), "new");
 
-        self.move_cursor(&captures[0].to_string());
+        let deprecated_keys = dotenv.report().deprecated_keys();
+        assert_eq!(deprecated_keys.len(), 2);
+        assert_eq!(deprecated_keys[0].to_string(), format!("{}:1: \"DOTENV_TEST_ALIAS_OLD\" is deprecated, use \"DOTENV_TEST_ALIAS_NEW\" instead", path.to_string_lossy()));
+        assert_eq!(deprecated_keys[1].line_number(), 2);
+    }
 
-        let token = &self.get_token();
+    #[test]
+    fn unload() {
+        env::set_var("DOTENV_TEST_UNLOAD_EXISTING", "existing");
 
-        if self.cursor == self.end || token == "\n" || token == "#" {
-            if captures.get(1).is_some() {
-                return Err(self.create_format_exception("Unable to unset an environment variable".to_string()));
-            }
+        let mut dotenv = Dotenv::new();
+        dotenv.load_from_str("DOTENV_TEST_UNLOAD_NEW=loaded").unwrap();
+        dotenv.overload_from_str("DOTENV_TEST_UNLOAD_EXISTING=overloaded\nDOTENV_TEST_UNLOAD_NEW=overloaded").unwrap();
 
-            return Err(self.create_format_exception("Missing = in the environment variable declaration".to_string()));
-        }
+        dotenv.unload();
+        assert!(env::var("DOTENV_TEST_UNLOAD_NEW").is_err());
+        assert_eq!(env::var("DOTENV_TEST_UNLOAD_EXISTING").unwrap(), "existing");
+        assert!(!dotenv.loaded_keys().contains(&"DOTENV_TEST_UNLOAD_NEW".to_string()));
+        assert!(dotenv.values().is_empty());
+    }
 
-        if token == " " || token == "\t" {
-            return Err(self.create_format_exception("Whitespace characters are not supported after the variable name".to_string()));
-        }
+    #[test]
+    fn get_layered_lookup() {
+        env::set_var("DOTENV_TEST_GET_ENV", "env");
+        env::set_var("DOTENV_TEST_GET_LOADED", "env");
 
-        if token != "=" {
-            return Err(self.create_format_exception("Missing = in the environment variable declaration".to_string()));
+        let mut defaults = HashMap::new();
+        for key in ["DOTENV_TEST_GET_LOADED", "DOTENV_TEST_GET_ENV", "DOTENV_TEST_GET_DEFAULT"].iter() {
+            defaults.insert(key.to_string(), "default".to_string());
         }
 
-        self.cursor += 1;
+        let mut dotenv = Dotenv::new().defaults(defaults);
+        dotenv.overload_from_str("DOTENV_TEST_GET_LOADED=loaded").unwrap();
+        env::set_var("DOTENV_TEST_GET_LOADED", "changed");
 
-        Ok(captures[2].to_string())
+        assert_eq!(dotenv.get("DOTENV_TEST_GET_LOADED"), Some("loaded".to_string()));
+        assert_eq!(dotenv.get("DOTENV_TEST_GET_ENV"), Some("env".to_string()));
+        assert_eq!(dotenv.get("DOTENV_TEST_GET_DEFAULT"), Some("default".to_string()));
+        assert_eq!(dotenv.get("DOTENV_TEST_GET_MISSING"), None);
     }
 
-    fn lex_value(&mut self) -> Result<String, Exception> {
-        let regex = Regex::new(r"^[ \t]*+(?:#.*)?$").unwrap();
-        let regex_value = self.data.clone().chars().skip(self.cursor).collect::<String>();
-        let regex_match = regex.find(&regex_value);
+    #[test]
+    fn set_default() {
+        env::set_var("DOTENV_TEST_SET_DEFAULT_ENV", "env");
 
-        if regex_match.is_some() {
-            self.move_cursor(regex_match.unwrap().as_str());
-            self.skip_empty_lines();
+        let mut dotenv = Dotenv::new();
+        dotenv.set_default("DOTENV_TEST_SET_DEFAULT_ENV", "default");
+        dotenv.set_default("DOTENV_TEST_SET_DEFAULT_FILE", "default");
+        dotenv.set_default("DOTENV_TEST_SET_DEFAULT_UNSET", "default");
+        assert_eq!(dotenv.report().defaults(), ["DOTENV_TEST_SET_DEFAULT_FILE", "DOTENV_TEST_SET_DEFAULT_UNSET"]);
 
-            return Ok("".to_string());
-        }
+        dotenv.load_from_str("DOTENV_TEST_SET_DEFAULT_FILE=file").unwrap();
+        assert_eq!(dotenv.report().defaults(), ["DOTENV_TEST_SET_DEFAULT_UNSET"]);
+        assert_eq!(dotenv.get("DOTENV_TEST_SET_DEFAULT_ENV"), Some("env".to_string()));
+        assert_eq!(dotenv.get("DOTENV_TEST_SET_DEFAULT_FILE"), Some("file".to_string()));
+        assert_eq!(dotenv.get("DOTENV_TEST_SET_DEFAULT_UNSET"), Some("default".to_string()));
+        assert!(env::var("DOTENV_TEST_SET_DEFAULT_UNSET").is_err());
+    }
+
+    #[test]
+    fn interpolation_modes() {
+        let data = "DOTENV_TEST_INTERPOLATION_URL=http://${DOTENV_TEST_INTERPOLATION_HOST}:${DOTENV_TEST_INTERPOLATION_PORT}\n\
+            DOTENV_TEST_INTERPOLATION_HOST=localhost\n\
+            DOTENV_TEST_INTERPOLATION_PORT=${DOTENV_TEST_INTERPOLATION_HOST_PORT}\n\
+            DOTENV_TEST_INTERPOLATION_HOST_PORT=8080";
+
+        let mut dotenv = Dotenv::new().interpolation(InterpolationMode::Sequential);
+        dotenv.overload_from_str(data).unwrap();
+        assert_eq!(dotenv.values()["DOTENV_TEST_INTERPOLATION_URL"], "http://:");
+
+        let mut dotenv = Dotenv::new();
+        dotenv.overload_from_str(data).unwrap();
+        assert_eq!(dotenv.values()["DOTENV_TEST_INTERPOLATION_PORT"], "${DOTENV_TEST_INTERPOLATION_HOST_PORT}");
+
+        let mut dotenv = Dotenv::new().interpolation(InterpolationMode::Topological);
+        dotenv.overload_from_str(data).unwrap();
+        assert_eq!(dotenv.values()["DOTENV_TEST_INTERPOLATION_URL"], "http://localhost:8080");
+
+        let exception = dotenv.overload_from_str("DOTENV_TEST_INTERPOLATION_A=${DOTENV_TEST_INTERPOLATION_B}\nDOTENV_TEST_INTERPOLATION_B=${DOTENV_TEST_INTERPOLATION_A}").unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::CircularReference);
+        assert_eq!(exception.to_string(), "Circular reference in the value of \"DOTENV_TEST_INTERPOLATION_A\": \
+            DOTENV_TEST_INTERPOLATION_A -> DOTENV_TEST_INTERPOLATION_B -> DOTENV_TEST_INTERPOLATION_A");
 
-        if &self.get_token() == " " || &self.get_token() == "\t" {
-            return Err(self.create_format_exception("Whitespace are not supported before the value".to_string()));
+        let exception = dotenv.overload_from_str("DOTENV_TEST_INTERPOLATION_SELF=${DOTENV_TEST_INTERPOLATION_SELF}").unwrap_err();
+        assert_eq!(exception.key(), Some("DOTENV_TEST_INTERPOLATION_SELF"));
+    }
+
+    #[test]
+    fn escape_styles() {
+        let data = "DOTENV_TEST_ESCAPE_NAME=app\n\
+            DOTENV_TEST_ESCAPE_UNQUOTED=\\${DOTENV_TEST_ESCAPE_NAME}$${DOTENV_TEST_ESCAPE_NAME}\n\
+            DOTENV_TEST_ESCAPE_DOUBLE=\"\\\\${DOTENV_TEST_ESCAPE_NAME} costs \\$5\"\n\
+            DOTENV_TEST_ESCAPE_SINGLE='${DOTENV_TEST_ESCAPE_NAME}\\$'";
+
+        for interpolation_mode in [InterpolationMode::Sequential, InterpolationMode::Topological].iter() {
+            let mut dotenv = Dotenv::new().interpolation(*interpolation_mode).escape_style(EscapeStyle::Symfony);
+            dotenv.overload_from_str(data).unwrap();
+            assert_eq!(dotenv.values()["DOTENV_TEST_ESCAPE_UNQUOTED"], "${DOTENV_TEST_ESCAPE_NAME}${DOTENV_TEST_ESCAPE_NAME}");
+            assert_eq!(dotenv.values()["DOTENV_TEST_ESCAPE_DOUBLE"], "\\app costs $5");
+            assert_eq!(dotenv.values()["DOTENV_TEST_ESCAPE_SINGLE"], "${DOTENV_TEST_ESCAPE_NAME}\\$");
         }
 
-        let mut value = "".to_string();
+        let mut dotenv = Dotenv::new().interpolation(InterpolationMode::Sequential).escape_style(EscapeStyle::Shell);
+        dotenv.overload_from_str(data).unwrap();
+        assert_eq!(dotenv.values()["DOTENV_TEST_ESCAPE_UNQUOTED"], "${DOTENV_TEST_ESCAPE_NAME}$app");
 
-        loop {
-            if &self.get_token() == "'" {
-                let mut len = 0;
+        dotenv.overload_from_str("DOTENV_TEST_ESCAPE_ANSI_C=$'${DOTENV_TEST_ESCAPE_NAME}\\n\\x41'").unwrap();
+        assert_eq!(dotenv.values()["DOTENV_TEST_ESCAPE_ANSI_C"], "${DOTENV_TEST_ESCAPE_NAME}\nA");
 
-                loop {
-                    len += 1;
-                    if self.cursor + len == self.end {
-                        self.cursor += len;
+        let mut dotenv = Dotenv::new().escape_style(EscapeStyle::Symfony);
+        dotenv.overload_from_str(data).unwrap();
+        assert_eq!(dotenv.values()["DOTENV_TEST_ESCAPE_UNQUOTED"], "${DOTENV_TEST_ESCAPE_NAME}${DOTENV_TEST_ESCAPE_NAME}");
+        assert_eq!(dotenv.values()["DOTENV_TEST_ESCAPE_DOUBLE"], "\\${DOTENV_TEST_ESCAPE_NAME} costs $5");
 
-                        return Err(self.create_format_exception("Missing quote to end the value".to_string()));
-                    }
+        let mut dotenv = Dotenv::new().interpolation(InterpolationMode::Sequential);
+        dotenv.overload_from_str(data).unwrap();
+        assert_eq!(dotenv.values()["DOTENV_TEST_ESCAPE_SINGLE"], "app\\$");
+    }
 
-                    if &self.get_token_at(self.cursor + len) == "'" {
-                        break;
-                    }
-                }
+    #[test]
+    fn populate_override_policies() {
+        env::set_var("DOTENV_TEST_POLICY_EMPTY", "");
+        env::set_var("DOTENV_TEST_POLICY_SET", "existing");
+        env::set_var("DOTENV_TEST_POLICY_LISTED", "existing");
 
-                value = format!("{}{}", value, self.data.chars().skip(self.cursor + 1).take(len - 1).collect::<String>());
-                self.cursor += 1 + len;
-            } else if &self.get_token() == "\"" {
-                let mut len = 0;
+        let values: HashMap<String, String> = [
+            ("DOTENV_TEST_POLICY_EMPTY", "populated"),
+            ("DOTENV_TEST_POLICY_SET", "populated"),
+            ("DOTENV_TEST_POLICY_LISTED", "populated"),
+        ].iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
 
-                loop {
-                    len += 1;
-                    if self.cursor + len == self.end {
-                        self.cursor += len;
+        let mut dotenv = Dotenv::new();
+        dotenv.populate(&values, OverridePolicy::OnlyIfEmpty).unwrap();
+        assert_eq!(env::var("DOTENV_TEST_POLICY_EMPTY").unwrap(), "populated");
+        assert_eq!(env::var("DOTENV_TEST_POLICY_SET").unwrap(), "existing");
 
-                        return Err(self.create_format_exception("Missing quote to end the value".to_string()));
-                    }
+        dotenv.populate(&values, OverridePolicy::List(vec!["DOTENV_TEST_POLICY_LISTED".to_string()])).unwrap();
+        assert_eq!(env::var("DOTENV_TEST_POLICY_SET").unwrap(), "existing");
+        assert_eq!(env::var("DOTENV_TEST_POLICY_LISTED").unwrap(), "populated");
+    }
 
-                    if &self.get_token_at(self.cursor + len) == "\"" && &self.get_token_at(self.cursor + len - 1) != "\\" && &self.get_token_at(self.cursor + len - 2) != "\"" {
-                        break;
-                    }
-                }
+    #[test]
+    fn load_env_overrides_earlier_stages() {
+        let directory = TempDir::new("load-env");
+        fs::write(directory.join(".env"), "DOTENV_TEST_LOAD_ENV_A=env\nDOTENV_TEST_LOAD_ENV_B=env\nDOTENV_TEST_LOAD_ENV_EXISTING=env").unwrap();
+        fs::write(directory.join(".env.local"), "DOTENV_TEST_LOAD_ENV_B=local").unwrap();
+        fs::write(directory.join(".env.test"), "DOTENV_TEST_LOAD_ENV_A=test\nDOTENV_TEST_LOAD_ENV_EXISTING=test").unwrap();
+        env::set_var("DOTENV_TEST_LOAD_ENV_EXISTING", "existing");
 
-                let mut resolved_value = format!("{}{}", value, self.data.chars().skip(self.cursor + 1).take(len - 1).collect::<String>());
-                resolved_value = resolved_value.replace("\\\"", "\"");
-                resolved_value = resolved_value.replace("\\r", "\r");
-                resolved_value = resolved_value.replace("\\n", "\n");
-                resolved_value = resolved_value.replace("\\\\", "\\");
+        let mut dotenv = Dotenv::new();
+        dotenv.load_env(directory.join(".env").to_string_lossy(), "DOTENV_TEST_LOAD_ENV_ENV", "test").unwrap();
+        assert_eq!(env::var("DOTENV_TEST_LOAD_ENV_A").unwrap(), "test");
+        assert_eq!(env::var("DOTENV_TEST_LOAD_ENV_B").unwrap(), "local");
+        assert_eq!(env::var("DOTENV_TEST_LOAD_ENV_EXISTING").unwrap(), "existing");
+    }
 
-                value = format!("{}{}", value, resolved_value);
-                self.cursor += 1 + len;
-            } else {
-                let mut resolved_value = "".to_string();
-                let mut previous_character = self.get_token_at(self.cursor - 1);
+    #[test]
+    fn audit() {
+        env::set_var("DOTENV_TEST_AUDIT_EXISTING", "existing");
 
-                loop {
-                    if self.cursor == self.end || self.get_token() == "\n" || self.get_token() == "\"" || self.get_token() == "'" || ((previous_character == " " || previous_character == "\t") && self.get_token() == "#") {
-                        break;
-                    }
+        let mut dotenv = Dotenv::new().audit(true);
+        dotenv.load_from_str("DOTENV_TEST_AUDIT_NEW=new\nDOTENV_TEST_AUDIT_EXISTING=loaded").unwrap();
+        dotenv.overload_from_str("DOTENV_TEST_AUDIT_EXISTING=overridden").unwrap();
 
-                    if self.get_token() == "\\" && self.cursor + 1 < self.end && (self.get_token_at(self.cursor + 1) == "\"" || self.get_token_at(self.cursor + 1) == "'") {
-                        self.cursor += 1;
-                    }
+        let mut entries: Vec<(AuditAction, &str, &str)> = dotenv.audit_log().iter()
+            .map(|entry| (entry.action(), entry.key(), entry.policy()))
+            .collect();
+        entries.sort_unstable_by_key(|(_, key, policy)| (*policy, *key));
 
-                    previous_character = self.get_token();
-                    resolved_value = format!("{}{}", resolved_value, previous_character);
+        assert_eq!(entries, vec![
+            (AuditAction::Override, "DOTENV_TEST_AUDIT_EXISTING", "OverridePolicy::Always"),
+            (AuditAction::Skip, "DOTENV_TEST_AUDIT_EXISTING", "OverridePolicy::Never"),
+            (AuditAction::Set, "DOTENV_TEST_AUDIT_NEW", "OverridePolicy::Never"),
+        ]);
+        assert!(dotenv.audit_log().iter().all(|entry| entry.value_hash().is_some_and(|hash| hash.starts_with("hmac-sha256:"))));
+        assert!(Dotenv::new().audit_log().is_empty());
+    }
 
-                    self.cursor += 1;
-                }
+    #[test]
+    fn freeze() {
+        let mut dotenv = Dotenv::new();
+        dotenv.load_from_str("DOTENV_TEST_FREEZE_A=a\nDOTENV_TEST_FREEZE_B=b").unwrap();
+        dotenv.freeze();
+        assert!(dotenv.is_frozen());
+        assert!(dotenv.verify().is_ok());
 
-                resolved_value = resolved_value.trim_end().to_string();
-                resolved_value = resolved_value.replace("\\\\", "\\");
+        let exception = dotenv.load_from_str("DOTENV_TEST_FREEZE_A=changed").unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Frozen);
+        assert_eq!(exception.key(), Some("DOTENV_TEST_FREEZE_A"));
+        dotenv.load_from_str("DOTENV_TEST_FREEZE_A=a\nDOTENV_TEST_FREEZE_C=c").unwrap();
 
-                if resolved_value.contains(" ") || resolved_value.contains("\t") {
-                    return Err(self.create_format_exception("A value containing spaces must be surrounded by quotes".to_string()));
-                }
+        env::set_var("DOTENV_TEST_FREEZE_B", "changed");
+        let exception = dotenv.verify().unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Frozen);
+        assert_eq!(exception.param("keys"), Some("\"DOTENV_TEST_FREEZE_B\""));
+    }
 
-                value = format!("{}{}", value, resolved_value);
+    #[test]
+    fn load_env_forced() {
+        let directory = TempDir::new("load-env-forced");
+        fs::write(directory.join(".env"), "DOTENV_TEST_LOAD_ENV_FORCED_ENV=dev\nDOTENV_TEST_LOAD_ENV_FORCED_A=env").unwrap();
+        fs::write(directory.join(".env.dev"), "DOTENV_TEST_LOAD_ENV_FORCED_A=dev").unwrap();
+        fs::write(directory.join(".env.prod"), "DOTENV_TEST_LOAD_ENV_FORCED_A=prod").unwrap();
 
-                if self.cursor < self.end && self.get_token() == "#" {
-                    break;
-                }
-            }
+        let mut dotenv = Dotenv::new();
+        assert_eq!(dotenv.resolved_env(), None);
 
-            if self.cursor == self.end || &self.get_token() == "\n" {
-                break;
-            }
-        }
+        dotenv.load_env_forced(directory.join(".env").to_string_lossy(), "prod").unwrap();
+        assert_eq!(dotenv.resolved_env(), Some("prod"));
+        assert_eq!(env::var("DOTENV_TEST_LOAD_ENV_FORCED_A").unwrap(), "prod");
+    }
 
-        self.skip_empty_lines();
+    #[test]
+    fn load_test_env_ignores_local() {
+        let directory = TempDir::new("load-test-env");
+        fs::write(directory.join(".env"), "DOTENV_TEST_LOAD_TEST_ENV_A=env\nDOTENV_TEST_LOAD_TEST_ENV_B=env\nDOTENV_TEST_LOAD_TEST_ENV_C=env").unwrap();
+        fs::write(directory.join(".env.local"), "DOTENV_TEST_LOAD_TEST_ENV_A=local").unwrap();
+        fs::write(directory.join(".env.test"), "DOTENV_TEST_LOAD_TEST_ENV_B=test\nDOTENV_TEST_LOAD_TEST_ENV_C=test").unwrap();
+        fs::write(directory.join(".env.test.local"), "DOTENV_TEST_LOAD_TEST_ENV_C=test-local").unwrap();
 
-        Ok(value.to_string())
+        let mut dotenv = Dotenv::new();
+        dotenv.load_test_env(directory.join(".env").to_string_lossy()).unwrap();
+        assert_eq!(env::var("DOTENV_TEST_LOAD_TEST_ENV_A").unwrap(), "env");
+        assert_eq!(env::var("DOTENV_TEST_LOAD_TEST_ENV_B").unwrap(), "test");
+        assert_eq!(env::var("DOTENV_TEST_LOAD_TEST_ENV_C").unwrap(), "test-local");
     }
 
-    fn skip_empty_lines(&mut self) {
-        let regex = Regex::new(r"^(?:\s*+(?:#[^\n]*+)?+)++").unwrap();
-        let regex_value = self.data.clone().chars().skip(self.cursor).collect::<String>();
+    #[test]
+    fn required_in_env() {
+        let directory = TempDir::new("required-in-env");
+        fs::write(directory.join(".env"), "DOTENV_TEST_REQUIRED_IN_ENV_A=a\nDOTENV_TEST_REQUIRED_IN_ENV_B=").unwrap();
+        fs::write(directory.join(".env.prod"), "DOTENV_TEST_REQUIRED_IN_ENV_D=prod").unwrap();
+
+        let mut dotenv = Dotenv::new()
+            .required_in_env("dev", &["DOTENV_TEST_REQUIRED_IN_ENV_A"])
+            .required_in_env("prod", &["DOTENV_TEST_REQUIRED_IN_ENV_A", "DOTENV_TEST_REQUIRED_IN_ENV_B", "DOTENV_TEST_REQUIRED_IN_ENV_C"]);
+        dotenv.load_env(directory.join(".env").to_string_lossy(), "DOTENV_TEST_REQUIRED_IN_ENV_ENV", "dev").unwrap();
 
-        if let Some(regex_match) = regex.find(&regex_value) {
-            self.move_cursor(regex_match.as_str());
-        }
+        let exception = dotenv.load_env(directory.join(".env").to_string_lossy(), "DOTENV_TEST_REQUIRED_IN_ENV_ENV", "prod").unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::UndefinedVariable);
+        assert_eq!(exception.param("env"), Some("prod"));
+        assert_eq!(exception.param("keys"), Some("\"DOTENV_TEST_REQUIRED_IN_ENV_B\", \"DOTENV_TEST_REQUIRED_IN_ENV_C\""));
+        assert!(env::var("DOTENV_TEST_REQUIRED_IN_ENV_D").is_err());
     }
 
-    fn move_cursor(&mut self, text: &str) {
-        self.cursor += text.len();
-        self.line_number += text.matches("\n").count();
+    #[test]
+    fn unset_exports() {
+        let directory = TempDir::new("unset-exports");
+        fs::write(directory.join(".env"), "DOTENV_TEST_UNSET_A=env\nDOTENV_TEST_UNSET_B=env\nDOTENV_TEST_UNSET_C=env").unwrap();
+        fs::write(directory.join(".env.local"), "export DOTENV_TEST_UNSET_A\nexport DOTENV_TEST_UNSET_EXISTING").unwrap();
+        fs::write(directory.join(".env.test"), "export DOTENV_TEST_UNSET_B\nexport 'DOTENV_TEST_UNSET_C'\nDOTENV_TEST_UNSET_C=test").unwrap();
+        env::set_var("DOTENV_TEST_UNSET_EXISTING", "existing");
+
+        let path = directory.join(".env").to_string_lossy().to_string();
+        assert_eq!(Dotenv::new().load_env(&path, "DOTENV_TEST_UNSET_ENV", "test").unwrap_err().kind(), ExceptionKind::Format);
+
+        let mut dotenv = Dotenv::new().unset_exports(true);
+        dotenv.load_env(&path, "DOTENV_TEST_UNSET_ENV", "test").unwrap();
+        assert!(env::var("DOTENV_TEST_UNSET_A").is_err());
+        assert!(env::var("DOTENV_TEST_UNSET_B").is_err());
+        assert!(!dotenv.loaded_keys().contains(&"DOTENV_TEST_UNSET_B".to_string()));
+        assert_eq!(env::var("DOTENV_TEST_UNSET_C").unwrap(), "test");
+        assert_eq!(env::var("DOTENV_TEST_UNSET_EXISTING").unwrap(), "existing");
     }
 
-    fn get_token(&self) -> String {
-        self.get_token_at(self.cursor)
+    #[test]
+    fn inject_metadata() {
+        let directory = TempDir::new("metadata");
+        fs::write(directory.join(".env"), "DOTENV_TEST_METADATA=env").unwrap();
+        fs::write(directory.join(".env.test"), "DOTENV_TEST_METADATA=test").unwrap();
+
+        let path = directory.join(".env").to_string_lossy().to_string();
+        let mut dotenv = Dotenv::new().inject_metadata(true);
+        dotenv.load_env(&path, "DOTENV_TEST_METADATA_ENV", "test").unwrap();
+
+        let files = [path.clone(), format!("{}.test", path)].join(if cfg!(windows) { ";" } else { ":" });
+        assert_eq!(env::var("DOTENV_FILES").unwrap(), files);
+        assert_eq!(env::var("DOTENV_ENV").unwrap(), "test");
+        assert!(env::var("DOTENV_LOADED_AT").unwrap().ends_with('Z'));
+
+        dotenv.unload();
+        assert!(env::var("DOTENV_FILES").is_err());
     }
 
-    fn get_token_at(&self, position: usize) -> String {
-        self.data.chars().skip(position).take(1).collect::<String>()
+    #[test]
+    fn fingerprint() {
+        let mut dotenv_a = Dotenv::new();
+        dotenv_a.overload_from_str("DOTENV_TEST_FINGERPRINT_A=a\nDOTENV_TEST_FINGERPRINT_B=b").unwrap();
+
+        let mut dotenv_b = Dotenv::new();
+        dotenv_b.overload_from_str("DOTENV_TEST_FINGERPRINT_B=b\nDOTENV_TEST_FINGERPRINT_A=a").unwrap();
+
+        assert_eq!(dotenv_a.fingerprint(), dotenv_b.fingerprint());
+        assert_eq!(dotenv_a.fingerprint().len(), 16);
+
+        dotenv_b.overload_from_str("DOTENV_TEST_FINGERPRINT_C=c").unwrap();
+        assert_ne!(dotenv_a.fingerprint(), dotenv_b.fingerprint());
+        assert_eq!(dotenv_a.fingerprint(), dotenv_b.fingerprint_excluding(&["DOTENV_TEST_FINGERPRINT_C"]));
     }
 
-    fn create_format_exception(&self, message: String) -> Exception {
-        Exception::FormatException(message, self.path.clone(), self.line_number)
+    #[test]
+    fn load_decorated_values() {
+        let mut dotenv = Dotenv::new().decorator("base64", Base64Decorator);
+
+        dotenv.load_from_str("DOTENV_TEST_DECORATED=base64:SGVsbG8=\nDOTENV_TEST_UNDECORATED=other:SGVsbG8=").unwrap();
+        assert_eq!(dotenv.values().get("DOTENV_TEST_DECORATED").unwrap(), "Hello");
+        assert_eq!(dotenv.values().get("DOTENV_TEST_UNDECORATED").unwrap(), "other:SGVsbG8=");
+
+        let exception = dotenv.load_from_str("DOTENV_TEST_DECORATED=base64:***").unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Decoration);
     }
 
-    fn populate(&self, values: &HashMap<String, String>, override_existing: bool) {
-        for (key, value) in values.iter() {
-            if override_existing && env::var_os(key).is_some() {
-                continue;
-            }
-            env::set_var(key, value);
-        }
+    #[test]
+    fn load_resolved_paths() {
+        let directory = TempDir::new("resolve");
+        fs::write(directory.join(".env"), "DOTENV_TEST_RESOLVE_CERT=resolve:./certs/dev.pem").unwrap();
+
+        let mut dotenv = Dotenv::new().decorator("resolve", ResolveDecorator);
+        dotenv.load(directory.join(".env").to_string_lossy()).unwrap();
+        assert_eq!(dotenv.values()["DOTENV_TEST_RESOLVE_CERT"], directory.join("certs").join("dev.pem").to_string_lossy());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::Dotenv;
+    #[test]
+    fn is_defined_case_insensitive() {
+        env::set_var("DOTENV_TEST_IS_DEFINED", "1");
+        assert!(!Dotenv::new().case_insensitive_keys(false).is_defined("dotenv_test_is_defined"));
+        assert!(Dotenv::new().case_insensitive_keys(true).is_defined("dotenv_test_is_defined"));
+    }
 
     #[test]
-    fn parse_no_quotes() {
-        let mut dotenv = Dotenv::new();
-        let values = dotenv.parse("FOO=bar", ".env").unwrap();
-        assert_eq!(values.get("FOO").unwrap(), "bar");
+    fn expand_percent_variables() {
+        env::set_var("DOTENV_TEST_PERCENT_HOME", "C:\\Users\\me");
+        let mut dotenv = Dotenv::new().expand_percent_variables(true);
+        dotenv.load_from_str("DOTENV_TEST_PERCENT_A=foo\nDOTENV_TEST_PERCENT_B='%DOTENV_TEST_PERCENT_A%;%DOTENV_TEST_PERCENT_HOME%;%UNDEFINED%'").unwrap();
+        assert_eq!(dotenv.values().get("DOTENV_TEST_PERCENT_B").unwrap(), "foo;C:\\Users\\me;%UNDEFINED%");
     }
 
     #[test]
-    fn parse_single_quotes() {
-        let mut dotenv = Dotenv::new();
-        let values = dotenv.parse("FOO='bar'", ".env").unwrap();
-        assert_eq!(values.get("FOO").unwrap(), "bar");
+    fn expand_tilde() {
+        let home = env::var(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).unwrap();
+        let mut dotenv = Dotenv::new().expand_tilde(true);
+        dotenv.load_from_str("DOTENV_TEST_TILDE_A=~/data\nDOTENV_TEST_TILDE_B=a~/b\nDOTENV_TEST_TILDE_C=~DOTENV_TEST_UNKNOWN/c").unwrap();
+        assert_eq!(dotenv.values()["DOTENV_TEST_TILDE_A"], format!("{}/data", home));
+        assert_eq!(dotenv.values()["DOTENV_TEST_TILDE_B"], "a~/b");
+        assert_eq!(dotenv.values()["DOTENV_TEST_TILDE_C"], "~DOTENV_TEST_UNKNOWN/c");
     }
 
     #[test]
-    fn parse_single_quotes_concatenation() {
-        let mut dotenv = Dotenv::new();
-        let values = dotenv.parse("FOO='bar'\\''baz'", ".env").unwrap();
-        assert_eq!(values.get("FOO").unwrap(), "bar'baz");
+    fn parse_format_exception_path() {
+        let dotenv = Dotenv::new();
+        let exception = dotenv.parse("FOO=bar\nBAR=\"baz", ".env").unwrap_err();
+        assert_eq!(exception.path(), Some(".env"));
+        assert_eq!(exception.to_string(), "Missing quote to end the value in \".env\" at line 2, column 9\n\n    BAR=\"baz\n            ^");
     }
 
     #[test]
-    fn parse_double_quotes() {
+    fn format() {
+        let directory = TempDir::new("format");
+        let path = directory.join(".env");
+        let path = path.to_string_lossy().to_string();
+        fs::write(&path, "B='b'\nA=a\n").unwrap();
+
         let mut dotenv = Dotenv::new();
-        let values = dotenv.parse("FOO=\"bar\"", ".env").unwrap();
-        assert_eq!(values.get("FOO").unwrap(), "bar");
+        let style = FormatStyle::new().key_order(KeyOrder::Sorted);
+        assert!(!dotenv.check_format(&path, &style).unwrap());
+        assert!(dotenv.format(&path, &style).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "A=a\nB=b\n");
+        assert!(dotenv.check_format(&path, &style).unwrap());
+        assert!(!dotenv.format(&path, &style).unwrap());
     }
 
     #[test]
-    fn parse_double_quotes_escaped_quotes() {
+    fn load_non_regular_files() {
+        let directory = TempDir::new("non-regular");
+
         let mut dotenv = Dotenv::new();
-        let values = dotenv.parse("FOO=\"bar\\\"baz\"", ".env").unwrap();
-        assert_eq!(values.get("FOO").unwrap(), "bar\"baz");
+        let exception = dotenv.load(directory.to_string_lossy()).unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Directory);
+
+        #[cfg(unix)]
+        {
+            let symlink = directory.join(".env");
+            std::os::unix::fs::symlink(directory.join(".env.missing"), &symlink).unwrap();
+
+            let exception = dotenv.load(symlink.to_string_lossy()).unwrap_err();
+            assert_eq!(exception.kind(), ExceptionKind::BrokenSymlink);
+
+            let exception = dotenv.load("/dev/null").unwrap_err();
+            assert_eq!(exception.kind(), ExceptionKind::SpecialFile);
+            assert!(exception.to_string().contains("it is a device"));
+
+            dotenv.load_env(symlink.to_string_lossy(), "DOTENV_TEST_NON_REGULAR_ENV", "local").unwrap();
+        }
     }
 
     #[test]
-    fn parse_double_quotes_newlines() {
+    fn load_encrypted_files() {
+        let directory = TempDir::new("encrypted-file");
+
+        let path = directory.join(".env");
+        fs::write(&path, b"\0GITCRYPT\0\x8a\x01").unwrap();
+
         let mut dotenv = Dotenv::new();
-        let values = dotenv.parse("FOO=\"bar\\r\\nbaz\"", ".env").unwrap();
-        assert_eq!(values.get("FOO").unwrap(), "bar\r\nbaz");
+        let exception = dotenv.load(path.to_string_lossy()).unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Decryption);
+        assert!(exception.to_string().contains("git-crypt unlock"));
+
+        #[cfg(not(feature = "sops"))]
+        {
+            fs::write(&path, "DOTENV_TEST_SOPS=ENC[AES256_GCM,data:Tr7o=,type:str]\nsops_version=3.8.1\n").unwrap();
+
+            let exception = dotenv.load_env(path.to_string_lossy(), "DOTENV_TEST_SOPS_ENV", "dev").unwrap_err();
+            assert_eq!(exception.kind(), ExceptionKind::Decryption);
+            assert!(exception.to_string().contains("`sops` feature"));
+            assert!(env::var("DOTENV_TEST_SOPS").is_err());
+        }
     }
 
     #[test]
-    fn parse_double_quotes_slashes() {
+    fn pull_and_push() {
+        use std::cell::RefCell;
+
+        use crate::{EnvMap, Exception};
+        use crate::store::RemoteEnvStore;
+
+        struct MemoryStore(RefCell<EnvMap>);
+
+        impl RemoteEnvStore for MemoryStore {
+            fn pull(&self) -> Result<EnvMap, Exception> {
+                Ok(self.0.borrow().clone())
+            }
+
+            fn push(&self, values: &EnvMap) -> Result<(), Exception> {
+                *self.0.borrow_mut() = values.clone();
+                Ok(())
+            }
+        }
+
+        let directory = TempDir::new("store");
+        let path = directory.join(".env");
+        let path = path.to_string_lossy();
+        let store = MemoryStore(RefCell::new(EnvMap::parse("APP_NAME=example\nAPP_SECRET=\"s3cr3t!\"").unwrap()));
+
         let mut dotenv = Dotenv::new();
-        let values = dotenv.parse("FOO=\"bar\\\\baz\"", ".env").unwrap();
-        assert_eq!(values.get("FOO").unwrap(), "bar\\baz");
+        let diff = dotenv.pull(&store, &path).unwrap();
+        assert_eq!(diff.added().len(), 2);
+        assert_eq!(fs::read_to_string(&*path).unwrap(), "APP_NAME=example\nAPP_SECRET='s3cr3t!'\n");
+
+        fs::write(&*path, "# The name\nAPP_NAME=renamed\n# Unused\nAPP_DEBUG=1\n").unwrap();
+        let diff = dotenv.pull(&store, &path).unwrap();
+        assert_eq!(diff.changed()["APP_NAME"], ("renamed".to_string(), "example".to_string()));
+        assert_eq!(fs::read_to_string(&*path).unwrap(), "# The name\nAPP_NAME=example\nAPP_SECRET='s3cr3t!'\n");
+        assert!(dotenv.pull(&store, &path).unwrap().is_empty());
+
+        fs::write(&*path, "APP_NAME=pushed\n").unwrap();
+        let diff = dotenv.push(&store, &path).unwrap();
+        assert_eq!(diff.removed().keys().collect::<Vec<_>>(), vec!["APP_SECRET"]);
+        assert_eq!(store.0.borrow().as_map().len(), 1);
+        assert_eq!(store.0.borrow().get("APP_NAME"), Some("pushed"));
     }
 
     #[test]
-    fn parse_export() {
+    fn persist_set() {
+        let directory = TempDir::new("persist-set");
+        let path = directory.join(".env");
+        let path = path.to_string_lossy();
+
         let mut dotenv = Dotenv::new();
-        let values = dotenv.parse("export FOO=bar", ".env").unwrap();
-        assert_eq!(values.get("FOO").unwrap(), "bar");
+        dotenv.persist_set(&path, "DOTENV_TEST_PERSIST_A", "first").unwrap();
+        assert_eq!(fs::read_to_string(&*path).unwrap(), "DOTENV_TEST_PERSIST_A=first\n");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            assert_eq!(fs::metadata(&*path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        assert_eq!(env::var("DOTENV_TEST_PERSIST_A").unwrap(), "first");
+        assert_eq!(dotenv.source_of("DOTENV_TEST_PERSIST_A"), Some(&*path));
+
+        fs::write(&*path, "# The token\nDOTENV_TEST_PERSIST_A=first # keep\n").unwrap();
+        dotenv.persist_set(&path, "DOTENV_TEST_PERSIST_A", "it's new").unwrap();
+        dotenv.persist_set(&path, "DOTENV_TEST_PERSIST_B", "second").unwrap();
+        assert_eq!(fs::read_to_string(&*path).unwrap(), "# The token\nDOTENV_TEST_PERSIST_A='it'\"'\"'s new' # keep\nDOTENV_TEST_PERSIST_B=second\n");
+        assert_eq!(env::var("DOTENV_TEST_PERSIST_A").unwrap(), "it's new");
+
+        let exception = dotenv.persist_set(&path, "DOTENV TEST", "invalid").unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Format);
+        assert_eq!(exception.path(), Some(&*path));
+
+        env::set_var("DOTENV_TEST_PERSIST_PROTECTED", "original");
+        let mut dotenv = Dotenv::new().protected_variables(&["DOTENV_TEST_PERSIST_PROTECTED"]).protected_variable_policy(ProtectedVariablePolicy::Error);
+        let exception = dotenv.persist_set(&path, "DOTENV_TEST_PERSIST_PROTECTED", "changed").unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::ProtectedVariable);
+        assert!(!fs::read_to_string(&*path).unwrap().contains("DOTENV_TEST_PERSIST_PROTECTED"));
+
+        let mut dotenv = Dotenv::new().protected_variables(&["DOTENV_TEST_PERSIST_PROTECTED"]).protected_variable_policy(ProtectedVariablePolicy::Skip);
+        dotenv.persist_set(&path, "DOTENV_TEST_PERSIST_PROTECTED", "changed").unwrap();
+        assert_eq!(env::var("DOTENV_TEST_PERSIST_PROTECTED").unwrap(), "original");
+        assert!(!fs::read_to_string(&*path).unwrap().contains("DOTENV_TEST_PERSIST_PROTECTED"));
+
+        let mut dotenv = Dotenv::new().except(&["DOTENV_TEST_PERSIST_EXCEPT"]);
+        dotenv.persist_set(&path, "DOTENV_TEST_PERSIST_EXCEPT", "excluded").unwrap();
+        assert!(env::var("DOTENV_TEST_PERSIST_EXCEPT").is_err());
+        assert!(!fs::read_to_string(&*path).unwrap().contains("DOTENV_TEST_PERSIST_EXCEPT"));
+
+        let unwritable = directory.join("missing").join(".env").to_string_lossy().to_string();
+        let exception = Dotenv::new().persist_set(&unwritable, "DOTENV_TEST_PERSIST_UNWRITABLE", "value").unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Write);
+        assert!(env::var("DOTENV_TEST_PERSIST_UNWRITABLE").is_err());
     }
 }
@@ -0,0 +1,67 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Detection of environment files encrypted as a whole by SOPS or git-crypt, and decryption of SOPS files with the
+//! `sops` command line tool with the `sops` feature.
+
+#[cfg(feature = "sops")]
+use std::process::Command;
+
+/// Header of the files git-crypt encrypts, which are not decrypted in locked repositories.
+const GIT_CRYPT_HEADER: &[u8] = b"\0GITCRYPT\0";
+
+/// Keys of the metadata SOPS adds to the dotenv files it encrypts.
+const SOPS_METADATA_KEYS: &[&str] = &["sops_version=", "sops_mac="];
+
+///
+/// Whether the contents of a file are encrypted by git-crypt.
+///
+pub(crate) fn is_git_crypt_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(GIT_CRYPT_HEADER)
+}
+
+///
+/// Whether the contents of a file are encrypted by SOPS, as marked by its metadata.
+///
+pub(crate) fn is_sops_encrypted(bytes: &[u8]) -> bool {
+    bytes.split(|byte| *byte == b'\n')
+        .any(|line| SOPS_METADATA_KEYS.iter().any(|key| line.starts_with(key.as_bytes())))
+}
+
+///
+/// Decrypts a SOPS-encrypted dotenv file with the `sops` command line tool, and thereby its regular key management.
+///
+#[cfg(feature = "sops")]
+pub(crate) fn decrypt_sops(path: &str) -> Result<Vec<u8>, String> {
+    let output = crate::resolver::run(Command::new("sops").args(["--decrypt", "--input-type", "dotenv", "--output-type", "dotenv", path]))?;
+
+    Ok(output.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encrypted_file;
+
+    #[test]
+    fn detect_encrypted_files() {
+        assert!(encrypted_file::is_git_crypt_encrypted(b"\0GITCRYPT\0\x8a\x01"));
+        assert!(!encrypted_file::is_git_crypt_encrypted(b"FOO=bar"));
+
+        assert!(encrypted_file::is_sops_encrypted(b"FOO=ENC[AES256_GCM,data:Tr7o=,type:str]\nsops_version=3.8.1\n"));
+        assert!(!encrypted_file::is_sops_encrypted(b"FOO=bar\nMY_sops_version=1\n"));
+    }
+}
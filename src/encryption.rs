@@ -0,0 +1,153 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Values encrypted at rest, such as `DB_PASS=enc:v1:BASE64...`, decrypted transparently at load time.
+//!
+//! Decryption is pluggable through the `Decryptor` trait. With the `encryption` feature, a ChaCha20-Poly1305 based
+//! implementation is provided, along with `encrypt()` to produce the values to commit.
+
+use crate::base64;
+
+/// Prefix marking an encrypted value.
+pub const PREFIX: &str = "enc:v1:";
+
+/// Name of the environment variable the master key is read from by default.
+pub const MASTER_KEY_VARIABLE: &str = "DOTENV_MASTER_KEY";
+
+/// Decryptor of encrypted environment variable values
+pub trait Decryptor {
+    ///
+    /// Decrypts the base64-decoded payload of an `enc:v1:` value with the master key.
+    ///
+    fn decrypt(&self, master_key: &str, ciphertext: &[u8]) -> Result<String, String>;
+}
+
+pub(crate) fn decrypt(decryptor: &dyn Decryptor, master_key: &str, value: &str) -> Result<String, String> {
+    let ciphertext = base64::decode(&value[PREFIX.len()..])
+        .ok_or_else(|| "the payload is not valid base64".to_string())?;
+
+    decryptor.decrypt(master_key, &ciphertext)
+}
+
+/// Decryptor for values encrypted with ChaCha20-Poly1305
+///
+/// The master key is the base64 encoding of 32 random bytes. The payload is the 12 byte nonce followed by the
+/// ciphertext and authentication tag.
+#[cfg(feature = "encryption")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ChaCha20Poly1305Decryptor;
+
+#[cfg(feature = "encryption")]
+impl Decryptor for ChaCha20Poly1305Decryptor {
+    fn decrypt(&self, master_key: &str, ciphertext: &[u8]) -> Result<String, String> {
+        use chacha20poly1305::aead::Aead;
+
+        const NONCE_LENGTH: usize = 12;
+
+        if ciphertext.len() < NONCE_LENGTH {
+            return Err("the payload is too short".to_string());
+        }
+
+        let (nonce, ciphertext) = ciphertext.split_at(NONCE_LENGTH);
+
+        let plaintext = cipher(master_key)?
+            .decrypt(chacha20poly1305::Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "the value could not be authenticated with the master key".to_string())?;
+
+        String::from_utf8(plaintext).map_err(|_| "the decrypted value is not valid UTF-8".to_string())
+    }
+}
+
+///
+/// Encrypts a value for use with `ChaCha20Poly1305Decryptor`, returning the complete `enc:v1:` value.
+///
+/// # Examples
+///
+/// ```rust
+/// use darkweb_dotenv::encryption;
+///
+/// let master_key = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+/// let value = encryption::encrypt(master_key, "pass").unwrap();
+///
+/// assert!(value.starts_with("enc:v1:"));
+/// ```
+///
+#[cfg(feature = "encryption")]
+pub fn encrypt<MasterKey, Plaintext>(master_key: MasterKey, plaintext: Plaintext) -> Result<String, String>
+    where
+        MasterKey: AsRef<str>,
+        Plaintext: AsRef<str> {
+
+    use chacha20poly1305::aead::{Aead, AeadCore, OsRng};
+
+    let nonce = chacha20poly1305::ChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let ciphertext = cipher(master_key.as_ref())?
+        .encrypt(&nonce, plaintext.as_ref().as_bytes())
+        .map_err(|_| "the value could not be encrypted".to_string())?;
+
+    let mut payload = nonce.to_vec();
+    payload.extend(ciphertext);
+
+    Ok(format!("{}{}", PREFIX, base64::encode(&payload)))
+}
+
+#[cfg(feature = "encryption")]
+fn cipher(master_key: &str) -> Result<chacha20poly1305::ChaCha20Poly1305, String> {
+    use chacha20poly1305::KeyInit;
+
+    let key = base64::decode(master_key.trim())
+        .filter(|key| key.len() == 32)
+        .ok_or_else(|| "the master key must be 32 base64 encoded bytes".to_string())?;
+
+    Ok(chacha20poly1305::ChaCha20Poly1305::new(chacha20poly1305::Key::from_slice(&key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encryption::{self, Decryptor};
+
+    struct ReverseDecryptor;
+
+    impl Decryptor for ReverseDecryptor {
+        fn decrypt(&self, master_key: &str, ciphertext: &[u8]) -> Result<String, String> {
+            if master_key != "key" {
+                return Err("wrong key".to_string());
+            }
+
+            Ok(String::from_utf8_lossy(ciphertext).chars().rev().collect())
+        }
+    }
+
+    #[test]
+    fn decrypt() {
+        assert_eq!(encryption::decrypt(&ReverseDecryptor, "key", "enc:v1:c3NhcA==").unwrap(), "pass");
+        assert_eq!(encryption::decrypt(&ReverseDecryptor, "key", "enc:v1:***").unwrap_err(), "the payload is not valid base64");
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn encrypt_decrypt_round_trip() {
+        use crate::encryption::ChaCha20Poly1305Decryptor;
+
+        let master_key = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+        let value = encryption::encrypt(master_key, "pass").unwrap();
+
+        assert_eq!(encryption::decrypt(&ChaCha20Poly1305Decryptor, master_key, &value).unwrap(), "pass");
+        assert!(encryption::decrypt(&ChaCha20Poly1305Decryptor, "QUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUE=", &value).is_err());
+    }
+}
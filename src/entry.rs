@@ -0,0 +1,252 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::mem;
+use std::vec;
+
+use crate::{EntrySpans, Exception, ExceptionKind};
+use crate::fast_parser::{LexedEntry, Lexer};
+use crate::parser::{self, ParserOptions, Statement};
+
+/// Variable declared in an environment file, as yielded by `Dotenv::iter()` and `DotenvDocument::entries()`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    key: String,
+    value: String,
+    quoting: Quoting,
+    comment: Option<String>,
+    line_number: usize,
+    spans: EntrySpans,
+}
+
+/// Quoting of a value as written
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Quoting {
+    /// The value is not quoted, like `localhost`.
+    #[default]
+    Unquoted,
+    /// The value is enclosed in single quotes, like `'localhost'`.
+    Single,
+    /// The value is enclosed in double quotes, like `"localhost"`.
+    Double,
+    /// The value is a heredoc, like `<<EOF`.
+    Heredoc,
+    /// The value uses ANSI-C quoting, like `$'line1\nline2'`.
+    AnsiC,
+}
+
+impl Entry {
+    pub(crate) fn new(key: String, value: String, line_number: usize, spans: EntrySpans, data: &str) -> Self {
+        let quoting = Quoting::of(&data[spans.value()]);
+
+        Self { key, value, quoting, comment: None, line_number, spans }
+    }
+
+    pub(crate) fn with_comment(mut self, comment: Option<String>) -> Self {
+        self.comment = comment;
+        self
+    }
+
+    fn lexed(entry: LexedEntry, data: &str) -> Self {
+        let spans = entry.spans(data);
+
+        Self::new(entry.name.to_string(), entry.value.into_owned(), entry.line_number, spans, data)
+    }
+
+    /// Name of the variable.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Value of the variable, unquoted and unescaped.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Quoting of the value as written.
+    pub fn quoting(&self) -> Quoting {
+        self.quoting
+    }
+
+    ///
+    /// Comment lines directly above the declaration, without the leading `#` and the annotation lines, as documented in
+    /// a `DotenvDocument`. Always `None` for the entries yielded by `Dotenv::iter()`.
+    ///
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    /// Line number of the declaration.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Byte ranges of the name, the value and the lines of the declaration.
+    pub fn spans(&self) -> &EntrySpans {
+        &self.spans
+    }
+
+    /// Consumes the entry, returning its name and value.
+    pub fn into_pair(self) -> (String, String) {
+        (self.key, self.value)
+    }
+}
+
+impl Quoting {
+    fn of(written_value: &str) -> Self {
+        match written_value.chars().next() {
+            Some('\'') => Self::Single,
+            Some('"') => Self::Double,
+            Some('<') if written_value.starts_with("<<") => Self::Heredoc,
+            Some('$') if written_value.starts_with("$'") => Self::AnsiC,
+            _ => Self::Unquoted,
+        }
+    }
+}
+
+///
+/// Iterator over the entries of an environment file, parsing them one at a time, as returned by `Dotenv::iter()`.
+///
+/// Yields an exception and stops at the first invalid declaration.
+///
+#[derive(Debug)]
+pub struct Entries {
+    path: String,
+    state: State,
+}
+
+#[derive(Debug)]
+enum State {
+    Lexing { data: String, cursor: usize, line_number: usize },
+    Parsed(vec::IntoIter<Entry>),
+    Failed(Exception),
+    Done,
+}
+
+impl Entries {
+    ///
+    /// Entries of the contents of a file, parsed lazily when using the default syntax, or up front when parser options
+    /// extend the syntax.
+    ///
+    pub(crate) fn new(data: String, path: String, options: &ParserOptions) -> Self {
+        if *options == ParserOptions::default() {
+            return Self { path, state: State::Lexing { data, cursor: 0, line_number: 1 } };
+        }
+
+        let state = match parser::parse_statements(&data, options) {
+            Ok(statements) => State::Parsed(statements.into_iter()
+                .filter_map(|statement| match statement {
+                    Statement::Assignment(key, value, line_number, spans) => Some(Entry::new(key, value, line_number, spans, &data)),
+                    Statement::Include(..) | Statement::Section(..) | Statement::Comment(..) | Statement::Unset(..) => None,
+                })
+                .collect::<Vec<_>>()
+                .into_iter()),
+            Err(exception) => State::Failed(exception.with_path(path.clone())),
+        };
+
+        Self { path, state }
+    }
+
+    /// Entries yielding a single exception, for a file that cannot be read.
+    pub(crate) fn failed(exception: Exception, path: String) -> Self {
+        Self { path, state: State::Failed(exception) }
+    }
+}
+
+impl Iterator for Entries {
+    type Item = Result<Entry, Exception>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match mem::replace(&mut self.state, State::Done) {
+            State::Lexing { data, cursor, line_number } => {
+                let mut lexer = Lexer::new(&data, cursor, line_number);
+
+                let (entry, cursor, line_number) = match lexer.next_entry() {
+                    Some(Some(entry)) => {
+                        let (cursor, line_number) = lexer.position();
+                        (Entry::lexed(entry, &data), cursor, line_number)
+                    },
+                    Some(None) => return None,
+                    None => {
+                        let exception = parser::parse_statements(&data, &ParserOptions::default()).err()
+                            .unwrap_or_else(|| Exception::new(ExceptionKind::Format, "format.unparsable", Vec::new()));
+
+                        return Some(Err(exception.with_path(self.path.clone())));
+                    },
+                };
+
+                self.state = State::Lexing { data, cursor, line_number };
+
+                Some(Ok(entry))
+            },
+            State::Parsed(mut entries) => {
+                let entry = entries.next();
+                self.state = State::Parsed(entries);
+
+                entry.map(Ok)
+            },
+            State::Failed(exception) => Some(Err(exception)),
+            State::Done => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Dotenv, Entries, Entry, ExceptionKind, Quoting};
+    use crate::parser::ParserOptions;
+
+    #[test]
+    fn iterate_lazily() {
+        let data = "# database\nDB_HOST=localhost\r\nDB_NAME='app'\nDB_PORT=5432 5433\n";
+        let mut entries = Entries::new(data.to_string(), ".env".to_string(), &ParserOptions::default());
+
+        let entry = entries.next().unwrap().unwrap();
+        assert_eq!((entry.key(), entry.value(), entry.line_number()), ("DB_HOST", "localhost", 2));
+
+        let entry = entries.next().unwrap().unwrap();
+        assert_eq!((entry.key(), entry.value(), entry.line_number()), ("DB_NAME", "app", 3));
+        assert_eq!(&data[entry.spans().value()], "'app'");
+        assert_eq!(entry.quoting(), Quoting::Single);
+
+        let exception = entries.next().unwrap().unwrap_err();
+        assert_eq!((exception.kind(), exception.line(), exception.path()), (ExceptionKind::Format, Some(4), Some(".env")));
+        assert!(entries.next().is_none());
+    }
+
+    #[test]
+    fn iterate_extended_syntax() {
+        let options = ParserOptions { heredocs: true, ..ParserOptions::default() };
+        let entries = Entries::new("CERT=<<EOF\nabc\nEOF\nFOO=bar".to_string(), ".env".to_string(), &options)
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+
+        assert_eq!(entries.iter().map(Entry::quoting).collect::<Vec<_>>(), [Quoting::Heredoc, Quoting::Unquoted]);
+
+        let entries = entries.into_iter().map(Entry::into_pair).collect::<Vec<_>>();
+        assert_eq!(entries, [("CERT".to_string(), "abc".to_string()), ("FOO".to_string(), "bar".to_string())]);
+    }
+
+    #[test]
+    fn iterate_unreadable_file() {
+        let mut entries = Dotenv::new().iter("dotenv-test-iter-missing.env");
+
+        assert_eq!(entries.next().unwrap().unwrap_err().kind(), ExceptionKind::Path);
+        assert!(entries.next().is_none());
+    }
+}
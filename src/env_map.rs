@@ -0,0 +1,189 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{Exception, MergePolicy};
+use crate::parser;
+
+/// Set of environment variables that have not been populated yet, for layering independently parsed sources
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnvMap {
+    values: HashMap<String, String>,
+}
+
+impl EnvMap {
+    ///
+    /// Creates an empty map.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Parses environment variables from a string into a new map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::EnvMap;
+    ///
+    /// let map = EnvMap::parse("APP_NAME=example").unwrap();
+    /// assert_eq!(map.get("APP_NAME"), Some("example"));
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// - `ExceptionKind::Format` if the data has an invalid format.
+    ///
+    pub fn parse<Data>(data: Data) -> Result<Self, Exception>
+        where
+            Data: AsRef<str> {
+
+        Ok(Self::from(parser::parse(data)?))
+    }
+
+    /// Returns the value of a variable in the map.
+    pub fn get<Key>(&self, key: Key) -> Option<&str>
+        where
+            Key: AsRef<str> {
+
+        self.values.get(key.as_ref()).map(String::as_str)
+    }
+
+    /// Sets the value of a variable in the map, returning the previous value.
+    pub fn insert<Key, Value>(&mut self, key: Key, value: Value) -> Option<String>
+        where
+            Key: Into<String>,
+            Value: Into<String> {
+
+        self.values.insert(key.into(), value.into())
+    }
+
+    /// Removes a variable from the map, returning its value.
+    pub fn remove<Key>(&mut self, key: Key) -> Option<String>
+        where
+            Key: AsRef<str> {
+
+        self.values.remove(key.as_ref())
+    }
+
+    /// Number of variables in the map.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the map contains no variables.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Variables in the map, as passed to `Dotenv::populate()`.
+    pub fn as_map(&self) -> &HashMap<String, String> {
+        &self.values
+    }
+
+    ///
+    /// Merges the variables of another map into this map, resolving conflicting values with the given policy.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::{Dotenv, EnvMap, MergePolicy, OverridePolicy};
+    ///
+    /// let mut map = EnvMap::parse("APP_NAME=example\nAPP_DEBUG=false").unwrap();
+    /// map.merge(EnvMap::parse("APP_DEBUG=true").unwrap(), MergePolicy::Overwrite).unwrap();
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.populate(map.as_map(), OverridePolicy::Never).unwrap();
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// - `ExceptionKind::DuplicateKey` if the policy is `MergePolicy::Error` and a variable is defined in both maps
+    ///   with a different value. The map is left unchanged.
+    ///
+    pub fn merge(&mut self, other: EnvMap, merge_policy: MergePolicy) -> Result<(), Exception> {
+        if merge_policy == MergePolicy::Error {
+            let mut conflicting_keys: Vec<&String> = other.values.iter()
+                .filter(|(key, value)| self.values.get(*key).is_some_and(|existing_value| existing_value != *value))
+                .map(|(key, _)| key)
+                .collect();
+            conflicting_keys.sort_unstable();
+
+            if let Some(key) = conflicting_keys.first() {
+                return Err(Exception::conflicting_key(key.to_string()));
+            }
+        }
+
+        for (key, value) in other.values {
+            if merge_policy == MergePolicy::KeepExisting && self.values.contains_key(&key) {
+                continue;
+            }
+
+            self.values.insert(key, value);
+        }
+
+        Ok(())
+    }
+}
+
+impl From<HashMap<String, String>> for EnvMap {
+    fn from(values: HashMap<String, String>) -> Self {
+        Self { values }
+    }
+}
+
+impl From<BTreeMap<String, String>> for EnvMap {
+    fn from(values: BTreeMap<String, String>) -> Self {
+        Self { values: values.into_iter().collect() }
+    }
+}
+
+impl From<EnvMap> for HashMap<String, String> {
+    fn from(map: EnvMap) -> Self {
+        map.values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EnvMap, ExceptionKind, MergePolicy};
+
+    #[test]
+    fn merge_policies() {
+        let base = EnvMap::parse("A=file\nB=file").unwrap();
+        let other = EnvMap::parse("B=remote\nC=remote").unwrap();
+
+        let mut map = base.clone();
+        map.merge(other.clone(), MergePolicy::Overwrite).unwrap();
+        assert_eq!((map.get("A"), map.get("B"), map.get("C")), (Some("file"), Some("remote"), Some("remote")));
+
+        let mut map = base.clone();
+        map.merge(other.clone(), MergePolicy::KeepExisting).unwrap();
+        assert_eq!((map.get("A"), map.get("B"), map.get("C")), (Some("file"), Some("file"), Some("remote")));
+
+        let mut map = base.clone();
+        let exception = map.merge(other, MergePolicy::Error).unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::DuplicateKey);
+        assert_eq!(map, base);
+
+        let mut map = base.clone();
+        map.merge(EnvMap::parse("A=file\nD=inline").unwrap(), MergePolicy::Error).unwrap();
+        assert_eq!(map.len(), 3);
+    }
+}
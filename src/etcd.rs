@@ -0,0 +1,333 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::time::Duration;
+
+use crate::{base64, json, source, Dotenv, EnvDiff, EnvValue, Exception};
+
+/// Stream of changes to the keys under a prefix of etcd, returned by `Dotenv::watch_etcd()`
+///
+/// Every item is the difference between the variables before and after a change, which has been loaded into the
+/// environment by then. The stream ends when etcd closes the watch.
+pub struct EtcdWatch<'a> {
+    dotenv: &'a mut Dotenv,
+    url: String,
+    prefix: String,
+    values: HashMap<String, String>,
+    events: BufReader<ureq::BodyReader<'static>>,
+}
+
+impl<'a> EtcdWatch<'a> {
+    pub(crate) fn new(dotenv: &'a mut Dotenv, url: &str, prefix: &str, timeout: Duration, token: Option<&str>) -> Result<Self, Exception> {
+        let (pairs, revision) = fetch(url, prefix, timeout, token)?;
+
+        dotenv.load_source(&format!("etcd:{}", prefix), pairs.clone())?;
+
+        let request_url = format!("{}/v3/watch", url.trim_end_matches('/'));
+        let (key, range_end) = range(prefix);
+        let body = format!(
+            "{{\"create_request\":{{\"key\":{},\"range_end\":{},\"start_revision\":\"{}\"}}}}",
+            json::string(key), json::string(range_end), revision + 1,
+        );
+
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_connect(Some(timeout))
+            .build()
+            .into();
+
+        let response = authorize(agent.post(&request_url), token).send(body)
+            .map_err(|error| Exception::unavailable_source(request_url.clone(), error.to_string()))?;
+
+        Ok(Self {
+            dotenv,
+            url: request_url,
+            prefix: prefix.to_string(),
+            values: pairs.into_iter().collect(),
+            events: BufReader::new(response.into_body().into_with_config().limit(u64::MAX).reader()),
+        })
+    }
+}
+
+impl Iterator for EtcdWatch<'_> {
+    type Item = Result<EnvDiff, Exception>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+
+            match self.events.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) if line.trim().is_empty() => continue,
+                Ok(_) => {},
+                Err(error) => return Some(Err(Exception::unavailable_source(self.url.clone(), error.to_string()))),
+            }
+
+            let events = match events(&self.prefix, &line) {
+                Ok(events) if events.is_empty() => continue,
+                Ok(events) => events,
+                Err(reason) => return Some(Err(Exception::unavailable_source(self.url.clone(), reason))),
+            };
+
+            let mut values = self.values.clone();
+
+            for (key, value) in events {
+                match value {
+                    Some(value) => values.insert(key, value),
+                    None => values.remove(&key),
+                };
+            }
+
+            let diff = EnvDiff::new(&self.values, &values);
+            let pairs = diff.added().iter().map(|(key, value)| (key.clone(), value.clone()))
+                .chain(diff.changed().iter().map(|(key, (_, value))| (key.clone(), value.clone())))
+                .collect();
+
+            self.values = values;
+
+            return Some(self.dotenv.load_source(&format!("etcd:{}", self.prefix), pairs).map(|_| diff));
+        }
+    }
+}
+
+///
+/// Fetches the keys under a prefix of etcd as pairs of environment variable names and values, along with the revision
+/// of the store.
+///
+pub(crate) fn fetch(url: &str, prefix: &str, timeout: Duration, token: Option<&str>) -> Result<(Vec<(String, String)>, i64), Exception> {
+    let request_url = format!("{}/v3/kv/range", url.trim_end_matches('/'));
+    let unavailable = |reason: String| Exception::unavailable_source(request_url.clone(), reason);
+
+    let (key, range_end) = range(prefix);
+    let body = format!("{{\"key\":{},\"range_end\":{}}}", json::string(key), json::string(range_end));
+
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .build()
+        .into();
+
+    let mut response = authorize(agent.post(&request_url), token).send(body)
+        .map_err(|error| unavailable(error.to_string()))?;
+
+    let body = response.body_mut().read_to_string().map_err(|error| unavailable(error.to_string()))?;
+
+    key_values(prefix, &body).map_err(unavailable)
+}
+
+/// etcd expects the token of `/v3/auth/authenticate` as is, without a scheme.
+fn authorize<Body>(request: ureq::RequestBuilder<Body>, token: Option<&str>) -> ureq::RequestBuilder<Body> {
+    match token {
+        Some(token) => request.header("Authorization", token),
+        None => request,
+    }
+}
+
+///
+/// Returns the base64 encoded key and range end that select all keys starting with the prefix.
+///
+fn range(prefix: &str) -> (String, String) {
+    let mut range_end = prefix.as_bytes().to_vec();
+
+    match range_end.last_mut() {
+        // UTF-8 never contains 0xff, so the last byte can always be incremented
+        Some(byte) => *byte += 1,
+        None => return (base64::encode(b"\0"), base64::encode(b"\0")),
+    }
+
+    (base64::encode(prefix.as_bytes()), base64::encode(&range_end))
+}
+
+fn key_values(prefix: &str, body: &str) -> Result<(Vec<(String, String)>, i64), String> {
+    let mut response = match json::parse(body)? {
+        EnvValue::Object(response) => response,
+        _ => return Err("expected a range response".to_string()),
+    };
+
+    let revision = match response.remove("header") {
+        Some(EnvValue::Object(mut header)) => integer(header.remove("revision"))?,
+        _ => return Err("expected a range response with a header".to_string()),
+    };
+
+    let pairs = match response.remove("kvs") {
+        Some(EnvValue::Array(kvs)) => kvs.into_iter()
+            .map(|kv| key_value(prefix, kv).map(|(key, value)| (key, value.unwrap_or_default())))
+            .collect::<Result<_, _>>()?,
+        None => Vec::new(),
+        _ => return Err("expected a list of key-values".to_string()),
+    };
+
+    Ok((pairs, revision))
+}
+
+///
+/// Parses a message of a watch stream into the changed environment variables, with `None` for deleted keys.
+///
+fn events(prefix: &str, line: &str) -> Result<Vec<(String, Option<String>)>, String> {
+    let mut result = match json::parse(line)? {
+        EnvValue::Object(mut message) => match (message.remove("result"), message.remove("error")) {
+            (Some(EnvValue::Object(result)), _) => result,
+            (_, Some(EnvValue::Object(mut error))) => match error.remove("message") {
+                Some(EnvValue::String(message)) => return Err(message),
+                _ => return Err("the watch failed".to_string()),
+            },
+            _ => return Err("expected a watch response".to_string()),
+        },
+        _ => return Err("expected a watch response".to_string()),
+    };
+
+    if let Some(EnvValue::Bool(true)) = result.remove("canceled") {
+        return match result.remove("cancel_reason") {
+            Some(EnvValue::String(reason)) => Err(format!("the watch was canceled: {}", reason)),
+            _ => Err("the watch was canceled".to_string()),
+        };
+    }
+
+    match result.remove("events") {
+        Some(EnvValue::Array(events)) => events.into_iter()
+            .map(|event| match event {
+                EnvValue::Object(mut event) => {
+                    let kv = event.remove("kv").ok_or_else(|| "expected events with a key-value".to_string())?;
+                    let (key, value) = key_value(prefix, kv)?;
+
+                    match event.remove("type") {
+                        Some(EnvValue::String(kind)) if kind == "DELETE" => Ok((key, None)),
+                        _ => Ok((key, Some(value.unwrap_or_default()))),
+                    }
+                },
+                _ => Err("expected a list of events".to_string()),
+            })
+            .collect(),
+        None => Ok(Vec::new()),
+        _ => Err("expected a list of events".to_string()),
+    }
+}
+
+/// Decodes a key-value, in which etcd omits empty values.
+fn key_value(prefix: &str, kv: EnvValue) -> Result<(String, Option<String>), String> {
+    let mut kv = match kv {
+        EnvValue::Object(kv) => kv,
+        _ => return Err("expected a key-value".to_string()),
+    };
+
+    let key = match kv.remove("key") {
+        Some(EnvValue::String(key)) => decode(&key)?,
+        _ => return Err("expected key-values with a key".to_string()),
+    };
+
+    let value = match kv.remove("value") {
+        Some(EnvValue::String(value)) => Some(decode(&value)?),
+        _ => None,
+    };
+
+    Ok((source::key(prefix, &key), value))
+}
+
+fn decode(encoded: &str) -> Result<String, String> {
+    base64::decode(encoded)
+        .and_then(|decoded| String::from_utf8(decoded).ok())
+        .ok_or_else(|| format!("\"{}\" is not valid base64 encoded UTF-8", encoded))
+}
+
+/// etcd encodes 64-bit integers as strings.
+fn integer(value: Option<EnvValue>) -> Result<i64, String> {
+    match value {
+        Some(EnvValue::String(value)) => value.parse().map_err(|_| format!("invalid revision \"{}\"", value)),
+        Some(EnvValue::Int(value)) => Ok(value),
+        _ => Err("expected a revision".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use crate::{base64, etcd, Dotenv};
+
+    #[test]
+    fn range() {
+        assert_eq!(etcd::range("myapp/"), ("bXlhcHAv".to_string(), "bXlhcHAw".to_string()));
+        assert_eq!(etcd::range(""), ("AA==".to_string(), "AA==".to_string()));
+    }
+
+    #[test]
+    fn events() {
+        let line = r#"{"result":{"header":{"revision":"8"},"events":[
+            {"kv":{"key":"bXlhcHAvZGIvdXNlcg==","value":"cm9vdA==","mod_revision":"8"}},
+            {"type":"DELETE","kv":{"key":"bXlhcHAvZGVidWc=","mod_revision":"8"}}
+        ]}}"#.replace('\n', "");
+
+        assert_eq!(etcd::events("myapp/", &line).unwrap(), vec![
+            ("DB_USER".to_string(), Some("root".to_string())),
+            ("DEBUG".to_string(), None),
+        ]);
+        assert_eq!(etcd::events("myapp/", r#"{"result":{"header":{"revision":"7"},"created":true}}"#).unwrap(), vec![]);
+        assert!(etcd::events("myapp/", r#"{"result":{"canceled":true,"cancel_reason":"compacted"}}"#).unwrap_err().contains("compacted"));
+    }
+
+    #[test]
+    fn watch() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let key = base64::encode(b"dotenv/DOTENV_TEST_ETCD_USER");
+
+        let responses = [
+            format!(r#"{{"header":{{"revision":"7"}},"kvs":[{{"key":"{}","value":"{}"}}],"count":"1"}}"#, key, base64::encode(b"root")),
+            r#"{"result":{"header":{"revision":"7"},"created":true}}"#.to_string(),
+            format!(r#"{{"result":{{"header":{{"revision":"8"}},"events":[{{"kv":{{"key":"{}","value":"{}"}}}}]}}}}"#, key, base64::encode(b"admin")),
+        ];
+
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+
+            for body in [responses[0].clone(), responses[1..].join("\n")] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = String::new();
+                let mut buffer = [0; 4096];
+
+                while !request.ends_with('}') {
+                    let length = stream.read(&mut buffer).unwrap();
+                    request.push_str(&String::from_utf8_lossy(&buffer[..length]));
+                }
+
+                write!(stream, "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n{}\n", body).unwrap();
+                requests.push(request);
+            }
+
+            requests
+        });
+
+        let mut dotenv = Dotenv::new().bearer_token("token");
+        let mut watch = dotenv.watch_etcd(&url, "dotenv/").unwrap();
+        assert_eq!(env::var("DOTENV_TEST_ETCD_USER").unwrap(), "root");
+
+        let diff = watch.next().unwrap().unwrap();
+        assert_eq!(diff.changed().get("DOTENV_TEST_ETCD_USER").unwrap(), &("root".to_string(), "admin".to_string()));
+        assert_eq!(env::var("DOTENV_TEST_ETCD_USER").unwrap(), "admin");
+        assert!(watch.next().is_none());
+
+        let requests = handle.join().unwrap();
+        assert!(requests[0].starts_with("POST /v3/kv/range "));
+        assert!(requests[0].to_lowercase().contains("authorization: token"));
+        assert!(requests[1].contains(r#""start_revision":"8""#));
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::fmt;
+
+use crate::parser::Statement;
+
+/// Result of synchronizing an example file with an environment file, as returned by `Dotenv::sync_example()`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExampleSync {
+    added: Vec<String>,
+    missing: Vec<String>,
+}
+
+impl ExampleSync {
+    pub(crate) fn new(added: Vec<String>, missing: Vec<String>) -> Self {
+        Self { added, missing }
+    }
+
+    /// Variables that were added to the example file.
+    pub fn added(&self) -> &[String] {
+        &self.added
+    }
+
+    /// Variables documented in the example file, but missing from the environment file.
+    pub fn missing(&self) -> &[String] {
+        &self.missing
+    }
+
+    /// Whether both files define exactly the same variables.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.missing.is_empty()
+    }
+}
+
+impl fmt::Display for ExampleSync {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for name in self.added.iter() {
+            writeln!(formatter, "+ {}", name)?;
+        }
+
+        for name in self.missing.iter() {
+            writeln!(formatter, "? {}", name)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Result of scaffolding an environment file from an example file, as returned by `Dotenv::init_from_example()`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExampleInit {
+    filled: Vec<String>,
+    missing: Vec<String>,
+}
+
+impl ExampleInit {
+    pub(crate) fn new(filled: Vec<String>, missing: Vec<String>) -> Self {
+        Self { filled, missing }
+    }
+
+    /// Variables that were given a value, from their `@default` annotation or the example file.
+    pub fn filled(&self) -> &[String] {
+        &self.filled
+    }
+
+    /// Variables that were left empty, which the user still has to fill in.
+    pub fn missing(&self) -> &[String] {
+        &self.missing
+    }
+
+    /// Whether no variable was left empty.
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+impl fmt::Display for ExampleInit {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for name in self.filled.iter() {
+            writeln!(formatter, "+ {}", name)?;
+        }
+
+        for name in self.missing.iter() {
+            writeln!(formatter, "? {}", name)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders an example file from the statements of an environment file, replacing each value with a placeholder.
+/// Comments, ordering and blank lines between statements are preserved.
+pub(crate) fn generate(statements: &[Statement], data: &str) -> String {
+    let lines: Vec<&str> = data.lines().collect();
+    let mut example = String::new();
+    let mut last_line_number = 0;
+
+    for statement in statements {
+        let (line, line_number) = match statement {
+            Statement::Assignment(name, value, line_number, _) => (format!("{}={}", name, placeholder(value)), *line_number),
+            Statement::Include(path, line_number) => (format!("#!include {}", path), *line_number),
+            Statement::Section(name, line_number) => (format!("[{}]", name), *line_number),
+            Statement::Unset(name, line_number) => (format!("export {}", name), *line_number),
+            Statement::Comment(comment, line_number) if *line_number == last_line_number => {
+                example.pop();
+                example.push_str(&format!(" # {}\n", comment));
+                continue;
+            },
+            Statement::Comment(comment, line_number) => (format!("# {}", comment).trim_end().to_string(), *line_number),
+        };
+
+        let preceded_by_blank_line = line_number > 1 && lines.get(line_number - 2).is_some_and(|line| line.trim().is_empty());
+
+        if last_line_number > 0 && line_number > last_line_number + 1 && preceded_by_blank_line {
+            example.push('\n');
+        }
+
+        example.push_str(&line);
+        example.push('\n');
+        last_line_number = line_number;
+    }
+
+    example
+}
+
+/// Whether a value is a placeholder of a generated example file, rather than a usable default.
+pub(crate) fn is_placeholder(value: &str) -> bool {
+    ["changeme", "<int>", "<bool>"].contains(&value)
+}
+
+fn placeholder(value: &str) -> &'static str {
+    if value.is_empty() {
+        ""
+    } else if value.parse::<i64>().is_ok() {
+        "<int>"
+    } else if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+        "<bool>"
+    } else {
+        "changeme"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::example;
+    use crate::parser::{parse_statements, ParserOptions};
+
+    #[test]
+    fn generate() {
+        let data = "# Application\nAPP_NAME=example\nAPP_PORT=8080 # listen port\n\n# Secrets\nAPP_DEBUG=true\nAPP_SECRET=\"s3cr3t\"\nAPP_EMPTY=\n";
+        let statements = parse_statements(data, &ParserOptions { comments: true, ..ParserOptions::default() }).unwrap();
+
+        assert_eq!(example::generate(&statements, data), "# Application\nAPP_NAME=changeme\nAPP_PORT=<int> # listen port\n\n\
+            # Secrets\nAPP_DEBUG=<bool>\nAPP_SECRET=changeme\nAPP_EMPTY=\n");
+    }
+}
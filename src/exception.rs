@@ -15,19 +15,427 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use std::fmt;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
 
-/// Exception enumeration
-pub enum Exception {
-    FormatException(/*message*/ String, /*path*/ String, /*line_number*/ usize),
-    PathException(/*path*/ String),
+use crate::{Locale, MessageCatalog};
+
+/// Exception kind enumeration
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ExceptionKind {
+    /// The contents of an environment file could not be parsed.
+    Format,
+    /// An environment file could not be read.
+    Path,
+    /// The path of an environment file is a directory.
+    Directory,
+    /// The path of an environment file is a symbolic link to a file that does not exist.
+    BrokenSymlink,
+    /// The path of an environment file is a fifo, socket or device rather than a regular file.
+    SpecialFile,
+    /// A referenced environment variable is not defined.
+    UndefinedVariable,
+    /// A file could not be written.
+    Write,
+    /// Environment variables could not be read from a source other than a file.
+    Source,
+    /// An environment file (indirectly) includes itself.
+    CircularInclude,
+    /// A variable (indirectly) references itself.
+    CircularReference,
+    /// An encrypted value could not be decrypted.
+    Decryption,
+    /// A `scheme://path` reference could not be resolved.
+    Resolution,
+    /// A variable is defined more than once in the same file.
+    DuplicateKey,
+    /// A variable has an empty value.
+    EmptyValue,
+    /// A `prefix:value` value could not be decorated.
+    Decoration,
+    /// An env processor could not process a value.
+    Processing,
+    /// A protected variable would be modified.
+    ProtectedVariable,
+    /// A configured limit on the file size, number of variables or value length is exceeded.
+    LimitExceeded,
+    /// A middleware rejected or could not process the environment variables.
+    Middleware,
+    /// An environment file containing secrets is accessible by other users than its owner.
+    InsecurePermissions,
+    /// A value cannot be represented in the requested output format.
+    Unrepresentable,
+    /// A value does not satisfy the constraints declared for its variable.
+    Validation,
+    /// A variable managed by a frozen `Dotenv` instance is, or would be, modified.
+    Frozen,
 }
 
-impl fmt::Debug for Exception {
-    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Exception::FormatException(message, path, line_number) => write!(formatter, "{} in \"{}\" at line {}", message, path, line_number),
-            Exception::PathException(path) => write!(formatter, "Unable to read the \"{}\" environment file.", path),
+/// Exception
+///
+/// Each message has a stable code, such as `format.missing_equals`, and named parameters, so it can be presented in
+/// another language with `localized()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Exception {
+    kind: ExceptionKind,
+    code: &'static str,
+    message: String,
+    context: Box<Context>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Context {
+    path: Option<String>,
+    line: Option<usize>,
+    column: Option<usize>,
+    snippet: Option<String>,
+    key: Option<String>,
+    params: Vec<(&'static str, String)>,
+}
+
+impl Exception {
+    pub(crate) fn new(kind: ExceptionKind, code: &'static str, params: Vec<(&'static str, String)>) -> Self {
+        let message = {
+            let params: Vec<(&str, &str)> = params.iter().map(|(name, value)| (*name, value.as_str())).collect();
+            Locale::English.message(code, &params).unwrap_or_default()
+        };
+
+        Self {
+            kind,
+            code,
+            message,
+            context: Box::new(Context { params, ..Context::default() }),
         }
     }
+
+    pub(crate) fn format(code: &'static str, params: Vec<(&'static str, String)>, line: usize, column: usize, snippet: String) -> Self {
+        let mut exception = Self::new(ExceptionKind::Format, code, params);
+        exception.context.line = Some(line);
+        exception.context.column = Some(column);
+        exception.context.snippet = Some(snippet);
+        exception
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn unreadable(path: String) -> Self {
+        Self::new(ExceptionKind::Path, "path.unreadable", vec![("path", path.clone())]).with_path(path)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn directory(path: String) -> Self {
+        Self::new(ExceptionKind::Directory, "directory", vec![("path", path.clone())]).with_path(path)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn broken_symlink(path: String) -> Self {
+        Self::new(ExceptionKind::BrokenSymlink, "broken_symlink", vec![("path", path.clone())]).with_path(path)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn special_file(path: String, file_type: &str) -> Self {
+        Self::new(ExceptionKind::SpecialFile, "special_file", vec![("path", path.clone()), ("file_type", file_type.to_string())]).with_path(path)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn insecure_permissions(path: String, mode: u32) -> Self {
+        Self::new(ExceptionKind::InsecurePermissions, "insecure_permissions", vec![("path", path.clone()), ("mode", format!("{:04o}", mode))]).with_path(path)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn unrepresentable(key: String, format: &str, reason: &str) -> Self {
+        Self::new(ExceptionKind::Unrepresentable, "unrepresentable", vec![("key", key.clone()), ("format", format.to_string()), ("reason", reason.to_string())])
+            .with_key(key)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn validation(key: String, reason: &str) -> Self {
+        Self::new(ExceptionKind::Validation, "validation", vec![("key", key.clone()), ("reason", reason.to_string())]).with_key(key)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn undefined_variable(key: String, path: String, line: usize) -> Self {
+        let mut exception = Self::new(ExceptionKind::UndefinedVariable, "undefined_variable", vec![("key", key.clone())])
+            .with_path(path)
+            .with_key(key);
+        exception.context.line = Some(line);
+        exception
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn required_variable(key: String) -> Self {
+        Self::new(ExceptionKind::UndefinedVariable, "undefined_variable.required", vec![("key", key.clone())]).with_key(key)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn required_in_env(env: String, keys: &[&str]) -> Self {
+        let quoted_keys: Vec<String> = keys.iter().map(|key| format!("\"{}\"", key)).collect();
+
+        Self::new(ExceptionKind::UndefinedVariable, "undefined_variable.required_in_env", vec![("env", env), ("keys", quoted_keys.join(", "))])
+    }
+
+    #[cfg(any(feature = "remote", feature = "prompt"))]
+    pub(crate) fn unwritable_source(source: String, reason: String) -> Self {
+        Self::new(ExceptionKind::Write, "write.source", vec![("source", source), ("reason", reason)])
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn unwritable(path: String) -> Self {
+        Self::new(ExceptionKind::Write, "write", vec![("path", path.clone())]).with_path(path)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn already_exists(path: String) -> Self {
+        Self::new(ExceptionKind::Write, "write.already_exists", vec![("path", path.clone())]).with_path(path)
+    }
+
+    #[cfg(any(feature = "web", feature = "remote", feature = "aws", feature = "azure", feature = "prompt", feature = "signals"))]
+    pub(crate) fn unavailable_source(source: String, reason: String) -> Self {
+        Self::new(ExceptionKind::Source, "source", vec![("source", source), ("reason", reason)])
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn circular_include(path: String, chain: &[String]) -> Self {
+        Self::new(ExceptionKind::CircularInclude, "circular_include", vec![("path", path.clone()), ("chain", chain.join(" -> "))])
+            .with_path(path)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn circular_reference(key: String, chain: &[String]) -> Self {
+        Self::new(ExceptionKind::CircularReference, "circular_reference", vec![("key", key.clone()), ("chain", chain.join(" -> "))])
+            .with_key(key)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn decryption(key: String, reason: String) -> Self {
+        Self::new(ExceptionKind::Decryption, "decryption", vec![("key", key.clone()), ("reason", reason)]).with_key(key)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn encrypted_file(path: String, reason: &str) -> Self {
+        Self::new(ExceptionKind::Decryption, "decryption.file", vec![("path", path.clone()), ("reason", reason.to_string())]).with_path(path)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn resolution(key: String, reference: String, reason: String) -> Self {
+        Self::new(ExceptionKind::Resolution, "resolution", vec![("key", key.clone()), ("reference", reference), ("reason", reason)]).with_key(key)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn decoration(key: String, reason: String) -> Self {
+        Self::new(ExceptionKind::Decoration, "decoration", vec![("key", key.clone()), ("reason", reason)]).with_key(key)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn processing(expression: String, reason: String) -> Self {
+        Self::new(ExceptionKind::Processing, "processing", vec![("expression", expression), ("reason", reason)])
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn protected_variable(key: String, path: Option<String>) -> Self {
+        let mut exception = Self::new(ExceptionKind::ProtectedVariable, "protected_variable", vec![("key", key.clone())])
+            .with_key(key);
+        exception.context.path = path;
+        exception
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn frozen(key: String, path: Option<String>) -> Self {
+        let mut exception = Self::new(ExceptionKind::Frozen, "frozen", vec![("key", key.clone())])
+            .with_key(key);
+        exception.context.path = path;
+        exception
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn frozen_modified(keys: &[&str]) -> Self {
+        let quoted_keys: Vec<String> = keys.iter().map(|key| format!("\"{}\"", key)).collect();
+
+        Self::new(ExceptionKind::Frozen, "frozen.modified", vec![("keys", quoted_keys.join(", "))])
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn limit_exceeded(code: &'static str, params: Vec<(&'static str, String)>, path: Option<String>) -> Self {
+        let mut exception = Self::new(ExceptionKind::LimitExceeded, code, params);
+        exception.context.path = path;
+        exception
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn middleware(stage: &str, reason: String) -> Self {
+        Self::new(ExceptionKind::Middleware, "middleware", vec![("stage", stage.to_string()), ("reason", reason)])
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn duplicate_key(key: String, path: Option<String>, line: usize) -> Self {
+        let mut exception = Self::new(ExceptionKind::DuplicateKey, "duplicate_key", vec![("key", key.clone())])
+            .with_key(key);
+        exception.context.path = path;
+        exception.context.line = Some(line);
+        exception
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn conflicting_key(key: String) -> Self {
+        Self::new(ExceptionKind::DuplicateKey, "duplicate_key.conflicting", vec![("key", key.clone())])
+            .with_key(key)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn empty_value(key: String, path: Option<String>, line: usize) -> Self {
+        let mut exception = Self::new(ExceptionKind::EmptyValue, "empty_value", vec![("key", key.clone())])
+            .with_key(key);
+        exception.context.path = path;
+        exception.context.line = Some(line);
+        exception
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn with_path(mut self, path: String) -> Self {
+        self.context.path = Some(path);
+        self
+    }
+
+    pub(crate) fn with_key(mut self, key: String) -> Self {
+        self.context.key = Some(key);
+        self
+    }
+
+    /// The kind of exception, for reacting to it programmatically.
+    pub fn kind(&self) -> ExceptionKind {
+        self.kind
+    }
+
+    /// The stable code of the message, such as `format.missing_equals`, for translating it.
+    pub fn code(&self) -> &str {
+        self.code
+    }
+
+    /// The human readable message in English, without location information.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The value of a named parameter of the message, such as `key` or `path`.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.context.params.iter().find(|(param_name, _)| *param_name == name).map(|(_, value)| value.as_str())
+    }
+
+    /// The named parameters of the message, in the order they were given.
+    pub fn params(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.context.params.iter().map(|(name, value)| (*name, value.as_str()))
+    }
+
+    ///
+    /// The message translated by a catalog, without location information. Falls back to the English message if the
+    /// catalog does not translate it.
+    ///
+    pub fn localized_message<Catalog>(&self, catalog: &Catalog) -> String
+        where
+            Catalog: MessageCatalog + ?Sized {
+
+        let params: Vec<(&str, &str)> = self.params().collect();
+
+        catalog.message(self.code, &params).unwrap_or_else(|| self.message.clone())
+    }
+
+    ///
+    /// The message with its location, as shown by `Display`, translated by a catalog such as `Locale::Dutch`, or a
+    /// closure overriding the messages. Falls back to English for the parts the catalog does not translate.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::{parse, Locale};
+    ///
+    /// let exception = parse("APP_NAME").unwrap_err();
+    /// assert_eq!(exception.code(), "format.missing_equals");
+    /// assert_eq!(exception.localized(&Locale::Dutch).lines().next(), Some("Ontbrekende = in de declaratie van de omgevingsvariabele op regel 1, kolom 9"));
+    ///
+    /// let shouting = |code: &str, _: &[(&str, &str)]| match code {
+    ///     "format.missing_equals" => Some("MISSING =".to_string()),
+    ///     _ => None,
+    /// };
+    /// assert!(exception.localized(&shouting).starts_with("MISSING = at line 1"));
+    /// ```
+    ///
+    pub fn localized<Catalog>(&self, catalog: &Catalog) -> String
+        where
+            Catalog: MessageCatalog + ?Sized {
+
+        let mut localized = String::new();
+        let _ = self.write_localized(&mut localized, &self.localized_message(catalog), catalog);
+        localized
+    }
+
+    fn write_localized<Output, Catalog>(&self, output: &mut Output, message: &str, catalog: &Catalog) -> fmt::Result
+        where
+            Output: fmt::Write,
+            Catalog: MessageCatalog + ?Sized {
+
+        let fragment = |code: &str, name: &str, value: &str| {
+            catalog.message(code, &[(name, value)]).or_else(|| Locale::English.message(code, &[(name, value)])).unwrap_or_default()
+        };
+
+        write!(output, "{}", message)?;
+
+        if let Some(line) = self.context.line {
+            if let Some(path) = &self.context.path {
+                write!(output, "{}", fragment("location.path", "path", path))?;
+            }
+
+            write!(output, "{}", fragment("location.line", "line", &line.to_string()))?;
+        }
+
+        if let Some(column) = self.context.column {
+            write!(output, "{}", fragment("location.column", "column", &column.to_string()))?;
+
+            if let Some(snippet) = &self.context.snippet {
+                let caret_indent = snippet.chars()
+                    .take(column - 1)
+                    .map(|character| if character == '\t' { '\t' } else { ' ' })
+                    .collect::<String>();
+
+                write!(output, "\n\n    {}\n    {}^", snippet, caret_indent)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The path of the file the exception relates to.
+    pub fn path(&self) -> Option<&str> {
+        self.context.path.as_deref()
+    }
+
+    /// The 1-based line number the exception relates to.
+    pub fn line(&self) -> Option<usize> {
+        self.context.line
+    }
+
+    /// The 1-based column the exception relates to.
+    pub fn column(&self) -> Option<usize> {
+        self.context.column
+    }
+
+    /// The contents of the offending line.
+    pub fn snippet(&self) -> Option<&str> {
+        self.context.snippet.as_deref()
+    }
+
+    /// The environment variable name the exception relates to.
+    pub fn key(&self) -> Option<&str> {
+        self.context.key.as_deref()
+    }
 }
+
+impl fmt::Display for Exception {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.write_localized(formatter, &self.message, &Locale::English)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Exception {}
@@ -0,0 +1,128 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::secrets;
+
+/// Trace of what loading environment files would do, as returned by `Dotenv::explain()`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Explanation {
+    files: Vec<ExplainedFile>,
+    variables: BTreeMap<String, ExplainedVariable>,
+}
+
+/// Environment file considered while loading
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExplainedFile {
+    path: String,
+    found: bool,
+}
+
+/// Final state of an environment variable after loading
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExplainedVariable {
+    value: String,
+    source: Option<String>,
+    skipped: bool,
+}
+
+impl Explanation {
+    pub(crate) fn file(&mut self, path: &str, found: bool) {
+        self.files.push(ExplainedFile { path: path.to_string(), found });
+    }
+
+    pub(crate) fn variable(&mut self, name: &str, value: &str, source: Option<&String>, skipped: bool) {
+        if skipped && self.variables.contains_key(name) {
+            return;
+        }
+
+        self.variables.insert(name.to_string(), ExplainedVariable {
+            value: value.to_string(),
+            source: source.cloned(),
+            skipped,
+        });
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&str> {
+        self.variables.get(name)
+            .filter(|variable| !variable.skipped)
+            .map(|variable| variable.value.as_str())
+    }
+
+    /// Files in the order they were considered, whether they were found or not.
+    pub fn files(&self) -> &[ExplainedFile] {
+        &self.files
+    }
+
+    /// Variables by name, with the value they end up with.
+    pub fn variables(&self) -> &BTreeMap<String, ExplainedVariable> {
+        &self.variables
+    }
+}
+
+impl ExplainedFile {
+    /// Path of the file.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Whether the file exists and was read.
+    pub fn found(&self) -> bool {
+        self.found
+    }
+}
+
+impl ExplainedVariable {
+    /// Value the variable ends up with.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// File that provided the value, if it did not come from the existing environment.
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// Whether the loaded value was skipped, because the variable was already defined in the environment.
+    pub fn skipped(&self) -> bool {
+        self.skipped
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for file in self.files.iter() {
+            writeln!(formatter, "{} {}", if file.found { "found  " } else { "missing" }, file.path)?;
+        }
+
+        for (name, variable) in self.variables.iter() {
+            let value = match secrets::classify(name, &variable.value) {
+                Some(_) => secrets::redact(&variable.value),
+                None => variable.value.clone(),
+            };
+
+            match (&variable.source, variable.skipped) {
+                (Some(source), false) => writeln!(formatter, "{}={} (from {})", name, value, source)?,
+                _ => writeln!(formatter, "{}={} (skipped, already defined)", name, value)?,
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,441 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{parser, EntrySpans, SpannedToken, TokenKind};
+
+///
+/// Byte-oriented parser for the default grammar, which borrows the values from the input unless they contain escape
+/// sequences or Windows line endings. Parses in a single pass, without normalizing the line endings first.
+///
+/// Returns `None` for invalid input, so the caller can fall back to the regular parser for the exception.
+///
+pub(crate) fn parse(data: &str) -> Option<Vec<LexedEntry<'_>>> {
+    let mut lexer = Lexer::new(data, 0, 1);
+    let mut entries = Vec::new();
+
+    while let Some(entry) = lexer.next_entry()? {
+        entries.push(entry);
+    }
+
+    Some(entries)
+}
+
+/// Variable lexed by the `Lexer`, with the byte range of its declaration
+pub(crate) struct LexedEntry<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) value: Cow<'a, str>,
+    pub(crate) line_number: usize,
+    pub(crate) start: usize,
+    pub(crate) key_start: usize,
+    pub(crate) end: usize,
+}
+
+impl LexedEntry<'_> {
+    pub(crate) fn spans(&self, data: &str) -> EntrySpans {
+        let bytes = data.as_bytes();
+        let key_end = self.key_start + self.name.len();
+        // A quoted name, as in `export "FOO"=bar`, ends with its closing quote.
+        let value_start = if bytes[key_end] == b'=' { key_end + 1 } else { key_end + 2 };
+
+        let mut value_end = self.end;
+
+        while value_end > value_start && is_blank(bytes[value_end - 1]) {
+            value_end -= 1;
+        }
+
+        let line_start = bytes[..self.start].iter().rposition(|byte| is_line_break(*byte)).map_or(0, |index| index + 1);
+        let line_end = bytes[value_end..].iter().position(|byte| is_line_break(*byte)).map_or(bytes.len(), |index| value_end + index);
+
+        EntrySpans::new(self.key_start..key_end, value_start..value_end, line_start..line_end)
+    }
+}
+
+/// Lexer that can resume at any position between two declarations
+pub(crate) struct Lexer<'a> {
+    data: &'a str,
+    bytes: &'a [u8],
+    cursor: usize,
+    line_number: usize,
+    tokens: Option<Vec<SpannedToken>>,
+}
+
+impl<'a> Lexer<'a> {
+    pub(crate) fn new(data: &'a str, cursor: usize, line_number: usize) -> Self {
+        Self { data, bytes: data.as_bytes(), cursor, line_number, tokens: None }
+    }
+
+    /// Records the tokens of the lexed declarations, comments included.
+    pub(crate) fn with_tokens(mut self) -> Self {
+        self.tokens = Some(Vec::new());
+        self
+    }
+
+    pub(crate) fn into_tokens(self) -> Vec<SpannedToken> {
+        self.tokens.unwrap_or_default()
+    }
+
+    /// Position and line number to resume lexing at.
+    #[cfg(feature = "std")]
+    pub(crate) fn position(&self) -> (usize, usize) {
+        (self.cursor, self.line_number)
+    }
+
+    /// Skips the rest of the current line, to resume lexing after an invalid declaration.
+    pub(crate) fn skip_line(&mut self) {
+        while self.cursor < self.bytes.len() && !is_line_break(self.bytes[self.cursor]) {
+            self.cursor += 1;
+        }
+    }
+
+    fn token(&mut self, kind: TokenKind, span: Range<usize>) {
+        if let Some(tokens) = self.tokens.as_mut() {
+            tokens.push(SpannedToken::new(kind, span));
+        }
+    }
+
+    ///
+    /// Lexes the next declaration, skipping the empty lines and comments before it. Returns `Some(None)` at the end of
+    /// the input, and `None` for invalid input.
+    ///
+    pub(crate) fn next_entry(&mut self) -> Option<Option<LexedEntry<'a>>> {
+        self.skip_empty_lines();
+
+        if self.cursor == self.bytes.len() {
+            return Some(None);
+        }
+
+        let start = self.cursor;
+        let line_number = self.line_number;
+        let (name, key_start) = self.lex_varname()?;
+
+        let value = match self.cursor == self.bytes.len() {
+            true => Cow::Borrowed(""),
+            false => self.lex_value()?,
+        };
+
+        Some(Some(LexedEntry { name, value, line_number, start, key_start, end: self.cursor }))
+    }
+
+    fn lex_varname(&mut self) -> Option<(&'a str, usize)> {
+        let mut quote = None;
+
+        if self.bytes[self.cursor..].starts_with(b"export") {
+            let mut position = self.cursor + "export".len();
+
+            while position < self.bytes.len() && is_blank(self.bytes[position]) {
+                position += 1;
+            }
+
+            if position > self.cursor + "export".len() && position < self.bytes.len() {
+                if self.bytes[position] == b'"' || self.bytes[position] == b'\'' {
+                    quote = Some(self.bytes[position]);
+                    position += 1;
+                }
+
+                if quote.is_some() || self.bytes[position].is_ascii_alphabetic() {
+                    self.token(TokenKind::Export, self.cursor..self.cursor + "export".len());
+                    self.cursor = position;
+                }
+            }
+        }
+
+        if self.cursor == self.bytes.len() || !self.bytes[self.cursor].is_ascii_alphabetic() {
+            return None;
+        }
+
+        let start = self.cursor;
+
+        while self.cursor < self.bytes.len() && (self.bytes[self.cursor].is_ascii_alphanumeric() || self.bytes[self.cursor] == b'_') {
+            self.cursor += 1;
+        }
+
+        let name = &self.data[start..self.cursor];
+
+        if let Some(quote) = quote {
+            if self.bytes.get(self.cursor) != Some(&quote) {
+                return None;
+            }
+
+            self.cursor += 1;
+        }
+
+        if self.bytes.get(self.cursor) != Some(&b'=') {
+            return None;
+        }
+
+        self.token(TokenKind::Key, start..start + name.len());
+        self.token(TokenKind::Equals, self.cursor..self.cursor + 1);
+        self.cursor += 1;
+
+        Some((name, start))
+    }
+
+    fn lex_value(&mut self) -> Option<Cow<'a, str>> {
+        let mut position = self.cursor;
+
+        while position < self.bytes.len() && is_blank(self.bytes[position]) {
+            position += 1;
+        }
+
+        if position == self.bytes.len() || is_line_break(self.bytes[position]) || self.bytes[position] == b'#' {
+            return Some(Cow::Borrowed(""));
+        }
+
+        if position != self.cursor {
+            return None;
+        }
+
+        let mut value = Cow::Borrowed("");
+
+        loop {
+            let segment = match self.bytes[self.cursor] {
+                b'\'' => self.lex_single_quoted()?,
+                b'"' => self.lex_double_quoted()?,
+                _ => self.lex_unquoted()?,
+            };
+
+            if value.is_empty() {
+                value = segment;
+            } else {
+                value.to_mut().push_str(&segment);
+            }
+
+            // A comment only starts after whitespace, so `'x'#y` continues the value with `#y`.
+            if self.cursor == self.bytes.len() || is_line_break(self.bytes[self.cursor]) || (self.bytes[self.cursor] == b'#' && is_blank(self.bytes[self.cursor - 1])) {
+                break;
+            }
+        }
+
+        Some(value)
+    }
+
+    fn lex_single_quoted(&mut self) -> Option<Cow<'a, str>> {
+        let start = self.cursor + 1;
+        let closing = start + self.bytes[start..].iter().position(|byte| *byte == b'\'')?;
+        let segment = &self.data[start..closing];
+
+        self.quoted_tokens(start, closing);
+        self.line_number += count_line_breaks(segment);
+        self.cursor = closing + 1;
+
+        if !segment.contains('\r') {
+            return Some(Cow::Borrowed(segment));
+        }
+
+        Some(Cow::Owned(segment.replace("\r\n", "\n")))
+    }
+
+    fn lex_double_quoted(&mut self) -> Option<Cow<'a, str>> {
+        let start = self.cursor + 1;
+        let mut closing = start;
+
+        loop {
+            match self.bytes.get(closing) {
+                None => return None,
+                Some(b'"') => break,
+                Some(b'\\') => closing += 2,
+                Some(_) => closing += 1,
+            }
+        }
+
+        let segment = &self.data[start..closing];
+
+        self.quoted_tokens(start, closing);
+        self.line_number += count_line_breaks(segment);
+        self.cursor = closing + 1;
+
+        if !segment.contains('\\') && !segment.contains('\r') {
+            return Some(Cow::Borrowed(segment));
+        }
+
+        parser::unescape(&segment.replace("\r\n", "\n"), false).ok().map(Cow::Owned)
+    }
+
+    fn lex_unquoted(&mut self) -> Option<Cow<'a, str>> {
+        let start = self.cursor;
+        let mut escaped_quotes = false;
+
+        while self.cursor < self.bytes.len() {
+            let byte = self.bytes[self.cursor];
+
+            if is_line_break(byte) || byte == b'"' || byte == b'\'' || (byte == b'#' && is_blank(self.bytes[self.cursor - 1])) {
+                break;
+            }
+
+            if byte == b'\\' && matches!(self.bytes.get(self.cursor + 1), Some(b'"') | Some(b'\'')) {
+                escaped_quotes = true;
+                self.cursor += 1;
+            }
+
+            self.cursor += 1;
+        }
+
+        let segment = self.data[start..self.cursor].trim_end();
+
+        if segment.contains(' ') || segment.contains('\t') {
+            return None;
+        }
+
+        if !segment.is_empty() {
+            self.token(TokenKind::Value, start..start + segment.len());
+        }
+
+        if !escaped_quotes && !segment.contains("\\\\") {
+            return Some(Cow::Borrowed(segment));
+        }
+
+        Some(Cow::Owned(segment.replace("\\\"", "\"").replace("\\'", "'").replace("\\\\", "\\")))
+    }
+
+    fn quoted_tokens(&mut self, start: usize, closing: usize) {
+        self.token(TokenKind::Quote, start - 1..start);
+
+        if closing > start {
+            self.token(TokenKind::Value, start..closing);
+        }
+
+        self.token(TokenKind::Quote, closing..closing + 1);
+    }
+
+    // Position of the comment after an `export` keyword at the given position, as in `export # FOO=bar`.
+    fn commented_export_at(&self, position: usize) -> Option<usize> {
+        if !self.bytes[position..].starts_with(b"export") {
+            return None;
+        }
+
+        let mut comment_position = position + "export".len();
+
+        while comment_position < self.bytes.len() && is_blank(self.bytes[comment_position]) {
+            comment_position += 1;
+        }
+
+        if comment_position == position + "export".len() || self.bytes.get(comment_position) != Some(&b'#') {
+            return None;
+        }
+
+        Some(comment_position)
+    }
+
+    fn skip_empty_lines(&mut self) {
+        loop {
+            while self.cursor < self.bytes.len() && self.bytes[self.cursor].is_ascii_whitespace() {
+                if is_line_end(self.bytes, self.cursor) {
+                    self.line_number += 1;
+                }
+
+                self.cursor += 1;
+            }
+
+            if let Some(position) = self.commented_export_at(self.cursor) {
+                self.token(TokenKind::Export, self.cursor..self.cursor + "export".len());
+                self.cursor = position;
+            }
+
+            if self.cursor == self.bytes.len() || self.bytes[self.cursor] != b'#' {
+                break;
+            }
+
+            let start = self.cursor;
+            self.skip_line();
+            self.token(TokenKind::Comment, start..self.cursor);
+        }
+    }
+}
+
+pub(crate) fn count_line_breaks(segment: &str) -> usize {
+    count_line_break_bytes(segment.as_bytes())
+}
+
+pub(crate) fn count_line_break_bytes(bytes: &[u8]) -> usize {
+    (0..bytes.len()).filter(|position| is_line_end(bytes, *position)).count()
+}
+
+// A `\r\n` pair counts as a single line break, at its `\n`.
+fn is_line_end(bytes: &[u8], position: usize) -> bool {
+    bytes[position] == b'\n' || (bytes[position] == b'\r' && bytes.get(position + 1) != Some(&b'\n'))
+}
+
+fn is_line_break(byte: u8) -> bool {
+    byte == b'\n' || byte == b'\r'
+}
+
+fn is_blank(byte: u8) -> bool {
+    byte == b' ' || byte == b'\t'
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::Cow;
+
+    use crate::fast_parser;
+    use crate::parser::{parse_entries, ParserOptions};
+
+    const CORPUS: [&str; 27] = [
+        "",
+        "FOO=bar\nBAR=baz",
+        "\n\n# comment\nexport FOO=bar # inline\n",
+        "FOO=\nBAR= # empty\nBAZ",
+        "FOO='single\nquoted'\nBAR=\"double \\\"quoted\\\"\\n\"",
+        "FOO=\"a\\\\nb\"\nBAR=a\\\\b",
+        "FOO=\"a\\\\\\nb\\t\"",
+        "FOO=a\"b\"'c'\nBAR=\"x\" # comment",
+        "FOO=bar baz",
+        "FOO =bar",
+        "FOO= bar",
+        "FOO=\"unterminated",
+        "FOO=it\\'s\\\"\nBAR=a\\\\\"b\"",
+        "FOO=bar\r\nBAR='a\r\nb'\r\n\r\n# comment\r\nBAZ=\"c\r\nd\"\r\n",
+        "FOO=bar\rBAR='a\rb'\r# comment\rBAZ=1",
+        "FOO=ünïcödé\nBAR=\"€\"",
+        "FOO",
+        "1FOO=bar",
+        "FOO=bar\n\n\nBAR='multi\nline'\nBAZ=qux",
+        "export \t FOO=bar\nexport # BAR=baz\nexport\tBAZ=qux",
+        "export \"FOO\"=bar\nexport 'BAR'='baz'",
+        "export \"FOO'=bar",
+        "export FOO\nBAR=baz",
+        "A='x'#y\nB=\"x\"#y\nC='x' #y",
+        "A=\"x\"#'y'#\"z\" # comment",
+        "A=#y",
+        "FOO=\"a\\\\\\\"b\"\nBAR=\"a\\\\\"\nBAZ=\"\\\\\\\\\"",
+    ];
+
+    #[test]
+    fn parse_matches_regular_parser() {
+        for data in CORPUS.iter() {
+            let expected = parse_entries(data, &ParserOptions::default()).ok();
+            let actual = fast_parser::parse(data).map(|entries| {
+                entries.into_iter().map(|entry| (entry.name.into(), entry.value.into_owned(), entry.line_number)).collect()
+            });
+
+            assert_eq!(actual, expected, "{:?}", data);
+        }
+    }
+
+    #[test]
+    fn parse_borrows_plain_values() {
+        let entries = fast_parser::parse("FOO=bar\nBAR='baz'\nBAZ=\"a\\nb\"").unwrap();
+
+        assert!(matches!(entries[0].value, Cow::Borrowed("bar")));
+        assert!(matches!(entries[1].value, Cow::Borrowed("baz")));
+        assert!(matches!(entries[2].value, Cow::Owned(_)));
+    }
+}
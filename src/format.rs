@@ -0,0 +1,539 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::HashMap;
+
+use crate::{EntrySpans, Quoting};
+use crate::parser::Statement;
+
+/// Style of the environment files written by `Dotenv::format()`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FormatStyle {
+    quote_style: QuoteStyle,
+    key_order: KeyOrder,
+    unaligned_comments: bool,
+}
+
+/// Quoting of values by `Dotenv::format()`, and of the values written by `DotenvDocument::set()`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QuoteStyle {
+    /// Values are quoted as written. Changed values keep the quotes of the value they replace.
+    Preserve,
+    /// Quotes are removed from values that do not need them, like `'localhost'`.
+    #[default]
+    Minimal,
+    /// Values are written in double quotes, like `"localhost"`, unless they contain backslashes or dollar signs, which
+    /// are ambiguous in double quotes.
+    AlwaysDouble,
+}
+
+/// Order of the variables written by `Dotenv::format()`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyOrder {
+    /// Variables are kept in the order they are written in.
+    #[default]
+    Preserve,
+    /// Variables are sorted by name within each group of lines separated by blank lines, along with the comments
+    /// directly above them.
+    Sorted,
+}
+
+impl FormatStyle {
+    ///
+    /// Creates the default style, removing unneeded quotes, keeping the order of the variables and aligning the
+    /// comments after consecutive variables.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the quoting of values, which defaults to `QuoteStyle::Minimal`.
+    pub fn quote_style(mut self, quote_style: QuoteStyle) -> Self {
+        self.quote_style = quote_style;
+        self
+    }
+
+    /// Sets the order of the variables, which defaults to `KeyOrder::Preserve`.
+    pub fn key_order(mut self, key_order: KeyOrder) -> Self {
+        self.key_order = key_order;
+        self
+    }
+
+    /// Sets whether the comments after consecutive variables are aligned, which defaults to `true`.
+    pub fn align_comments(mut self, align_comments: bool) -> Self {
+        self.unaligned_comments = !align_comments;
+        self
+    }
+}
+
+/// Line of a formatted file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Line {
+    Blank,
+    Comment(String),
+    Entry { key: String, text: String, comment: Option<String> },
+    Other(String),
+}
+
+///
+/// Splits the contents of an environment file into lines. With a quote style, the lines are normalized: declarations on
+/// a single line without indentation or blanks around the equals sign, and comments with a space after the `#`.
+/// Without, and for declarations spanning multiple lines like heredocs, the lines are kept as written.
+///
+pub(crate) fn lines(statements: &[Statement], data: &str, quote_style: Option<QuoteStyle>) -> Vec<Line> {
+    let source_lines: Vec<&str> = data.lines().collect();
+    let assignments: HashMap<usize, (&String, &String, &EntrySpans)> = statements.iter()
+        .filter_map(|statement| match statement {
+            Statement::Assignment(name, value, line_number, spans) => Some((*line_number, (name, value, spans))),
+            _ => None,
+        })
+        .collect();
+
+    let mut lines = Vec::new();
+    let mut line_number = 1;
+
+    while line_number <= source_lines.len() {
+        if let Some((name, value, spans)) = assignments.get(&line_number) {
+            lines.push(entry(name, value, spans, data, quote_style));
+            line_number += data[spans.line()].lines().count().max(1);
+            continue;
+        }
+
+        let line = source_lines[line_number - 1];
+
+        lines.push(match (line.trim(), quote_style) {
+            ("", _) => Line::Blank,
+            (trimmed, Some(_)) if trimmed.starts_with('#') => Line::Comment(comment(trimmed)),
+            (trimmed, None) if trimmed.starts_with('#') => Line::Comment(line.to_string()),
+            (trimmed, Some(_)) => Line::Other(trimmed.to_string()),
+            (_, None) => Line::Other(line.to_string()),
+        });
+
+        line_number += 1;
+    }
+
+    lines
+}
+
+fn entry(name: &str, value: &str, spans: &EntrySpans, data: &str, quote_style: Option<QuoteStyle>) -> Line {
+    let line = &data[spans.line()];
+
+    let quote_style = match quote_style {
+        Some(quote_style) if !line.contains('\n') => quote_style,
+        _ => return Line::Entry { key: name.to_string(), text: line.trim_end_matches('\r').to_string(), comment: None },
+    };
+
+    let export = data[spans.line().start..spans.key().start].trim_start().starts_with("export");
+    let double_quoted;
+    let value = match quote_style {
+        QuoteStyle::Minimal if is_bare(value) => value,
+        QuoteStyle::AlwaysDouble if !data[spans.value()].starts_with("<<") => match double_quote(value) {
+            Some(value) => {
+                double_quoted = value;
+                &double_quoted
+            },
+            None => &data[spans.value()],
+        },
+        _ => &data[spans.value()],
+    };
+
+    let rest = data[spans.value().end..spans.line().end].trim();
+    let comment = match rest.starts_with('#') {
+        true => Some(self::comment(rest)),
+        false => None,
+    };
+
+    Line::Entry {
+        key: name.to_string(),
+        text: format!("{}{}={}", if export { "export " } else { "" }, name, value),
+        comment,
+    }
+}
+
+/// Whether a value can be written without quotes without changing its meaning.
+fn is_bare(value: &str) -> bool {
+    value.chars().all(|character| character.is_ascii_alphanumeric() || "_-./:@%+,=".contains(character))
+}
+
+///
+/// Quotes a value so it parses back to the same value: as is if it can be written without quotes, and in single
+/// quotes otherwise, in which the value is taken literally and single quotes are written as `'"'"'`.
+///
+pub(crate) fn quote(value: &str) -> String {
+    match is_bare(value) {
+        true => value.to_string(),
+        false => single_quote(value),
+    }
+}
+
+///
+/// Quotes a value in the given style, so it parses back to the same value. With `QuoteStyle::Preserve`, the value is
+/// quoted like the value it replaces, and as with `quote()` if that was not quoted.
+///
+pub(crate) fn quote_as(value: &str, quote_style: QuoteStyle, original: Option<Quoting>) -> String {
+    match (quote_style, original) {
+        (QuoteStyle::AlwaysDouble, _) | (QuoteStyle::Preserve, Some(Quoting::Double)) => {
+            double_quote(value).unwrap_or_else(|| single_quote(value))
+        },
+        (QuoteStyle::Preserve, Some(Quoting::Single)) => single_quote(value),
+        _ => quote(value),
+    }
+}
+
+fn single_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\"'\"'"))
+}
+
+// Quotes a value in double quotes, escaping quotes and line breaks. Backslashes and dollar signs would be taken as
+// escapes or references, so values containing them cannot be double quoted.
+fn double_quote(value: &str) -> Option<String> {
+    if value.contains(['\\', '$']) {
+        return None;
+    }
+
+    Some(format!("\"{}\"", value.replace('"', "\\\"").replace('\r', "\\r").replace('\n', "\\n")))
+}
+
+fn comment(comment: &str) -> String {
+    let text = &comment[1..];
+
+    match text.is_empty() || text.starts_with([' ', '\t', '#', '!']) {
+        true => comment.to_string(),
+        false => format!("# {}", text),
+    }
+}
+
+///
+/// Sorts the variables by name within each group of lines separated by blank lines or other statements, along with
+/// the comments directly above them.
+///
+pub(crate) fn sort(lines: Vec<Line>) -> Vec<Line> {
+    let mut sorted = Vec::with_capacity(lines.len());
+    let mut units: Vec<(String, Vec<Line>)> = Vec::new();
+    let mut comments = Vec::new();
+
+    let flush = |sorted: &mut Vec<Line>, units: &mut Vec<(String, Vec<Line>)>, comments: &mut Vec<Line>| {
+        units.sort_by(|(key_a, _), (key_b, _)| key_a.cmp(key_b));
+        sorted.extend(units.drain(..).flat_map(|(_, lines)| lines));
+        sorted.append(comments);
+    };
+
+    for line in lines {
+        match line {
+            Line::Comment(_) => comments.push(line),
+            Line::Entry { ref key, .. } => {
+                let key = key.clone();
+                comments.push(line);
+                units.push((key, std::mem::take(&mut comments)));
+            },
+            Line::Blank | Line::Other(_) => {
+                flush(&mut sorted, &mut units, &mut comments);
+                sorted.push(line);
+            },
+        }
+    }
+
+    flush(&mut sorted, &mut units, &mut comments);
+
+    sorted
+}
+
+///
+/// Groups the variables by the part of their name before the first underscore, with a `# PREFIX` comment above each
+/// group, within each group of lines separated by other statements. Groups are ordered by their first variable, and
+/// variables keep their order and the comments directly above them. Other comments are moved above the groups.
+///
+pub(crate) fn group(lines: Vec<Line>) -> Vec<Line> {
+    let mut grouped = Vec::with_capacity(lines.len());
+    let mut segment = Vec::new();
+
+    for line in lines {
+        match line {
+            Line::Other(_) => {
+                grouped.extend(group_segment(std::mem::take(&mut segment)));
+                grouped.push(line);
+            },
+            _ => segment.push(line),
+        }
+    }
+
+    grouped.extend(group_segment(segment));
+
+    grouped
+}
+
+fn group_segment(segment: Vec<Line>) -> Vec<Line> {
+    let mut loose_comments = Vec::new();
+    let mut comments = Vec::new();
+    let mut groups: Vec<(String, Vec<Line>)> = Vec::new();
+
+    for line in segment {
+        let prefix = match &line {
+            Line::Comment(_) => {
+                comments.push(line);
+                continue;
+            },
+            Line::Blank => {
+                loose_comments.append(&mut comments);
+                continue;
+            },
+            Line::Entry { key, .. } => prefix(key).to_string(),
+            Line::Other(_) => continue,
+        };
+
+        let header = Line::Comment(format!("# {}", prefix));
+        comments.retain(|comment| *comment != header);
+
+        let position = match groups.iter().position(|(group_prefix, _)| *group_prefix == prefix) {
+            Some(position) => position,
+            None => {
+                groups.push((prefix, Vec::new()));
+                groups.len() - 1
+            },
+        };
+
+        groups[position].1.append(&mut comments);
+        groups[position].1.push(line);
+    }
+
+    loose_comments.append(&mut comments);
+
+    let mut lines = Vec::new();
+
+    if !loose_comments.is_empty() {
+        lines.append(&mut loose_comments);
+        lines.push(Line::Blank);
+    }
+
+    for (prefix, mut group) in groups {
+        if !prefix.is_empty() {
+            lines.push(Line::Comment(format!("# {}", prefix)));
+        }
+
+        lines.append(&mut group);
+        lines.push(Line::Blank);
+    }
+
+    lines
+}
+
+/// Part of a name before the first underscore, if any.
+fn prefix(key: &str) -> &str {
+    key.split_once('_').map(|(prefix, _)| prefix).unwrap_or_default()
+}
+
+///
+/// Removes all but the last declaration of each variable within each group of lines separated by other statements,
+/// along with the comments directly above them. If the last declaration has no comments directly above it, the
+/// comments of the removed declaration are moved above it.
+///
+pub(crate) fn dedupe(lines: Vec<Line>) -> Vec<Line> {
+    let mut last_positions = HashMap::new();
+    let mut segment = 0;
+
+    for (index, line) in lines.iter().enumerate() {
+        match line {
+            Line::Other(_) => segment += 1,
+            Line::Entry { key, .. } => {
+                last_positions.insert((segment, key.clone()), index);
+            },
+            _ => {},
+        }
+    }
+
+    let mut deduped: Vec<Line> = Vec::with_capacity(lines.len());
+    let mut removed_comments: HashMap<String, Vec<Line>> = HashMap::new();
+    let mut segment = 0;
+
+    for (index, line) in lines.into_iter().enumerate() {
+        let key = match &line {
+            Line::Other(_) => {
+                segment += 1;
+                None
+            },
+            Line::Entry { key, .. } => Some(key.clone()),
+            _ => None,
+        };
+
+        let key = match key {
+            Some(key) => key,
+            None => {
+                deduped.push(line);
+                continue;
+            },
+        };
+
+        let comment_count = deduped.iter().rev().take_while(|line| matches!(line, Line::Comment(_))).count();
+
+        if last_positions[&(segment, key.clone())] != index {
+            let comments = deduped.split_off(deduped.len() - comment_count);
+
+            if !comments.is_empty() {
+                removed_comments.insert(key, comments);
+            }
+
+            continue;
+        }
+
+        if comment_count == 0 {
+            deduped.extend(removed_comments.remove(&key).unwrap_or_default());
+        }
+
+        deduped.push(line);
+    }
+
+    deduped
+}
+
+///
+/// Removes the declarations of a variable, along with the comments directly above them.
+///
+pub(crate) fn remove(lines: Vec<Line>, key: &str) -> Vec<Line> {
+    let mut kept: Vec<Line> = Vec::with_capacity(lines.len());
+
+    for line in lines {
+        if matches!(&line, Line::Entry { key: name, .. } if name == key) {
+            let comment_count = kept.iter().rev().take_while(|line| matches!(line, Line::Comment(_))).count();
+            kept.truncate(kept.len() - comment_count);
+            continue;
+        }
+
+        kept.push(line);
+    }
+
+    kept
+}
+
+///
+/// Renders lines, collapsing consecutive blank lines and removing leading and trailing ones, and optionally aligning
+/// the comments after consecutive variables.
+///
+pub(crate) fn render(lines: &[Line], align_comments: bool) -> String {
+    let mut lines: Vec<&Line> = lines.iter()
+        .skip_while(|line| **line == Line::Blank)
+        .collect();
+
+    lines.dedup_by(|line, previous| *line == &Line::Blank && *previous == &Line::Blank);
+
+    while lines.last() == Some(&&Line::Blank) {
+        lines.pop();
+    }
+
+    write(&lines, align_comments)
+}
+
+/// Renders lines as they are.
+fn write(lines: &[&Line], align_comments: bool) -> String {
+    let mut output = String::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        match line {
+            Line::Blank => {},
+            Line::Comment(text) | Line::Other(text) => output.push_str(text),
+            Line::Entry { text, comment: None, .. } => output.push_str(text),
+            Line::Entry { text, comment: Some(comment), .. } => {
+                let width = match align_comments {
+                    true => comment_column(lines, index),
+                    false => 0,
+                };
+
+                output.push_str(&format!("{:width$} {}", text, comment, width = width));
+            },
+        }
+
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Width of the longest declaration followed by a comment in the run of declarations around a line.
+fn comment_column(lines: &[&Line], index: usize) -> usize {
+    let is_entry = |line: &&&Line| matches!(line, Line::Entry { .. });
+    let start = index - lines[..index].iter().rev().take_while(is_entry).count();
+    let end = index + lines[index..].iter().take_while(is_entry).count();
+
+    lines[start..end].iter()
+        .filter_map(|line| match line {
+            Line::Entry { text, comment: Some(_), .. } if !text.contains('\n') => Some(text.chars().count()),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+///
+/// Formats the contents of an environment file in the given style.
+///
+pub(crate) fn format(statements: &[Statement], data: &str, style: &FormatStyle) -> String {
+    let lines = lines(statements, data, Some(style.quote_style));
+    let lines = match style.key_order {
+        KeyOrder::Preserve => lines,
+        KeyOrder::Sorted => sort(lines),
+    };
+
+    render(&lines, !style.unaligned_comments)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FormatStyle, KeyOrder, QuoteStyle, Quoting};
+    use crate::format;
+    use crate::parser::{parse_statements, ParserOptions};
+
+    fn format(data: &str, style: &FormatStyle) -> String {
+        let options = ParserOptions { whitespace_around_equals: true, heredocs: true, ..ParserOptions::default() };
+
+        format::format(&parse_statements(data, &options).unwrap(), data, style)
+    }
+
+    #[test]
+    fn format_default_style() {
+        let data = "\n\n#Application\n  APP_NAME = 'example'   #name\nexport APP_URL=\"https://example.com/?a=b\"\nAPP_PORT=8080 # port\n\n\n\
+            APP_TITLE='My app'\r\nCERT=<<EOF\n  abc\nEOF\n\n";
+
+        assert_eq!(format(data, &FormatStyle::new()), "# Application\nAPP_NAME=example # name\n\
+            export APP_URL=\"https://example.com/?a=b\"\nAPP_PORT=8080    # port\n\n\
+            APP_TITLE='My app'\nCERT=<<EOF\n  abc\nEOF\n");
+    }
+
+    #[test]
+    fn format_custom_style() {
+        let data = "# Zebra\nZ='z' # last\nA=a\n\nC=c\nB=b\n# trailing\n";
+        let style = FormatStyle::new().quote_style(QuoteStyle::Preserve).key_order(KeyOrder::Sorted).align_comments(false);
+
+        assert_eq!(format(data, &style), "A=a\n# Zebra\nZ='z' # last\n\nB=b\nC=c\n# trailing\n");
+    }
+
+    #[test]
+    fn format_double_quotes() {
+        let data = "A=a\nB='it'\"'\"'s'\nC='$HOME'\nD=\"line\\nbreak\"\n";
+        let style = FormatStyle::new().quote_style(QuoteStyle::AlwaysDouble);
+
+        assert_eq!(format(data, &style), "A=\"a\"\nB=\"it's\"\nC='$HOME'\nD=\"line\\nbreak\"\n");
+    }
+
+    #[test]
+    fn quote_as() {
+        assert_eq!(format::quote_as("a b", QuoteStyle::Preserve, Some(Quoting::Double)), "\"a b\"");
+        assert_eq!(format::quote_as("a", QuoteStyle::Preserve, Some(Quoting::Single)), "'a'");
+        assert_eq!(format::quote_as("a", QuoteStyle::Preserve, Some(Quoting::Unquoted)), "a");
+        assert_eq!(format::quote_as("a", QuoteStyle::Minimal, Some(Quoting::Double)), "a");
+        assert_eq!(format::quote_as("say \"hi\"\n", QuoteStyle::AlwaysDouble, None), "\"say \\\"hi\\\"\\n\"");
+        assert_eq!(format::quote_as("C:\\", QuoteStyle::AlwaysDouble, None), "'C:\\'");
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::fs;
+use std::path::Path;
+
+use crate::Exception;
+
+///
+/// Expands a pattern whose last path component may contain `*` and `?` wildcards into the matching regular files,
+/// sorted lexicographically.
+///
+pub(crate) fn expand(pattern: &str) -> Result<Vec<String>, Exception> {
+    let pattern_path = Path::new(pattern);
+
+    let file_pattern = match pattern_path.file_name() {
+        Some(file_pattern) => file_pattern.to_string_lossy().to_string(),
+        None => return Err(Exception::unreadable(pattern.to_string())),
+    };
+
+    let directory = match pattern_path.parent() {
+        Some(directory) if directory != Path::new("") => directory.to_path_buf(),
+        _ => Path::new(".").to_path_buf(),
+    };
+
+    let mut paths = list_files(&directory)?
+        .into_iter()
+        .filter(|file_name| matches(&file_pattern, file_name))
+        .map(|file_name| pattern_path.with_file_name(file_name).to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+
+    paths.sort();
+
+    Ok(paths)
+}
+
+///
+/// Lists the names of the regular files (or symlinks to regular files) in a directory.
+///
+pub(crate) fn list_files(directory: &Path) -> Result<Vec<String>, Exception> {
+    let entries = fs::read_dir(directory)
+        .map_err(|_| Exception::unreadable(directory.to_string_lossy().to_string()))?;
+
+    Ok(entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect())
+}
+
+///
+/// Matches a file name against a pattern supporting `*` (any sequence) and `?` (any single character).
+///
+pub(crate) fn matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+
+    let (mut pattern_index, mut name_index) = (0, 0);
+    let mut backtrack = None;
+
+    while name_index < name.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                backtrack = Some((pattern_index, name_index));
+                pattern_index += 1;
+            },
+            Some(character) if *character == '?' || *character == name[name_index] => {
+                pattern_index += 1;
+                name_index += 1;
+            },
+            _ => match backtrack {
+                Some((star_index, star_name_index)) => {
+                    pattern_index = star_index + 1;
+                    name_index = star_name_index + 1;
+                    backtrack = Some((star_index, star_name_index + 1));
+                },
+                None => return false,
+            },
+        }
+    }
+
+    pattern[pattern_index..].iter().all(|character| *character == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::glob;
+
+    #[test]
+    fn matches() {
+        assert!(glob::matches("*.env", "10-database.env"));
+        assert!(glob::matches("?0-*.env", "10-database.env"));
+        assert!(glob::matches("*", ".env"));
+        assert!(!glob::matches("*.env", "10-database.env.bak"));
+        assert!(!glob::matches("?.env", "10.env"));
+    }
+}
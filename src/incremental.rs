@@ -0,0 +1,329 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{EntrySpans, Exception, ExceptionKind};
+use crate::fast_parser::{self, LexedEntry, Lexer};
+use crate::parser::{self, ParserOptions};
+
+/// Variable parsed by an `IncrementalParser`, with the byte range of its declaration
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParsedEntry {
+    name: String,
+    value: String,
+    line_number: usize,
+    span: Range<usize>,
+    spans: EntrySpans,
+}
+
+impl ParsedEntry {
+    fn new(entry: LexedEntry, text: &str) -> Self {
+        Self {
+            spans: entry.spans(text),
+            name: entry.name.to_string(),
+            value: entry.value.into_owned(),
+            line_number: entry.line_number,
+            span: entry.start..entry.end,
+        }
+    }
+
+    fn shifted(mut self, delta: isize, line_delta: isize) -> Self {
+        self.span = (self.span.start as isize + delta) as usize..(self.span.end as isize + delta) as usize;
+        self.spans = self.spans.shifted(delta);
+        self.line_number = (self.line_number as isize + line_delta) as usize;
+        self
+    }
+
+    /// Name of the variable.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Value of the variable.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Line number of the declaration.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Byte range of the declaration, from the start of its name to the end of its value and
+    /// the blanks after it.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Byte ranges of the name, the value and the lines of the declaration.
+    pub fn spans(&self) -> &EntrySpans {
+        &self.spans
+    }
+}
+
+///
+/// Parser that keeps the parse result of a document up to date with text edits, as for an editor or language server.
+///
+/// An edit only re-lexes the declarations around it: lexing resumes at the start of the line of the last declaration
+/// before the edit, and stops as soon as it is back in step with a declaration on a line after the edit, whose result
+/// is reused.
+///
+/// # Examples
+///
+/// ```rust
+/// use darkweb_dotenv::IncrementalParser;
+///
+/// let mut parser = IncrementalParser::new("DB_HOST=localhost\nDB_PORT=5432\nDB_NAME=app\n");
+/// parser.edit(18..20, "SQL");
+///
+/// assert_eq!(parser.entries()[1].name(), "SQL_PORT");
+/// assert_eq!(parser.relexed_entries(), 2);
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct IncrementalParser {
+    text: String,
+    entries: Vec<ParsedEntry>,
+    error: Option<Exception>,
+    relexed_entries: usize,
+}
+
+impl IncrementalParser {
+    ///
+    /// Parses a document.
+    ///
+    pub fn new<Text>(text: Text) -> Self
+        where
+            Text: Into<String> {
+
+        let mut parser = Self { text: text.into(), entries: Vec::new(), error: None, relexed_entries: 0 };
+        parser.relex(0, 1, Vec::new(), 0, 0);
+        parser
+    }
+
+    ///
+    /// Replaces a byte range of the document with new text, and updates the parse result.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, or does not lie on character boundaries, like `String::replace_range()`.
+    ///
+    pub fn edit(&mut self, range: Range<usize>, text: &str) {
+        // The line breaks are counted with a byte of context on both sides, as an edit can split or join a `\r\n` pair.
+        let context = range.start.saturating_sub(1);
+        let removed_line_breaks = fast_parser::count_line_break_bytes(&self.text.as_bytes()[context..(range.end + 1).min(self.text.len())]);
+        let delta = text.len() as isize - range.len() as isize;
+
+        self.text.replace_range(range.clone(), text);
+
+        let inserted_end = (range.start + text.len() + 1).min(self.text.len());
+        let inserted_line_breaks = fast_parser::count_line_break_bytes(&self.text.as_bytes()[context..inserted_end]);
+        let line_delta = inserted_line_breaks as isize - removed_line_breaks as isize;
+
+        if self.error.is_some() {
+            self.entries.clear();
+            self.relex(0, 1, Vec::new(), 0, 0);
+            return;
+        }
+
+        // Lexing resumes at the start of the line of the last declaration whose lines end before the edit, and entries
+        // are only reused if their lines start after the edit, so their line spans are unaffected by it.
+        let entries = core::mem::take(&mut self.entries);
+        let first = entries.iter().position(|entry| entry.spans.line().end >= range.start).unwrap_or(entries.len());
+        let resumed = first.saturating_sub(1);
+
+        let (cursor, line_number) = match first.checked_sub(1).map(|index| &entries[index]) {
+            Some(entry) => (entry.spans.line().start, entry.line_number),
+            None => (0, 1),
+        };
+
+        let mut entries = entries.into_iter();
+        let preceding: Vec<ParsedEntry> = entries.by_ref().take(resumed).collect();
+        let following: Vec<ParsedEntry> = entries.filter(|entry| entry.spans.line().start > range.end).collect();
+
+        self.entries = preceding;
+        self.relex(cursor, line_number, following, delta, line_delta);
+    }
+
+    // Lexes from the cursor until the end of the document, or until a declaration starts where one of the following
+    // entries, shifted by the edit, starts.
+    fn relex(&mut self, cursor: usize, line_number: usize, following: Vec<ParsedEntry>, delta: isize, line_delta: isize) {
+        let mut lexer = Lexer::new(&self.text, cursor, line_number);
+        let mut entries = core::mem::take(&mut self.entries);
+        let mut following = following.into_iter().map(|entry| entry.shifted(delta, line_delta)).peekable();
+
+        self.error = None;
+        self.relexed_entries = 0;
+
+        loop {
+            let entry = match lexer.next_entry() {
+                Some(Some(entry)) => entry,
+                Some(None) => break,
+                None => {
+                    self.error = Some(parser::parse_entries(&self.text, &ParserOptions::default()).err()
+                        .unwrap_or_else(|| Exception::new(ExceptionKind::Format, "format.unparsable", Vec::new())));
+                    break;
+                },
+            };
+
+            while following.peek().is_some_and(|following_entry| following_entry.span.start < entry.start) {
+                following.next();
+            }
+
+            if following.peek().is_some_and(|following_entry| following_entry.span.start == entry.start) {
+                entries.extend(following);
+                break;
+            }
+
+            entries.push(ParsedEntry::new(entry, &self.text));
+            self.relexed_entries += 1;
+        }
+
+        self.entries = entries;
+    }
+
+    /// Current text of the document.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Variables of the document, in the order of the document. If the document is invalid, only the variables
+    /// before the invalid declaration.
+    pub fn entries(&self) -> &[ParsedEntry] {
+        &self.entries
+    }
+
+    /// Format exception of the document, if it is invalid.
+    pub fn error(&self) -> Option<&Exception> {
+        self.error.as_ref()
+    }
+
+    /// Number of declarations lexed by the last parse or edit.
+    pub fn relexed_entries(&self) -> usize {
+        self.relexed_entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ExceptionKind, IncrementalParser};
+
+    const DOCUMENT: &str = "# Database\nDB_HOST=localhost\nDB_PORT=5432\n\nexport DB_NAME=\"app\ndata\"\nDB_USER='root' # admin\nDB_PASS=\n";
+
+    fn assert_reparsed(parser: &IncrementalParser) {
+        let reparsed = IncrementalParser::new(parser.text());
+
+        assert_eq!(parser.entries(), reparsed.entries());
+        assert_eq!(parser.error(), reparsed.error());
+    }
+
+    #[test]
+    fn edit_relexes_affected_entries() {
+        let mut parser = IncrementalParser::new(DOCUMENT);
+        assert_eq!(parser.relexed_entries(), 5);
+
+        parser.edit(37..41, "3306");
+        assert_reparsed(&parser);
+        assert_eq!(parser.entries()[1].value(), "3306");
+        assert_eq!(parser.relexed_entries(), 2);
+
+        parser.edit(42..42, "DB_DRIVER=mysql\n");
+        assert_reparsed(&parser);
+        assert_eq!(parser.entries()[2].name(), "DB_DRIVER");
+        assert_eq!(parser.entries()[4].line_number(), 8);
+        assert_eq!(parser.relexed_entries(), 2);
+
+        parser.edit(79..83, "db");
+        assert_reparsed(&parser);
+        assert_eq!(parser.entries()[3].value(), "app\ndb");
+        assert_eq!(parser.entries()[4].span(), 83..98);
+        assert_eq!(parser.entries()[4].spans().value(), 91..97);
+        assert_eq!(parser.relexed_entries(), 2);
+    }
+
+    #[test]
+    fn edit_merges_and_splits_lines() {
+        let mut parser = IncrementalParser::new(DOCUMENT);
+
+        parser.edit(28..29, "");
+        assert_reparsed(&parser);
+        assert_eq!(parser.entries()[0].value(), "localhostDB_PORT=5432");
+
+        parser.edit(28..28, "\n");
+        assert_reparsed(&parser);
+        assert_eq!(parser.entries()[1].name(), "DB_PORT");
+    }
+
+    #[test]
+    fn edit_relexes_affected_lines() {
+        let edits: [(&str, core::ops::Range<usize>, &str); 3] = [
+            ("A= #\n", 3..3, "export export "),
+            ("\r\nD='q'\n", 1..2, ""),
+            ("BB=#", 4..4, "xx"),
+        ];
+
+        for (text, range, replacement) in edits.iter() {
+            let mut parser = IncrementalParser::new(*text);
+            parser.edit(range.clone(), replacement);
+            assert_reparsed(&parser);
+        }
+    }
+
+    #[test]
+    fn edit_matches_full_parse() {
+        const FRAGMENTS: [&str; 16] = [
+            "A", "B=", "=", "'", "\"", "#", " ", "\t", "\n", "\r", "\r\n", "export ", "\\", "x", "C=1\n", "# c\n",
+        ];
+
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut random = |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % bound as u64) as usize
+        };
+
+        for _ in 0..200 {
+            let mut parser = IncrementalParser::new(DOCUMENT);
+
+            for _ in 0..20 {
+                let start = random(parser.text().len() + 1);
+                let end = start + random((parser.text().len() - start).min(4) + 1);
+                let replacement = (0..random(3)).map(|_| FRAGMENTS[random(FRAGMENTS.len())]).collect::<String>();
+
+                parser.edit(start..end, &replacement);
+                assert_reparsed(&parser);
+            }
+        }
+    }
+
+    #[test]
+    fn edit_recovers_from_errors() {
+        let mut parser = IncrementalParser::new(DOCUMENT);
+
+        parser.edit(67..68, "");
+        assert_eq!(parser.error().unwrap().kind(), ExceptionKind::Format);
+        assert_reparsed(&parser);
+
+        parser.edit(67..67, "\"");
+        assert!(parser.error().is_none());
+        assert_eq!(parser.entries(), IncrementalParser::new(DOCUMENT).entries());
+    }
+}
@@ -0,0 +1,279 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::EnvValue;
+
+/// Renders a string as a quoted JSON string literal.
+pub(crate) fn string<Value>(value: Value) -> String
+    where
+        Value: AsRef<str> {
+
+    let mut json = String::with_capacity(value.as_ref().len() + 2);
+    json.push('"');
+
+    for character in value.as_ref().chars() {
+        match character {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            character if (character as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", character as u32);
+            },
+            character => json.push(character),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+/// Parses a JSON document.
+pub(crate) fn parse(json: &str) -> Result<EnvValue, String> {
+    let mut parser = Parser { json: json.as_bytes(), cursor: 0 };
+
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+
+    if parser.cursor != parser.json.len() {
+        return Err(format!("unexpected character at offset {}", parser.cursor));
+    }
+
+    Ok(value)
+}
+
+struct Parser<'a> {
+    json: &'a [u8],
+    cursor: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse_value(&mut self) -> Result<EnvValue, String> {
+        self.skip_whitespace();
+
+        match self.json.get(self.cursor) {
+            Some(b'{') => self.parse_object(),
+            Some(b'[') => self.parse_array(),
+            Some(b'"') => Ok(EnvValue::String(self.parse_string()?)),
+            Some(b't') => self.parse_literal("true", EnvValue::Bool(true)),
+            Some(b'f') => self.parse_literal("false", EnvValue::Bool(false)),
+            Some(b'n') => self.parse_literal("null", EnvValue::Null),
+            Some(byte) if *byte == b'-' || byte.is_ascii_digit() => self.parse_number(),
+            Some(_) => Err(format!("unexpected character at offset {}", self.cursor)),
+            None => Err("unexpected end of input".to_string()),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<EnvValue, String> {
+        let mut object = BTreeMap::new();
+        self.cursor += 1;
+        self.skip_whitespace();
+
+        if self.eat(b'}') {
+            return Ok(EnvValue::Object(object));
+        }
+
+        loop {
+            self.skip_whitespace();
+
+            if self.json.get(self.cursor) != Some(&b'"') {
+                return Err(format!("expected a member name at offset {}", self.cursor));
+            }
+
+            let name = self.parse_string()?;
+            self.skip_whitespace();
+
+            if !self.eat(b':') {
+                return Err(format!("expected : at offset {}", self.cursor));
+            }
+
+            object.insert(name, self.parse_value()?);
+            self.skip_whitespace();
+
+            if self.eat(b'}') {
+                return Ok(EnvValue::Object(object));
+            }
+
+            if !self.eat(b',') {
+                return Err(format!("expected , or }} at offset {}", self.cursor));
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<EnvValue, String> {
+        let mut array = Vec::new();
+        self.cursor += 1;
+        self.skip_whitespace();
+
+        if self.eat(b']') {
+            return Ok(EnvValue::Array(array));
+        }
+
+        loop {
+            array.push(self.parse_value()?);
+            self.skip_whitespace();
+
+            if self.eat(b']') {
+                return Ok(EnvValue::Array(array));
+            }
+
+            if !self.eat(b',') {
+                return Err(format!("expected , or ] at offset {}", self.cursor));
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        let mut string = Vec::new();
+        self.cursor += 1;
+
+        loop {
+            let byte = *self.json.get(self.cursor).ok_or_else(|| "unterminated string".to_string())?;
+            self.cursor += 1;
+
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escape = *self.json.get(self.cursor).ok_or_else(|| "unterminated string".to_string())?;
+                    self.cursor += 1;
+
+                    let character = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.parse_unicode_escape()?,
+                        _ => return Err(format!("invalid escape at offset {}", self.cursor - 1)),
+                    };
+
+                    let mut buffer = [0; 4];
+                    string.extend_from_slice(character.encode_utf8(&mut buffer).as_bytes());
+                },
+                byte => string.push(byte),
+            }
+        }
+
+        String::from_utf8(string).map_err(|_| "invalid UTF-8 in string".to_string())
+    }
+
+    fn parse_unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.parse_hex()?;
+
+        if !(0xd800..0xdc00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| "invalid unicode escape".to_string());
+        }
+
+        if self.json.get(self.cursor..self.cursor + 2) != Some(b"\\u") {
+            return Err("unpaired surrogate in unicode escape".to_string());
+        }
+
+        self.cursor += 2;
+        let low = self.parse_hex()?;
+
+        if !(0xdc00..0xe000).contains(&low) {
+            return Err("unpaired surrogate in unicode escape".to_string());
+        }
+
+        char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)).ok_or_else(|| "invalid unicode escape".to_string())
+    }
+
+    fn parse_hex(&mut self) -> Result<u32, String> {
+        let hex = self.json.get(self.cursor..self.cursor + 4)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| "invalid unicode escape".to_string())?;
+
+        self.cursor += 4;
+
+        Ok(hex)
+    }
+
+    fn parse_number(&mut self) -> Result<EnvValue, String> {
+        let start = self.cursor;
+
+        while self.cursor < self.json.len() && (self.json[self.cursor].is_ascii_digit() || b"+-.eE".contains(&self.json[self.cursor])) {
+            self.cursor += 1;
+        }
+
+        let number = std::str::from_utf8(&self.json[start..self.cursor]).unwrap();
+
+        if let Ok(integer) = number.parse::<i64>() {
+            return Ok(EnvValue::Int(integer));
+        }
+
+        number.parse::<f64>()
+            .map(EnvValue::Float)
+            .map_err(|_| format!("invalid number at offset {}", start))
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: EnvValue) -> Result<EnvValue, String> {
+        if !self.json[self.cursor..].starts_with(literal.as_bytes()) {
+            return Err(format!("unexpected character at offset {}", self.cursor));
+        }
+
+        self.cursor += literal.len();
+
+        Ok(value)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.cursor < self.json.len() && b" \t\r\n".contains(&self.json[self.cursor]) {
+            self.cursor += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.json.get(self.cursor) == Some(&byte) {
+            self.cursor += 1;
+            return true;
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{json, EnvValue};
+
+    #[test]
+    fn string_escapes_special_characters() {
+        assert_eq!(json::string("a\"b\\c\nd\u{1}"), "\"a\\\"b\\\\c\\nd\\u0001\"");
+    }
+
+    #[test]
+    fn parse() {
+        let mut object = BTreeMap::new();
+        object.insert("a".to_string(), EnvValue::Array(vec![EnvValue::Int(1), EnvValue::Float(2.5), EnvValue::Null]));
+        object.insert("b".to_string(), EnvValue::String("x\"y\u{e9}\u{1f600}".to_string()));
+        object.insert("c".to_string(), EnvValue::Bool(true));
+
+        assert_eq!(json::parse(r#" {"a": [1, 2.5, null], "b": "x\"y\u00e9\ud83d\ude00", "c": true} "#).unwrap(), EnvValue::Object(object));
+        assert!(json::parse("{\"a\": }").is_err());
+        assert!(json::parse("[1] 2").is_err());
+    }
+}
@@ -15,8 +15,179 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod audit;
+#[cfg(feature = "azure")]
+mod azure;
+#[cfg(feature = "std")]
+mod base64;
+#[cfg(feature = "std")]
+mod cache;
+#[cfg(feature = "std")]
+pub mod command;
+#[cfg(feature = "consul")]
+mod consul;
+mod compat;
+#[cfg(feature = "std")]
+pub mod decorator;
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "std")]
+mod document;
+#[cfg(feature = "std")]
+mod docker;
+#[cfg(feature = "std")]
+mod documentation;
+#[cfg(feature = "std")]
 mod dotenv;
+#[cfg(feature = "std")]
+mod encrypted_file;
+#[cfg(feature = "std")]
+pub mod encryption;
+#[cfg(feature = "std")]
+mod entry;
+#[cfg(feature = "std")]
+mod env_map;
 mod exception;
+mod fast_parser;
+#[cfg(feature = "etcd")]
+mod etcd;
+#[cfg(feature = "std")]
+mod example;
+#[cfg(feature = "std")]
+mod explain;
+#[cfg(feature = "std")]
+mod format;
+#[cfg(feature = "std")]
+mod glob;
+#[cfg(feature = "std")]
+mod hash;
+mod incremental;
+#[cfg(feature = "std")]
+mod json;
+#[cfg(feature = "std")]
+mod lint;
+#[cfg(feature = "mmap")]
+mod mapped;
+#[cfg(feature = "lsp")]
+pub mod lsp;
+#[cfg(feature = "std")]
+mod merge;
+mod messages;
+#[cfg(feature = "std")]
+mod metadata;
+#[cfg(feature = "std")]
+pub mod middleware;
+#[cfg(feature = "std")]
+mod overlay;
+mod parser;
+#[cfg(feature = "std")]
+mod policy;
+#[cfg(feature = "std")]
+mod processor;
+#[cfg(feature = "color")]
+mod pretty;
+#[cfg(feature = "prompt")]
+mod prompt;
+#[cfg(feature = "remote")]
+mod remote;
+#[cfg(all(feature = "signals", unix))]
+mod reload;
+#[cfg(feature = "std")]
+mod report;
+#[cfg(feature = "std")]
+pub mod resolver;
+#[cfg(feature = "std")]
+mod scope;
+#[cfg(feature = "std")]
+pub mod secrets;
+#[cfg(feature = "shared")]
+mod shared;
+#[cfg(feature = "std")]
+mod shell;
+#[cfg(any(feature = "aws", feature = "azure", feature = "consul", feature = "etcd"))]
+mod source;
+#[cfg(feature = "std")]
+mod spec;
+mod span;
+#[cfg(feature = "aws")]
+mod ssm;
+#[cfg(feature = "std")]
+pub mod store;
+#[cfg(feature = "std")]
+mod template;
+#[cfg(all(test, feature = "std"))]
+mod temp_dir;
+mod token;
+#[cfg(feature = "std")]
+mod tracking;
+#[cfg(feature = "std")]
+mod validation;
+#[cfg(feature = "web")]
+mod web;
 
+#[cfg(feature = "std")]
+pub use audit::{AuditAction, AuditEntry};
+#[cfg(feature = "std")]
+pub use cache::CacheStats;
+pub use compat::{compat_report, CompatReport, Dialect, Divergence};
+#[cfg(feature = "std")]
+pub use diff::EnvDiff;
+#[cfg(feature = "std")]
+pub use document::{DotenvDocument, Trivia};
+#[cfg(feature = "std")]
+pub use documentation::DocumentationFormat;
+#[cfg(feature = "std")]
 pub use dotenv::Dotenv;
-pub use exception::Exception;
+#[cfg(feature = "std")]
+pub use entry::{Entries, Entry, Quoting};
+#[cfg(feature = "std")]
+pub use env_map::EnvMap;
+#[cfg(feature = "etcd")]
+pub use etcd::EtcdWatch;
+#[cfg(feature = "std")]
+pub use example::{ExampleInit, ExampleSync};
+pub use exception::{Exception, ExceptionKind};
+#[cfg(feature = "std")]
+pub use explain::{ExplainedFile, ExplainedVariable, Explanation};
+#[cfg(feature = "std")]
+pub use format::{FormatStyle, KeyOrder, QuoteStyle};
+pub use incremental::{IncrementalParser, ParsedEntry};
+#[cfg(feature = "std")]
+pub use lint::{LintKind, LintWarning};
+#[cfg(feature = "mmap")]
+pub use mapped::MappedFile;
+#[cfg(feature = "std")]
+pub use merge::{MergeConflict, MergeResult};
+pub use messages::{Locale, MessageCatalog};
+#[cfg(feature = "std")]
+pub use overlay::EnvOverlay;
+pub use parser::{parse, parse_borrowed, parse_bytes};
+#[cfg(feature = "color")]
+pub use pretty::ColorChoice;
+#[cfg(feature = "std")]
+pub use policy::{DuplicateKeyPolicy, EmptyValuePolicy, EscapeStyle, FilePermissionPolicy, InterpolationMode, MergePolicy, OverridePolicy, ProtectedVariablePolicy};
+#[cfg(feature = "std")]
+pub use processor::EnvValue;
+#[cfg(all(feature = "signals", unix))]
+pub use reload::HupReload;
+#[cfg(feature = "std")]
+pub use report::{DeprecatedKey, DuplicateKey, InsecureFile, LoadReport, ProtectedVariable};
+#[cfg(feature = "std")]
+pub use scope::ScopedEnv;
+#[cfg(feature = "shared")]
+pub use shared::SharedEnv;
+#[cfg(feature = "std")]
+pub use shell::Shell;
+pub use span::EntrySpans;
+#[cfg(feature = "std")]
+pub use spec::KeySpec;
+pub use token::{tokenize, SpannedToken, TokenKind};
+#[cfg(feature = "std")]
+pub use tracking::TrackedEnv;
+#[cfg(feature = "std")]
+pub use validation::{Violation, ViolationKind};
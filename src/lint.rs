@@ -0,0 +1,232 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::secrets;
+
+/// Lint warning kind enumeration
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LintKind {
+    DuplicateKey,
+    UndocumentedKey,
+    UnquotedSpecialCharacters,
+    TrailingWhitespace,
+    LowercaseKey,
+    CommittedSecret,
+    InsecurePermissions,
+}
+
+/// Lint warning
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LintWarning {
+    kind: LintKind,
+    path: String,
+    line_number: usize,
+    message: String,
+}
+
+impl LintWarning {
+    fn new(kind: LintKind, path: &str, line_number: usize, message: String) -> Self {
+        Self {
+            kind,
+            path: path.to_string(),
+            line_number,
+            message,
+        }
+    }
+
+    pub fn kind(&self) -> LintKind {
+        self.kind
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}:{}: {}", self.path, self.line_number, self.message)
+    }
+}
+
+const EXAMPLE_FILE_NAME: &str = ".env.example";
+
+const SPECIAL_CHARACTERS: &[char] = &['$', '`', '\\', '!', '&', '|', ';', '<', '>', '(', ')', '*', '?', '[', ']', '{', '}', '~'];
+
+pub(crate) fn example_path(path: &str) -> Option<String> {
+    let path = Path::new(path);
+
+    if path.file_name()? == EXAMPLE_FILE_NAME {
+        return None;
+    }
+
+    let example_path = path.with_file_name(EXAMPLE_FILE_NAME);
+
+    Some(example_path.to_string_lossy().to_string())
+}
+
+///
+/// Permission bits of a file that are more permissive than `0600`, as checked by ssh for private keys. Always `None`
+/// on platforms other than unix.
+///
+pub(crate) fn insecure_mode(path: &str) -> Option<u32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = std::fs::metadata(path).ok()?.permissions().mode() & 0o777;
+
+        if mode & 0o077 != 0 {
+            return Some(mode);
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = path;
+
+    None
+}
+
+pub(crate) fn lint(path: &str, data: &str, entries: &[(String, String, usize)], example: Option<&HashMap<String, String>>) -> Vec<LintWarning> {
+    let assignment = Regex::new(r"^[ \t]*(?:export[ \t]+)?[A-Za-z][A-Za-z0-9_]*=(.*)$").unwrap();
+
+    let lines = data.lines().collect::<Vec<_>>();
+    let committed = !path.ends_with(".local");
+
+    let mut warnings = Vec::new();
+    let mut first_line_numbers = HashMap::new();
+
+    for (line_index, line) in lines.iter().enumerate() {
+        if line.ends_with(' ') || line.ends_with('\t') {
+            warnings.push(LintWarning::new(LintKind::TrailingWhitespace, path, line_index + 1, "Trailing whitespace".to_string()));
+        }
+    }
+
+    for (name, value, line_number) in entries.iter() {
+        if let Some(first_line_number) = first_line_numbers.get(name) {
+            warnings.push(LintWarning::new(LintKind::DuplicateKey, path, *line_number, format!("Duplicate key {} (first defined at line {})", name, first_line_number)));
+        } else {
+            first_line_numbers.insert(name.clone(), *line_number);
+        }
+
+        if name.chars().any(|character| character.is_ascii_lowercase()) {
+            warnings.push(LintWarning::new(LintKind::LowercaseKey, path, *line_number, format!("Key {} contains lowercase characters, which is not portable", name)));
+        }
+
+        if let Some(example) = example {
+            if !example.contains_key(name) {
+                warnings.push(LintWarning::new(LintKind::UndocumentedKey, path, *line_number, format!("Key {} is missing from {}", name, EXAMPLE_FILE_NAME)));
+            }
+        }
+
+        if committed && secrets::classify(name, value).is_some() {
+            warnings.push(LintWarning::new(LintKind::CommittedSecret, path, *line_number, format!("Key {} looks like a secret in a committed file", name)));
+        }
+
+        let raw_value = lines.get(line_number - 1)
+            .and_then(|line| assignment.captures(line))
+            .map(|captures| captures[1].to_string())
+            .unwrap_or_default();
+
+        if !raw_value.starts_with('\'') && !raw_value.starts_with('"') {
+            let raw_value = raw_value.split(" #").next().unwrap_or_default();
+
+            if raw_value.contains(SPECIAL_CHARACTERS) {
+                warnings.push(LintWarning::new(LintKind::UnquotedSpecialCharacters, path, *line_number, format!("Value of {} contains special characters and should be quoted", name)));
+            }
+        }
+    }
+
+    if let Some(mode) = insecure_mode(path) {
+        let secret = entries.iter().find(|(name, value, _)| secrets::classify(name, value).is_some());
+
+        if let Some((name, _, line_number)) = secret {
+            warnings.push(LintWarning::new(LintKind::InsecurePermissions, path, *line_number, format!("Key {} looks like a secret in a file with permissions {:04o}, which should be 0600", name, mode)));
+        }
+    }
+
+    warnings.sort_by_key(|warning| warning.line_number);
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{Dotenv, LintKind};
+    use crate::lint;
+
+    fn kinds(data: &str, path: &str, example: Option<&HashMap<String, String>>) -> Vec<LintKind> {
+        let dotenv = Dotenv::new();
+        let entries = dotenv.parse_entries(data, path).unwrap();
+
+        lint::lint(path, data, &entries, example).into_iter().map(|warning| warning.kind()).collect()
+    }
+
+    #[test]
+    fn lint_clean_file() {
+        assert!(kinds("FOO=bar\nBAR='baz qux'\n", ".env", None).is_empty());
+    }
+
+    #[test]
+    fn lint_duplicate_key() {
+        assert_eq!(kinds("FOO=bar\nFOO=baz\n", ".env", None), vec![LintKind::DuplicateKey]);
+    }
+
+    #[test]
+    fn lint_lowercase_key_and_trailing_whitespace() {
+        assert_eq!(kinds("foo=bar \n", ".env", None), vec![LintKind::TrailingWhitespace, LintKind::LowercaseKey]);
+    }
+
+    #[test]
+    fn lint_unquoted_special_characters() {
+        assert_eq!(kinds("FOO=a&b\nBAR='a&b'\nBAZ=a #&\n", ".env", None), vec![LintKind::UnquotedSpecialCharacters]);
+    }
+
+    #[test]
+    fn lint_undocumented_key() {
+        let example = vec![("FOO".to_string(), "".to_string())].into_iter().collect();
+        assert_eq!(kinds("FOO=bar\nBAR=baz\n", ".env", Some(&example)), vec![LintKind::UndocumentedKey]);
+    }
+
+    #[test]
+    fn lint_committed_secret() {
+        assert_eq!(kinds("API_TOKEN=abc\n", ".env", None), vec![LintKind::CommittedSecret]);
+        assert!(kinds("API_TOKEN=abc\n", ".env.local", None).is_empty());
+    }
+
+    #[test]
+    fn example_path() {
+        assert_eq!(lint::example_path("config/.env.local").unwrap(), "config/.env.example");
+        assert_eq!(lint::example_path(".env.example"), None);
+    }
+}
@@ -0,0 +1,547 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Language server for environment files, speaking the Language Server Protocol over stdin and stdout.
+//!
+//! The server publishes the parse error and the lint warnings of every open document as diagnostics, shows the value
+//! a variable resolves to and the file it comes from on hover, jumps to the definitions of a variable across the
+//! `.env`, `.env.local`, `.env.{env}` and `.env.{env}.local` hierarchy, and completes the keys of the `.env.example`
+//! file that the document does not define yet.
+//!
+//! Run it with the `dotenv-lsp` binary of the `lsp` feature, or embed it with `serve()`.
+//!
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use crate::{json, lint, parser, secrets, Dotenv, EnvValue, Exception, IncrementalParser};
+use crate::parser::ParserOptions;
+
+const ENV_KEY: &str = "APP_ENV";
+const DEFAULT_ENV: &str = "dev";
+const MAX_CONTENT_LENGTH: usize = 64 * 1024 * 1024;
+
+///
+/// Serves language server requests read from the input, and writes the responses and notifications to the output,
+/// until the client sends the `exit` notification or closes the input. Fails with `io::ErrorKind::InvalidData` on a
+/// message larger than 64 MiB.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io;
+///
+/// darkweb_dotenv::lsp::serve(io::stdin().lock(), io::stdout().lock()).unwrap();
+/// ```
+///
+pub fn serve<Input, Output>(mut input: Input, mut output: Output) -> io::Result<()>
+    where
+        Input: BufRead,
+        Output: Write {
+
+    let mut server = Server::default();
+
+    while let Some(message) = read_message(&mut input)? {
+        let message = match json::parse(&message) {
+            Ok(message) => message,
+            Err(_) => continue,
+        };
+
+        let method = member(&message, "method").and_then(EnvValue::as_str).unwrap_or_default();
+
+        if method == "exit" {
+            break;
+        }
+
+        let params = member(&message, "params").cloned().unwrap_or(EnvValue::Null);
+
+        for notification in server.notify(method, &params) {
+            write_message(&mut output, &notification)?;
+        }
+
+        if let Some(id) = member(&message, "id") {
+            let response = match server.respond(method, &params) {
+                Some(result) => format!("{{\"jsonrpc\":\"2.0\",\"id\":{},\"result\":{}}}", render(id), result),
+                None => format!("{{\"jsonrpc\":\"2.0\",\"id\":{},\"error\":{{\"code\":-32601,\"message\":{}}}}}", render(id), json::string(format!("Unsupported method {}", method))),
+            };
+
+            write_message(&mut output, &response)?;
+        }
+    }
+
+    output.flush()
+}
+
+#[derive(Default)]
+struct Server {
+    documents: HashMap<String, IncrementalParser>,
+}
+
+impl Server {
+    fn notify(&mut self, method: &str, params: &EnvValue) -> Vec<String> {
+        let uri = match member(params, "textDocument").and_then(|document| member(document, "uri")).and_then(EnvValue::as_str) {
+            Some(uri) => uri.to_string(),
+            None => return Vec::new(),
+        };
+
+        match method {
+            "textDocument/didOpen" => {
+                let text = member(params, "textDocument").and_then(|document| member(document, "text")).and_then(EnvValue::as_str).unwrap_or_default();
+                self.documents.insert(uri.clone(), IncrementalParser::new(text));
+            },
+            "textDocument/didChange" => {
+                let document = match self.documents.get_mut(&uri) {
+                    Some(document) => document,
+                    None => return Vec::new(),
+                };
+
+                for change in member(params, "contentChanges").and_then(EnvValue::as_array).unwrap_or_default() {
+                    let text = member(change, "text").and_then(EnvValue::as_str).unwrap_or_default();
+
+                    let range = member(change, "range").and_then(|range| {
+                        let start = offset(document.text(), member(range, "start")?)?;
+                        let end = offset(document.text(), member(range, "end")?)?;
+                        Some(start..end.max(start))
+                    });
+
+                    match range {
+                        Some(range) => document.edit(range, text),
+                        None => *document = IncrementalParser::new(text),
+                    }
+                }
+            },
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                return vec![publish_diagnostics(&uri, &[])];
+            },
+            _ => return Vec::new(),
+        }
+
+        vec![publish_diagnostics(&uri, &self.diagnostics(&uri))]
+    }
+
+    fn respond(&mut self, method: &str, params: &EnvValue) -> Option<String> {
+        match method {
+            "initialize" => Some(concat!(
+                "{\"capabilities\":{\"textDocumentSync\":{\"openClose\":true,\"change\":2},",
+                "\"hoverProvider\":true,\"definitionProvider\":true,\"completionProvider\":{}},",
+                "\"serverInfo\":{\"name\":\"dotenv-lsp\"}}",
+            ).to_string()),
+            "shutdown" => Some("null".to_string()),
+            "textDocument/hover" => Some(self.hover(params).unwrap_or_else(|| "null".to_string())),
+            "textDocument/definition" => Some(self.definition(params).unwrap_or_else(|| "null".to_string())),
+            "textDocument/completion" => Some(self.completion(params).unwrap_or_else(|| "[]".to_string())),
+            _ => None,
+        }
+    }
+
+    fn diagnostics(&self, uri: &str) -> Vec<String> {
+        let document = &self.documents[uri];
+        let path = path(uri);
+        let mut diagnostics = Vec::new();
+
+        if let Some(exception) = document.error() {
+            diagnostics.push(error_diagnostic(document.text(), exception));
+        }
+
+        let entries = document.entries().iter()
+            .map(|entry| (entry.name().to_string(), entry.value().to_string(), entry.line_number()))
+            .collect::<Vec<_>>();
+
+        let example = lint::example_path(&path)
+            .and_then(|example_path| fs::read_to_string(example_path).ok())
+            .and_then(|example_data| parser::parse(example_data).ok())
+            .map(|example| example.into_iter().collect::<HashMap<_, _>>());
+
+        for warning in lint::lint(&path, document.text(), &entries, example.as_ref()) {
+            let line = warning.line_number() - 1;
+            let length = line_text(document.text(), line).encode_utf16().count();
+
+            diagnostics.push(format!(
+                "{{\"range\":{},\"severity\":2,\"source\":\"dotenv\",\"code\":{},\"message\":{}}}",
+                range(line, 0, line, length),
+                json::string(format!("{:?}", warning.kind())),
+                json::string(warning.message()),
+            ));
+        }
+
+        diagnostics
+    }
+
+    fn hover(&self, params: &EnvValue) -> Option<String> {
+        let (uri, name) = self.name_at(params)?;
+
+        let mut dotenv = Dotenv::new();
+        let explanation = dotenv.explain(hierarchy_path(&path(&uri)), ENV_KEY, DEFAULT_ENV).ok()?;
+        let variable = explanation.variables().get(&name)?;
+
+        let value = match secrets::classify(&name, variable.value()) {
+            Some(_) => secrets::redact(variable.value()),
+            None => variable.value().to_string(),
+        };
+
+        let contents = match (variable.source(), variable.skipped()) {
+            (Some(source), false) => format!("```dotenv\n{}={}\n```\nfrom `{}`", name, value, source),
+            _ => format!("```dotenv\n{}={}\n```\nskipped, already defined", name, value),
+        };
+
+        Some(format!("{{\"contents\":{{\"kind\":\"markdown\",\"value\":{}}}}}", json::string(contents)))
+    }
+
+    fn definition(&self, params: &EnvValue) -> Option<String> {
+        let (uri, name) = self.name_at(params)?;
+        let path = path(&uri);
+
+        let mut dotenv = Dotenv::new();
+        let paths = match dotenv.explain(hierarchy_path(&path), ENV_KEY, DEFAULT_ENV) {
+            Ok(explanation) => explanation.files().iter()
+                .filter(|file| file.found())
+                .map(|file| file.path().to_string())
+                .collect(),
+            Err(_) => vec![path.clone()],
+        };
+
+        let mut locations = Vec::new();
+
+        for file_path in paths {
+            let open_document = self.documents.iter().find(|(document_uri, _)| self::path(document_uri) == file_path);
+
+            let (file_uri, entries) = match open_document {
+                Some((document_uri, document)) => (document_uri.clone(), document.entries().iter()
+                    .map(|entry| (entry.name().to_string(), entry.line_number()))
+                    .collect::<Vec<_>>()),
+                None => match fs::read_to_string(&file_path) {
+                    Ok(data) => (uri_for(&file_path), parser::parse_entries(data, &ParserOptions::default()).unwrap_or_default().into_iter()
+                        .map(|(entry_name, _, line_number)| (entry_name, line_number))
+                        .collect()),
+                    Err(_) => continue,
+                },
+            };
+
+            for (_, line_number) in entries.iter().filter(|(entry_name, _)| *entry_name == name) {
+                locations.push(format!("{{\"uri\":{},\"range\":{}}}", json::string(&file_uri), range(line_number - 1, 0, line_number - 1, 0)));
+            }
+        }
+
+        Some(format!("[{}]", locations.join(",")))
+    }
+
+    fn completion(&self, params: &EnvValue) -> Option<String> {
+        let uri = member(params, "textDocument").and_then(|document| member(document, "uri")).and_then(EnvValue::as_str)?;
+        let document = self.documents.get(uri)?;
+
+        let position = offset(document.text(), member(params, "position")?)?;
+        let line_start = document.text()[..position].rfind('\n').map_or(0, |index| index + 1);
+
+        if document.text()[line_start..position].contains(['=', '#']) {
+            return None;
+        }
+
+        let example_data = fs::read_to_string(lint::example_path(&path(uri))?).ok()?;
+        let example = parser::parse_entries(&example_data, &ParserOptions::default()).ok()?;
+
+        let items = example.iter()
+            .filter(|(name, _, _)| document.entries().iter().all(|entry| entry.name() != name))
+            .map(|(name, value, _)| format!(
+                "{{\"label\":{},\"kind\":6,\"detail\":{},\"insertText\":{}}}",
+                json::string(name),
+                json::string(value),
+                json::string(format!("{}=", name)),
+            ))
+            .collect::<Vec<_>>();
+
+        Some(format!("[{}]", items.join(",")))
+    }
+
+    // Returns the document and the variable name under the position of a text document position request.
+    fn name_at(&self, params: &EnvValue) -> Option<(String, String)> {
+        let uri = member(params, "textDocument").and_then(|document| member(document, "uri")).and_then(EnvValue::as_str)?;
+        let text = self.documents.get(uri)?.text();
+
+        let position = offset(text, member(params, "position")?)?;
+        let is_name_byte = |byte: &u8| byte.is_ascii_alphanumeric() || *byte == b'_';
+
+        let start = text.as_bytes()[..position].iter().rposition(|byte| !is_name_byte(byte)).map_or(0, |index| index + 1);
+        let end = text.as_bytes()[position..].iter().position(|byte| !is_name_byte(byte)).map_or(text.len(), |index| position + index);
+
+        match start < end {
+            true => Some((uri.to_string(), text[start..end].to_string())),
+            false => None,
+        }
+    }
+}
+
+fn read_message<Input>(input: &mut Input) -> io::Result<Option<String>>
+    where
+        Input: BufRead {
+
+    let mut content_length = None;
+
+    loop {
+        let mut header = String::new();
+
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let content_length = content_length.unwrap_or_default();
+
+    if content_length > MAX_CONTENT_LENGTH {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("message of {} bytes exceeds the maximum of {} bytes", content_length, MAX_CONTENT_LENGTH)));
+    }
+
+    let mut content = vec![0; content_length];
+    input.read_exact(&mut content)?;
+
+    Ok(Some(String::from_utf8_lossy(&content).to_string()))
+}
+
+fn write_message<Output>(output: &mut Output, message: &str) -> io::Result<()>
+    where
+        Output: Write {
+
+    write!(output, "Content-Length: {}\r\n\r\n{}", message.len(), message)?;
+    output.flush()
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: &[String]) -> String {
+    format!(
+        "{{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/publishDiagnostics\",\"params\":{{\"uri\":{},\"diagnostics\":[{}]}}}}",
+        json::string(uri),
+        diagnostics.join(","),
+    )
+}
+
+fn error_diagnostic(text: &str, exception: &Exception) -> String {
+    let line = exception.line().unwrap_or(1) - 1;
+    let line_text = line_text(text, line);
+
+    let character = line_text.chars()
+        .take(exception.column().unwrap_or(1) - 1)
+        .map(char::len_utf16)
+        .sum::<usize>();
+
+    format!(
+        "{{\"range\":{},\"severity\":1,\"source\":\"dotenv\",\"message\":{}}}",
+        range(line, character, line, line_text.encode_utf16().count().max(character)),
+        json::string(exception.message()),
+    )
+}
+
+fn range(start_line: usize, start_character: usize, end_line: usize, end_character: usize) -> String {
+    format!(
+        "{{\"start\":{{\"line\":{},\"character\":{}}},\"end\":{{\"line\":{},\"character\":{}}}}}",
+        start_line, start_character, end_line, end_character,
+    )
+}
+
+fn member<'a>(value: &'a EnvValue, name: &str) -> Option<&'a EnvValue> {
+    value.as_object()?.get(name)
+}
+
+fn render(id: &EnvValue) -> String {
+    match id {
+        EnvValue::String(id) => json::string(id),
+        id => id.to_string(),
+    }
+}
+
+fn line_text(text: &str, line: usize) -> &str {
+    text.split('\n').nth(line).unwrap_or_default().trim_end_matches('\r')
+}
+
+// Converts an LSP position, whose character counts UTF-16 code units, to a byte offset in the text.
+fn offset(text: &str, position: &EnvValue) -> Option<usize> {
+    let line = member(position, "line")?.as_int()? as usize;
+    let character = member(position, "character")?.as_int()? as usize;
+
+    let mut line_start = 0;
+
+    for _ in 0..line {
+        line_start += text[line_start..].find('\n')? + 1;
+    }
+
+    let mut units = 0;
+
+    for (index, text_character) in text[line_start..].char_indices() {
+        if units >= character || text_character == '\n' {
+            return Some(line_start + index);
+        }
+
+        units += text_character.len_utf16();
+    }
+
+    Some(text.len())
+}
+
+// Returns the path of the `.env` file at the root of the hierarchy the file belongs to.
+fn hierarchy_path(path: &str) -> String {
+    Path::new(path).with_file_name(".env").to_string_lossy().to_string()
+}
+
+fn path(uri: &str) -> String {
+    let path = uri.strip_prefix("file://").unwrap_or(uri).as_bytes();
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut index = 0;
+
+    while index < path.len() {
+        let escape = path.get(index + 1..index + 3)
+            .filter(|_| path[index] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            },
+            None => {
+                decoded.push(path[index]);
+                index += 1;
+            },
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn uri_for(path: &str) -> String {
+    let mut uri = "file://".to_string();
+
+    for byte in path.bytes() {
+        match byte {
+            byte if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) => uri.push(byte as char),
+            byte => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::Cursor;
+
+    use crate::{json, lsp, EnvValue};
+    use crate::temp_dir::TempDir;
+
+    fn frame(message: &str) -> String {
+        format!("Content-Length: {}\r\n\r\n{}", message.len(), message)
+    }
+
+    fn exchange(messages: &[String]) -> Vec<EnvValue> {
+        let input = messages.iter().map(|message| frame(message)).collect::<String>();
+        let mut output = Vec::new();
+
+        lsp::serve(Cursor::new(input), &mut output).unwrap();
+
+        String::from_utf8(output).unwrap()
+            .split("Content-Length: ")
+            .filter_map(|message| message.split_once("\r\n\r\n"))
+            .map(|(_, message)| json::parse(message).unwrap())
+            .collect()
+    }
+
+    fn member<'a>(value: &'a EnvValue, path: &[&str]) -> &'a EnvValue {
+        path.iter().fold(value, |value, name| &value.as_object().unwrap()[*name])
+    }
+
+    #[test]
+    fn read_message_too_large() {
+        let input = format!("Content-Length: {}\r\n\r\n", usize::MAX);
+        let error = lsp::serve(Cursor::new(input), &mut Vec::new()).unwrap_err();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn serve() {
+        let directory = TempDir::new("lsp");
+
+        let text = "DOTENV_TEST_LSP_HOST=localhost\nDOTENV_TEST_LSP_PORT=a&b \nDOTENV_TEST_LSP_NAME=\"app\n";
+        fs::write(directory.join(".env"), "DOTENV_TEST_LSP_HOST=localhost\n").unwrap();
+        fs::write(directory.join(".env.local"), "DOTENV_TEST_LSP_HOST=db\n").unwrap();
+        fs::write(directory.join(".env.example"), "DOTENV_TEST_LSP_HOST=\nDOTENV_TEST_LSP_USER=root\n").unwrap();
+
+        let uri = lsp::uri_for(&directory.join(".env").to_string_lossy());
+        let document = format!("{{\"uri\":{}}}", json::string(&uri));
+        let position = |line: usize, character: usize| format!(
+            "{{\"textDocument\":{},\"position\":{{\"line\":{},\"character\":{}}}}}", document, line, character,
+        );
+
+        let responses = exchange(&[
+            "{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"initialize\",\"params\":{}}".to_string(),
+            format!("{{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/didOpen\",\"params\":{{\"textDocument\":{{\"uri\":{},\"text\":{}}}}}}}", json::string(&uri), json::string(text)),
+            format!("{{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/didChange\",\"params\":{{\"textDocument\":{},\"contentChanges\":[{{\"range\":{},\"text\":\"\\\"\"}}]}}}}", document, lsp::range(2, 25, 2, 25)),
+            format!("{{\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"textDocument/hover\",\"params\":{}}}", position(0, 3)),
+            format!("{{\"jsonrpc\":\"2.0\",\"id\":3,\"method\":\"textDocument/definition\",\"params\":{}}}", position(0, 3)),
+            format!("{{\"jsonrpc\":\"2.0\",\"id\":4,\"method\":\"textDocument/completion\",\"params\":{}}}", position(3, 0)),
+            "{\"jsonrpc\":\"2.0\",\"id\":5,\"method\":\"textDocument/formatting\",\"params\":{}}".to_string(),
+            "{\"jsonrpc\":\"2.0\",\"method\":\"exit\"}".to_string(),
+        ]);
+
+        assert_eq!(responses.len(), 7);
+        assert_eq!(member(&responses[0], &["result", "capabilities", "hoverProvider"]), &EnvValue::Bool(true));
+
+        let diagnostics = member(&responses[1], &["params", "diagnostics"]).as_array().unwrap();
+        assert_eq!(member(&diagnostics[0], &["severity"]), &EnvValue::Int(1));
+        assert_eq!(member(&diagnostics[0], &["range", "start", "line"]), &EnvValue::Int(3));
+
+        let diagnostics = member(&responses[2], &["params", "diagnostics"]).as_array().unwrap()
+            .iter()
+            .map(|diagnostic| member(diagnostic, &["code"]).as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(diagnostics, ["TrailingWhitespace", "UndocumentedKey", "UnquotedSpecialCharacters", "UndocumentedKey"]);
+
+        let hover = member(&responses[3], &["result", "contents", "value"]).as_str().unwrap();
+        assert!(hover.contains("DOTENV_TEST_LSP_HOST=db\n"));
+        assert!(hover.ends_with(".env.local`"));
+
+        let definitions = member(&responses[4], &["result"]).as_array().unwrap();
+        assert_eq!(definitions.len(), 2);
+        assert!(member(&definitions[1], &["uri"]).as_str().unwrap().ends_with("/.env.local"));
+
+        let completions = member(&responses[5], &["result"]).as_array().unwrap();
+        assert_eq!(completions.len(), 1);
+        assert_eq!(member(&completions[0], &["label"]), &EnvValue::String("DOTENV_TEST_LSP_USER".to_string()));
+
+        assert_eq!(member(&responses[6], &["error", "code"]), &EnvValue::Int(-32601));
+    }
+
+    #[test]
+    fn path() {
+        assert_eq!(lsp::path("file:///home/my%20app/.env"), "/home/my app/.env");
+        assert_eq!(lsp::uri_for("/home/my app/.env"), "file:///home/my%20app/.env");
+    }
+}
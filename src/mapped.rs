@@ -0,0 +1,169 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::borrow::Cow;
+use std::fs::File;
+
+use memmap2::Mmap;
+
+use crate::{parser, Exception};
+
+///
+/// Contents of an environment file, memory-mapped when it is at least `MappedFile::THRESHOLD` bytes, for tools that
+/// process machine-generated dumps of tens of megabytes. Combined with the zero-copy parser of `parse()`, the names and
+/// values are borrowed from the mapping instead of being copied into memory.
+///
+/// The file must not be modified or truncated by other processes while it is mapped, which is why opening it is
+/// `unsafe`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use darkweb_dotenv::MappedFile;
+///
+/// // SAFETY: the dump is not modified while it is processed.
+/// let file = unsafe { MappedFile::open("dump.env") }.unwrap();
+///
+/// for (name, value) in file.parse().unwrap() {
+///     println!("{}={}", name, value);
+/// }
+/// ```
+///
+pub struct MappedFile {
+    path: String,
+    contents: Contents,
+}
+
+enum Contents {
+    Mapped(Mmap),
+    Read(Vec<u8>),
+}
+
+impl MappedFile {
+    /// Size in bytes from which files are memory-mapped by `open()`.
+    pub const THRESHOLD: u64 = 1024 * 1024;
+
+    ///
+    /// Opens a file, memory-mapping it if it is at least `THRESHOLD` bytes, and reading it otherwise.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while the `MappedFile` exists. Reading a mapping whose file was
+    /// truncated raises `SIGBUS`, and a modification changes contents that were assumed to be immutable and valid UTF-8.
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Path`
+    ///
+    pub unsafe fn open<Path>(path: Path) -> Result<Self, Exception>
+        where
+            Path: AsRef<str> {
+
+        Self::open_with_threshold(path, Self::THRESHOLD)
+    }
+
+    ///
+    /// Opens a file, memory-mapping it if it is at least the given number of bytes, and reading it otherwise.
+    ///
+    /// # Safety
+    ///
+    /// The same as for `open()`.
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Path`
+    ///
+    pub unsafe fn open_with_threshold<Path>(path: Path, threshold: u64) -> Result<Self, Exception>
+        where
+            Path: AsRef<str> {
+
+        let path = path.as_ref().to_string();
+        let unreadable = |_| Exception::unreadable(path.clone());
+
+        let file = File::open(&path).map_err(unreadable)?;
+        let size = file.metadata().map_err(unreadable)?.len();
+
+        let contents = match size >= threshold {
+            // SAFETY: the caller guarantees that the file is not modified while it is mapped.
+            true => Contents::Mapped(unsafe { Mmap::map(&file) }.map_err(unreadable)?),
+            false => Contents::Read(std::fs::read(&path).map_err(unreadable)?),
+        };
+
+        Ok(Self { path, contents })
+    }
+
+    /// Path of the file.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Whether the file is memory-mapped, rather than read into memory.
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.contents, Contents::Mapped(_))
+    }
+
+    /// Raw contents of the file.
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.contents {
+            Contents::Mapped(mmap) => mmap,
+            Contents::Read(bytes) => bytes,
+        }
+    }
+
+    ///
+    /// Parses the file like `parse_borrowed()`, borrowing the names and values from the file contents.
+    ///
+    /// # Exceptions
+    ///
+    /// * `ExceptionKind::Format`
+    ///
+    pub fn parse(&self) -> Result<Vec<(&str, Cow<'_, str>)>, Exception> {
+        parser::from_utf8(self.as_bytes())
+            .and_then(parser::parse_borrowed)
+            .map_err(|exception| exception.with_path(self.path.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::fs;
+
+    use crate::{ExceptionKind, MappedFile};
+    use crate::temp_dir::TempDir;
+
+    #[test]
+    fn parse() {
+        let directory = TempDir::new("mapped");
+        let path = directory.join(".env");
+        fs::write(&path, "DB_USER=root\nDB_PASS=\"p\\\"ss\"").unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        // SAFETY: the test files are only modified while they are not mapped.
+        let read = unsafe { MappedFile::open(&path) }.unwrap();
+        let mapped = unsafe { MappedFile::open_with_threshold(&path, 0) }.unwrap();
+        assert!(!read.is_mapped());
+        assert!(mapped.is_mapped());
+        assert_eq!(read.parse().unwrap(), mapped.parse().unwrap());
+        assert_eq!(mapped.parse().unwrap()[0], ("DB_USER", Cow::Borrowed("root")));
+
+        fs::write(&path, b"DB_USER=\xff").unwrap();
+        let exception = unsafe { MappedFile::open_with_threshold(&path, 0) }.unwrap().parse().unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Format);
+        assert_eq!(exception.path(), Some(path.as_str()));
+    }
+}
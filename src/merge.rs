@@ -0,0 +1,273 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::HashMap;
+
+use crate::format::{self, Line};
+use crate::parser::Statement;
+
+/// Result of a three-way merge of environment files, as returned by `Dotenv::merge_files()`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MergeResult {
+    merged: String,
+    conflicts: Vec<MergeConflict>,
+}
+
+/// Variable changed differently on both sides of a merge
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MergeConflict {
+    key: String,
+    base: Option<String>,
+    ours: Option<String>,
+    theirs: Option<String>,
+}
+
+impl MergeResult {
+    ///
+    /// Contents of the merged file, based on our file. Conflicting variables are surrounded by `<<<<<<< ours`,
+    /// `=======` and `>>>>>>> theirs` markers.
+    ///
+    pub fn merged(&self) -> &str {
+        &self.merged
+    }
+
+    /// Variables changed differently on both sides, in the order of the merged file.
+    pub fn conflicts(&self) -> &[MergeConflict] {
+        &self.conflicts
+    }
+
+    /// Whether the files merged without conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+impl MergeConflict {
+    /// Name of the variable.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Value in the common ancestor, if defined there.
+    pub fn base(&self) -> Option<&str> {
+        self.base.as_deref()
+    }
+
+    /// Value in our file, if defined there.
+    pub fn ours(&self) -> Option<&str> {
+        self.ours.as_deref()
+    }
+
+    /// Value in their file, if defined there.
+    pub fn theirs(&self) -> Option<&str> {
+        self.theirs.as_deref()
+    }
+}
+
+/// Environment file taking part in a merge
+pub(crate) struct MergeSide {
+    values: HashMap<String, String>,
+    lines: Vec<Line>,
+}
+
+impl MergeSide {
+    pub(crate) fn new(statements: &[Statement], data: &str) -> Self {
+        let mut values = HashMap::new();
+
+        for statement in statements {
+            match statement {
+                Statement::Assignment(name, value, ..) => {
+                    values.insert(name.clone(), value.clone());
+                },
+                Statement::Unset(name, _) => {
+                    values.remove(name);
+                },
+                _ => {},
+            }
+        }
+
+        Self { values, lines: format::lines(statements, data, None) }
+    }
+
+    /// Last declaration of a variable, along with the comments directly above it.
+    fn declaration(&self, key: &str) -> Vec<Line> {
+        let index = match self.lines.iter().rposition(|line| is_declaration_of(line, key)) {
+            Some(index) => index,
+            None => return Vec::new(),
+        };
+
+        let comment_count = self.lines[..index].iter().rev().take_while(|line| matches!(line, Line::Comment(_))).count();
+
+        self.lines[index - comment_count..=index].to_vec()
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &String> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Entry { key, .. } => Some(key),
+            _ => None,
+        })
+    }
+}
+
+enum Resolution {
+    Ours,
+    Theirs,
+    Conflict,
+}
+
+fn is_declaration_of(line: &Line, key: &str) -> bool {
+    matches!(line, Line::Entry { key: entry_key, .. } if entry_key == key)
+}
+
+///
+/// Merges two environment files changed from a common ancestor, variable by variable. A variable changed on one side
+/// takes the value of that side, and a variable changed differently on both sides is a conflict. Values are compared
+/// as parsed, so requoting a value is not a change.
+///
+pub(crate) fn merge(base: &MergeSide, ours: &MergeSide, theirs: &MergeSide) -> MergeResult {
+    let mut keys: Vec<&String> = Vec::new();
+
+    for key in ours.keys().chain(theirs.keys()) {
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    let resolutions: HashMap<&String, Resolution> = keys.iter()
+        .map(|key| {
+            let (base_value, our_value, their_value) = (base.values.get(*key), ours.values.get(*key), theirs.values.get(*key));
+
+            let resolution = if our_value == their_value || their_value == base_value {
+                Resolution::Ours
+            } else if our_value == base_value {
+                Resolution::Theirs
+            } else {
+                Resolution::Conflict
+            };
+
+            (*key, resolution)
+        })
+        .collect();
+
+    let mut merged: Vec<Line> = Vec::new();
+    let mut conflicts = Vec::new();
+
+    let mut conflict = |merged: &mut Vec<Line>, key: &String, our_declaration: Vec<Line>| {
+        merged.push(Line::Other("<<<<<<< ours".to_string()));
+        merged.extend(our_declaration);
+        merged.push(Line::Other("=======".to_string()));
+        merged.extend(theirs.declaration(key));
+        merged.push(Line::Other(">>>>>>> theirs".to_string()));
+
+        conflicts.push(MergeConflict {
+            key: key.clone(),
+            base: base.values.get(key).cloned(),
+            ours: ours.values.get(key).cloned(),
+            theirs: theirs.values.get(key).cloned(),
+        });
+    };
+
+    for (index, line) in ours.lines.iter().enumerate() {
+        let key = match line {
+            Line::Entry { key, .. } => key,
+            _ => {
+                merged.push(line.clone());
+                continue;
+            },
+        };
+
+        // A variable removed on their side loses all of its declarations, along with the comments directly above them
+        if matches!(resolutions[key], Resolution::Theirs) && !theirs.values.contains_key(key) {
+            while matches!(merged.last(), Some(Line::Comment(_))) {
+                merged.pop();
+            }
+
+            continue;
+        }
+
+        let is_last = !ours.lines[index + 1..].iter().any(|line| is_declaration_of(line, key));
+
+        match (&resolutions[key], is_last) {
+            (Resolution::Ours, _) | (_, false) => merged.push(line.clone()),
+            (Resolution::Theirs, true) => merged.extend(theirs.declaration(key).into_iter().filter(|line| !matches!(line, Line::Comment(_)))),
+            (Resolution::Conflict, true) => conflict(&mut merged, key, vec![line.clone()]),
+        }
+    }
+
+    let mut additions = Vec::new();
+
+    for key in keys.iter().filter(|key| !ours.keys().any(|our_key| our_key == **key)) {
+        match resolutions[key] {
+            Resolution::Ours => {},
+            Resolution::Theirs => additions.extend(theirs.declaration(key)),
+            Resolution::Conflict => conflict(&mut additions, key, Vec::new()),
+        }
+    }
+
+    if !additions.is_empty() {
+        merged.push(Line::Blank);
+        merged.append(&mut additions);
+    }
+
+    MergeResult { merged: format::render(&merged, false), conflicts }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::merge::{self, MergeSide};
+    use crate::parser::{parse_statements, ParserOptions};
+
+    fn side(data: &str) -> MergeSide {
+        MergeSide::new(&parse_statements(data, &ParserOptions::default()).unwrap(), data)
+    }
+
+    #[test]
+    fn merge_clean() {
+        let base = side("# Application\nAPP_NAME=example\nAPP_DEBUG=0\nAPP_OLD=1\n");
+        let ours = side("# Application\nAPP_NAME=example\nAPP_DEBUG=1\n# Old\nAPP_OLD=1\nAPP_OURS=1\n");
+        let theirs = side("# Application\nAPP_NAME='renamed'\nAPP_DEBUG=0\n# New\nAPP_THEIRS=1\n");
+
+        let result = merge::merge(&base, &ours, &theirs);
+        assert!(result.is_clean());
+        assert_eq!(result.merged(), "# Application\nAPP_NAME='renamed'\nAPP_DEBUG=1\nAPP_OURS=1\n\n# New\nAPP_THEIRS=1\n");
+    }
+
+    #[test]
+    fn merge_removes_all_declarations() {
+        let base = side("A=1\n# Override\nA=2\nB=1\n");
+        let ours = side("A=1\n# Override\nA=2\nB=2\n");
+        let theirs = side("B=1\n");
+
+        let result = merge::merge(&base, &ours, &theirs);
+        assert!(result.is_clean());
+        assert_eq!(result.merged(), "B=2\n");
+    }
+
+    #[test]
+    fn merge_conflicts() {
+        let base = side("A=base\nB=base\nC=base\n");
+        let ours = side("A=ours\nC=ours\nD=ours\n");
+        let theirs = side("A=theirs\nB=theirs\nC=ours\nD=theirs\n");
+
+        let result = merge::merge(&base, &ours, &theirs);
+        assert_eq!(result.merged(), "<<<<<<< ours\nA=ours\n=======\nA=theirs\n>>>>>>> theirs\nC=ours\n<<<<<<< ours\nD=ours\n=======\nD=theirs\n>>>>>>> theirs\n\n\
+            <<<<<<< ours\n=======\nB=theirs\n>>>>>>> theirs\n");
+
+        let conflicts = result.conflicts().iter().map(|conflict| (conflict.key(), conflict.base(), conflict.ours(), conflict.theirs())).collect::<Vec<_>>();
+        assert_eq!(conflicts, [("A", Some("base"), Some("ours"), Some("theirs")), ("D", None, Some("ours"), Some("theirs")), ("B", Some("base"), None, Some("theirs"))]);
+    }
+}
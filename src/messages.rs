@@ -0,0 +1,189 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::string::String;
+
+/// Catalog translating exception messages, as used by `Exception::localized()`
+///
+/// Implemented by `Locale` for the built-in translations, and by closures taking the code of a message and its
+/// parameters, which return `None` to fall back to English.
+pub trait MessageCatalog {
+    /// Returns the message for a code, such as `format.missing_equals`, with its parameters filled in.
+    fn message(&self, code: &str, params: &[(&str, &str)]) -> Option<String>;
+}
+
+/// Language of the built-in message catalog
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Locale {
+    #[default]
+    English,
+    Dutch,
+}
+
+impl Locale {
+    ///
+    /// Returns the locale of a language tag, such as `nl`, `nl-BE` or the `nl_NL.UTF-8` of the `LANG` environment
+    /// variable, or `None` if the language is not supported.
+    ///
+    pub fn from_tag<Tag>(tag: Tag) -> Option<Self>
+        where
+            Tag: AsRef<str> {
+
+        let language = tag.as_ref().split(['-', '_', '.']).next().unwrap_or_default();
+
+        match language.to_ascii_lowercase().as_str() {
+            "en" | "c" | "posix" => Some(Locale::English),
+            "nl" => Some(Locale::Dutch),
+            _ => None,
+        }
+    }
+
+    fn template(&self, code: &str) -> Option<&'static str> {
+        let (_, english, dutch) = MESSAGES.iter().find(|(message_code, _, _)| *message_code == code)?;
+
+        match self {
+            Locale::English => Some(english),
+            Locale::Dutch => Some(dutch),
+        }
+    }
+}
+
+impl MessageCatalog for Locale {
+    fn message(&self, code: &str, params: &[(&str, &str)]) -> Option<String> {
+        self.template(code).map(|template| render(template, params))
+    }
+}
+
+impl<F> MessageCatalog for F
+    where
+        F: Fn(&str, &[(&str, &str)]) -> Option<String> {
+
+    fn message(&self, code: &str, params: &[(&str, &str)]) -> Option<String> {
+        self(code, params)
+    }
+}
+
+/// Fills in the `{name}` placeholders of a template, leaving unknown placeholders as is.
+pub(crate) fn render(template: &str, params: &[(&str, &str)]) -> String {
+    let mut message = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        message.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest.find('}')
+            .and_then(|end| params.iter().find(|(name, _)| *name == &rest[1..end]).map(|(_, value)| (end, value)));
+
+        match value {
+            Some((end, value)) => {
+                message.push_str(value);
+                rest = &rest[end + 1..];
+            },
+            None => {
+                message.push('{');
+                rest = &rest[1..];
+            },
+        }
+    }
+
+    message.push_str(rest);
+    message
+}
+
+// Code, English and Dutch templates of every message. The codes are stable, so catalogs can rely on them.
+const MESSAGES: &[(&str, &str, &str)] = &[
+    ("location.path", " in \"{path}\"", " in \"{path}\""),
+    ("location.line", " at line {line}", " op regel {line}"),
+    ("location.column", ", column {column}", ", kolom {column}"),
+    ("format.invalid_utf8", "Invalid UTF-8 sequence", "Ongeldige UTF-8-reeks"),
+    ("format.unparsable", "Unable to parse the environment variables", "Kan de omgevingsvariabelen niet verwerken"),
+    ("format.invalid_name", "\"{key}\" is not a valid variable name", "\"{key}\" is geen geldige variabelenaam"),
+    ("format.missing_include_path", "Missing path in the include directive", "Ontbrekend pad in de include-instructie"),
+    ("format.invalid_section", "Invalid section header", "Ongeldige sectiekop"),
+    ("format.invalid_name_character", "Invalid character in variable name", "Ongeldig teken in variabelenaam"),
+    ("format.unterminated_name", "Missing quote to end the variable name", "Ontbrekend aanhalingsteken aan het einde van de variabelenaam"),
+    ("format.unset_without_value", "Unable to unset an environment variable, \"export {key}\" needs a value unless unsetting is enabled", "Kan de omgevingsvariabele niet verwijderen, \"export {key}\" heeft een waarde nodig tenzij verwijderen is ingeschakeld"),
+    ("format.missing_equals", "Missing = in the environment variable declaration", "Ontbrekende = in de declaratie van de omgevingsvariabele"),
+    ("format.whitespace_after_name", "Whitespace characters are not supported after the variable name", "Witruimte wordt niet ondersteund na de variabelenaam"),
+    ("format.whitespace_before_value", "Whitespace are not supported before the value", "Witruimte wordt niet ondersteund voor de waarde"),
+    ("format.unterminated_value", "Missing quote to end the value", "Ontbrekend aanhalingsteken aan het einde van de waarde"),
+    ("format.invalid_escape", "Invalid escape sequence \"{escape}\"", "Ongeldige escapereeks \"{escape}\""),
+    ("format.unquoted_spaces", "A value containing spaces must be surrounded by quotes", "Een waarde met spaties moet tussen aanhalingstekens staan"),
+    ("format.invalid_heredoc_delimiter", "Invalid heredoc delimiter", "Ongeldig heredoc-scheidingsteken"),
+    ("format.unterminated_heredoc", "Missing {delimiter} to end the heredoc", "Ontbrekende {delimiter} aan het einde van de heredoc"),
+    ("path.unreadable", "Unable to read the \"{path}\" environment file.", "Kan het omgevingsbestand \"{path}\" niet lezen."),
+    ("directory", "Unable to read the \"{path}\" environment file, it is a directory.", "Kan het omgevingsbestand \"{path}\" niet lezen, het is een map."),
+    ("broken_symlink", "Unable to read the \"{path}\" environment file, it is a symbolic link to a file that does not exist.", "Kan het omgevingsbestand \"{path}\" niet lezen, het is een symbolische koppeling naar een bestand dat niet bestaat."),
+    ("special_file", "Unable to read the \"{path}\" environment file, it is a {file_type} rather than a regular file.", "Kan het omgevingsbestand \"{path}\" niet lezen, het is een {file_type} in plaats van een gewoon bestand."),
+    ("insecure_permissions", "Permissions {mode} for the \"{path}\" environment file containing secrets are too open, it must not be accessible by others than its owner.", "Rechten {mode} voor het omgevingsbestand \"{path}\" met geheimen zijn te ruim, het mag alleen toegankelijk zijn voor de eigenaar."),
+    ("unrepresentable", "Environment variable \"{key}\" cannot be exported as {format}: {reason}.", "Omgevingsvariabele \"{key}\" kan niet worden geëxporteerd als {format}: {reason}."),
+    ("undefined_variable", "Environment variable \"{key}\" is not defined", "Omgevingsvariabele \"{key}\" is niet gedefinieerd"),
+    ("undefined_variable.required", "Required environment variable \"{key}\" is not defined", "Verplichte omgevingsvariabele \"{key}\" is niet gedefinieerd"),
+    ("undefined_variable.required_in_env", "Environment variables required in the \"{env}\" environment are not defined: {keys}", "Omgevingsvariabelen die verplicht zijn in de omgeving \"{env}\" zijn niet gedefinieerd: {keys}"),
+    ("write", "Unable to write the \"{path}\" file.", "Kan het bestand \"{path}\" niet schrijven."),
+    ("write.already_exists", "The \"{path}\" file already exists.", "Het bestand \"{path}\" bestaat al."),
+    ("write.source", "Unable to write environment variables to \"{source}\": {reason}", "Kan de omgevingsvariabelen niet schrijven naar \"{source}\": {reason}"),
+    ("source", "Unable to read environment variables from \"{source}\": {reason}", "Kan de omgevingsvariabelen niet lezen uit \"{source}\": {reason}"),
+    ("circular_include", "Circular include of the \"{path}\" environment file: {chain}.", "Circulaire include van het omgevingsbestand \"{path}\": {chain}."),
+    ("circular_reference", "Circular reference in the value of \"{key}\": {chain}", "Circulaire verwijzing in de waarde van \"{key}\": {chain}"),
+    ("decryption", "Unable to decrypt the value of \"{key}\": {reason}", "Kan de waarde van \"{key}\" niet ontsleutelen: {reason}"),
+    ("decryption.file", "Unable to decrypt the \"{path}\" environment file: {reason}", "Kan het omgevingsbestand \"{path}\" niet ontsleutelen: {reason}"),
+    ("resolution", "Unable to resolve \"{reference}\" for \"{key}\": {reason}", "Kan \"{reference}\" voor \"{key}\" niet herleiden: {reason}"),
+    ("decoration", "Unable to decorate the value of \"{key}\": {reason}", "Kan de waarde van \"{key}\" niet decoreren: {reason}"),
+    ("processing", "Unable to process \"{expression}\": {reason}", "Kan \"{expression}\" niet verwerken: {reason}"),
+    ("frozen", "Refusing to modify the frozen environment variable \"{key}\"", "Weigert de bevroren omgevingsvariabele \"{key}\" te wijzigen"),
+    ("frozen.modified", "Frozen environment variables were modified: {keys}", "Bevroren omgevingsvariabelen zijn gewijzigd: {keys}"),
+    ("protected_variable", "Refusing to modify the protected environment variable \"{key}\"", "Weigert de beschermde omgevingsvariabele \"{key}\" te wijzigen"),
+    ("limit_exceeded.value_length", "The value of \"{key}\" is {length} bytes, which exceeds the maximum of {max} bytes", "De waarde van \"{key}\" is {length} bytes, wat het maximum van {max} bytes overschrijdt"),
+    ("limit_exceeded.file_size", "The environment file is {size} bytes, which exceeds the maximum of {max} bytes", "Het omgevingsbestand is {size} bytes, wat het maximum van {max} bytes overschrijdt"),
+    ("limit_exceeded.variables", "More than the maximum of {max} environment variables are defined", "Er zijn meer dan het maximum van {max} omgevingsvariabelen gedefinieerd"),
+    ("middleware", "The {stage} stage of a middleware failed: {reason}", "De {stage}-fase van een middleware is mislukt: {reason}"),
+    ("duplicate_key", "Environment variable \"{key}\" is already defined", "Omgevingsvariabele \"{key}\" is al gedefinieerd"),
+    ("duplicate_key.conflicting", "Environment variable \"{key}\" is defined with conflicting values", "Omgevingsvariabele \"{key}\" is gedefinieerd met tegenstrijdige waarden"),
+    ("empty_value", "Environment variable \"{key}\" has an empty value", "Omgevingsvariabele \"{key}\" heeft een lege waarde"),
+    ("validation", "Environment variable \"{key}\" {reason}", "Omgevingsvariabele \"{key}\" {reason}"),
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::{Locale, MessageCatalog};
+    use super::{render, MESSAGES};
+
+    #[test]
+    fn render_placeholders() {
+        assert_eq!(render("Missing {delimiter} to end {unknown} {", &[("delimiter", "EOT")]), "Missing EOT to end {unknown} {");
+    }
+
+    #[test]
+    fn locale_from_tag() {
+        assert_eq!(Locale::from_tag("nl_NL.UTF-8"), Some(Locale::Dutch));
+        assert_eq!(Locale::from_tag("en-US"), Some(Locale::English));
+        assert_eq!(Locale::from_tag("fr"), None);
+    }
+
+    #[test]
+    fn catalog() {
+        assert_eq!(Locale::Dutch.message("duplicate_key", &[("key", "APP")]).unwrap(), "Omgevingsvariabele \"APP\" is al gedefinieerd");
+        assert_eq!(Locale::Dutch.message("unknown", &[]), None);
+
+        for (index, (code, _, _)) in MESSAGES.iter().enumerate() {
+            assert!(MESSAGES[index + 1..].iter().all(|(other_code, _, _)| other_code != code), "duplicate code {}", code);
+        }
+    }
+}
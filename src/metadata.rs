@@ -0,0 +1,76 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Synthetic variables describing a load, for runtime diagnostics.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Name of the variable holding the time of the last load, as an RFC 3339 timestamp in UTC.
+pub(crate) const LOADED_AT_VARIABLE: &str = "DOTENV_LOADED_AT";
+
+/// Name of the variable holding the paths of the files loaded, separated like the entries of `PATH`.
+pub(crate) const FILES_VARIABLE: &str = "DOTENV_FILES";
+
+/// Name of the variable holding the environment resolved by `Dotenv::load_env()`.
+pub(crate) const ENV_VARIABLE: &str = "DOTENV_ENV";
+
+/// Formats a time as an RFC 3339 timestamp in UTC, such as `2020-06-01T12:00:00Z`.
+pub(crate) fn timestamp(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs());
+    let (year, month, day) = civil_date((seconds / 86_400) as i64);
+    let seconds_of_day = seconds % 86_400;
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, seconds_of_day / 3600, seconds_of_day / 60 % 60, seconds_of_day % 60,
+    )
+}
+
+/// Joins paths like the entries of `PATH`.
+pub(crate) fn join_paths(paths: &[String]) -> String {
+    paths.join(if cfg!(windows) { ";" } else { ":" })
+}
+
+// Date of a number of days since 1970-01-01 in the proleptic Gregorian calendar, after Howard Hinnant's
+// `civil_from_days` algorithm.
+fn civil_date(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::metadata;
+
+    #[test]
+    fn timestamp() {
+        assert_eq!(metadata::timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(metadata::timestamp(UNIX_EPOCH + Duration::from_secs(951_827_696)), "2000-02-29T12:34:56Z");
+        assert_eq!(metadata::timestamp(UNIX_EPOCH + Duration::from_secs(1_700_000_000)), "2023-11-14T22:13:20Z");
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Stages of the loading pipeline that third parties can hook into, such as for decryption, templating or auditing.
+//!
+//! Files are loaded in stages: read → decode → transform → validate → populate. The built-in behavior of each stage
+//! runs first, such as the decryption and decorators of the transform stage, followed by the registered middleware in
+//! the order of registration.
+//!
+//! Parsing, between the decode and transform stages, is not a stage: middleware can rewrite the contents before it in
+//! the decode stage, and the values after it in the transform stage.
+
+use std::collections::HashMap;
+
+/// Middleware of the loading pipeline
+///
+/// Every stage defaults to passing its input through unchanged, so a middleware only implements the stages it needs.
+pub trait Middleware {
+    ///
+    /// Transforms the raw contents of an environment file after reading it.
+    ///
+    fn read(&self, _path: &str, bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+        Ok(bytes)
+    }
+
+    ///
+    /// Transforms the contents of an environment file after decoding them as UTF-8, before parsing them.
+    ///
+    fn decode(&self, _path: &str, data: String) -> Result<String, String> {
+        Ok(data)
+    }
+
+    ///
+    /// Transforms the value of a variable after parsing it, before interpolation. The path is the one of the
+    /// environment file that defines the variable, if any.
+    ///
+    fn transform(&self, _key: &str, value: String, _path: Option<&str>) -> Result<String, String> {
+        Ok(value)
+    }
+
+    ///
+    /// Validates the variables about to be populated, failing the load before any of them is set.
+    ///
+    fn validate(&self, _values: &HashMap<String, String>) -> Result<(), String> {
+        Ok(())
+    }
+
+    ///
+    /// Observes the variables that were populated.
+    ///
+    fn populate(&self, _values: &HashMap<String, String>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    use crate::{Dotenv, ExceptionKind};
+    use crate::middleware::Middleware;
+    use crate::temp_dir::TempDir;
+
+    struct Uppercase;
+
+    impl Middleware for Uppercase {
+        fn transform(&self, _key: &str, value: String, _path: Option<&str>) -> Result<String, String> {
+            Ok(value.to_uppercase())
+        }
+    }
+
+    struct RequireKey(&'static str);
+
+    impl Middleware for RequireKey {
+        fn validate(&self, values: &HashMap<String, String>) -> Result<(), String> {
+            match values.contains_key(self.0) {
+                true => Ok(()),
+                false => Err(format!("\"{}\" is required", self.0)),
+            }
+        }
+    }
+
+    struct Audit(Rc<RefCell<Vec<String>>>);
+
+    impl Middleware for Audit {
+        fn populate(&self, values: &HashMap<String, String>) {
+            self.0.borrow_mut().extend(values.keys().cloned());
+        }
+    }
+
+    #[test]
+    fn middleware_stages() {
+        let audited = Rc::new(RefCell::new(Vec::new()));
+        let mut dotenv = Dotenv::new()
+            .middleware(Uppercase)
+            .middleware(RequireKey("DOTENV_TEST_MIDDLEWARE_A"))
+            .middleware(Audit(audited.clone()));
+
+        dotenv.overload_from_str("DOTENV_TEST_MIDDLEWARE_A=value").unwrap();
+        assert_eq!(dotenv.values()["DOTENV_TEST_MIDDLEWARE_A"], "VALUE");
+        assert_eq!(*audited.borrow(), ["DOTENV_TEST_MIDDLEWARE_A"]);
+
+        let exception = dotenv.overload_from_str("DOTENV_TEST_MIDDLEWARE_B=value").unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Middleware);
+        assert!(std::env::var("DOTENV_TEST_MIDDLEWARE_B").is_err());
+    }
+
+    #[test]
+    fn middleware_file_stages() {
+        struct Reversed;
+
+        impl Middleware for Reversed {
+            fn read(&self, _path: &str, bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+                Ok(bytes.into_iter().rev().collect())
+            }
+
+            fn decode(&self, _path: &str, data: String) -> Result<String, String> {
+                Ok(data.replace("NAME", "DOTENV_TEST_MIDDLEWARE_FILE"))
+            }
+        }
+
+        let directory = TempDir::new("middleware");
+        let path = directory.join(".env");
+        std::fs::write(&path, "eulav=EMAN").unwrap();
+
+        let mut dotenv = Dotenv::new().middleware(Reversed);
+        dotenv.load(path.to_string_lossy()).unwrap();
+        assert_eq!(dotenv.values()["DOTENV_TEST_MIDDLEWARE_FILE"], "value");
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+
+use crate::EnvMap;
+
+thread_local! {
+    static OVERLAYS: RefCell<Vec<Arc<HashMap<String, String>>>> = const { RefCell::new(Vec::new()) };
+}
+
+///
+/// Set of environment variables taking precedence over the environment of the process within a scope on the current
+/// thread, for running several logical environments in the same process, like the tenants of a worker, without
+/// setting variables globally.
+///
+/// # Examples
+///
+/// ```rust
+/// use darkweb_dotenv::{EnvMap, EnvOverlay};
+///
+/// let overlay = EnvOverlay::from(EnvMap::parse("TENANT=acme").unwrap());
+///
+/// overlay.scope(|| {
+///     assert_eq!(EnvOverlay::lookup("TENANT").as_deref(), Some("acme"));
+/// });
+///
+/// assert_eq!(EnvOverlay::lookup("TENANT"), None);
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnvOverlay {
+    values: Arc<HashMap<String, String>>,
+}
+
+impl EnvOverlay {
+    ///
+    /// Returns the value of a variable in the overlay, falling back to the environment of the process.
+    ///
+    pub fn get<Key>(&self, key: Key) -> Option<String>
+        where
+            Key: AsRef<str> {
+
+        let key = key.as_ref();
+
+        self.values.get(key).cloned().or_else(|| env::var(key).ok())
+    }
+
+    ///
+    /// Runs a closure with the overlay active on the current thread. Scopes can be nested, the innermost overlay
+    /// taking precedence. Threads and tasks spawned by the closure do not inherit the overlay.
+    ///
+    pub fn scope<Scope, Output>(&self, scope: Scope) -> Output
+        where
+            Scope: FnOnce() -> Output {
+
+        OVERLAYS.with(|overlays| overlays.borrow_mut().push(self.values.clone()));
+
+        let _guard = ScopeGuard;
+
+        scope()
+    }
+
+    ///
+    /// Returns the value of a variable in the overlays active on the current thread, innermost first, falling back to
+    /// the environment of the process.
+    ///
+    pub fn lookup<Key>(key: Key) -> Option<String>
+        where
+            Key: AsRef<str> {
+
+        let key = key.as_ref();
+
+        OVERLAYS.with(|overlays| overlays.borrow().iter().rev().find_map(|values| values.get(key).cloned()))
+            .or_else(|| env::var(key).ok())
+    }
+}
+
+impl From<EnvMap> for EnvOverlay {
+    fn from(map: EnvMap) -> Self {
+        Self { values: Arc::new(map.into()) }
+    }
+}
+
+/// Deactivates the innermost overlay when a scope ends, also when it panics
+struct ScopeGuard;
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        OVERLAYS.with(|overlays| overlays.borrow_mut().pop());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::thread;
+
+    use crate::{EnvMap, EnvOverlay};
+
+    #[test]
+    fn scoped_lookup() {
+        env::set_var("DOTENV_TEST_OVERLAY_PROCESS", "process");
+
+        let acme = EnvOverlay::from(EnvMap::parse("DOTENV_TEST_OVERLAY_TENANT=acme\nDOTENV_TEST_OVERLAY_DB=acme").unwrap());
+        let globex = EnvOverlay::from(EnvMap::parse("DOTENV_TEST_OVERLAY_TENANT=globex").unwrap());
+
+        acme.scope(|| {
+            assert_eq!(EnvOverlay::lookup("DOTENV_TEST_OVERLAY_TENANT").as_deref(), Some("acme"));
+
+            globex.scope(|| {
+                assert_eq!(EnvOverlay::lookup("DOTENV_TEST_OVERLAY_TENANT").as_deref(), Some("globex"));
+                assert_eq!(EnvOverlay::lookup("DOTENV_TEST_OVERLAY_DB").as_deref(), Some("acme"));
+                assert_eq!(EnvOverlay::lookup("DOTENV_TEST_OVERLAY_PROCESS").as_deref(), Some("process"));
+            });
+
+            let other_thread = thread::spawn(|| EnvOverlay::lookup("DOTENV_TEST_OVERLAY_TENANT")).join().unwrap();
+            assert_eq!(other_thread, None);
+        });
+
+        assert_eq!(EnvOverlay::lookup("DOTENV_TEST_OVERLAY_TENANT"), None);
+        assert_eq!(globex.get("DOTENV_TEST_OVERLAY_TENANT").as_deref(), Some("globex"));
+        assert!(env::var("DOTENV_TEST_OVERLAY_TENANT").is_err());
+
+        env::remove_var("DOTENV_TEST_OVERLAY_PROCESS");
+    }
+}
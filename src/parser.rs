@@ -0,0 +1,1379 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{EntrySpans, Exception, ExceptionKind};
+use crate::fast_parser;
+
+///
+/// Parses the contents of a `.env` file into a map of environment variables.
+///
+/// This is the environment-agnostic core of the crate: it neither touches the filesystem nor the process
+/// environment, and is available without the `std` feature. Parsing never panics: any invalid input results in an
+/// exception.
+///
+/// # Examples
+///
+/// ```rust
+/// let values = darkweb_dotenv::parse("DB_USER=root\nDB_PASS='pass'").unwrap();
+///
+/// assert_eq!(values["DB_PASS"], "pass");
+/// ```
+///
+/// # Exceptions
+///
+/// * `ExceptionKind::Format`
+///
+pub fn parse<Data>(data: Data) -> Result<BTreeMap<String, String>, Exception>
+    where
+        Data: AsRef<str> {
+
+    Ok(parse_entries(data, &ParserOptions::default())?
+        .into_iter()
+        .map(|(name, value, _)| (name, value))
+        .collect())
+}
+
+///
+/// Parses the raw contents of a `.env` file into a map of environment variables, like `parse()`, accepting any byte
+/// sequence.
+///
+/// # Examples
+///
+/// ```rust
+/// use darkweb_dotenv::ExceptionKind;
+///
+/// assert_eq!(darkweb_dotenv::parse_bytes(b"DB_USER=root").unwrap()["DB_USER"], "root");
+/// assert_eq!(darkweb_dotenv::parse_bytes(b"DB_USER=\xff").unwrap_err().kind(), ExceptionKind::Format);
+/// ```
+///
+/// # Exceptions
+///
+/// * `ExceptionKind::Format`
+///
+pub fn parse_bytes(data: &[u8]) -> Result<BTreeMap<String, String>, Exception> {
+    parse(from_utf8(data)?)
+}
+
+///
+/// Parses the contents of a `.env` file into a list of environment variables, in the order of the file, borrowing the
+/// names and values from the input instead of allocating them.
+///
+/// Values containing escape sequences or Windows line endings are returned as `Cow::Owned`, all other values are
+/// borrowed. Variables defined more than once are returned once per definition.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::borrow::Cow;
+///
+/// let values = darkweb_dotenv::parse_borrowed("DB_USER=root\nDB_PASS=\"p\\\"ss\"").unwrap();
+///
+/// assert_eq!(values[0], ("DB_USER", Cow::Borrowed("root")));
+/// assert_eq!(values[1].1, "p\"ss");
+/// ```
+///
+/// # Exceptions
+///
+/// * `ExceptionKind::Format`
+///
+pub fn parse_borrowed(data: &str) -> Result<Vec<(&str, Cow<'_, str>)>, Exception> {
+    match fast_parser::parse(data) {
+        Some(entries) => Ok(entries.into_iter().map(|entry| (entry.name, entry.value)).collect()),
+        None => Err(Parser::new(data, &ParserOptions::default()).parse().err()
+            .unwrap_or_else(|| Exception::new(ExceptionKind::Format, "format.unparsable", Vec::new()))),
+    }
+}
+
+///
+/// Resolves the escape sequences of a double-quoted value in a single pass, so an escaped backslash is never taken as
+/// the start of another escape: `\"`, `\r`, `\n` and `\\`, and with extended escapes also `\t`, `\f`, `\0`,
+/// `\uXXXX` and `\u{1F600}`. Other backslashes are kept as written. Returns the invalid escape on failure.
+///
+pub(crate) fn unescape(segment: &str, extended: bool) -> Result<String, String> {
+    let mut unescaped = String::with_capacity(segment.len());
+    let mut characters = segment.char_indices().peekable();
+
+    while let Some((start, character)) = characters.next() {
+        if character != '\\' {
+            unescaped.push(character);
+            continue;
+        }
+
+        let escaped = match characters.peek() {
+            Some((_, escaped)) => *escaped,
+            None => {
+                unescaped.push(character);
+                continue;
+            },
+        };
+
+        let resolved = match (escaped, extended) {
+            ('"', _) => '"',
+            ('r', _) => '\r',
+            ('n', _) => '\n',
+            ('\\', _) => '\\',
+            ('t', true) => '\t',
+            ('f', true) => '\u{c}',
+            ('0', true) => '\0',
+            ('u', true) => {
+                characters.next();
+
+                let rest = &segment[start + 2..];
+                let (digits, length, valid_length) = match rest.strip_prefix('{') {
+                    Some(braced) => match braced.find('}') {
+                        Some(end) => (&braced[..end], end + 2, (1..=6).contains(&end)),
+                        None => (braced, rest.len(), false),
+                    },
+                    None => {
+                        let end = rest.char_indices().nth(4).map_or(rest.len(), |(index, _)| index);
+                        (&rest[..end], end, end == 4)
+                    },
+                };
+
+                let resolved = Some(digits)
+                    .filter(|digits| valid_length && digits.chars().all(|digit| digit.is_ascii_hexdigit()))
+                    .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+                    .and_then(char::from_u32);
+
+                match resolved {
+                    Some(resolved) => unescaped.push(resolved),
+                    None => return Err(format!("\\u{}", &rest[..length])),
+                }
+
+                for _ in 0..length {
+                    characters.next();
+                }
+
+                continue;
+            },
+            _ => {
+                unescaped.push(character);
+                continue;
+            },
+        };
+
+        characters.next();
+        unescaped.push(resolved);
+    }
+
+    Ok(unescaped)
+}
+
+///
+/// Resolves the escape sequences of an ANSI-C quoted value, as by bash: `\a`, `\b`, `\e`, `\f`, `\n`, `\r`, `\t`,
+/// `\v`, `\\`, `\'`, `\"`, `\?`, `\cX`, octal `\nnn`, hexadecimal `\xHH`, and `\uXXXX` and `\UXXXXXXXX`. Other
+/// backslashes are kept as written. Octal and hexadecimal escapes are bytes, which may form UTF-8 sequences together.
+///
+fn unescape_ansi_c(segment: &str) -> String {
+    fn take_digits(bytes: &[u8], max: usize, radix: u32) -> (u32, usize) {
+        let length = bytes.iter().take(max).take_while(|byte| (**byte as char).is_digit(radix)).count();
+        let value = bytes[..length].iter().fold(0, |value, byte| value * radix + (*byte as char).to_digit(radix).unwrap_or(0));
+
+        (value, length)
+    }
+
+    let bytes = segment.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut position = 0;
+
+    while position < bytes.len() {
+        if bytes[position] != b'\\' || position + 1 == bytes.len() {
+            unescaped.push(bytes[position]);
+            position += 1;
+            continue;
+        }
+
+        let rest = &bytes[position + 2..];
+        let (resolved, length) = match bytes[position + 1] {
+            b'a' => (Some(0x07), 0),
+            b'b' => (Some(0x08), 0),
+            b'e' | b'E' => (Some(0x1b), 0),
+            b'f' => (Some(0x0c), 0),
+            b'n' => (Some(u32::from(b'\n')), 0),
+            b'r' => (Some(u32::from(b'\r')), 0),
+            b't' => (Some(u32::from(b'\t')), 0),
+            b'v' => (Some(0x0b), 0),
+            byte @ (b'\\' | b'\'' | b'"' | b'?') => (Some(u32::from(byte)), 0),
+            b'c' if !rest.is_empty() && rest[0].is_ascii() => (Some(u32::from(rest[0] & 0x1f)), 1),
+            b'0'..=b'7' => {
+                let (value, length) = take_digits(&bytes[position + 1..], 3, 8);
+
+                unescaped.push(value as u8);
+                position += 1 + length;
+                continue;
+            },
+            b'x' => match take_digits(rest, 2, 16) {
+                (_, 0) => (None, 0),
+                (value, length) => {
+                    unescaped.push(value as u8);
+                    position += 2 + length;
+                    continue;
+                },
+            },
+            byte @ (b'u' | b'U') => match take_digits(rest, if byte == b'u' { 4 } else { 8 }, 16) {
+                (_, 0) => (None, 0),
+                (value, length) => (Some(value), length),
+            },
+            _ => (None, 0),
+        };
+
+        match resolved.and_then(char::from_u32) {
+            Some(character) => {
+                unescaped.extend_from_slice(character.encode_utf8(&mut [0; 4]).as_bytes());
+                position += 2 + length;
+            },
+            None => {
+                unescaped.push(b'\\');
+                position += 1;
+            },
+        }
+    }
+
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+// Converts the raw contents of a `.env` file to a string, reporting the location of the first invalid byte.
+pub(crate) fn from_utf8(data: &[u8]) -> Result<&str, Exception> {
+    core::str::from_utf8(data).map_err(|error| {
+        let valid = core::str::from_utf8(&data[..error.valid_up_to()]).unwrap_or_default();
+        let line_start = valid.rfind(Parser::LINE_BREAKS).map_or(0, |index| index + 1);
+        let line_number = valid.replace("\r\n", "\n").matches(Parser::LINE_BREAKS).count() + 1;
+        let column = valid[line_start..].chars().count() + 1;
+
+        Exception::format("format.invalid_utf8", Vec::new(), line_number, column, valid[line_start..].to_string())
+    })
+}
+
+pub(crate) fn parse_entries<Data>(data: Data, options: &ParserOptions) -> Result<Vec<(String, String, usize)>, Exception>
+    where
+        Data: AsRef<str> {
+
+    let mut entries = Vec::new();
+
+    for statement in parse_statements(data, options)? {
+        match statement {
+            Statement::Assignment(name, value, line_number, _) => entries.push((name, value, line_number)),
+            Statement::Unset(name, _) => entries.retain(|(entry_name, _, _)| *entry_name != name),
+            Statement::Include(..) | Statement::Section(..) | Statement::Comment(..) => {},
+        }
+    }
+
+    Ok(entries)
+}
+
+pub(crate) fn parse_statements<Data>(data: Data, options: &ParserOptions) -> Result<Vec<Statement>, Exception>
+    where
+        Data: AsRef<str> {
+
+    #[cfg(feature = "fast-parse")]
+    if *options == ParserOptions::default() {
+        if let Some(entries) = fast_parser::parse(data.as_ref()) {
+            return Ok(entries.into_iter()
+                .map(|entry| {
+                    let spans = entry.spans(data.as_ref());
+                    Statement::Assignment(entry.name.to_string(), entry.value.into_owned(), entry.line_number, spans)
+                })
+                .collect());
+        }
+    }
+
+    Parser::new(data.as_ref(), options).parse()
+}
+
+/// Whether a name is accepted as a variable name by the parser.
+#[cfg(feature = "std")]
+pub(crate) fn is_valid_name(name: &str) -> bool {
+    name.as_bytes().first().is_some_and(u8::is_ascii_alphabetic) && name.bytes().all(is_name_character)
+}
+
+/// Derives a valid variable name by replacing invalid characters with `_` and dropping a leading non-letter prefix.
+#[cfg(feature = "std")]
+pub(crate) fn sanitize_name(name: &str) -> Option<String> {
+    let name: String = name.chars()
+        .map(|character| if character.is_ascii() && is_name_character(character as u8) { character } else { '_' })
+        .skip_while(|character| !character.is_ascii_alphabetic())
+        .collect();
+
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(name)
+}
+
+fn is_name_character(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ParserOptions {
+    /// Recognize `#!include path` and `source path` directives.
+    pub(crate) includes: bool,
+    /// Allow whitespace around `=`, as in `FOO = bar`.
+    pub(crate) whitespace_around_equals: bool,
+    /// Recognize `FOO=<<EOF` heredoc values.
+    pub(crate) heredocs: bool,
+    /// Recognize `[section]` headers.
+    pub(crate) sections: bool,
+    /// Keep comments as statements.
+    pub(crate) comments: bool,
+    /// Treat `export FOO` without a value as unsetting the variable.
+    pub(crate) unset_exports: bool,
+    /// Treat `\$`, and `$` in single-quoted values, as literal dollar signs.
+    pub(crate) literal_dollars: bool,
+    /// Treat `$$` as a literal dollar sign as well.
+    pub(crate) double_dollars: bool,
+    /// Escape literal dollar signs as `$$` in the values, so they are not interpolated.
+    pub(crate) interpolation: bool,
+    /// Recognize `\t`, `\f`, `\0` and `\u` escapes in double-quoted values.
+    pub(crate) extended_escapes: bool,
+    /// Recognize `$'...'` values with ANSI-C escapes, as by bash.
+    pub(crate) ansi_c_quotes: bool,
+    /// Allow spaces in unquoted values, as in `GREETING=Hello World`.
+    pub(crate) unquoted_spaces: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Statement {
+    Assignment(/*name*/ String, /*value*/ String, /*line_number*/ usize, /*spans*/ EntrySpans),
+    Include(/*path*/ String, /*line_number*/ usize),
+    Section(/*name*/ String, /*line_number*/ usize),
+    Comment(/*text*/ String, /*line_number*/ usize),
+    Unset(/*name*/ String, /*line_number*/ usize),
+}
+
+struct Parser<'a> {
+    data: String,
+    cursor: usize,
+    end: usize,
+    options: &'a ParserOptions,
+    comments: Vec<Statement>,
+    // Positions of the `\n` of the Windows line endings that were normalized, to map positions back to the input.
+    carriage_returns: Vec<usize>,
+    // Positions of the line breaks, to look up the line number of a position without scanning the data before it.
+    line_breaks: Vec<usize>,
+    key_span: Range<usize>,
+    value_span: Range<usize>,
+}
+
+impl<'a> Parser<'a> {
+    const INCLUDE_DIRECTIVES: [&'static str; 2] = ["#!include", "source"];
+    const LINE_BREAKS: [char; 2] = ['\n', '\r'];
+
+    fn new(data: &str, options: &'a ParserOptions) -> Self {
+        let mut carriage_returns = Vec::new();
+
+        for (index, _) in data.match_indices("\r\n") {
+            carriage_returns.push(index - carriage_returns.len());
+        }
+
+        let data = data.replace("\r\n", "\n");
+        let end = data.len();
+        let line_breaks = data.bytes().enumerate()
+            .filter(|(_, byte)| matches!(byte, b'\n' | b'\r'))
+            .map(|(index, _)| index)
+            .collect();
+
+        Self {
+            data,
+            cursor: 0,
+            end,
+            options,
+            comments: Vec::new(),
+            carriage_returns,
+            line_breaks,
+            key_span: 0..0,
+            value_span: 0..0,
+        }
+    }
+
+    fn parse(mut self) -> Result<Vec<Statement>, Exception> {
+        let mut statements = Vec::new();
+
+        self.skip_empty_lines();
+
+        while self.cursor < self.end {
+            statements.append(&mut self.comments);
+
+            let line_number = self.line_number();
+
+            if let Some(path) = self.lex_include()? {
+                statements.push(Statement::Include(path, line_number));
+                self.skip_empty_lines();
+                continue;
+            }
+
+            if let Some(name) = self.lex_section()? {
+                statements.push(Statement::Section(name, line_number));
+                self.skip_empty_lines();
+                continue;
+            }
+
+            if let Some(name) = self.lex_unset() {
+                statements.push(Statement::Unset(name, line_number));
+                self.skip_empty_lines();
+                continue;
+            }
+
+            let name = self.lex_varname()?;
+
+            if self.cursor == self.end {
+                self.value_span = self.cursor..self.cursor;
+                statements.push(Statement::Assignment(name, "".to_string(), line_number, self.entry_spans()));
+                break;
+            }
+
+            let value = self.lex_value().map_err(|exception| exception.with_key(name.clone()))?;
+            statements.push(Statement::Assignment(name, value, line_number, self.entry_spans()));
+        }
+
+        statements.append(&mut self.comments);
+
+        Ok(statements)
+    }
+
+    fn lex_include(&mut self) -> Result<Option<String>, Exception> {
+        let directive_length = match self.include_directive_at(self.cursor) {
+            Some(directive) => directive.len(),
+            None => return Ok(None),
+        };
+
+        let start = self.cursor + directive_length;
+        let end = self.data[start..].find(Self::LINE_BREAKS).map_or(self.end, |offset| start + offset);
+        let path = self.data[start..end].trim().trim_matches(|character| character == '"' || character == '\'');
+
+        if path.is_empty() {
+            return Err(self.create_format_exception("format.missing_include_path"));
+        }
+
+        let path = path.to_string();
+        self.cursor = end;
+
+        Ok(Some(path))
+    }
+
+    fn lex_section(&mut self) -> Result<Option<String>, Exception> {
+        if !self.options.sections || self.byte_at(self.cursor) != b'[' {
+            return Ok(None);
+        }
+
+        let end = self.data[self.cursor..].find(Self::LINE_BREAKS).map_or(self.end, |offset| self.cursor + offset);
+        let header = self.data[self.cursor..end].trim_end();
+
+        let name = match header.strip_prefix('[').and_then(|header| header.strip_suffix(']')) {
+            Some(name) if !name.trim().is_empty() => name.trim().to_string(),
+            _ => return Err(self.create_format_exception("format.invalid_section")),
+        };
+
+        self.cursor = end;
+
+        Ok(Some(name))
+    }
+
+    fn include_directive_at(&self, position: usize) -> Option<&'static str> {
+        if !self.options.includes || !self.is_line_start(position) {
+            return None;
+        }
+
+        Self::INCLUDE_DIRECTIVES.iter()
+            .find(|directive| {
+                let end = position + directive.len();
+
+                self.data[position..].starts_with(*directive) && end < self.end && self.is_blank_at(end)
+            })
+            .copied()
+    }
+
+    fn is_line_start(&self, position: usize) -> bool {
+        self.data[..position].bytes().rev()
+            .take_while(|byte| *byte != b'\n' && *byte != b'\r')
+            .all(|byte| byte == b' ' || byte == b'\t')
+    }
+
+    fn lex_unset(&mut self) -> Option<String> {
+        if !self.options.unset_exports {
+            return None;
+        }
+
+        let start = self.exported_name_at(self.cursor)?;
+        let mut end = start;
+
+        while end < self.end && is_name_character(self.byte_at(end)) {
+            end += 1;
+        }
+
+        let name_end = match self.byte_at(start - 1) {
+            quote @ (b'"' | b'\'') if end < self.end && self.byte_at(end) == quote => end + 1,
+            b'"' | b'\'' => return None,
+            _ => end,
+        };
+
+        let cursor = self.cursor;
+        self.cursor = name_end;
+
+        if end == start || !self.is_rest_of_line_empty() {
+            self.cursor = cursor;
+            return None;
+        }
+
+        Some(self.data[start..end].to_string())
+    }
+
+    // Position of the variable name after an `export` keyword at the given position, which may be quoted.
+    fn exported_name_at(&self, position: usize) -> Option<usize> {
+        if !self.data[position..].starts_with("export") {
+            return None;
+        }
+
+        let mut name_position = position + "export".len();
+
+        while name_position < self.end && self.is_blank_at(name_position) {
+            name_position += 1;
+        }
+
+        if name_position == position + "export".len() || name_position == self.end {
+            return None;
+        }
+
+        match self.byte_at(name_position) {
+            b'"' | b'\'' => Some(name_position + 1),
+            byte if byte.is_ascii_alphabetic() => Some(name_position),
+            _ => None,
+        }
+    }
+
+    // Position of the comment after an `export` keyword at the given position, as in `export # FOO=bar`.
+    fn commented_export_at(&self, position: usize) -> Option<usize> {
+        if !self.data[position..].starts_with("export") {
+            return None;
+        }
+
+        let mut comment_position = position + "export".len();
+
+        while comment_position < self.end && self.is_blank_at(comment_position) {
+            comment_position += 1;
+        }
+
+        if comment_position == position + "export".len() || comment_position == self.end || self.byte_at(comment_position) != b'#' {
+            return None;
+        }
+
+        Some(comment_position)
+    }
+
+    fn lex_varname(&mut self) -> Result<String, Exception> {
+        let mut exported = false;
+        let mut quote = None;
+
+        if let Some(position) = self.exported_name_at(self.cursor) {
+            exported = true;
+
+            if position > self.cursor && matches!(self.byte_at(position - 1), b'"' | b'\'') {
+                quote = Some(self.byte_at(position - 1));
+            }
+
+            self.cursor = position;
+        }
+
+        if self.cursor == self.end || !self.byte_at(self.cursor).is_ascii_alphabetic() {
+            return Err(self.create_format_exception("format.invalid_name_character"));
+        }
+
+        let start = self.cursor;
+
+        while self.cursor < self.end && is_name_character(self.byte_at(self.cursor)) {
+            self.cursor += 1;
+        }
+
+        let name = self.data[start..self.cursor].to_string();
+        self.key_span = start..self.cursor;
+
+        if let Some(quote) = quote {
+            if self.cursor == self.end || self.byte_at(self.cursor) != quote {
+                return Err(self.create_format_exception("format.unterminated_name"));
+            }
+
+            self.cursor += 1;
+        }
+
+        if exported && self.is_rest_of_line_empty() {
+            return Err(self.create_format_exception_with("format.unset_without_value", vec![("key", name.clone())]).with_key(name));
+        }
+
+        if self.cursor == self.end || self.is_line_break_at(self.cursor) || self.byte_at(self.cursor) == b'#' {
+            return Err(self.create_format_exception("format.missing_equals"));
+        }
+
+        if self.is_blank_at(self.cursor) && self.options.whitespace_around_equals {
+            while self.cursor < self.end && self.is_blank_at(self.cursor) {
+                self.cursor += 1;
+            }
+        } else if self.is_blank_at(self.cursor) {
+            return Err(self.create_format_exception("format.whitespace_after_name"));
+        }
+
+        if self.cursor == self.end || self.is_line_break_at(self.cursor) || self.byte_at(self.cursor) == b'#' {
+            return Err(self.create_format_exception("format.missing_equals"));
+        }
+
+        if self.byte_at(self.cursor) != b'=' {
+            return Err(self.create_format_exception("format.missing_equals"));
+        }
+
+        self.cursor += 1;
+
+        Ok(name)
+    }
+
+    fn lex_value(&mut self) -> Result<String, Exception> {
+        if self.is_rest_of_line_empty() {
+            self.value_span = self.cursor..self.cursor;
+            self.skip_empty_lines();
+
+            return Ok("".to_string());
+        }
+
+        if self.is_blank_at(self.cursor) && self.options.whitespace_around_equals {
+            while self.is_blank_at(self.cursor) {
+                self.cursor += 1;
+            }
+        } else if self.is_blank_at(self.cursor) {
+            return Err(self.create_format_exception("format.whitespace_before_value"));
+        }
+
+        let start = self.cursor;
+
+        if let Some(value) = self.lex_heredoc()? {
+            self.value_span = start..self.cursor;
+            self.skip_empty_lines();
+
+            return Ok(value);
+        }
+
+        let mut value = "".to_string();
+
+        loop {
+            if self.options.ansi_c_quotes && self.data[self.cursor..self.end].starts_with("$'") {
+                let mut closing = self.cursor + 2;
+
+                loop {
+                    if closing >= self.end {
+                        self.cursor = self.end;
+
+                        return Err(self.create_format_exception("format.unterminated_value"));
+                    }
+
+                    match self.byte_at(closing) {
+                        b'\'' => break,
+                        b'\\' => closing += 2,
+                        _ => closing += 1,
+                    }
+                }
+
+                value.push_str(&self.resolve_dollars(&unescape_ansi_c(&self.data[self.cursor + 2..closing]), true));
+                self.cursor = closing + 1;
+            } else if self.byte_at(self.cursor) == b'\'' {
+                let closing = match self.data[self.cursor + 1..].find('\'') {
+                    Some(offset) => self.cursor + 1 + offset,
+                    None => {
+                        self.cursor = self.end;
+
+                        return Err(self.create_format_exception("format.unterminated_value"));
+                    },
+                };
+
+                value.push_str(&self.resolve_dollars(&self.data[self.cursor + 1..closing], true));
+                self.cursor = closing + 1;
+            } else if self.byte_at(self.cursor) == b'"' {
+                let mut closing = self.cursor + 1;
+
+                // Escape sequences are skipped as pairs, so an escaped backslash never escapes the quote after it.
+                loop {
+                    if closing >= self.end {
+                        self.cursor = self.end;
+
+                        return Err(self.create_format_exception("format.unterminated_value"));
+                    }
+
+                    match self.byte_at(closing) {
+                        b'"' => break,
+                        b'\\' => closing += 2,
+                        _ => closing += 1,
+                    }
+                }
+
+                let resolved_value = self.resolve_dollars(&self.data[self.cursor + 1..closing], false);
+                let resolved_value = unescape(&resolved_value, self.options.extended_escapes).map_err(|escape| {
+                    self.create_format_exception_with("format.invalid_escape", vec![("escape", escape)])
+                })?;
+
+                value.push_str(&resolved_value);
+                self.cursor = closing + 1;
+            } else {
+                let mut resolved_value = "".to_string();
+                let mut previous_byte = self.cursor.checked_sub(1).map_or(b'\n', |position| self.byte_at(position));
+
+                while self.cursor < self.end {
+                    let byte = self.byte_at(self.cursor);
+
+                    if byte == b'\n' || byte == b'\r' || byte == b'"' || byte == b'\'' || ((previous_byte == b' ' || previous_byte == b'\t') && byte == b'#') {
+                        break;
+                    }
+
+                    if byte == b'\\' && self.cursor + 1 < self.end && (self.byte_at(self.cursor + 1) == b'"' || self.byte_at(self.cursor + 1) == b'\'') {
+                        self.cursor += 1;
+                    }
+
+                    let character = match self.data[self.cursor..].chars().next() {
+                        Some(character) => character,
+                        None => break,
+                    };
+                    resolved_value.push(character);
+
+                    previous_byte = self.byte_at(self.cursor);
+                    self.cursor += character.len_utf8();
+                }
+
+                // Whitespace before a quoted part of the value is kept when spaces are allowed, as in `Hello "World"`.
+                let ends_value = self.cursor == self.end || self.is_line_break_at(self.cursor) || self.byte_at(self.cursor) == b'#';
+                let resolved_value = if self.options.unquoted_spaces && !ends_value { &resolved_value } else { resolved_value.trim_end() };
+                let resolved_value = self.resolve_dollars(resolved_value, false).replace("\\\\", "\\");
+
+                if !self.options.unquoted_spaces && (resolved_value.contains(' ') || resolved_value.contains('\t')) {
+                    return Err(self.create_format_exception("format.unquoted_spaces"));
+                }
+
+                value.push_str(&resolved_value);
+
+                if self.cursor < self.end && self.byte_at(self.cursor) == b'#' {
+                    break;
+                }
+            }
+
+            if self.cursor == self.end || self.is_line_break_at(self.cursor) {
+                break;
+            }
+        }
+
+        let mut end = self.cursor;
+
+        while end > start && self.is_blank_at(end - 1) {
+            end -= 1;
+        }
+
+        self.value_span = start..end;
+        self.skip_empty_lines();
+
+        Ok(value)
+    }
+
+    // Resolves the escaped dollar signs of a value segment. For interpolation, literal dollar signs are escaped as `$$`,
+    // and a single `$` only remains where it starts a `${VAR}` reference.
+    fn resolve_dollars(&self, segment: &str, single_quoted: bool) -> String {
+        if !self.options.literal_dollars || !segment.contains('$') {
+            return segment.to_string();
+        }
+
+        let literal_dollar = if self.options.interpolation { "$$" } else { "$" };
+        let mut resolved = String::with_capacity(segment.len());
+        let mut characters = segment.chars().peekable();
+
+        while let Some(character) = characters.next() {
+            match (character, characters.peek(), single_quoted) {
+                ('\\', Some('$'), false) => {
+                    characters.next();
+                    resolved.push_str(literal_dollar);
+                },
+                ('\\', Some('\\'), false) => {
+                    characters.next();
+                    resolved.push_str("\\\\");
+                },
+                ('$', Some('$'), false) if self.options.double_dollars => {
+                    characters.next();
+                    resolved.push_str(literal_dollar);
+                },
+                ('$', Some('{'), false) => resolved.push('$'),
+                ('$', ..) => resolved.push_str(literal_dollar),
+                _ => resolved.push(character),
+            }
+        }
+
+        resolved
+    }
+
+    fn lex_heredoc(&mut self) -> Result<Option<String>, Exception> {
+        if !self.options.heredocs || !self.data[self.cursor..].starts_with("<<") {
+            return Ok(None);
+        }
+
+        let start = self.cursor + 2;
+        let line_end = self.data[start..].find(Self::LINE_BREAKS).map_or(self.end, |offset| start + offset);
+        let delimiter = self.data[start..line_end].trim_end();
+
+        if delimiter.is_empty() || !delimiter.bytes().all(|byte| byte.is_ascii_alphanumeric() || byte == b'_') {
+            return Err(self.create_format_exception("format.invalid_heredoc_delimiter"));
+        }
+
+        let delimiter = delimiter.to_string();
+        let content_start = (line_end + 1).min(self.end);
+        let mut line_start = content_start;
+
+        while line_start < self.end {
+            let line_end = self.data[line_start..].find(Self::LINE_BREAKS).map_or(self.end, |offset| line_start + offset);
+
+            if self.data[line_start..line_end].trim_end() == delimiter {
+                let value = self.resolve_dollars(&self.data[content_start..line_start.saturating_sub(1).max(content_start)], false);
+                self.cursor = line_end;
+
+                return Ok(Some(value));
+            }
+
+            line_start = line_end + 1;
+        }
+
+        Err(self.create_format_exception_with("format.unterminated_heredoc", vec![("delimiter", delimiter)]))
+    }
+
+    fn is_rest_of_line_empty(&self) -> bool {
+        let mut position = self.cursor;
+
+        while position < self.end && self.is_blank_at(position) {
+            position += 1;
+        }
+
+        position == self.end || self.is_line_break_at(position) || self.byte_at(position) == b'#'
+    }
+
+    fn skip_empty_lines(&mut self) {
+        loop {
+            while self.cursor < self.end && self.byte_at(self.cursor).is_ascii_whitespace() {
+                self.cursor += 1;
+            }
+
+            if let Some(position) = self.commented_export_at(self.cursor) {
+                self.cursor = position;
+            }
+
+            if self.cursor == self.end || self.byte_at(self.cursor) != b'#' || self.include_directive_at(self.cursor).is_some() {
+                break;
+            }
+
+            let start = self.cursor;
+
+            while self.cursor < self.end && !self.is_line_break_at(self.cursor) {
+                self.cursor += 1;
+            }
+
+            if self.options.comments {
+                let comment = self.data[start + 1..self.cursor].trim().to_string();
+                self.comments.push(Statement::Comment(comment, self.line_number()));
+            }
+        }
+    }
+
+    fn entry_spans(&self) -> EntrySpans {
+        let line_start = self.data[..self.key_span.start].rfind(Self::LINE_BREAKS).map_or(0, |index| index + 1);
+        let line_end = self.data[self.value_span.end..].find(Self::LINE_BREAKS).map_or(self.end, |offset| self.value_span.end + offset);
+
+        EntrySpans::new(
+            self.input_position(self.key_span.start)..self.input_position(self.key_span.end),
+            self.input_position(self.value_span.start)..self.input_position(self.value_span.end),
+            self.input_position(line_start)..self.input_position(line_end),
+        )
+    }
+
+    fn input_position(&self, position: usize) -> usize {
+        position + self.carriage_returns.partition_point(|carriage_return| *carriage_return < position)
+    }
+
+    fn byte_at(&self, position: usize) -> u8 {
+        self.data.as_bytes()[position]
+    }
+
+    // Windows line endings are normalized beforehand, so a `\r` at this point is a classic Mac line ending. Quoted
+    // values do not use this check, and preserve a `\r` as is.
+    fn is_line_break_at(&self, position: usize) -> bool {
+        self.byte_at(position) == b'\n' || self.byte_at(position) == b'\r'
+    }
+
+    fn is_blank_at(&self, position: usize) -> bool {
+        self.byte_at(position) == b' ' || self.byte_at(position) == b'\t'
+    }
+
+    fn line_number(&self) -> usize {
+        self.line_breaks.partition_point(|line_break| *line_break < self.cursor) + 1
+    }
+
+    fn position(&self) -> (usize, usize) {
+        let line_number = self.line_number();
+        let line_start = match line_number {
+            1 => 0,
+            _ => self.line_breaks[line_number - 2] + 1,
+        };
+
+        (line_number, self.data[line_start..self.cursor].chars().count() + 1)
+    }
+
+    fn create_format_exception(&self, code: &'static str) -> Exception {
+        self.create_format_exception_with(code, Vec::new())
+    }
+
+    fn create_format_exception_with(&self, code: &'static str, params: Vec<(&'static str, String)>) -> Exception {
+        let (line_number, column) = self.position();
+        let snippet = self.data.split(Self::LINE_BREAKS).nth(line_number - 1).unwrap_or_default().to_string();
+
+        Exception::format(code, params, line_number, column, snippet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use crate::{parse, parse_borrowed, parse_bytes, tokenize, EntrySpans, ExceptionKind};
+    use crate::parser::{is_valid_name, parse_entries, parse_statements, sanitize_name, ParserOptions, Statement};
+
+    #[test]
+    fn parse_no_quotes() {
+        let values = parse("FOO=bar").unwrap();
+        assert_eq!(values.get("FOO").unwrap(), "bar");
+    }
+
+    #[test]
+    fn parse_no_quotes_equals_and_urls() {
+        let data = "TOKEN=dGVzdA==\nQUERY=a=1&b=2\nURL=postgres://user:p@ss@db:5432/app?ssl=true#main\nEMPTY_PARAM=a=";
+        let expected = [
+            ("TOKEN", "dGVzdA=="),
+            ("QUERY", "a=1&b=2"),
+            ("URL", "postgres://user:p@ss@db:5432/app?ssl=true#main"),
+            ("EMPTY_PARAM", "a="),
+        ];
+
+        let values = parse(data).unwrap();
+        assert_eq!(values.len(), expected.len());
+
+        for (name, value) in expected.iter() {
+            assert_eq!(values.get(*name).map(String::as_str), Some(*value));
+        }
+
+        let options = [
+            ParserOptions { whitespace_around_equals: true, ..ParserOptions::default() },
+            ParserOptions { literal_dollars: true, double_dollars: true, ..ParserOptions::default() },
+            ParserOptions { literal_dollars: true, ansi_c_quotes: true, ..ParserOptions::default() },
+            ParserOptions { unquoted_spaces: true, ..ParserOptions::default() },
+        ];
+
+        for options in options.iter() {
+            let entries = parse_entries(data, options).unwrap();
+            assert_eq!(entries.iter().map(|(name, value, _)| (name.as_str(), value.as_str())).collect::<Vec<_>>(), expected);
+        }
+
+        let options = ParserOptions { whitespace_around_equals: true, ..ParserOptions::default() };
+        assert_eq!(parse_entries("URL = https://example.com/?a=b", &options).unwrap()[0].1, "https://example.com/?a=b");
+    }
+
+    #[test]
+    fn parse_no_quotes_spaces() {
+        let options = ParserOptions { unquoted_spaces: true, ..ParserOptions::default() };
+        let entries = parse_entries("GREETING=Hello World  \nMESSAGE=Hello \"big\" World # comment\nPATH_LIST=a b#c", &options).unwrap();
+        assert_eq!(entries[0].1, "Hello World");
+        assert_eq!(entries[1].1, "Hello big World");
+        assert_eq!(entries[2].1, "a b#c");
+
+        let exception = parse("GREETING=Hello World").unwrap_err();
+        assert_eq!(exception.code(), "format.unquoted_spaces");
+    }
+
+    #[test]
+    fn parse_single_quotes() {
+        let values = parse("FOO='bar'").unwrap();
+        assert_eq!(values.get("FOO").unwrap(), "bar");
+    }
+
+    #[test]
+    fn parse_single_quotes_concatenation() {
+        let values = parse("FOO='bar'\\''baz'").unwrap();
+        assert_eq!(values.get("FOO").unwrap(), "bar'baz");
+    }
+
+    #[test]
+    fn parse_double_quotes() {
+        let values = parse("FOO=\"bar\"").unwrap();
+        assert_eq!(values.get("FOO").unwrap(), "bar");
+    }
+
+    #[test]
+    fn parse_double_quotes_escaped_quotes() {
+        let values = parse("FOO=\"bar\\\"baz\"").unwrap();
+        assert_eq!(values.get("FOO").unwrap(), "bar\"baz");
+    }
+
+    #[test]
+    fn parse_double_quotes_newlines() {
+        let values = parse("FOO=\"bar\\r\\nbaz\"").unwrap();
+        assert_eq!(values.get("FOO").unwrap(), "bar\r\nbaz");
+    }
+
+    #[test]
+    fn parse_double_quotes_slashes() {
+        let values = parse("FOO=\"bar\\\\baz\"").unwrap();
+        assert_eq!(values.get("FOO").unwrap(), "bar\\baz");
+    }
+
+    #[test]
+    fn parse_double_quotes_escaped_slash_before_n() {
+        let values = parse("FOO=\"a\\\\nb\"\nBAR=\"a\\\\\\nb\"\nBAZ=\"a\\\\\\\"b\"").unwrap();
+        assert_eq!(values.get("FOO").unwrap(), "a\\nb");
+        assert_eq!(values.get("BAR").unwrap(), "a\\\nb");
+        assert_eq!(values.get("BAZ").unwrap(), "a\\\"b");
+    }
+
+    #[test]
+    fn parse_double_quotes_extended_escapes() {
+        let options = ParserOptions { extended_escapes: true, ..ParserOptions::default() };
+        let entries = parse_entries("FOO=\"a\\tb\\fc\\0d\"\nBAR=\"\\u00e9\\u{1F600}\\q\"", &options).unwrap();
+        assert_eq!(entries[0].1, "a\tb\u{c}c\0d");
+        assert_eq!(entries[1].1, "\u{e9}\u{1F600}\\q");
+
+        assert_eq!(parse("FOO=\"a\\tb\"").unwrap().get("FOO").unwrap(), "a\\tb");
+
+        let exception = parse_entries("FOO=\"\\u{110000}\"", &options).unwrap_err();
+        assert_eq!(exception.code(), "format.invalid_escape");
+        assert_eq!(exception.param("escape"), Some("\\u{110000}"));
+        assert_eq!(parse_entries("FOO=\"\\u12\"", &options).unwrap_err().param("escape"), Some("\\u12"));
+    }
+
+    #[test]
+    fn parse_ansi_c_quotes() {
+        let options = ParserOptions { ansi_c_quotes: true, ..ParserOptions::default() };
+        let data = "FOO=$'line1\\nline2\\t\\x41\\101\\'\\\\'\nBAR=$'caf\\u00e9 \\303\\251 \\q \\x'\nBAZ=$'a'b\"c\"";
+        let entries = parse_entries(data, &options).unwrap();
+        assert_eq!(entries[0].1, "line1\nline2\tAA'\\");
+        assert_eq!(entries[1].1, "café é \\q \\x");
+        assert_eq!(entries[2].1, "abc");
+
+        let exception = parse_entries("FOO=$'a\\'", &options).unwrap_err();
+        assert_eq!(exception.code(), "format.unterminated_value");
+
+        assert_eq!(parse("FOO=$'a\\nb'").unwrap().get("FOO").unwrap(), "$a\\nb");
+    }
+
+    #[test]
+    fn parse_double_quotes_trailing_escaped_slash() {
+        let values = parse("FOO=\"bar\\\\\"").unwrap();
+        assert_eq!(values.get("FOO").unwrap(), "bar\\");
+    }
+
+    #[test]
+    fn parse_mixed_quotes_concatenation() {
+        let values = parse("FOO='bar'\"baz\"qux").unwrap();
+        assert_eq!(values.get("FOO").unwrap(), "barbazqux");
+    }
+
+    #[test]
+    fn parse_export() {
+        let values = parse("export FOO=bar").unwrap();
+        assert_eq!(values.get("FOO").unwrap(), "bar");
+
+        let values = parse("export \t BAR=baz\nexport # QUX=quux\nexport \"QUOTED\"=1\nexport 'SINGLE'=2").unwrap();
+        assert_eq!(values.get("BAR").unwrap(), "baz");
+        assert_eq!(values.get("QUX"), None);
+        assert_eq!(values.get("QUOTED").unwrap(), "1");
+        assert_eq!(values.get("SINGLE").unwrap(), "2");
+
+        assert_eq!(parse("export \"FOO'=bar").unwrap_err().kind(), ExceptionKind::Format);
+    }
+
+    #[test]
+    fn parse_export_without_value() {
+        let exception = parse("FOO=bar\nexport FOO # unset").unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Format);
+        assert_eq!(exception.key(), Some("FOO"));
+        assert!(exception.to_string().contains("export FOO"));
+
+        let options = ParserOptions { unset_exports: true, ..ParserOptions::default() };
+
+        assert_eq!(parse_statements("FOO=bar\nexport \"FOO\" # unset\nBAR=baz", &options).unwrap()[1], Statement::Unset("FOO".to_string(), 2));
+        assert_eq!(parse_entries("FOO=bar\nexport FOO\nBAR=baz", &options).unwrap(), vec![("BAR".to_string(), "baz".to_string(), 3)]);
+        assert_eq!(parse_entries("export FOO=bar\nexport \"FOO", &options).unwrap_err().kind(), ExceptionKind::Format);
+    }
+
+    #[test]
+    fn parse_empty_value_with_comment() {
+        let values = parse("FOO= # comment\nBAR=baz").unwrap();
+        assert_eq!(values.get("FOO").unwrap(), "");
+        assert_eq!(values.get("BAR").unwrap(), "baz");
+    }
+
+    #[test]
+    fn parse_multibyte_characters() {
+        let values = parse("FOO=café\nBAR='naïve'").unwrap();
+        assert_eq!(values.get("FOO").unwrap(), "café");
+        assert_eq!(values.get("BAR").unwrap(), "naïve");
+    }
+
+    #[test]
+    fn parse_double_quotes_inline_comment() {
+        let values = parse("FOO=\"multi word\" # comment\nBAR='single quoted' # comment").unwrap();
+        assert_eq!(values.get("FOO").unwrap(), "multi word");
+        assert_eq!(values.get("BAR").unwrap(), "single quoted");
+    }
+
+    #[test]
+    fn parse_line_endings() {
+        let values = parse("FOO=bar\rBAR=baz\r\nBAZ=qux\n# comment\rQUX=quux").unwrap();
+        assert_eq!(values.get("FOO").unwrap(), "bar");
+        assert_eq!(values.get("BAR").unwrap(), "baz");
+        assert_eq!(values.get("BAZ").unwrap(), "qux");
+        assert_eq!(values.get("QUX").unwrap(), "quux");
+    }
+
+    #[test]
+    fn parse_quoted_carriage_returns() {
+        let values = parse("FOO=\"bar\rbaz\"\rBAR='bar\rbaz'\rBAZ=\"bar\\rbaz\"").unwrap();
+        assert_eq!(values.get("FOO").unwrap(), "bar\rbaz");
+        assert_eq!(values.get("BAR").unwrap(), "bar\rbaz");
+        assert_eq!(values.get("BAZ").unwrap(), "bar\rbaz");
+    }
+
+    #[test]
+    fn parse_line_numbers_carriage_returns() {
+        let entries = parse_entries("FOO=bar\rBAR=baz", &ParserOptions::default()).unwrap();
+        assert_eq!(entries[1].2, 2);
+
+        let exception = parse("FOO=bar\rBAR baz").unwrap_err();
+        assert_eq!((exception.line(), exception.column(), exception.snippet()), (Some(2), Some(4), Some("BAR baz")));
+    }
+
+    #[test]
+    fn parse_heredocs() {
+        let options = ParserOptions { heredocs: true, ..ParserOptions::default() };
+        let entries = parse_entries("CERT=<<EOF\n-----BEGIN-----\n  'raw' \"data\" # kept\nEOF\nEMPTY=<<END\nEND\nFOO=bar", &options).unwrap();
+        assert_eq!(entries, vec![
+            ("CERT".to_string(), "-----BEGIN-----\n  'raw' \"data\" # kept".to_string(), 1),
+            ("EMPTY".to_string(), "".to_string(), 5),
+            ("FOO".to_string(), "bar".to_string(), 7),
+        ]);
+    }
+
+    #[test]
+    fn parse_heredocs_unterminated() {
+        let options = ParserOptions { heredocs: true, ..ParserOptions::default() };
+        let exception = parse_entries("FOO=<<EOF\nbar\n", &options).unwrap_err();
+        assert_eq!(exception.message(), "Missing EOF to end the heredoc");
+        assert_eq!(exception.line(), Some(1));
+        assert_eq!(exception.key(), Some("FOO"));
+    }
+
+    #[test]
+    fn parse_statements_includes() {
+        let options = ParserOptions { includes: true, ..ParserOptions::default() };
+        let statements = parse_statements("#!include base.env\nFOO=bar #!include ignored.env\nsource 'other.env'\n# comment", &options).unwrap();
+        assert_eq!(statements, vec![
+            Statement::Include("base.env".to_string(), 1),
+            Statement::Assignment("FOO".to_string(), "bar".to_string(), 2, EntrySpans::new(19..22, 23..26, 19..48)),
+            Statement::Include("other.env".to_string(), 3),
+        ]);
+    }
+
+    #[test]
+    fn parse_includes_disabled() {
+        let values = parse("#!include base.env\nFOO=bar").unwrap();
+        assert_eq!(values.len(), 1);
+    }
+
+    #[test]
+    fn parse_whitespace_around_equals() {
+        let options = ParserOptions { whitespace_around_equals: true, ..ParserOptions::default() };
+        let entries = parse_entries("FOO = bar\nBAR\t=\t'baz qux'\nBAZ = # comment", &options).unwrap();
+        assert_eq!(entries, vec![
+            ("FOO".to_string(), "bar".to_string(), 1),
+            ("BAR".to_string(), "baz qux".to_string(), 2),
+            ("BAZ".to_string(), "".to_string(), 3),
+        ]);
+
+        assert!(parse_entries("FOO bar", &options).is_err());
+        assert!(parse("FOO = bar").is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn parse_large_file() {
+        use std::time::{Duration, Instant};
+
+        let data: String = (0..50_000).map(|index| format!("# Variable {0}\nKEY_{0} = \"value {0}\"\n", index)).collect();
+        let options = ParserOptions { whitespace_around_equals: true, comments: true, ..ParserOptions::default() };
+
+        let start = Instant::now();
+        let statements = parse_statements(&data, &options).unwrap();
+
+        assert!(start.elapsed() < Duration::from_secs(10), "parsing 100k lines took {:?}", start.elapsed());
+        assert_eq!(statements.len(), 100_000);
+        assert!(matches!(&statements[99_999], Statement::Assignment(name, _, 100_000, _) if name == "KEY_49999"));
+    }
+
+    #[test]
+    fn parse_format_exception_position() {
+        let exception = parse("FOO=\"multi\nline\"\nBAR baz").unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Format);
+        assert_eq!((exception.line(), exception.column()), (Some(3), Some(4)));
+        assert_eq!(exception.to_string(), "Whitespace characters are not supported after the variable name at line 3, column 4\n\n    BAR baz\n       ^");
+    }
+
+    #[test]
+    fn parse_borrowed_format_exception() {
+        let exception = parse_borrowed("FOO=bar\nBAR baz").unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Format);
+        assert_eq!((exception.line(), exception.column()), (Some(2), Some(4)));
+    }
+
+    #[test]
+    fn parse_bytes_invalid_utf8() {
+        let exception = parse_bytes(b"FOO=bar\r\nBAR=b\xc3").unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::Format);
+        assert_eq!((exception.line(), exception.column(), exception.snippet()), (Some(2), Some(6), Some("BAR=b")));
+    }
+
+    #[test]
+    fn parse_never_panics() {
+        const FRAGMENTS: [&str; 14] = ["A", "=", " ", "\n", "\r", "\"", "'", "\\", "#", "<<", "[", "]", "export ", "é"];
+
+        let options = ParserOptions { includes: true, whitespace_around_equals: true, heredocs: true, sections: true, comments: true, unset_exports: true, literal_dollars: true, double_dollars: true, interpolation: true, extended_escapes: true, ansi_c_quotes: true, unquoted_spaces: true };
+
+        for length in 0..=4 {
+            for combination in 0..FRAGMENTS.len().pow(length) {
+                let data = (0..length)
+                    .map(|index| FRAGMENTS[combination / FRAGMENTS.len().pow(index) % FRAGMENTS.len()])
+                    .collect::<String>();
+
+                let _ = parse(&data);
+                let _ = parse_borrowed(&data);
+                let _ = parse_statements(&data, &options);
+                let _ = tokenize(&data);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_format_exception_key() {
+        let exception = parse("FOO=bar\nBAR=\"baz").unwrap_err();
+        assert_eq!(exception.key(), Some("BAR"));
+    }
+
+    #[test]
+    fn parse_entries_line_numbers() {
+        let entries = parse_entries("FOO='a\nb'\n\n# comment\nBAR=baz", &ParserOptions::default()).unwrap();
+        assert_eq!(entries[1], ("BAR".to_string(), "baz".to_string(), 5));
+    }
+
+    #[test]
+    fn parse_statements_spans() {
+        let data = "FOO=bar  # comment\r\nexport BAR='multi\r\nline'\r\n\r\nBAZ=\r\nQUX=\"a\"b";
+        let statements = parse_statements(data, &ParserOptions::default()).unwrap();
+        let spans = statements.iter()
+            .map(|statement| match statement {
+                Statement::Assignment(.., spans) => (&data[spans.key()], &data[spans.value()], &data[spans.line()]),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(spans, [
+            ("FOO", "bar", "FOO=bar  # comment"),
+            ("BAR", "'multi\r\nline'", "export BAR='multi\r\nline'"),
+            ("BAZ", "", "BAZ="),
+            ("QUX", "\"a\"b", "QUX=\"a\"b"),
+        ]);
+
+        let options = ParserOptions { comments: true, ..ParserOptions::default() };
+        let slow_statements = parse_statements(data, &options).unwrap().into_iter()
+            .filter(|statement| matches!(statement, Statement::Assignment(..)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(slow_statements, statements);
+        assert_eq!(fast_parser_statements(data), statements);
+    }
+
+    fn fast_parser_statements(data: &str) -> Vec<Statement> {
+        crate::fast_parser::parse(data).unwrap().into_iter()
+            .map(|entry| {
+                let spans = entry.spans(data);
+                Statement::Assignment(entry.name.to_string(), entry.value.into_owned(), entry.line_number, spans)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_statements_sections() {
+        let options = ParserOptions { sections: true, ..ParserOptions::default() };
+
+        assert_eq!(parse_statements("FOO=bar\n[ production ]\nFOO=baz", &options).unwrap(), vec![
+            Statement::Assignment("FOO".to_string(), "bar".to_string(), 1, EntrySpans::new(0..3, 4..7, 0..7)),
+            Statement::Section("production".to_string(), 2),
+            Statement::Assignment("FOO".to_string(), "baz".to_string(), 3, EntrySpans::new(23..26, 27..30, 23..30)),
+        ]);
+        assert_eq!(parse_statements("[production", &options).unwrap_err().kind(), ExceptionKind::Format);
+        assert_eq!(parse_statements("[production]", &ParserOptions::default()).unwrap_err().kind(), ExceptionKind::Format);
+    }
+
+    #[test]
+    fn parse_statements_comments() {
+        let options = ParserOptions { comments: true, ..ParserOptions::default() };
+
+        assert_eq!(parse_statements("# Database\n#   host\nDB_HOST=localhost # inline\n\n# end", &options).unwrap(), vec![
+            Statement::Comment("Database".to_string(), 1),
+            Statement::Comment("host".to_string(), 2),
+            Statement::Assignment("DB_HOST".to_string(), "localhost".to_string(), 3, EntrySpans::new(20..27, 28..37, 20..46)),
+            Statement::Comment("inline".to_string(), 3),
+            Statement::Comment("end".to_string(), 5),
+        ]);
+    }
+
+    #[test]
+    fn parse_entries_literal_dollars() {
+        let data = "A=\\$HOME$$\nB=\"${HOME} \\$\"\nC='${HOME}'";
+        let options = ParserOptions { literal_dollars: true, double_dollars: true, ..ParserOptions::default() };
+
+        let values: Vec<String> = parse_entries(data, &options).unwrap().into_iter().map(|(_, value, _)| value).collect();
+        assert_eq!(values, ["$HOME$", "${HOME} $", "${HOME}"]);
+
+        let options = ParserOptions { interpolation: true, ..options };
+
+        let values: Vec<String> = parse_entries(data, &options).unwrap().into_iter().map(|(_, value, _)| value).collect();
+        assert_eq!(values, ["$$HOME$$", "${HOME} $$", "$${HOME}"]);
+    }
+
+    #[test]
+    fn name_validation() {
+        assert!(is_valid_name("FOO_1"));
+        assert!(!is_valid_name("_FOO"));
+        assert!(!is_valid_name("1FOO"));
+        assert!(!is_valid_name("FOO-BAR"));
+        assert!(!is_valid_name(""));
+
+        assert_eq!(sanitize_name("1st-app.näme"), Some("st_app_n_me".to_string()));
+        assert_eq!(sanitize_name("FOO"), Some("FOO".to_string()));
+        assert_eq!(sanitize_name("-_1"), None);
+    }
+}
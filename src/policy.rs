@@ -0,0 +1,120 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+/// Handling of variables that are defined more than once in the same file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateKeyPolicy {
+    /// The last definition wins.
+    #[default]
+    LastWins,
+    /// The first definition wins.
+    FirstWins,
+    /// Loading fails with `ExceptionKind::DuplicateKey`.
+    Error,
+    /// The last definition wins, and the duplicate is reported in the load report.
+    Warn,
+}
+
+/// Handling of environment variables that are already defined when populating
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OverridePolicy {
+    /// Existing variables are never overwritten, as by `Dotenv::load()`.
+    Never,
+    /// Existing variables are always overwritten, as by `Dotenv::overload()`.
+    Always,
+    /// Existing variables are only overwritten if they are empty.
+    OnlyIfEmpty,
+    /// Only the listed existing variables are overwritten.
+    List(Vec<String>),
+}
+
+/// Interpolation of `${VAR}` references in values
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InterpolationMode {
+    /// References are loaded as is.
+    #[default]
+    Disabled,
+    /// References are resolved against the variables defined above, and the environment.
+    Sequential,
+    /// References are resolved against all variables, wherever they are defined, and the environment. Loading fails
+    /// with `ExceptionKind::CircularReference` if variables reference each other in a cycle.
+    Topological,
+}
+
+/// Escaping of literal dollar signs in values, which keeps them from being interpolated
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EscapeStyle {
+    /// Dollar signs are loaded as is, and references are interpolated whatever the quoting of the value.
+    #[default]
+    None,
+    /// `\$` and `$$` are literal dollar signs, and references in single-quoted values are not interpolated, as by
+    /// Symfony.
+    Symfony,
+    /// `\$` is a literal dollar sign, and references in single-quoted values are not interpolated, as by POSIX shells.
+    /// ANSI-C quoted values like `$'line1\nline2'` are unescaped and not interpolated, as by bash.
+    Shell,
+}
+
+/// Handling of variables that are defined in both maps when merging an `EnvMap`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// The value of the other map wins.
+    #[default]
+    Overwrite,
+    /// The value of the existing map wins.
+    KeepExisting,
+    /// Merging fails with `ExceptionKind::DuplicateKey` if the values differ.
+    Error,
+}
+
+/// Handling of attempts to modify protected variables, such as `PATH` and `LD_PRELOAD`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProtectedVariablePolicy {
+    /// Protected variables are modified like any other variable.
+    #[default]
+    Allow,
+    /// Protected variables are modified, and the modification is reported in the load report.
+    Warn,
+    /// Protected variables are not modified, and the attempt is reported in the load report.
+    Skip,
+    /// Loading fails with `ExceptionKind::ProtectedVariable`, before any variable is modified.
+    Error,
+}
+
+/// Handling of loaded files that contain secrets and are accessible by other users than their owner, checked on unix
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FilePermissionPolicy {
+    /// File permissions are not checked.
+    #[default]
+    Ignore,
+    /// Files are loaded, and those with permissions more permissive than `0600` are reported in the load report.
+    Warn,
+    /// Loading fails with `ExceptionKind::InsecurePermissions` for files with permissions more permissive than `0600`.
+    Error,
+}
+
+/// Handling of variables with an empty value, as in `FOO=`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmptyValuePolicy {
+    /// The variable is set to an empty string.
+    #[default]
+    Allow,
+    /// The variable is not set.
+    Skip,
+    /// Loading fails with `ExceptionKind::EmptyValue`.
+    Error,
+}
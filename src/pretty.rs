@@ -0,0 +1,233 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::env;
+use std::io::{self, IsTerminal};
+
+use crate::{Exception, LoadReport};
+
+/// Whether `render_pretty()` colors its output with ANSI escape codes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Colors if stderr is a terminal and the `NO_COLOR` environment variable is not set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()) && io::stderr().is_terminal(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Style {
+    Error,
+    Warning,
+    Note,
+    Gutter,
+    Bold,
+}
+
+struct Painter {
+    enabled: bool,
+}
+
+impl Painter {
+    fn paint(&self, style: Style, text: &str) -> String {
+        if !self.enabled {
+            return text.to_string();
+        }
+
+        let code = match style {
+            Style::Error => "1;31",
+            Style::Warning => "1;33",
+            Style::Note => "1;36",
+            Style::Gutter => "1;34",
+            Style::Bold => "1",
+        };
+
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    }
+
+    fn location(&self, output: &mut String, path: Option<&str>, line: Option<usize>, column: Option<usize>) {
+        let location = match (path, line, column) {
+            (Some(path), Some(line), Some(column)) => format!("{}:{}:{}", path, line, column),
+            (Some(path), Some(line), None) => format!("{}:{}", path, line),
+            (Some(path), None, _) => path.to_string(),
+            (None, Some(line), Some(column)) => format!("line {}, column {}", line, column),
+            (None, Some(line), None) => format!("line {}", line),
+            (None, None, _) => return,
+        };
+
+        output.push_str(&format!("  {} {}\n", self.paint(Style::Gutter, "-->"), location));
+    }
+}
+
+impl Exception {
+    ///
+    /// Renders the exception for showing in a terminal, with its code, location and the offending line annotated with
+    /// a caret, optionally colored.
+    ///
+    /// Requires the `color` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::{parse, ColorChoice};
+    ///
+    /// let exception = parse("APP_NAME=example\nAPP_PORT").unwrap_err();
+    ///
+    /// assert_eq!(exception.render_pretty(ColorChoice::Never), "\
+    /// error[format.missing_equals]: Missing = in the environment variable declaration
+    ///   --> line 2, column 9
+    ///    |
+    ///  2 | APP_PORT
+    ///    |         ^
+    /// ");
+    /// ```
+    ///
+    pub fn render_pretty(&self, color: ColorChoice) -> String {
+        let painter = Painter { enabled: color.enabled() };
+        let mut output = String::new();
+
+        output.push_str(&painter.paint(Style::Error, &format!("error[{}]", self.code())));
+        output.push_str(&painter.paint(Style::Bold, &format!(": {}", self.message())));
+        output.push('\n');
+
+        painter.location(&mut output, self.path(), self.line(), self.column());
+
+        if let (Some(line), Some(column), Some(snippet)) = (self.line(), self.column(), self.snippet()) {
+            let line_number = line.to_string();
+            let gutter = " ".repeat(line_number.len() + 2);
+            let caret_indent: String = snippet.chars()
+                .take(column - 1)
+                .map(|character| if character == '\t' { '\t' } else { ' ' })
+                .collect();
+
+            output.push_str(&format!("{}{}\n", gutter, painter.paint(Style::Gutter, "|")));
+            output.push_str(&format!("{} {} {}\n", painter.paint(Style::Gutter, &format!(" {}", line_number)), painter.paint(Style::Gutter, "|"), snippet));
+            output.push_str(&format!("{}{} {}{}\n", gutter, painter.paint(Style::Gutter, "|"), caret_indent, painter.paint(Style::Error, "^")));
+        } else if let Some(key) = self.key().filter(|_| self.line().is_none()) {
+            output.push_str(&format!("   {} variable: {}\n", painter.paint(Style::Gutter, "="), key));
+        }
+
+        output
+    }
+}
+
+impl LoadReport {
+    ///
+    /// Renders the report for showing in a terminal, with a warning for each duplicate variable, protected variable,
+    /// insecure file and deprecated variable, and a note for each variable falling back to its default, optionally
+    /// colored. Returns an empty string if nothing was reported.
+    ///
+    /// Requires the `color` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::{ColorChoice, Dotenv};
+    ///
+    /// let mut dotenv = Dotenv::new();
+    /// dotenv.set_default("DOTENV_DOC_PRETTY_PORT", "8080");
+    ///
+    /// assert_eq!(dotenv.report().render_pretty(ColorChoice::Never), "note: \"DOTENV_DOC_PRETTY_PORT\" falls back to its default value\n");
+    /// ```
+    ///
+    pub fn render_pretty(&self, color: ColorChoice) -> String {
+        let painter = Painter { enabled: color.enabled() };
+        let mut output = String::new();
+
+        let warning = |output: &mut String, message: String| {
+            output.push_str(&painter.paint(Style::Warning, "warning"));
+            output.push_str(&painter.paint(Style::Bold, &format!(": {}", message)));
+            output.push('\n');
+        };
+
+        for duplicate in self.duplicates() {
+            warning(&mut output, format!("\"{}\" is already defined at line {}", duplicate.name(), duplicate.first_line_number()));
+            painter.location(&mut output, duplicate.path(), Some(duplicate.line_number()), None);
+        }
+
+        for protected_variable in self.protected_variables() {
+            match protected_variable.skipped() {
+                true => warning(&mut output, format!("refused to modify the protected variable \"{}\"", protected_variable.name())),
+                false => warning(&mut output, format!("modified the protected variable \"{}\"", protected_variable.name())),
+            }
+
+            painter.location(&mut output, protected_variable.path(), None, None);
+        }
+
+        for insecure_file in self.insecure_files() {
+            warning(&mut output, format!("permissions {:04o} are too open for a file containing secrets ({})", insecure_file.mode(), insecure_file.secret_keys().join(", ")));
+            painter.location(&mut output, Some(insecure_file.path()), None, None);
+        }
+
+        for deprecated_key in self.deprecated_keys() {
+            warning(&mut output, format!("\"{}\" is deprecated, use \"{}\" instead", deprecated_key.name(), deprecated_key.replacement()));
+            painter.location(&mut output, deprecated_key.path(), Some(deprecated_key.line_number()), None);
+        }
+
+        for name in self.defaults() {
+            output.push_str(&painter.paint(Style::Note, "note"));
+            output.push_str(&format!(": \"{}\" falls back to its default value\n", name));
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, ColorChoice, Dotenv, DuplicateKeyPolicy};
+
+    #[test]
+    fn render_exception() {
+        let exception = parse("\tAPP NAME=example").unwrap_err();
+
+        assert_eq!(exception.render_pretty(ColorChoice::Always), "\x1b[1;31merror[format.whitespace_after_name]\x1b[0m\x1b[1m: Whitespace characters are not supported after the variable name\x1b[0m\n  \
+            \x1b[1;34m-->\x1b[0m line 1, column 5\n   \x1b[1;34m|\x1b[0m\n\x1b[1;34m 1\x1b[0m \x1b[1;34m|\x1b[0m \tAPP NAME=example\n   \x1b[1;34m|\x1b[0m \t   \x1b[1;31m^\x1b[0m\n");
+    }
+
+    #[test]
+    fn render_exception_without_location() {
+        let mut dotenv = Dotenv::new();
+        let exception = dotenv.get_processed("int:DOTENV_TEST_PRETTY_UNDEFINED").unwrap_err();
+
+        assert_eq!(exception.render_pretty(ColorChoice::Never), "error[undefined_variable]: Environment variable \"DOTENV_TEST_PRETTY_UNDEFINED\" is not defined\n   \
+            = variable: DOTENV_TEST_PRETTY_UNDEFINED\n");
+
+        let exception = dotenv.load("/nonexistent/.env").unwrap_err();
+        assert_eq!(exception.render_pretty(ColorChoice::Never), "error[path.unreadable]: Unable to read the \"/nonexistent/.env\" environment file.\n  --> /nonexistent/.env\n");
+    }
+
+    #[test]
+    fn render_report() {
+        let mut dotenv = Dotenv::new().duplicate_key_policy(DuplicateKeyPolicy::Warn);
+        assert_eq!(dotenv.report().render_pretty(ColorChoice::Always), "");
+
+        dotenv.load_from_str("DOTENV_TEST_PRETTY_DUPLICATE=a\nDOTENV_TEST_PRETTY_DUPLICATE=b").unwrap();
+        assert_eq!(dotenv.report().render_pretty(ColorChoice::Never), "warning: \"DOTENV_TEST_PRETTY_DUPLICATE\" is already defined at line 1\n  --> line 2\n");
+    }
+}
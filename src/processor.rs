@@ -0,0 +1,280 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::{base64, json, Exception, ExceptionKind};
+
+const PROCESSORS: [&str; 10] = ["base64", "bool", "csv", "default", "float", "int", "json", "not", "string", "trim"];
+
+/// Value of an environment variable after processing, as returned by `Dotenv::get_processed()`
+#[derive(Clone, Debug, PartialEq)]
+pub enum EnvValue {
+    /// JSON `null`.
+    Null,
+    /// Result of `bool:` and `not:`, or a JSON boolean.
+    Bool(bool),
+    /// Result of `int:`, or a JSON integer.
+    Int(i64),
+    /// Result of `float:`, or a JSON number with a fraction or exponent.
+    Float(f64),
+    /// Unprocessed value, or the result of a string processor.
+    String(String),
+    /// Result of `csv:`, or a JSON array.
+    Array(Vec<EnvValue>),
+    /// JSON object.
+    Object(BTreeMap<String, EnvValue>),
+}
+
+impl EnvValue {
+    /// The string, if the value is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// The boolean, if the value is a boolean.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(boolean) => Some(*boolean),
+            _ => None,
+        }
+    }
+
+    /// The integer, if the value is an integer.
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Self::Int(integer) => Some(*integer),
+            _ => None,
+        }
+    }
+
+    /// The number, if the value is a float or an integer.
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Self::Float(float) => Some(*float),
+            Self::Int(integer) => Some(*integer as f64),
+            _ => None,
+        }
+    }
+
+    /// The elements, if the value is an array.
+    pub fn as_array(&self) -> Option<&[EnvValue]> {
+        match self {
+            Self::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// The members, if the value is an object.
+    pub fn as_object(&self) -> Option<&BTreeMap<String, EnvValue>> {
+        match self {
+            Self::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    fn to_json(&self) -> String {
+        match self {
+            Self::Null => "null".to_string(),
+            Self::String(string) => json::string(string),
+            Self::Array(array) => format!("[{}]", array.iter().map(Self::to_json).collect::<Vec<_>>().join(",")),
+            Self::Object(object) => format!("{{{}}}", object.iter()
+                .map(|(name, value)| format!("{}:{}", json::string(name), value.to_json()))
+                .collect::<Vec<_>>()
+                .join(",")),
+            scalar => scalar.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for EnvValue {
+    ///
+    /// Formats scalars as plain text, and arrays and objects as JSON.
+    ///
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Null => Ok(()),
+            Self::Bool(boolean) => write!(formatter, "{}", boolean),
+            Self::Int(integer) => write!(formatter, "{}", integer),
+            Self::Float(float) => write!(formatter, "{}", float),
+            Self::String(string) => formatter.write_str(string),
+            Self::Array(_) | Self::Object(_) => formatter.write_str(&self.to_json()),
+        }
+    }
+}
+
+///
+/// Processes an expression such as `int:default:8080:PORT`, where the last segment is the name of the variable and the
+/// preceding segments are processors, applied from right to left.
+///
+pub(crate) fn process<Lookup>(expression: &str, lookup: &Lookup) -> Result<EnvValue, Exception>
+    where
+        Lookup: Fn(&str) -> Option<String> {
+
+    let (processor, rest) = match expression.split_once(':') {
+        Some((processor, rest)) if PROCESSORS.contains(&processor) => (processor, rest),
+        _ => {
+            return lookup(expression)
+                .map(EnvValue::String)
+                .ok_or_else(|| Exception::new(ExceptionKind::UndefinedVariable, "undefined_variable", vec![("key", expression.to_string())])
+                    .with_key(expression.to_string()));
+        },
+    };
+
+    if processor == "default" {
+        let (fallback, rest) = rest.split_once(':')
+            .ok_or_else(|| Exception::processing(expression.to_string(), "the default processor requires a fallback and a variable, as in default:FALLBACK:NAME".to_string()))?;
+
+        return match process(rest, lookup) {
+            Ok(EnvValue::String(value)) if value.is_empty() => Ok(EnvValue::String(fallback.to_string())),
+            Err(exception) if exception.kind() == ExceptionKind::UndefinedVariable => Ok(EnvValue::String(fallback.to_string())),
+            result => result,
+        };
+    }
+
+    let value = process(rest, lookup)?;
+
+    apply(processor, value).map_err(|reason| Exception::processing(expression.to_string(), reason))
+}
+
+fn apply(processor: &str, value: EnvValue) -> Result<EnvValue, String> {
+    match processor {
+        "string" => Ok(EnvValue::String(value.to_string())),
+        "bool" => Ok(EnvValue::Bool(to_bool(&value))),
+        "not" => Ok(EnvValue::Bool(!to_bool(&value))),
+        "int" => match value {
+            EnvValue::Int(integer) => Ok(EnvValue::Int(integer)),
+            EnvValue::Bool(boolean) => Ok(EnvValue::Int(boolean as i64)),
+            EnvValue::Float(float) => Ok(EnvValue::Int(float as i64)),
+            value => to_number(&value).map(|number| EnvValue::Int(number as i64)),
+        },
+        "float" => match value {
+            EnvValue::Float(float) => Ok(EnvValue::Float(float)),
+            EnvValue::Int(integer) => Ok(EnvValue::Float(integer as f64)),
+            value => to_number(&value).map(EnvValue::Float),
+        },
+        "json" => json::parse(&value.to_string()).map_err(|reason| format!("invalid JSON: {}", reason)),
+        "csv" => Ok(EnvValue::Array(csv(&value.to_string()).into_iter().map(EnvValue::String).collect())),
+        "trim" => Ok(EnvValue::String(value.to_string().trim().to_string())),
+        "base64" => base64::decode(value.to_string().trim())
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .map(EnvValue::String)
+            .ok_or_else(|| "invalid base64".to_string()),
+        _ => unreachable!(),
+    }
+}
+
+fn to_bool(value: &EnvValue) -> bool {
+    match value {
+        EnvValue::Null => false,
+        EnvValue::Bool(boolean) => *boolean,
+        EnvValue::Int(integer) => *integer != 0,
+        EnvValue::Float(float) => *float != 0.0,
+        EnvValue::String(string) => {
+            let string = string.trim().to_ascii_lowercase();
+
+            match string.parse::<f64>() {
+                Ok(number) => number != 0.0,
+                Err(_) => ["true", "on", "yes", "y"].contains(&string.as_str()),
+            }
+        },
+        EnvValue::Array(array) => !array.is_empty(),
+        EnvValue::Object(object) => !object.is_empty(),
+    }
+}
+
+fn to_number(value: &EnvValue) -> Result<f64, String> {
+    let string = value.to_string();
+
+    string.trim().parse::<f64>().map_err(|_| format!("\"{}\" is not numeric", string))
+}
+
+fn csv(data: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut characters = data.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '"' if quoted && characters.peek() == Some(&'"') => {
+                field.push('"');
+                characters.next();
+            },
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            character => field.push(character),
+        }
+    }
+
+    fields.push(field);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{EnvValue, ExceptionKind};
+    use crate::processor;
+
+    fn process(expression: &str) -> Result<EnvValue, crate::Exception> {
+        let values: HashMap<&str, &str> = [
+            ("DEBUG", "on"),
+            ("EMPTY", ""),
+            ("HOSTS", "a,\"b,c\",\"d\"\"e\""),
+            ("JSON", "{\"port\": 80}"),
+            ("NAME", "  app  "),
+            ("PORT", "8081"),
+        ].iter().copied().collect();
+
+        processor::process(expression, &|name: &str| values.get(name).map(|value| value.to_string()))
+    }
+
+    #[test]
+    fn process_scalars() {
+        assert_eq!(process("PORT").unwrap(), EnvValue::String("8081".to_string()));
+        assert_eq!(process("int:PORT").unwrap(), EnvValue::Int(8081));
+        assert_eq!(process("float:PORT").unwrap(), EnvValue::Float(8081.0));
+        assert_eq!(process("bool:DEBUG").unwrap(), EnvValue::Bool(true));
+        assert_eq!(process("not:DEBUG").unwrap(), EnvValue::Bool(false));
+        assert_eq!(process("trim:NAME").unwrap(), EnvValue::String("app".to_string()));
+        assert_eq!(process("int:NAME").unwrap_err().kind(), ExceptionKind::Processing);
+    }
+
+    #[test]
+    fn process_structures() {
+        assert_eq!(process("csv:HOSTS").unwrap().as_array().unwrap(), &[
+            EnvValue::String("a".to_string()),
+            EnvValue::String("b,c".to_string()),
+            EnvValue::String("d\"e".to_string()),
+        ]);
+        assert_eq!(process("json:JSON").unwrap().as_object().unwrap()["port"], EnvValue::Int(80));
+    }
+
+    #[test]
+    fn process_default() {
+        assert_eq!(process("int:default:8080:PORT").unwrap(), EnvValue::Int(8081));
+        assert_eq!(process("int:default:8080:MISSING").unwrap(), EnvValue::Int(8080));
+        assert_eq!(process("default:fallback:EMPTY").unwrap(), EnvValue::String("fallback".to_string()));
+        assert_eq!(process("int:MISSING").unwrap_err().kind(), ExceptionKind::UndefinedVariable);
+    }
+}
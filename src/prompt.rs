@@ -0,0 +1,155 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+use crate::Exception;
+
+/// Variable to prompt for
+pub(crate) struct Question {
+    pub(crate) key: String,
+    pub(crate) comment: Option<String>,
+    pub(crate) secret: bool,
+}
+
+///
+/// Prompts for the values of variables, one line each, asking again while the answer is empty. The comment of a
+/// variable is shown above its prompt. If `hide_secrets` is set, the terminal does not echo the answers for secrets.
+///
+pub(crate) fn ask<Input, Output>(questions: &[Question], input: &mut Input, output: &mut Output, hide_secrets: bool) -> Result<HashMap<String, String>, Exception>
+    where
+        Input: BufRead,
+        Output: Write {
+
+    let mut answers = HashMap::new();
+
+    for question in questions {
+        if let Some(comment) = question.comment.as_deref() {
+            for line in comment.lines() {
+                write_prompt(output, &format!("# {}\n", line))?;
+            }
+        }
+
+        let hidden = hide_secrets && question.secret;
+
+        loop {
+            write_prompt(output, &format!("{}{}: ", question.key, if question.secret { " (secret)" } else { "" }))?;
+
+            let mut answer = String::new();
+
+            let read = if hidden {
+                let _echo = EchoGuard::disable();
+                input.read_line(&mut answer)
+            } else {
+                input.read_line(&mut answer)
+            };
+
+            if hidden {
+                write_prompt(output, "\n")?;
+            }
+
+            match read {
+                Ok(0) => {
+                    return Err(Exception::required_variable(question.key.clone()));
+                },
+                Ok(_) => {},
+                Err(error) => return Err(Exception::unavailable_source("stdin".to_string(), error.to_string())),
+            }
+
+            let answer = answer.trim_end_matches(['\n', '\r']);
+
+            if !answer.is_empty() {
+                answers.insert(question.key.clone(), answer.to_string());
+                break;
+            }
+        }
+    }
+
+    Ok(answers)
+}
+
+fn write_prompt<Output>(output: &mut Output, text: &str) -> Result<(), Exception>
+    where
+        Output: Write {
+
+    output.write_all(text.as_bytes())
+        .and_then(|_| output.flush())
+        .map_err(|error| Exception::unwritable_source("stderr".to_string(), error.to_string()))
+}
+
+// Disables the echo of the terminal until dropped, so it is restored even if reading panics.
+struct EchoGuard;
+
+impl EchoGuard {
+    fn disable() -> Self {
+        set_echo(false);
+        Self
+    }
+}
+
+impl Drop for EchoGuard {
+    fn drop(&mut self) {
+        set_echo(true);
+    }
+}
+
+// Toggles the echo of the terminal on stdin with `stty`, which is left as is where it is not available.
+fn set_echo(echo: bool) {
+    #[cfg(unix)]
+    {
+        let _ = std::process::Command::new("stty")
+            .arg(if echo { "echo" } else { "-echo" })
+            .stdin(std::process::Stdio::inherit())
+            .status();
+    }
+
+    #[cfg(not(unix))]
+    let _ = echo;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::ExceptionKind;
+    use super::{ask, Question};
+
+    #[test]
+    fn ask_questions() {
+        let questions = [
+            Question { key: "DB_HOST".to_string(), comment: Some("The database host".to_string()), secret: false },
+            Question { key: "DB_PASS".to_string(), comment: None, secret: true },
+        ];
+
+        let mut output = Vec::new();
+        let answers = ask(&questions, &mut Cursor::new("localhost\n\nhunter2\r\n"), &mut output, false).unwrap();
+
+        assert_eq!(answers["DB_HOST"], "localhost");
+        assert_eq!(answers["DB_PASS"], "hunter2");
+        assert_eq!(String::from_utf8(output).unwrap(), "# The database host\nDB_HOST: DB_PASS (secret): DB_PASS (secret): ");
+    }
+
+    #[test]
+    fn ask_end_of_input() {
+        let questions = [Question { key: "DB_HOST".to_string(), comment: None, secret: false }];
+
+        let exception = ask(&questions, &mut Cursor::new("\n"), &mut Vec::new(), false).unwrap_err();
+        assert_eq!(exception.kind(), ExceptionKind::UndefinedVariable);
+        assert_eq!(exception.key(), Some("DB_HOST"));
+    }
+}
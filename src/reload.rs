@@ -0,0 +1,184 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use arc_swap::ArcSwap;
+use signal_hook::consts::SIGHUP;
+use signal_hook::iterator::{Handle, Signals};
+
+use crate::{EnvMap, Exception, SharedEnv};
+
+///
+/// Reload of the environment variables whenever the process receives `SIGHUP`, as installed by `HupReload::install()`
+///
+/// The variables are kept in an `ArcSwap<EnvMap>`, so the application reads them without locking and always sees a
+/// complete set. A failed reload keeps the previous variables. Dropping the handle stops reloading.
+///
+pub struct HupReload {
+    values: SharedEnv,
+    state: Arc<State>,
+    handle: Handle,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[derive(Default)]
+struct State {
+    reloads: AtomicUsize,
+    last_error: Mutex<Option<Exception>>,
+}
+
+impl HupReload {
+    ///
+    /// Runs the load pipeline, and installs a `SIGHUP` handler that runs it again on a background thread.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::{Dotenv, HupReload};
+    ///
+    /// let reload = HupReload::install(|| Dotenv::new().layered_env_map(".env", "APP_ENV", "dev")).unwrap();
+    /// let values = reload.values();
+    ///
+    /// // After `kill -HUP <pid>`, the variables are read from the reloaded files.
+    /// let port = values.load().get("PORT").map(str::to_string);
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * The exceptions of the initial run of the load pipeline
+    /// * `ExceptionKind::Source` if the signal handler cannot be installed
+    ///
+    pub fn install<Load>(mut load: Load) -> Result<Self, Exception>
+        where
+            Load: FnMut() -> Result<EnvMap, Exception> + Send + 'static {
+
+        let values = SharedEnv::new(load()?);
+        let state = Arc::new(State::default());
+
+        let mut signals = Signals::new([SIGHUP])
+            .map_err(|error| Exception::unavailable_source("SIGHUP".to_string(), error.to_string()))?;
+        let handle = signals.handle();
+
+        let thread = {
+            let values = values.clone();
+            let state = Arc::clone(&state);
+
+            thread::spawn(move || {
+                for _ in signals.forever() {
+                    let result = values.reload(&mut load);
+
+                    #[cfg(feature = "tracing")]
+                    match &result {
+                        Ok(diff) => tracing::info!(added = diff.added().len(), changed = diff.changed().len(), removed = diff.removed().len(), "reloaded the environment variables on SIGHUP"),
+                        Err(exception) => tracing::warn!(%exception, "failed to reload the environment variables on SIGHUP"),
+                    }
+
+                    *state.last_error.lock().unwrap_or_else(|error| error.into_inner()) = result.err();
+                    state.reloads.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+        };
+
+        Ok(Self { values, state, handle, thread: Some(thread) })
+    }
+
+    /// Shared variables, which are swapped on every successful reload.
+    pub fn values(&self) -> Arc<ArcSwap<EnvMap>> {
+        Arc::clone(self.values.as_arc_swap())
+    }
+
+    /// Handle over the shared variables, which sees every successful reload.
+    pub fn shared_env(&self) -> SharedEnv {
+        self.values.clone()
+    }
+
+    /// Snapshot of the current variables.
+    pub fn current(&self) -> Arc<EnvMap> {
+        self.values.load()
+    }
+
+    /// Number of reloads attempted since the handler was installed, whether they succeeded or not.
+    pub fn reloads(&self) -> usize {
+        self.state.reloads.load(Ordering::SeqCst)
+    }
+
+    /// Exception of the last reload, if it failed and the previous variables were kept.
+    pub fn last_error(&self) -> Option<Exception> {
+        self.state.last_error.lock().unwrap_or_else(|error| error.into_inner()).clone()
+    }
+}
+
+impl Drop for HupReload {
+    fn drop(&mut self) {
+        self.handle.close();
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use signal_hook::consts::SIGHUP;
+    use signal_hook::low_level;
+
+    use crate::{EnvMap, Exception, HupReload};
+
+    #[test]
+    fn reload_on_hup() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let load = {
+            let runs = Arc::clone(&runs);
+
+            move || match runs.fetch_add(1, Ordering::SeqCst) {
+                1 => Err(Exception::unavailable_source("test".to_string(), "failed".to_string())),
+                run => EnvMap::parse(format!("DOTENV_TEST_RELOAD_RUN={}", run)),
+            }
+        };
+
+        let reload = HupReload::install(load).unwrap();
+        let values = reload.values();
+        assert_eq!(values.load().get("DOTENV_TEST_RELOAD_RUN"), Some("0"));
+
+        for (reloads, expected) in [(1, "0"), (2, "2")].iter() {
+            low_level::raise(SIGHUP).unwrap();
+
+            let deadline = Instant::now() + Duration::from_secs(5);
+
+            while reload.reloads() < *reloads && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(10));
+            }
+
+            assert_eq!(reload.current().get("DOTENV_TEST_RELOAD_RUN"), Some(*expected));
+            assert_eq!(reload.last_error().is_some(), *reloads == 1);
+        }
+
+        let shared_env = reload.shared_env();
+        drop(reload);
+        assert_eq!(values.load().get("DOTENV_TEST_RELOAD_RUN"), Some("2"));
+        assert_eq!(shared_env.get("DOTENV_TEST_RELOAD_RUN").as_deref(), Some("2"));
+    }
+}
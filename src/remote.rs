@@ -0,0 +1,100 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::time::Duration;
+
+use crate::Exception;
+
+pub(crate) fn fetch(url: &str, timeout: Duration, bearer_token: Option<&str>) -> Result<String, Exception> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .build()
+        .into();
+
+    let mut request = agent.get(url);
+
+    if let Some(token) = bearer_token {
+        request = request.header("Authorization", &format!("Bearer {}", token));
+    }
+
+    let mut response = request.call()
+        .map_err(|error| Exception::unavailable_source(url.to_string(), error.to_string()))?;
+
+    response.body_mut().read_to_string()
+        .map_err(|error| Exception::unavailable_source(url.to_string(), error.to_string()))
+}
+
+pub(crate) fn put(url: &str, timeout: Duration, bearer_token: Option<&str>, body: &str) -> Result<(), Exception> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .build()
+        .into();
+
+    let mut request = agent.put(url).header("Content-Type", "application/json");
+
+    if let Some(token) = bearer_token {
+        request = request.header("Authorization", &format!("Bearer {}", token));
+    }
+
+    request.send(body)
+        .map(|_| ())
+        .map_err(|error| Exception::unwritable_source(url.to_string(), error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::ExceptionKind;
+    use crate::remote;
+
+    fn serve(status: &'static str, body: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/app.env", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 4096];
+            let length = stream.read(&mut request).unwrap();
+
+            write!(stream, "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, body.len(), body).unwrap();
+
+            String::from_utf8_lossy(&request[..length]).to_string()
+        });
+
+        (url, handle)
+    }
+
+    #[test]
+    fn fetch() {
+        let (url, handle) = serve("200 OK", "FOO=bar\n");
+
+        assert_eq!(remote::fetch(&url, Duration::from_secs(5), Some("secret")).unwrap(), "FOO=bar\n");
+        assert!(handle.join().unwrap().to_lowercase().contains("authorization: bearer secret"));
+    }
+
+    #[test]
+    fn fetch_error_status() {
+        let (url, handle) = serve("404 Not Found", "");
+
+        assert_eq!(remote::fetch(&url, Duration::from_secs(5), None).unwrap_err().kind(), ExceptionKind::Source);
+        handle.join().unwrap();
+    }
+}
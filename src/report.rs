@@ -0,0 +1,254 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::fmt;
+
+/// Report of the notable things encountered while loading environment files
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoadReport {
+    duplicates: Vec<DuplicateKey>,
+    protected_variables: Vec<ProtectedVariable>,
+    defaults: Vec<String>,
+    insecure_files: Vec<InsecureFile>,
+    deprecated_keys: Vec<DeprecatedKey>,
+}
+
+/// Attempt to modify a protected variable
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtectedVariable {
+    name: String,
+    path: Option<String>,
+    skipped: bool,
+}
+
+/// Loaded file containing secrets that is accessible by other users than its owner
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InsecureFile {
+    path: String,
+    mode: u32,
+    secret_keys: Vec<String>,
+}
+
+/// Variable declared under a deprecated name registered with `Dotenv::alias()`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeprecatedKey {
+    name: String,
+    replacement: String,
+    path: Option<String>,
+    line_number: usize,
+}
+
+/// Variable defined more than once in the same file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateKey {
+    name: String,
+    path: Option<String>,
+    first_line_number: usize,
+    line_number: usize,
+}
+
+impl LoadReport {
+    pub(crate) fn duplicate(&mut self, duplicate: DuplicateKey) {
+        self.duplicates.push(duplicate);
+    }
+
+    /// Duplicate variables, as reported by `DuplicateKeyPolicy::Warn`.
+    pub fn duplicates(&self) -> &[DuplicateKey] {
+        &self.duplicates
+    }
+
+    pub(crate) fn protected_variable(&mut self, protected_variable: ProtectedVariable) {
+        self.protected_variables.push(protected_variable);
+    }
+
+    /// Attempts to modify protected variables, as reported by `ProtectedVariablePolicy::Warn` and `Skip`.
+    pub fn protected_variables(&self) -> &[ProtectedVariable] {
+        &self.protected_variables
+    }
+
+    pub(crate) fn set_defaults(&mut self, defaults: Vec<String>) {
+        self.defaults = defaults;
+    }
+
+    /// Variables that are neither loaded nor defined in the environment, and fall back to their default value.
+    pub fn defaults(&self) -> &[String] {
+        &self.defaults
+    }
+
+    pub(crate) fn insecure_file(&mut self, insecure_file: InsecureFile) {
+        if !self.insecure_files.contains(&insecure_file) {
+            self.insecure_files.push(insecure_file);
+        }
+    }
+
+    /// Files containing secrets with permissions more permissive than `0600`, as reported by `FilePermissionPolicy::Warn`.
+    pub fn insecure_files(&self) -> &[InsecureFile] {
+        &self.insecure_files
+    }
+
+    pub(crate) fn deprecated_key(&mut self, deprecated_key: DeprecatedKey) {
+        self.deprecated_keys.push(deprecated_key);
+    }
+
+    /// Variables declared under a deprecated name, which were loaded under their new name.
+    pub fn deprecated_keys(&self) -> &[DeprecatedKey] {
+        &self.deprecated_keys
+    }
+
+    /// Whether nothing was reported.
+    pub fn is_empty(&self) -> bool {
+        self.duplicates.is_empty()
+            && self.protected_variables.is_empty()
+            && self.defaults.is_empty()
+            && self.insecure_files.is_empty()
+            && self.deprecated_keys.is_empty()
+    }
+}
+
+impl DuplicateKey {
+    pub(crate) fn new(name: String, path: Option<String>, first_line_number: usize, line_number: usize) -> Self {
+        Self { name, path, first_line_number, line_number }
+    }
+
+    /// Name of the variable.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Path of the file, if the variables were not loaded from a string.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Line number of the first definition.
+    pub fn first_line_number(&self) -> usize {
+        self.first_line_number
+    }
+
+    /// Line number of the duplicate definition.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+}
+
+impl DeprecatedKey {
+    pub(crate) fn new(name: String, replacement: String, path: Option<String>, line_number: usize) -> Self {
+        Self { name, replacement, path, line_number }
+    }
+
+    /// Deprecated name of the variable.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// New name of the variable, under which it was loaded.
+    pub fn replacement(&self) -> &str {
+        &self.replacement
+    }
+
+    /// Path of the file, if the variables were not loaded from a string.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Line number of the declaration.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+}
+
+impl InsecureFile {
+    pub(crate) fn new(path: String, mode: u32, secret_keys: Vec<String>) -> Self {
+        Self { path, mode, secret_keys }
+    }
+
+    /// Path of the file.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Permission bits of the file, like `0o644`.
+    pub fn mode(&self) -> u32 {
+        self.mode
+    }
+
+    /// Names of the variables in the file that look like secrets.
+    pub fn secret_keys(&self) -> &[String] {
+        &self.secret_keys
+    }
+}
+
+impl ProtectedVariable {
+    pub(crate) fn new(name: String, path: Option<String>, skipped: bool) -> Self {
+        Self { name, path, skipped }
+    }
+
+    /// Name of the variable.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Path of the file that attempted the modification, if the variables were not loaded from a string.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Whether the modification was skipped.
+    pub fn skipped(&self) -> bool {
+        self.skipped
+    }
+}
+
+impl fmt::Display for ProtectedVariable {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(path) = &self.path {
+            write!(formatter, "{}: ", path)?;
+        }
+
+        if self.skipped {
+            write!(formatter, "refused to modify the protected variable \"{}\"", self.name)
+        } else {
+            write!(formatter, "modified the protected variable \"{}\"", self.name)
+        }
+    }
+}
+
+impl fmt::Display for InsecureFile {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}: permissions {:04o} are too open for a file containing secrets ({})", self.path, self.mode, self.secret_keys.join(", "))
+    }
+}
+
+impl fmt::Display for DuplicateKey {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(path) = &self.path {
+            write!(formatter, "{}:", path)?;
+        }
+
+        write!(formatter, "{}: \"{}\" is already defined at line {}", self.line_number, self.name, self.first_line_number)
+    }
+}
+
+impl fmt::Display for DeprecatedKey {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(path) = &self.path {
+            write!(formatter, "{}:", path)?;
+        }
+
+        write!(formatter, "{}: \"{}\" is deprecated, use \"{}\" instead", self.line_number, self.name, self.replacement)
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Resolution of `scheme://path` references in values through user-registered resolvers.
+//!
+//! With the `vault`, `aws`, `gcp`, `onepassword` and `bitwarden` features, resolvers for HashiCorp Vault, AWS Secrets
+//! Manager, GCP Secret Manager, 1Password and Bitwarden are provided. They use the respective command line tools, and
+//! thereby their regular authentication.
+
+#[cfg(any(feature = "vault", feature = "aws", feature = "azure", feature = "gcp", feature = "onepassword", feature = "bitwarden", feature = "sops"))]
+use std::process::Command;
+
+/// Resolver of `scheme://path` references
+pub trait Resolver {
+    ///
+    /// Resolves the path of a reference (everything after `scheme://`) to its value.
+    ///
+    fn resolve(&self, path: &str) -> Result<String, String>;
+}
+
+impl<F> Resolver for F
+    where
+        F: Fn(&str) -> Result<String, String> {
+
+    fn resolve(&self, path: &str) -> Result<String, String> {
+        self(path)
+    }
+}
+
+///
+/// Splits a value into its scheme and path if it is a `scheme://path` reference.
+///
+pub(crate) fn parse_reference(value: &str) -> Option<(&str, &str)> {
+    let (scheme, path) = value.split_once("://")?;
+
+    let is_scheme = scheme.starts_with(|character: char| character.is_ascii_alphabetic())
+        && scheme.chars().all(|character| character.is_ascii_alphanumeric() || "+-.".contains(character));
+
+    if !is_scheme {
+        return None;
+    }
+
+    Some((scheme, path))
+}
+
+/// Resolver for HashiCorp Vault KV secrets, referenced as `vault://mount/path#field`
+#[cfg(feature = "vault")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VaultResolver;
+
+#[cfg(feature = "vault")]
+impl Resolver for VaultResolver {
+    fn resolve(&self, path: &str) -> Result<String, String> {
+        let (path, field) = path.split_once('#')
+            .ok_or_else(|| "the reference must specify a field, as in vault://secret/myapp#password".to_string())?;
+
+        run(Command::new("vault").args(["kv", "get", &format!("-field={}", field), "--", positional(path)?]))
+    }
+}
+
+/// Resolver for AWS Secrets Manager secrets, referenced as `aws-sm://secret-id`
+#[cfg(feature = "aws")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AwsSecretsManagerResolver;
+
+#[cfg(feature = "aws")]
+impl Resolver for AwsSecretsManagerResolver {
+    fn resolve(&self, path: &str) -> Result<String, String> {
+        run(Command::new("aws").args(["secretsmanager", "get-secret-value", "--secret-id", path, "--query", "SecretString", "--output", "text"]))
+    }
+}
+
+/// Resolver for GCP Secret Manager secrets, referenced as `gcp-sm://[project/]secret[#version]`
+#[cfg(feature = "gcp")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GcpSecretManagerResolver;
+
+#[cfg(feature = "gcp")]
+impl Resolver for GcpSecretManagerResolver {
+    fn resolve(&self, path: &str) -> Result<String, String> {
+        let (path, version) = path.split_once('#').unwrap_or((path, "latest"));
+
+        let mut command = Command::new("gcloud");
+        command.args(["secrets", "versions", "access", version]);
+
+        match path.split_once('/') {
+            Some((project, secret)) => command.args([format!("--secret={}", secret), format!("--project={}", project)]),
+            None => command.arg(format!("--secret={}", path)),
+        };
+
+        run(&mut command)
+    }
+}
+
+/// Resolver for 1Password secret references, referenced as `op://vault/item/field`
+#[cfg(feature = "onepassword")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OnePasswordResolver;
+
+#[cfg(feature = "onepassword")]
+impl Resolver for OnePasswordResolver {
+    fn resolve(&self, path: &str) -> Result<String, String> {
+        if path.split('/').filter(|segment| !segment.is_empty()).count() < 3 {
+            return Err("the reference must specify a vault, item and field, as in op://dev/myapp/password".to_string());
+        }
+
+        run(Command::new("op").args(["read", "--no-newline", &format!("op://{}", path)]))
+    }
+}
+
+/// Resolver for Bitwarden vault items, referenced as `bw://item[#field]`, where the item is a name or id and the field
+/// is `password` (the default), `username`, `uri`, `totp` or `notes`
+#[cfg(feature = "bitwarden")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BitwardenResolver;
+
+#[cfg(feature = "bitwarden")]
+impl Resolver for BitwardenResolver {
+    fn resolve(&self, path: &str) -> Result<String, String> {
+        let (item, field) = path.split_once('#').unwrap_or((path, "password"));
+
+        if !["password", "username", "uri", "totp", "notes"].contains(&field) {
+            return Err(format!("unknown field \"{}\", expected password, username, uri, totp or notes", field));
+        }
+
+        run(Command::new("bw").args(["get", field, "--", positional(item)?]))
+    }
+}
+
+// Rejects a positional argument taken from an environment file that the command would read as a flag.
+#[cfg(any(feature = "vault", feature = "bitwarden"))]
+fn positional(argument: &str) -> Result<&str, String> {
+    if argument.starts_with('-') {
+        return Err(format!("\"{}\" must not start with -", argument));
+    }
+
+    Ok(argument)
+}
+
+#[cfg(any(feature = "vault", feature = "aws", feature = "azure", feature = "gcp", feature = "onepassword", feature = "bitwarden", feature = "sops"))]
+pub(crate) fn run(command: &mut Command) -> Result<String, String> {
+    let program = command.get_program().to_string_lossy().to_string();
+
+    let output = command.output()
+        .map_err(|error| format!("unable to run {}: {}", program, error))?;
+
+    if !output.status.success() {
+        return Err(format!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::resolver;
+
+    #[test]
+    fn parse_reference() {
+        assert_eq!(resolver::parse_reference("vault://secret/app#pass"), Some(("vault", "secret/app#pass")));
+        assert_eq!(resolver::parse_reference("aws-sm://db"), Some(("aws-sm", "db")));
+        assert_eq!(resolver::parse_reference("no reference"), None);
+        assert_eq!(resolver::parse_reference("a b://c"), None);
+    }
+
+    #[cfg(all(feature = "onepassword", feature = "bitwarden"))]
+    #[test]
+    fn invalid_password_manager_references() {
+        use crate::resolver::{BitwardenResolver, OnePasswordResolver, Resolver};
+
+        assert!(OnePasswordResolver.resolve("dev/myapp").unwrap_err().contains("vault, item and field"));
+        assert!(BitwardenResolver.resolve("myapp#secret").unwrap_err().contains("unknown field \"secret\""));
+        assert_eq!(BitwardenResolver.resolve("--session=x#password").unwrap_err(), "\"--session=x\" must not start with -");
+    }
+
+    #[cfg(feature = "vault")]
+    #[test]
+    fn vault_reference_flag_injection() {
+        use crate::resolver::{Resolver, VaultResolver};
+
+        let error = VaultResolver.resolve("-address=https://attacker.example#f").unwrap_err();
+        assert_eq!(error, "\"-address=https://attacker.example\" must not start with -");
+    }
+}
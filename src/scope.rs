@@ -0,0 +1,59 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::Dotenv;
+
+/// View of the environment variables with a common prefix, as returned by `Dotenv::scoped()`
+#[derive(Clone, Copy)]
+pub struct ScopedEnv<'a> {
+    dotenv: &'a Dotenv,
+    prefix: &'a str,
+}
+
+impl<'a> ScopedEnv<'a> {
+    pub(crate) fn new(dotenv: &'a Dotenv, prefix: &'a str) -> Self {
+        Self { dotenv, prefix }
+    }
+
+    ///
+    /// Returns the value of the prefixed variable, as looked up by `Dotenv::get()`.
+    ///
+    pub fn get<Name>(&self, name: Name) -> Option<String>
+        where
+            Name: AsRef<str> {
+
+        let name = format!("{}{}", self.prefix, name.as_ref());
+
+        self.dotenv.get(name)
+    }
+
+    /// Prefix of the scope.
+    pub fn prefix(&self) -> &str {
+        self.prefix
+    }
+
+    ///
+    /// Returns the unprefixed names of the variables in the scope that were loaded by the `Dotenv` instance.
+    ///
+    pub fn keys(&self) -> Vec<&'a str> {
+        let mut keys: Vec<&str> = self.dotenv.values().keys()
+            .filter_map(|key| key.strip_prefix(self.prefix))
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+
+use crate::{EnvDiff, EnvMap, Exception};
+
+///
+/// Handle over the resolved environment variables for long-running applications, which reads them without locking and
+/// swaps them atomically on reload
+///
+/// Clones share the same variables, so a handle can be passed to every worker or request handler, and a reload is seen
+/// by all of them at once. A reader holding a snapshot from `load()` keeps a complete set of variables, even while it is
+/// being reloaded.
+///
+#[derive(Clone, Debug, Default)]
+pub struct SharedEnv {
+    values: Arc<ArcSwap<EnvMap>>,
+}
+
+impl SharedEnv {
+    ///
+    /// Creates a handle over the given variables.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use darkweb_dotenv::{Dotenv, SharedEnv};
+    ///
+    /// let env = SharedEnv::new(Dotenv::new().env_map(".env").unwrap());
+    ///
+    /// let worker_env = env.clone();
+    /// std::thread::spawn(move || println!("{:?}", worker_env.get("DATABASE_URL")));
+    /// ```
+    ///
+    pub fn new(values: EnvMap) -> Self {
+        Self { values: Arc::new(ArcSwap::from_pointee(values)) }
+    }
+
+    /// Returns the value of a variable.
+    pub fn get<Key>(&self, key: Key) -> Option<String>
+        where
+            Key: AsRef<str> {
+
+        self.values.load().get(key).map(str::to_string)
+    }
+
+    /// Returns a snapshot of the variables, which is not affected by later reloads.
+    pub fn load(&self) -> Arc<EnvMap> {
+        self.values.load_full()
+    }
+
+    /// Replaces the variables, returning the previous ones.
+    pub fn store(&self, values: EnvMap) -> Arc<EnvMap> {
+        self.values.swap(Arc::new(values))
+    }
+
+    ///
+    /// Runs a load pipeline and swaps in its variables, returning the changes. The variables are left as they are if
+    /// the pipeline fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::{EnvMap, SharedEnv};
+    ///
+    /// let env = SharedEnv::new(EnvMap::parse("PORT=8080").unwrap());
+    /// let diff = env.reload(|| EnvMap::parse("PORT=9090")).unwrap();
+    ///
+    /// assert_eq!(diff.changed()["PORT"], ("8080".to_string(), "9090".to_string()));
+    /// assert_eq!(env.get("PORT").as_deref(), Some("9090"));
+    /// ```
+    ///
+    /// # Exceptions
+    ///
+    /// * The exceptions of the load pipeline
+    ///
+    pub fn reload<Load>(&self, load: Load) -> Result<EnvDiff, Exception>
+        where
+            Load: FnOnce() -> Result<EnvMap, Exception> {
+
+        let values = Arc::new(load()?);
+        let previous = self.values.swap(Arc::clone(&values));
+
+        Ok(EnvDiff::new(previous.as_map(), values.as_map()))
+    }
+
+    /// Underlying `ArcSwap`, for sharing the variables with code that uses `arc-swap` directly.
+    pub fn as_arc_swap(&self) -> &Arc<ArcSwap<EnvMap>> {
+        &self.values
+    }
+}
+
+impl From<EnvMap> for SharedEnv {
+    fn from(values: EnvMap) -> Self {
+        Self::new(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::{EnvMap, Exception, SharedEnv};
+
+    #[test]
+    fn reload() {
+        let env = SharedEnv::new(EnvMap::parse("DOTENV_TEST_SHARED_HOST=localhost\nDOTENV_TEST_SHARED_PORT=80").unwrap());
+        let clone = env.clone();
+        let snapshot = env.load();
+
+        let diff = env.reload(|| EnvMap::parse("DOTENV_TEST_SHARED_HOST=example.com")).unwrap();
+        assert_eq!(diff.changed().keys().collect::<Vec<_>>(), ["DOTENV_TEST_SHARED_HOST"]);
+        assert_eq!(diff.removed().keys().collect::<Vec<_>>(), ["DOTENV_TEST_SHARED_PORT"]);
+
+        let reader = thread::spawn(move || clone.get("DOTENV_TEST_SHARED_HOST"));
+        assert_eq!(reader.join().unwrap().as_deref(), Some("example.com"));
+        assert_eq!(snapshot.get("DOTENV_TEST_SHARED_HOST"), Some("localhost"));
+
+        let exception = env.reload(|| Err(Exception::validation("DOTENV_TEST_SHARED_HOST".to_string(), "invalid"))).unwrap_err();
+        assert_eq!(exception.key(), Some("DOTENV_TEST_SHARED_HOST"));
+        assert_eq!(env.get("DOTENV_TEST_SHARED_HOST").as_deref(), Some("example.com"));
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+/// Shell enumeration
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Fish,
+    PowerShell,
+}
+
+impl Shell {
+    pub(crate) fn render_export(&self, name: &str, value: &str) -> String {
+        match self {
+            Shell::Bash => format!("export {}='{}'", name, value.replace('\'', "'\\''")),
+            Shell::Fish => format!("set -gx {} '{}'", name, value.replace('\\', "\\\\").replace('\'', "\\'")),
+            Shell::PowerShell => format!("$env:{} = '{}'", name, value.replace('\'', "''")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Shell;
+
+    #[test]
+    fn render_export_bash() {
+        assert_eq!(Shell::Bash.render_export("FOO", "it's"), "export FOO='it'\\''s'");
+    }
+
+    #[test]
+    fn render_export_fish() {
+        assert_eq!(Shell::Fish.render_export("FOO", "it's a\\b"), "set -gx FOO 'it\\'s a\\\\b'");
+    }
+
+    #[test]
+    fn render_export_powershell() {
+        assert_eq!(Shell::PowerShell.render_export("FOO", "it's"), "$env:FOO = 'it''s'");
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+///
+/// Converts the name of a key in a remote store to an environment variable name, relative to the loaded prefix, as in
+/// `DB_PASSWORD` for `/myapp/prod/db/password` under `/myapp/prod/`.
+///
+pub(crate) fn key(prefix: &str, name: &str) -> String {
+    let relative_name = name.strip_prefix(prefix).unwrap_or(name);
+
+    relative_name.trim_start_matches(|character: char| !character.is_ascii_alphanumeric()).chars()
+        .map(|character| match character.is_ascii_alphanumeric() {
+            true => character.to_ascii_uppercase(),
+            false => '_',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source;
+
+    #[test]
+    fn key() {
+        assert_eq!(source::key("/myapp/prod/", "/myapp/prod/db/password"), "DB_PASSWORD");
+        assert_eq!(source::key("/myapp/prod", "/myapp/prod/api-url"), "API_URL");
+        assert_eq!(source::key("myapp:", "myapp:Logging.Level"), "LOGGING_LEVEL");
+        assert_eq!(source::key("", "db-password"), "DB_PASSWORD");
+    }
+}
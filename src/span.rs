@@ -0,0 +1,55 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::ops::Range;
+
+/// Byte ranges of a variable declaration in the contents of an environment file
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EntrySpans {
+    key: Range<usize>,
+    value: Range<usize>,
+    line: Range<usize>,
+}
+
+impl EntrySpans {
+    pub(crate) fn new(key: Range<usize>, value: Range<usize>, line: Range<usize>) -> Self {
+        Self { key, value, line }
+    }
+
+    pub(crate) fn shifted(&self, delta: isize) -> Self {
+        let shift = |range: &Range<usize>| (range.start as isize + delta) as usize..(range.end as isize + delta) as usize;
+
+        Self::new(shift(&self.key), shift(&self.value), shift(&self.line))
+    }
+
+    /// Range of the variable name.
+    pub fn key(&self) -> Range<usize> {
+        self.key.clone()
+    }
+
+    /// Range of the value as written, quotes included and the blanks and comment after it excluded. Empty for an empty
+    /// value.
+    pub fn value(&self) -> Range<usize> {
+        self.value.clone()
+    }
+
+    /// Range of the lines of the declaration, from the start of the first line to the end of the last line, without the
+    /// line break.
+    pub fn line(&self) -> Range<usize> {
+        self.line.clone()
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+/// Specification of a variable, declared by annotations such as `# @type: int @required @secret @default: 8080` in the
+/// comments above it, or in code with `KeySpec::parse()` and `Dotenv::key_spec()`
+///
+/// Constraints on the value are declared by `@pattern: <regex>`, `@min-length: <n>`, `@max-length: <n>`,
+/// `@enum: <a,b,c>` and `@format: <url|email|port>`, and checked by `Dotenv::violations()` and `Dotenv::validate()`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeySpec {
+    value_type: Option<String>,
+    required: bool,
+    secret: bool,
+    default_value: Option<String>,
+    pattern: Option<String>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    allowed_values: Vec<String>,
+    value_format: Option<String>,
+}
+
+impl KeySpec {
+    const TYPES: [&'static str; 6] = ["bool", "csv", "float", "int", "json", "string"];
+
+    const FORMATS: [&'static str; 3] = ["email", "port", "url"];
+
+    ///
+    /// Parses a specification from annotations, as written in the comments above a variable but without the leading
+    /// `#`. Unknown annotations and types are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use darkweb_dotenv::KeySpec;
+    ///
+    /// let spec = KeySpec::parse("@required @format: url @max-length: 255");
+    ///
+    /// assert!(spec.required());
+    /// assert_eq!(spec.value_format(), Some("url"));
+    /// ```
+    ///
+    pub fn parse<Annotations>(annotations: Annotations) -> Self
+        where
+            Annotations: AsRef<str> {
+
+        let mut spec = Self::default();
+        spec.parse_line(annotations.as_ref().trim_start());
+        spec
+    }
+
+    ///
+    /// Parses the annotations of a comment line, returning `None` if the line is not an annotation line. Annotation
+    /// lines start with an `@` annotation. Unknown annotations and types are ignored.
+    ///
+    pub(crate) fn parse_line(&mut self, line: &str) -> Option<()> {
+        if !line.starts_with('@') {
+            return None;
+        }
+
+        let mut tokens = line.split_whitespace();
+
+        while let Some(token) = tokens.next() {
+            let (annotation, value) = match token.split_once(':') {
+                Some((annotation, "")) => (annotation, tokens.next().unwrap_or_default()),
+                Some((annotation, value)) => (annotation, value),
+                None => (token, ""),
+            };
+
+            match annotation {
+                "@type" => self.set_type(value),
+                "@required" => self.required = true,
+                "@secret" => self.secret = true,
+                "@default" if !value.is_empty() => self.default_value = Some(value.to_string()),
+                "@pattern" if !value.is_empty() => self.pattern = Some(value.to_string()),
+                "@min-length" => self.min_length = value.parse().ok().or(self.min_length),
+                "@max-length" => self.max_length = value.parse().ok().or(self.max_length),
+                "@enum" => self.allowed_values = value.split(',').filter(|value| !value.is_empty()).map(str::to_string).collect(),
+                "@format" if Self::FORMATS.contains(&value) => self.value_format = Some(value.to_string()),
+                _ => {},
+            }
+        }
+
+        Some(())
+    }
+
+    fn set_type(&mut self, value_type: &str) {
+        if Self::TYPES.contains(&value_type) {
+            self.value_type = Some(value_type.to_string());
+        }
+    }
+
+    /// Type of the value, as the name of an env processor (`bool`, `csv`, `float`, `int`, `json` or `string`).
+    pub fn value_type(&self) -> Option<&str> {
+        self.value_type.as_deref()
+    }
+
+    /// Whether the variable must be defined, with a non-empty value.
+    pub fn required(&self) -> bool {
+        self.required
+    }
+
+    /// Whether the value is a secret, which is redacted when shown.
+    pub fn secret(&self) -> bool {
+        self.secret
+    }
+
+    /// Default value, which cannot contain whitespace, used when scaffolding an environment file from an example file.
+    pub fn default_value(&self) -> Option<&str> {
+        self.default_value.as_deref()
+    }
+
+    /// Regular expression the whole value must match, which cannot contain whitespace.
+    pub fn pattern(&self) -> Option<&str> {
+        self.pattern.as_deref()
+    }
+
+    /// Minimum length of the value, in characters.
+    pub fn min_length(&self) -> Option<usize> {
+        self.min_length
+    }
+
+    /// Maximum length of the value, in characters.
+    pub fn max_length(&self) -> Option<usize> {
+        self.max_length
+    }
+
+    /// Values the variable is restricted to, if any.
+    pub fn allowed_values(&self) -> &[String] {
+        &self.allowed_values
+    }
+
+    /// Format of the value (`email`, `port` or `url`).
+    pub fn value_format(&self) -> Option<&str> {
+        self.value_format.as_deref()
+    }
+
+    /// Whether no annotation was declared.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::KeySpec;
+
+    #[test]
+    fn parse_line() {
+        let mut spec = KeySpec::default();
+        assert_eq!(spec.parse_line("The database port"), None);
+        assert_eq!(spec.parse_line("@type: int @required"), Some(()));
+        assert_eq!((spec.value_type(), spec.required(), spec.secret()), (Some("int"), true, false));
+
+        let mut spec = KeySpec::default();
+        spec.parse_line("@secret @type:json @unknown");
+        assert_eq!((spec.value_type(), spec.required(), spec.secret()), (Some("json"), false, true));
+
+        let mut spec = KeySpec::default();
+        spec.parse_line("@type: uuid");
+        assert!(spec.is_empty());
+
+        let mut spec = KeySpec::default();
+        spec.parse_line("@default: 8080 @required");
+        assert_eq!((spec.default_value(), spec.required()), (Some("8080"), true));
+
+        let mut spec = KeySpec::default();
+        spec.parse_line("@default:localhost");
+        assert_eq!(spec.default_value(), Some("localhost"));
+    }
+
+    #[test]
+    fn parse_constraints() {
+        let spec = KeySpec::parse("@pattern: ^[a-z]+$ @min-length:3 @max-length: 8 @enum: dev,prod @format: url");
+        assert_eq!(spec.pattern(), Some("^[a-z]+$"));
+        assert_eq!((spec.min_length(), spec.max_length()), (Some(3), Some(8)));
+        assert_eq!(spec.allowed_values(), ["dev", "prod"]);
+        assert_eq!(spec.value_format(), Some("url"));
+
+        let spec = KeySpec::parse("@format: uuid @min-length: many");
+        assert!(spec.is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::process::Command;
+
+use crate::{json, resolver, source, EnvValue, Exception};
+
+///
+/// Fetches the parameters under a path of the AWS SSM Parameter Store, decrypting secure strings, as pairs of
+/// environment variable names and values.
+///
+pub(crate) fn fetch(path: &str) -> Result<Vec<(String, String)>, Exception> {
+    let output = resolver::run(Command::new("aws").args([
+        "ssm", "get-parameters-by-path", "--path", path, "--recursive", "--with-decryption", "--query", "Parameters", "--output", "json",
+    ])).map_err(|reason| Exception::unavailable_source(path.to_string(), reason))?;
+
+    parameters(path, &output).map_err(|reason| Exception::unavailable_source(path.to_string(), reason))
+}
+
+///
+/// Converts the parameters in the output of `aws ssm get-parameters-by-path` to pairs of environment variable names
+/// and values.
+///
+fn parameters(path: &str, output: &str) -> Result<Vec<(String, String)>, String> {
+    let parameters = match json::parse(output)? {
+        EnvValue::Array(parameters) => parameters,
+        EnvValue::Null => Vec::new(),
+        _ => return Err("expected a list of parameters".to_string()),
+    };
+
+    parameters.into_iter()
+        .map(|parameter| match parameter {
+            EnvValue::Object(mut parameter) => match (parameter.remove("Name"), parameter.remove("Value")) {
+                (Some(EnvValue::String(name)), Some(EnvValue::String(value))) => Ok((source::key(path, &name), value)),
+                _ => Err("expected parameters with a name and value".to_string()),
+            },
+            _ => Err("expected a list of parameters".to_string()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ssm;
+
+    #[test]
+    fn parameters() {
+        let output = r#"[
+            {"Name": "/myapp/prod/db/password", "Type": "SecureString", "Value": "secret", "Version": 3},
+            {"Name": "/myapp/prod/api-url", "Type": "String", "Value": "https://api.example.com", "Version": 1}
+        ]"#;
+
+        assert_eq!(ssm::parameters("/myapp/prod/", output).unwrap(), vec![
+            ("DB_PASSWORD".to_string(), "secret".to_string()),
+            ("API_URL".to_string(), "https://api.example.com".to_string()),
+        ]);
+        assert_eq!(ssm::parameters("/myapp/prod", "null").unwrap(), vec![]);
+        assert!(ssm::parameters("/myapp/prod", r#"[{"Name": "/myapp/prod/db"}]"#).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Synchronization of environment files with hosted configuration through user-implemented stores.
+//!
+//! `Dotenv::pull()` updates a local environment file with the variables of a store and `Dotenv::push()` replaces the
+//! variables of a store with the ones of a local environment file. With the `remote` feature, `HttpEnvStore` stores
+//! the variables as a JSON object behind a URL.
+
+#[cfg(feature = "remote")]
+use std::collections::HashMap;
+#[cfg(feature = "remote")]
+use std::time::Duration;
+
+use crate::{EnvMap, Exception};
+#[cfg(feature = "remote")]
+use crate::{json, remote, EnvValue};
+
+/// Hosted set of environment variables
+pub trait RemoteEnvStore {
+    ///
+    /// Fetches all variables of the store.
+    ///
+    fn pull(&self) -> Result<EnvMap, Exception>;
+
+    ///
+    /// Replaces all variables of the store.
+    ///
+    fn push(&self, values: &EnvMap) -> Result<(), Exception>;
+}
+
+/// Store of the variables as a JSON object of strings, fetched with `GET` and replaced with `PUT` requests to a URL
+#[cfg(feature = "remote")]
+#[derive(Clone, Debug)]
+pub struct HttpEnvStore {
+    url: String,
+    bearer_token: Option<String>,
+    timeout: Duration,
+}
+
+#[cfg(feature = "remote")]
+impl HttpEnvStore {
+    ///
+    /// Creates a store for the JSON object at a URL.
+    ///
+    pub fn new<Url>(url: Url) -> Self
+        where
+            Url: AsRef<str> {
+
+        Self { url: url.as_ref().to_string(), bearer_token: None, timeout: Duration::from_secs(30) }
+    }
+
+    ///
+    /// Sets a token that requests send as `Authorization: Bearer <token>`.
+    ///
+    pub fn bearer_token<Token>(mut self, bearer_token: Token) -> Self
+        where
+            Token: AsRef<str> {
+
+        self.bearer_token = Some(bearer_token.as_ref().to_string());
+        self
+    }
+
+    ///
+    /// Sets the timeout of requests, which defaults to 30 seconds.
+    ///
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[cfg(feature = "remote")]
+impl RemoteEnvStore for HttpEnvStore {
+    fn pull(&self) -> Result<EnvMap, Exception> {
+        let body = remote::fetch(&self.url, self.timeout, self.bearer_token.as_deref())?;
+
+        let object = match json::parse(&body) {
+            Ok(EnvValue::Object(object)) => object,
+            Ok(_) => return Err(Exception::unavailable_source(self.url.clone(), "expected a JSON object".to_string())),
+            Err(reason) => return Err(Exception::unavailable_source(self.url.clone(), reason)),
+        };
+
+        let values: HashMap<String, String> = object.into_iter()
+            .map(|(key, value)| match value {
+                EnvValue::String(value) => Ok((key, value)),
+                _ => Err(Exception::unavailable_source(self.url.clone(), format!("the value of \"{}\" is not a string", key))),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(EnvMap::from(values))
+    }
+
+    fn push(&self, values: &EnvMap) -> Result<(), Exception> {
+        let mut names: Vec<&String> = values.as_map().keys().collect();
+        names.sort();
+
+        let body = format!("{{{}}}", names.into_iter()
+            .map(|name| format!("{}:{}", json::string(name), json::string(&values.as_map()[name])))
+            .collect::<Vec<_>>()
+            .join(","));
+
+        remote::put(&self.url, self.timeout, self.bearer_token.as_deref(), &body)
+    }
+}
+
+#[cfg(all(test, feature = "remote"))]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use crate::store::{HttpEnvStore, RemoteEnvStore};
+
+    #[test]
+    fn http_env_store() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/envs/production", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+
+            for response in [r#"{"APP_DEBUG":"false","APP_NAME":"example"}"#, ""] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = String::new();
+                let mut buffer = [0; 4096];
+
+                while !request.contains("\r\n\r\n") || (request.starts_with("PUT") && !request.ends_with('}')) {
+                    let length = stream.read(&mut buffer).unwrap();
+                    request.push_str(&String::from_utf8_lossy(&buffer[..length]));
+                }
+
+                write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", response.len(), response).unwrap();
+                requests.push(request);
+            }
+
+            requests
+        });
+
+        let store = HttpEnvStore::new(&url).bearer_token("secret");
+
+        let mut values = store.pull().unwrap();
+        assert_eq!(values.get("APP_NAME"), Some("example"));
+
+        values.insert("APP_DEBUG", "true");
+        store.push(&values).unwrap();
+
+        let requests = handle.join().unwrap();
+        assert!(requests[1].starts_with("PUT /envs/production "));
+        assert!(requests[1].to_lowercase().contains("authorization: bearer secret"));
+        assert!(requests[1].ends_with(r#"{"APP_DEBUG":"true","APP_NAME":"example"}"#));
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+#[cfg(unix)]
+use std::fs;
+use std::env;
+
+use regex::{Captures, Regex};
+
+use crate::Exception;
+
+fn variable_regex() -> Regex {
+    Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap()
+}
+
+// Also matches `$$`, the escaped form of a literal dollar sign.
+fn escaped_variable_regex() -> Regex {
+    Regex::new(r"\$\$|\$\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap()
+}
+
+pub(crate) fn render<Lookup>(template: &str, path: &str, strict: bool, lookup: Lookup) -> Result<String, Exception>
+    where
+        Lookup: Fn(&str) -> Option<String> {
+
+    let regex = variable_regex();
+
+    if strict {
+        if let Some(captures) = regex.captures_iter(template).find(|captures| lookup(&captures[1]).is_none()) {
+            let line_number = template[..captures.get(0).unwrap().start()].matches('\n').count() + 1;
+
+            return Err(Exception::undefined_variable(captures[1].to_string(), path.to_string(), line_number));
+        }
+    }
+
+    Ok(regex.replace_all(template, |captures: &Captures| lookup(&captures[1]).unwrap_or_default()).to_string())
+}
+
+/// Interpolates the `${VAR}` references of a value. An escaped value has its literal dollar signs escaped as `$$`.
+pub(crate) fn interpolate<Lookup>(value: &str, escaped: bool, lookup: Lookup) -> String
+    where
+        Lookup: Fn(&str) -> Option<String> {
+
+    if !escaped {
+        return variable_regex().replace_all(value, |captures: &Captures| lookup(&captures[1]).unwrap_or_default()).to_string();
+    }
+
+    escaped_variable_regex().replace_all(value, |captures: &Captures| match captures.get(1) {
+        Some(reference) => lookup(reference.as_str()).unwrap_or_default(),
+        None => "$".to_string(),
+    }).to_string()
+}
+
+pub(crate) fn references(value: &str, escaped: bool) -> Vec<String> {
+    let regex = if escaped { escaped_variable_regex() } else { variable_regex() };
+
+    regex.captures_iter(value).filter_map(|captures| Some(captures.get(1)?.as_str().to_string())).collect()
+}
+
+pub(crate) fn expand_percent_variables<Lookup>(value: &str, lookup: Lookup) -> String
+    where
+        Lookup: Fn(&str) -> Option<String> {
+
+    let regex = Regex::new(r"%([^%\s=]+)%").unwrap();
+
+    regex.replace_all(value, |captures: &Captures| {
+        lookup(&captures[1]).unwrap_or_else(|| captures[0].to_string())
+    }).to_string()
+}
+
+/// Expands a leading `~` to the home directory of the current user, and a leading `~user` to the one of that user.
+pub(crate) fn expand_tilde<Lookup>(value: &str, home_directory: Lookup) -> String
+    where
+        Lookup: Fn(Option<&str>) -> Option<String> {
+
+    let rest = match value.strip_prefix('~') {
+        Some(rest) => rest,
+        None => return value.to_string(),
+    };
+
+    let end = rest.find(|character| character == '/' || (cfg!(windows) && character == '\\')).unwrap_or(rest.len());
+    let user = &rest[..end];
+
+    if !user.chars().all(|character| character.is_ascii_alphanumeric() || "._-".contains(character)) {
+        return value.to_string();
+    }
+
+    match home_directory(Some(user).filter(|user| !user.is_empty())) {
+        Some(home_directory) => format!("{}{}", home_directory.trim_end_matches('/'), &rest[end..]),
+        None => value.to_string(),
+    }
+}
+
+/// Home directory of the current user, or of the given user as listed in `/etc/passwd` on Unix.
+pub(crate) fn home_directory(user: Option<&str>) -> Option<String> {
+    let user = match user {
+        Some(user) => user,
+        None => return env::var(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).ok().filter(|home| !home.is_empty()),
+    };
+
+    #[cfg(unix)]
+    {
+        fs::read_to_string("/etc/passwd").ok()?
+            .lines()
+            .map(|line| line.split(':').collect::<Vec<_>>())
+            .find(|fields| fields.len() >= 6 && fields[0] == user)
+            .map(|fields| fields[5].to_string())
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = user;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::template;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "FOO" => Some("bar".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expand_percent_variables() {
+        assert_eq!(template::expand_percent_variables("%FOO%\\%BAR%", lookup), "bar\\%BAR%");
+    }
+
+    #[test]
+    fn expand_tilde() {
+        let home_directory = |user: Option<&str>| match user {
+            None => Some("/home/me/".to_string()),
+            Some("root") => Some("/root".to_string()),
+            Some(_) => None,
+        };
+
+        assert_eq!(template::expand_tilde("~", home_directory), "/home/me");
+        assert_eq!(template::expand_tilde("~/data", home_directory), "/home/me/data");
+        assert_eq!(template::expand_tilde("~root/.cache", home_directory), "/root/.cache");
+        assert_eq!(template::expand_tilde("~unknown/data", home_directory), "~unknown/data");
+        assert_eq!(template::expand_tilde("~ user", home_directory), "~ user");
+        assert_eq!(template::expand_tilde("data/~", home_directory), "data/~");
+    }
+
+    #[test]
+    fn interpolate() {
+        assert_eq!(template::interpolate("${FOO}-${MISSING}-$FOO", false, lookup), "bar--$FOO");
+        assert_eq!(template::references("${FOO}/${BAR}", false), ["FOO", "BAR"]);
+    }
+
+    #[test]
+    fn interpolate_escaped() {
+        assert_eq!(template::interpolate("${FOO}-$${FOO}-$$$$", true, lookup), "bar-${FOO}-$$");
+        assert_eq!(template::references("$${FOO}/${BAR}", true), ["BAR"]);
+    }
+
+    #[test]
+    fn render_substitutes_variables() {
+        assert_eq!(template::render("a=${FOO} $FOO", "tpl", false, lookup).unwrap(), "a=bar $FOO");
+    }
+
+    #[test]
+    fn render_lenient_missing_variable() {
+        assert_eq!(template::render("a=${MISSING}", "tpl", false, lookup).unwrap(), "a=");
+    }
+
+    #[test]
+    fn render_strict_missing_variable() {
+        let exception = template::render("a=${FOO}\nb=${MISSING}", "tpl", true, lookup).unwrap_err();
+        assert_eq!(exception.key(), Some("MISSING"));
+        assert_eq!(exception.to_string(), "Environment variable \"MISSING\" is not defined in \"tpl\" at line 2");
+    }
+}
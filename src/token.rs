@@ -0,0 +1,146 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::fast_parser::Lexer;
+
+/// Token kind enumeration
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// The `export` keyword before a variable name.
+    Export,
+    /// Variable name.
+    Key,
+    /// The `=` between a variable name and its value.
+    Equals,
+    /// Opening or closing quote of a quoted value.
+    Quote,
+    /// Value, or the part of a value between two quotes.
+    Value,
+    /// Comment, from `#` to the end of the line.
+    Comment,
+}
+
+/// Token with the byte range it covers, as returned by `tokenize()`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpannedToken {
+    kind: TokenKind,
+    span: Range<usize>,
+}
+
+impl SpannedToken {
+    pub(crate) fn new(kind: TokenKind, span: Range<usize>) -> Self {
+        Self { kind, span }
+    }
+
+    /// Kind of token.
+    pub fn kind(&self) -> TokenKind {
+        self.kind
+    }
+
+    /// Byte range of the token in the input.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+///
+/// Splits the contents of a `.env` file into tokens, following the exact grammar of the parser, for syntax
+/// highlighting.
+///
+/// A value is split into one token per quoted or unquoted part, with its quotes as separate tokens. Whitespace and
+/// line breaks are not tokens. Tokenizing does not fail: an invalid declaration yields the tokens lexed before the
+/// error, and tokenizing resumes at the next line.
+///
+/// # Examples
+///
+/// ```rust
+/// use darkweb_dotenv::TokenKind;
+///
+/// let tokens = darkweb_dotenv::tokenize("export DB_USER='root' # admin");
+/// let kinds = tokens.iter().map(|token| token.kind()).collect::<Vec<_>>();
+///
+/// assert_eq!(kinds, [
+///     TokenKind::Export, TokenKind::Key, TokenKind::Equals,
+///     TokenKind::Quote, TokenKind::Value, TokenKind::Quote, TokenKind::Comment,
+/// ]);
+/// assert_eq!(tokens[4].span(), 16..20);
+/// ```
+///
+pub fn tokenize(data: &str) -> Vec<SpannedToken> {
+    let mut lexer = Lexer::new(data, 0, 1).with_tokens();
+
+    loop {
+        match lexer.next_entry() {
+            Some(Some(_)) => {},
+            Some(None) => break,
+            None => lexer.skip_line(),
+        }
+    }
+
+    lexer.into_tokens()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{tokenize, TokenKind};
+
+    fn tokens(data: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(data).iter()
+            .map(|token| (token.kind(), &data[token.span()]))
+            .collect()
+    }
+
+    #[test]
+    fn tokenize_declarations() {
+        assert_eq!(tokens("# database\nDB_HOST=localhost\nDB_NAME=\"app\"'_'db # main\nDB_PASS=\n"), [
+            (TokenKind::Comment, "# database"),
+            (TokenKind::Key, "DB_HOST"),
+            (TokenKind::Equals, "="),
+            (TokenKind::Value, "localhost"),
+            (TokenKind::Key, "DB_NAME"),
+            (TokenKind::Equals, "="),
+            (TokenKind::Quote, "\""),
+            (TokenKind::Value, "app"),
+            (TokenKind::Quote, "\""),
+            (TokenKind::Quote, "'"),
+            (TokenKind::Value, "_"),
+            (TokenKind::Quote, "'"),
+            (TokenKind::Value, "db"),
+            (TokenKind::Comment, "# main"),
+            (TokenKind::Key, "DB_PASS"),
+            (TokenKind::Equals, "="),
+        ]);
+    }
+
+    #[test]
+    fn tokenize_recovers_from_invalid_declarations() {
+        assert_eq!(tokens("FOO=bar baz\n1BAR=qux\nBAZ=\"multi\r\nline\"\r\n"), [
+            (TokenKind::Key, "FOO"),
+            (TokenKind::Equals, "="),
+            (TokenKind::Key, "BAZ"),
+            (TokenKind::Equals, "="),
+            (TokenKind::Quote, "\""),
+            (TokenKind::Value, "multi\r\nline"),
+            (TokenKind::Quote, "\""),
+        ]);
+    }
+}
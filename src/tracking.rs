@@ -0,0 +1,99 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+use crate::Dotenv;
+
+/// View of the environment variables that records which ones are read, as returned by `Dotenv::tracked()`
+pub struct TrackedEnv<'a> {
+    dotenv: &'a Dotenv,
+    read_keys: Mutex<HashSet<String>>,
+}
+
+impl<'a> TrackedEnv<'a> {
+    pub(crate) fn new(dotenv: &'a Dotenv) -> Self {
+        Self { dotenv, read_keys: Mutex::new(HashSet::new()) }
+    }
+
+    ///
+    /// Returns the value of the variable, as looked up by `Dotenv::get()`, and records it as read.
+    ///
+    pub fn get<Key>(&self, key: Key) -> Option<String>
+        where
+            Key: AsRef<str> {
+
+        let key = key.as_ref();
+
+        self.read_keys.lock().unwrap_or_else(|error| error.into_inner()).insert(key.to_string());
+
+        self.dotenv.get(key)
+    }
+
+    ///
+    /// Returns the names of the variables that were read so far, whether they were defined or not.
+    ///
+    pub fn read_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.read_keys.lock().unwrap_or_else(|error| error.into_inner()).iter().cloned().collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    ///
+    /// Returns the names of the variables that were loaded by the `Dotenv` instance but never read, which are
+    /// candidates for removal from the environment files.
+    ///
+    pub fn unused_keys(&self) -> Vec<&'a str> {
+        let read_keys = self.read_keys.lock().unwrap_or_else(|error| error.into_inner());
+
+        let mut keys: Vec<&str> = self.dotenv.values().keys()
+            .filter(|key| !read_keys.contains(key.as_str()))
+            .map(String::as_str)
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for TrackedEnv<'_> {
+    fn drop(&mut self) {
+        let unused_keys = self.unused_keys();
+
+        if !unused_keys.is_empty() {
+            tracing::warn!(keys = unused_keys.join(",").as_str(), "loaded variables that were never read");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Dotenv;
+
+    #[test]
+    fn unused_keys() {
+        let mut dotenv = Dotenv::new();
+        dotenv.load_from_str("DOTENV_TEST_TRACKED_A=a\nDOTENV_TEST_TRACKED_B=b\nDOTENV_TEST_TRACKED_C=c").unwrap();
+
+        let tracked = dotenv.tracked();
+        assert_eq!(tracked.get("DOTENV_TEST_TRACKED_A"), Some("a".to_string()));
+        assert_eq!(tracked.get("DOTENV_TEST_TRACKED_MISSING"), None);
+
+        assert_eq!(tracked.read_keys(), vec!["DOTENV_TEST_TRACKED_A", "DOTENV_TEST_TRACKED_MISSING"]);
+        assert_eq!(tracked.unused_keys(), vec!["DOTENV_TEST_TRACKED_B", "DOTENV_TEST_TRACKED_C"]);
+    }
+}
@@ -0,0 +1,156 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::fmt;
+
+use regex::Regex;
+
+use crate::KeySpec;
+
+/// Violation kind enumeration
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// A required variable is not defined or empty.
+    Required,
+    /// The value does not match the `@type` of the variable.
+    Type,
+    /// The value does not match the `@pattern` of the variable.
+    Pattern,
+    /// The value is shorter than the `@min-length` or longer than the `@max-length` of the variable.
+    Length,
+    /// The value is not one of the `@enum` values of the variable.
+    Enum,
+    /// The value does not match the `@format` of the variable.
+    Format,
+}
+
+/// Value of a variable that does not satisfy its specification, as returned by `Dotenv::violations()`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    kind: ViolationKind,
+    key: String,
+    message: String,
+}
+
+impl Violation {
+    pub(crate) fn new(kind: ViolationKind, key: &str, message: String) -> Self {
+        Self {
+            kind,
+            key: key.to_string(),
+            message,
+        }
+    }
+
+    /// Kind of the violation.
+    pub fn kind(&self) -> ViolationKind {
+        self.kind
+    }
+
+    /// Name of the variable.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Description of the violation, which follows the name of the variable, as in `is not a valid int`.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}: {}", self.key, self.message)
+    }
+}
+
+/// Checks a defined, non-empty value against the constraints of a specification.
+pub(crate) fn check(key: &str, spec: &KeySpec, value: &str) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    if let Some(pattern) = spec.pattern() {
+        match Regex::new(&format!("^(?:{})$", pattern)) {
+            Ok(regex) if regex.is_match(value) => {},
+            Ok(_) => violations.push(Violation::new(ViolationKind::Pattern, key, format!("does not match the pattern \"{}\"", pattern))),
+            Err(_) => violations.push(Violation::new(ViolationKind::Pattern, key, format!("cannot be checked against the invalid pattern \"{}\"", pattern))),
+        }
+    }
+
+    let length = value.chars().count();
+
+    if let Some(min_length) = spec.min_length().filter(|min_length| length < *min_length) {
+        violations.push(Violation::new(ViolationKind::Length, key, format!("is shorter than {} characters", min_length)));
+    }
+
+    if let Some(max_length) = spec.max_length().filter(|max_length| length > *max_length) {
+        violations.push(Violation::new(ViolationKind::Length, key, format!("is longer than {} characters", max_length)));
+    }
+
+    if !spec.allowed_values().is_empty() && !spec.allowed_values().iter().any(|allowed_value| allowed_value == value) {
+        violations.push(Violation::new(ViolationKind::Enum, key, format!("is not one of {}", spec.allowed_values().join(", "))));
+    }
+
+    if let Some(value_format) = spec.value_format() {
+        let valid = match value_format {
+            "email" => Regex::new(r"^[^\s@]+@[^\s@]+\.[^\s@.]+$").unwrap().is_match(value),
+            "port" => value.parse::<u16>().is_ok_and(|port| port > 0),
+            "url" => Regex::new(r"^[A-Za-z][A-Za-z0-9+.-]*://[^\s/?#]+\S*$").unwrap().is_match(value),
+            _ => true,
+        };
+
+        if !valid {
+            violations.push(Violation::new(ViolationKind::Format, key, format!("is not a valid {}", value_format)));
+        }
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::KeySpec;
+    use super::{check, ViolationKind};
+
+    fn kinds(spec: &str, value: &str) -> Vec<ViolationKind> {
+        check("KEY", &KeySpec::parse(spec), value).iter().map(|violation| violation.kind()).collect()
+    }
+
+    #[test]
+    fn check_pattern_and_length() {
+        assert!(kinds("@pattern: [a-z]+ @min-length: 2 @max-length: 4", "abc").is_empty());
+        assert_eq!(kinds("@pattern: [a-z]+ @min-length: 2 @max-length: 4", "abcde1"), [ViolationKind::Pattern, ViolationKind::Length]);
+        assert_eq!(kinds("@min-length: 2", "é"), [ViolationKind::Length]);
+        assert_eq!(kinds("@pattern: [", "a"), [ViolationKind::Pattern]);
+    }
+
+    #[test]
+    fn check_enum() {
+        assert!(kinds("@enum: dev,prod", "prod").is_empty());
+        assert_eq!(check("APP_ENV", &KeySpec::parse("@enum: dev,prod"), "test")[0].to_string(), "APP_ENV: is not one of dev, prod");
+    }
+
+    #[test]
+    fn check_formats() {
+        assert!(kinds("@format: url", "https://example.com/path?query").is_empty());
+        assert!(kinds("@format: url", "postgres://user:pass@db:5432/app").is_empty());
+        assert_eq!(kinds("@format: url", "example.com"), [ViolationKind::Format]);
+        assert!(kinds("@format: email", "admin@example.com").is_empty());
+        assert_eq!(kinds("@format: email", "admin@localhost"), [ViolationKind::Format]);
+        assert!(kinds("@format: port", "8080").is_empty());
+        assert_eq!(kinds("@format: port", "0"), [ViolationKind::Format]);
+        assert_eq!(kinds("@format: port", "65536"), [ViolationKind::Format]);
+    }
+}
@@ -0,0 +1,42 @@
+// Copyright (c) 2020 DarkWeb Design
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::HashMap;
+
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::Exception;
+
+pub(crate) fn read_global(name: &str) -> Result<HashMap<String, String>, Exception> {
+    let global = Reflect::get(&js_sys::global(), &JsValue::from_str(name))
+        .map_err(|_| Exception::unavailable_source(name.to_string(), "the global is not accessible".to_string()))?;
+
+    let object = global.dyn_into::<Object>()
+        .map_err(|_| Exception::unavailable_source(name.to_string(), "the global is not an object".to_string()))?;
+
+    let values = Object::entries(&object)
+        .iter()
+        .filter_map(|entry| {
+            let entry = Array::from(&entry);
+
+            Some((entry.get(0).as_string()?, entry.get(1).as_string()?))
+        })
+        .collect();
+
+    Ok(values)
+}